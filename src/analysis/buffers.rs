@@ -1,12 +1,46 @@
 use tree_sitter::Node;
 
+use crate::analysis::scopes::is_scope_node;
+
 pub struct BufferMapping {
     pub alias: String,
     pub table: String,
     pub start_byte: usize,
+    /// Bytes of the procedure, function or method (or the file) the buffer is defined in.
+    pub scope_start_byte: usize,
+    pub scope_end_byte: usize,
+}
+
+/// Table behind `name_upper` where it is used at `offset`, honoring where each alias is defined.
+pub fn resolve_buffer_table<'a>(
+    mappings: &'a [BufferMapping],
+    name_upper: &str,
+    offset: usize,
+) -> Option<&'a str> {
+    mappings
+        .iter()
+        .find(|m| {
+            m.alias.eq_ignore_ascii_case(name_upper)
+                && m.scope_start_byte <= offset
+                && offset <= m.scope_end_byte
+        })
+        .map(|m| m.table.as_str())
 }
 
 pub fn collect_buffer_mappings(node: Node, src: &[u8], out: &mut Vec<BufferMapping>) {
+    if matches!(node.kind(), "parameter" | "parameter_definition")
+        && let Some((alias, table)) = buffer_parameter(node, src)
+    {
+        let (scope_start_byte, scope_end_byte) = defining_scope(node);
+        out.push(BufferMapping {
+            alias,
+            table,
+            start_byte: node.start_byte(),
+            scope_start_byte,
+            scope_end_byte,
+        });
+    }
+
     if node.kind() == "buffer_definition"
         && let (Some(name_node), Some(table_node)) = (
             node.child_by_field_name("name"),
@@ -17,10 +51,13 @@ pub fn collect_buffer_mappings(node: Node, src: &[u8], out: &mut Vec<BufferMappi
         let alias = alias.trim();
         let table = normalize_table_name(table_raw);
         if !alias.is_empty() && !table.is_empty() {
+            let (scope_start_byte, scope_end_byte) = defining_scope(node);
             out.push(BufferMapping {
                 alias: alias.to_string(),
                 table,
                 start_byte: node.start_byte(),
+                scope_start_byte,
+                scope_end_byte,
             });
         }
     }
@@ -32,6 +69,82 @@ pub fn collect_buffer_mappings(node: Node, src: &[u8], out: &mut Vec<BufferMappi
     }
 }
 
+fn defining_scope(node: Node) -> (usize, usize) {
+    let mut scope = node;
+    while let Some(parent) = scope.parent() {
+        scope = parent;
+        if is_scope_node(scope.kind()) {
+            break;
+        }
+    }
+    (scope.start_byte(), scope.end_byte())
+}
+
+/// Returns the join table of a record phrase's `OF` clause (`order OF customer`).
+pub fn record_phrase_of_table(node: Node, src: &[u8]) -> Option<String> {
+    if let Some(of_node) = node.child_by_field_name("of") {
+        let table = normalize_table_name(of_node.utf8_text(src).ok()?);
+        return (!table.is_empty()).then_some(table);
+    }
+
+    let text = node.utf8_text(src).ok()?;
+    let mut tokens = text.split_ascii_whitespace();
+    tokens.next()?;
+    if !tokens.next()?.eq_ignore_ascii_case("OF") {
+        return None;
+    }
+    let table = normalize_table_name(tokens.next()?.trim_end_matches([':', '.', ',']));
+    (!table.is_empty()).then_some(table)
+}
+
+/// Alias and table of a `BUFFER alias FOR table` parameter, in a header or `DEFINE PARAMETER`.
+pub fn buffer_parameter(node: Node, src: &[u8]) -> Option<(String, String)> {
+    let text = node.utf8_text(src).ok()?;
+    let tokens = text.split_ascii_whitespace().collect::<Vec<_>>();
+    let buffer_idx = tokens
+        .iter()
+        .position(|t| t.eq_ignore_ascii_case("BUFFER"))?;
+    let alias = tokens.get(buffer_idx + 1)?;
+    if !tokens.get(buffer_idx + 2)?.eq_ignore_ascii_case("FOR") {
+        return None;
+    }
+    let table = normalize_table_name(
+        tokens
+            .get(buffer_idx + 3)?
+            .trim_end_matches([':', '.', ',', ')']),
+    );
+    (!alias.is_empty() && !table.is_empty()).then(|| (alias.to_string(), table))
+}
+
+/// Buffers named in a `DO FOR` / `REPEAT FOR` block header (`DO FOR customer, order:`).
+pub fn block_for_buffers(node: Node, src: &[u8]) -> Vec<String> {
+    let Ok(text) = node.utf8_text(src) else {
+        return Vec::new();
+    };
+    let header = text.split_once(':').map_or(text, |(header, _)| header);
+    let mut tokens = header.split_ascii_whitespace();
+    let opens_block = tokens
+        .next()
+        .is_some_and(|t| t.eq_ignore_ascii_case("DO") || t.eq_ignore_ascii_case("REPEAT"));
+    if !opens_block || !tokens.next().is_some_and(|t| t.eq_ignore_ascii_case("FOR")) {
+        return Vec::new();
+    }
+
+    let mut out = Vec::new();
+    for token in tokens {
+        let continues = token.ends_with(',');
+        for name in token.split(',').map(normalize_table_name) {
+            if !name.is_empty() {
+                out.push(name);
+            }
+        }
+        if !continues {
+            break;
+        }
+    }
+    out
+}
+
 fn normalize_table_name(raw: &str) -> String {
     raw.trim()
         .split('.')
@@ -43,8 +156,12 @@ fn normalize_table_name(raw: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::collect_buffer_mappings;
+    use super::{
+        block_for_buffers, buffer_parameter, collect_buffer_mappings, record_phrase_of_table,
+        resolve_buffer_table,
+    };
     use crate::analysis::parse_abl;
+    use crate::utils::ts::collect_nodes_by_kind;
 
     #[test]
     fn collects_buffer_alias_and_table_name() {
@@ -67,4 +184,81 @@ DEFINE BUFFER b-pt FOR sports.pt_mstr.
                 .any(|m| m.alias == "b-pt" && m.table == "pt_mstr")
         );
     }
+
+    #[test]
+    fn scopes_buffer_alias_to_its_procedure() {
+        let src = r#"
+PROCEDURE a:
+  DEFINE BUFFER b FOR customer.
+END PROCEDURE.
+PROCEDURE c:
+  DEFINE BUFFER b FOR order.
+END PROCEDURE.
+"#;
+        let tree = parse_abl(src);
+        let mut out = Vec::new();
+        collect_buffer_mappings(tree.root_node(), src.as_bytes(), &mut out);
+
+        let in_a = src.find("END PROCEDURE").expect("end of a");
+        let in_c = src.rfind("END PROCEDURE").expect("end of c");
+        assert_eq!(resolve_buffer_table(&out, "B", in_a), Some("customer"));
+        assert_eq!(resolve_buffer_table(&out, "B", in_c), Some("order"));
+    }
+
+    #[test]
+    fn reads_of_clause_join_table() {
+        let src = "FOR EACH order OF sports.customer NO-LOCK:\nEND.\n";
+        let tree = parse_abl(src);
+
+        let mut phrases = Vec::new();
+        collect_nodes_by_kind(tree.root_node(), "record_phrase", &mut phrases);
+        let phrase = phrases.first().expect("record phrase");
+        assert_eq!(
+            record_phrase_of_table(*phrase, src.as_bytes()).as_deref(),
+            Some("customer")
+        );
+    }
+
+    #[test]
+    fn reads_buffer_parameters() {
+        let src = r#"
+DEFINE PARAMETER BUFFER bCust FOR sports.customer.
+"#;
+        let tree = parse_abl(src);
+
+        let mut params = Vec::new();
+        collect_nodes_by_kind(tree.root_node(), "parameter_definition", &mut params);
+        let param = params.first().expect("parameter definition");
+        assert_eq!(
+            buffer_parameter(*param, src.as_bytes()),
+            Some(("bCust".to_string(), "customer".to_string()))
+        );
+
+        let mut out = Vec::new();
+        collect_buffer_mappings(tree.root_node(), src.as_bytes(), &mut out);
+        assert!(
+            out.iter()
+                .any(|m| m.alias == "bCust" && m.table == "customer")
+        );
+    }
+
+    #[test]
+    fn reads_do_for_and_repeat_for_buffers() {
+        let src = "DO FOR customer, order TRANSACTION:\nEND.\nREPEAT FOR item:\nEND.\nDO WHILE TRUE:\nEND.\n";
+        let tree = parse_abl(src);
+        let root = tree.root_node();
+
+        let blocks = (0..root.named_child_count())
+            .filter_map(|i| root.named_child(i as u32))
+            .map(|block| block_for_buffers(block, src.as_bytes()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            blocks,
+            vec![
+                vec!["customer".to_string(), "order".to_string()],
+                vec!["item".to_string()],
+                Vec::<String>::new(),
+            ]
+        );
+    }
 }
@@ -337,6 +337,226 @@ pub fn is_builtin_function_name(name_upper: &str) -> bool {
     BUILTIN_FUNCTIONS.contains(&name_upper) || SQL_BUILTIN_FUNCTIONS.contains(&name_upper)
 }
 
+/// One-line signature and description of the most common builtin functions, for hover.
+const BUILTIN_FUNCTION_DOCS: &[(&str, &str, &str)] = &[
+    (
+        "ABSOLUTE",
+        "ABSOLUTE(expression)",
+        "Returns the absolute value of a numeric value.",
+    ),
+    (
+        "ADD-INTERVAL",
+        "ADD-INTERVAL(datetime, interval-amount, interval-unit)",
+        "Adds a time interval to a DATE, DATETIME or DATETIME-TZ value.",
+    ),
+    (
+        "ASC",
+        "ASC(expression [, target-codepage [, source-codepage]])",
+        "Returns the integer code of the first character of a string.",
+    ),
+    (
+        "AVAILABLE",
+        "AVAILABLE(record)",
+        "Returns TRUE if the buffer contains a record.",
+    ),
+    (
+        "CAN-DO",
+        "CAN-DO(id-list [, string])",
+        "Checks a user ID against a comma-separated list of permitted IDs.",
+    ),
+    (
+        "CAN-FIND",
+        "CAN-FIND([FIRST | LAST] record [WHERE ...])",
+        "Returns TRUE if a record satisfying the criteria exists, without reading it into a buffer.",
+    ),
+    (
+        "CAPS",
+        "CAPS(expression)",
+        "Converts a string to uppercase.",
+    ),
+    (
+        "CHR",
+        "CHR(expression [, target-codepage [, source-codepage]])",
+        "Returns the character for an integer code.",
+    ),
+    (
+        "DATE",
+        "DATE(month, day, year) | DATE(string) | DATE(datetime)",
+        "Builds or converts a value to a DATE.",
+    ),
+    (
+        "DAY",
+        "DAY(date)",
+        "Returns the day of the month of a date.",
+    ),
+    (
+        "DECIMAL",
+        "DECIMAL(expression)",
+        "Converts an expression to a DECIMAL value.",
+    ),
+    (
+        "DYNAMIC-FUNCTION",
+        "DYNAMIC-FUNCTION(function-name [IN proc-handle] [, param ...])",
+        "Invokes a user-defined function whose name is evaluated at run time.",
+    ),
+    (
+        "ENTRY",
+        "ENTRY(element, list [, delimiter])",
+        "Returns the element at a 1-based position in a delimited list.",
+    ),
+    (
+        "ERROR",
+        "ERROR(record | handle)",
+        "Returns TRUE if an error occurred on the last operation on the record or handle.",
+    ),
+    (
+        "FILL",
+        "FILL(expression, repeats)",
+        "Repeats a string the given number of times.",
+    ),
+    (
+        "GET-SIZE",
+        "GET-SIZE(memptr)",
+        "Returns the allocated byte size of a MEMPTR.",
+    ),
+    (
+        "INDEX",
+        "INDEX(source, target [, starting])",
+        "Returns the position of target in source, or 0 when absent.",
+    ),
+    (
+        "INTEGER",
+        "INTEGER(expression)",
+        "Converts an expression to an INTEGER value, rounding decimals.",
+    ),
+    (
+        "ISO-DATE",
+        "ISO-DATE(expression)",
+        "Formats a date or datetime as an ISO 8601 string.",
+    ),
+    ("LC", "LC(expression)", "Converts a string to lowercase."),
+    (
+        "LEFT-TRIM",
+        "LEFT-TRIM(expression [, trim-chars])",
+        "Removes leading white space or the given characters from a string.",
+    ),
+    (
+        "LENGTH",
+        "LENGTH(expression [, type])",
+        "Returns the number of characters, bytes or columns in a string, or the size of a RAW/BLOB.",
+    ),
+    (
+        "LOOKUP",
+        "LOOKUP(expression, list [, delimiter])",
+        "Returns the 1-based position of an element in a delimited list, or 0 when absent.",
+    ),
+    (
+        "MAXIMUM",
+        "MAXIMUM(expression, expression [, ...])",
+        "Returns the largest of its arguments.",
+    ),
+    (
+        "MINIMUM",
+        "MINIMUM(expression, expression [, ...])",
+        "Returns the smallest of its arguments.",
+    ),
+    (
+        "MONTH",
+        "MONTH(date)",
+        "Returns the month (1-12) of a date.",
+    ),
+    (
+        "NOW",
+        "NOW",
+        "Returns the current system date and time as a DATETIME-TZ.",
+    ),
+    (
+        "NUM-ENTRIES",
+        "NUM-ENTRIES(list [, delimiter])",
+        "Returns the number of elements in a delimited list.",
+    ),
+    (
+        "QUOTER",
+        "QUOTER(expression [, quote-char [, null-string]])",
+        "Wraps a value in quotes, escaping embedded quotes.",
+    ),
+    (
+        "R-INDEX",
+        "R-INDEX(source, target [, starting])",
+        "Returns the position of the last occurrence of target in source.",
+    ),
+    (
+        "REPLACE",
+        "REPLACE(source, from-string, to-string)",
+        "Replaces every occurrence of from-string in source.",
+    ),
+    (
+        "RIGHT-TRIM",
+        "RIGHT-TRIM(expression [, trim-chars])",
+        "Removes trailing white space or the given characters from a string.",
+    ),
+    (
+        "ROUND",
+        "ROUND(expression, precision)",
+        "Rounds a decimal value to the given number of places.",
+    ),
+    (
+        "STRING",
+        "STRING(source [, format])",
+        "Converts a value to a CHARACTER string, optionally using a display format.",
+    ),
+    (
+        "SUBSTITUTE",
+        "SUBSTITUTE(base-string [, arg1 ...])",
+        "Replaces &1 to &9 in base-string with the arguments.",
+    ),
+    (
+        "SUBSTRING",
+        "SUBSTRING(source, position [, length [, type]])",
+        "Returns part of a string, starting at a 1-based position.",
+    ),
+    (
+        "TIME",
+        "TIME",
+        "Returns the number of seconds since midnight.",
+    ),
+    ("TODAY", "TODAY", "Returns the current system date."),
+    (
+        "TRIM",
+        "TRIM(expression [, trim-chars])",
+        "Removes leading and trailing white space or the given characters from a string.",
+    ),
+    (
+        "TRUNCATE",
+        "TRUNCATE(expression, decimal-places)",
+        "Truncates a decimal value to the given number of places.",
+    ),
+    (
+        "VALID-HANDLE",
+        "VALID-HANDLE(handle)",
+        "Returns TRUE if the handle refers to a valid object.",
+    ),
+    (
+        "WEEKDAY",
+        "WEEKDAY(date)",
+        "Returns the day of the week (1 = Sunday) of a date.",
+    ),
+    ("YEAR", "YEAR(date)", "Returns the year of a date."),
+];
+
+/// Signature and one-line description of a builtin function, if documented.
+pub fn builtin_function_doc(name_upper: &str) -> Option<(&'static str, &'static str)> {
+    let name_upper = match name_upper {
+        "INT" => "INTEGER",
+        "SUBSTR" => "SUBSTRING",
+        other => other,
+    };
+    BUILTIN_FUNCTION_DOCS
+        .iter()
+        .find(|(name, _, _)| *name == name_upper)
+        .map(|(_, signature, description)| (*signature, *description))
+}
+
 pub fn is_builtin_variable_name(name_upper: &str) -> bool {
     const BUILTIN_VARIABLES: &[&str] = &[
         "SESSION",
@@ -355,9 +575,74 @@ pub fn is_builtin_variable_name(name_upper: &str) -> bool {
     BUILTIN_VARIABLES.contains(&name_upper)
 }
 
+/// Common widget/object handle attributes offered after `hWidget:`.
+pub const HANDLE_ATTRIBUTES: &[&str] = &[
+    "BGCOLOR",
+    "COLUMN",
+    "DATA-TYPE",
+    "FGCOLOR",
+    "FILE-NAME",
+    "FIRST-CHILD",
+    "FONT",
+    "FORMAT",
+    "FRAME",
+    "HANDLE",
+    "HEIGHT-CHARS",
+    "HIDDEN",
+    "INPUT-VALUE",
+    "LABEL",
+    "MODIFIED",
+    "NAME",
+    "NEXT-SIBLING",
+    "PARENT",
+    "PREV-SIBLING",
+    "PRIVATE-DATA",
+    "ROW",
+    "SCREEN-VALUE",
+    "SELECTED",
+    "SENSITIVE",
+    "TABLE",
+    "TITLE",
+    "TOOLTIP",
+    "TYPE",
+    "VALUE",
+    "VISIBLE",
+    "WIDTH-CHARS",
+    "WINDOW",
+];
+
+/// Common widget/object handle methods offered after `hWidget:`.
+pub const HANDLE_METHODS: &[&str] = &[
+    "ADD-FIRST",
+    "ADD-LAST",
+    "APPLY-CALLBACK",
+    "BUFFER-CREATE",
+    "BUFFER-DELETE",
+    "BUFFER-FIELD",
+    "CLEAR",
+    "DELETE",
+    "DISABLE",
+    "ENABLE",
+    "FIND-FIRST",
+    "FIND-UNIQUE",
+    "LOAD",
+    "LOOKUP",
+    "MOVE-TO-BOTTOM",
+    "MOVE-TO-TOP",
+    "QUERY-CLOSE",
+    "QUERY-OPEN",
+    "QUERY-PREPARE",
+    "READ-JSON",
+    "READ-XML",
+    "REFRESH",
+    "SET-CALLBACK",
+    "WRITE-JSON",
+    "WRITE-XML",
+];
+
 #[cfg(test)]
 mod tests {
-    use super::{is_builtin_function_name, is_builtin_variable_name};
+    use super::{builtin_function_doc, is_builtin_function_name, is_builtin_variable_name};
 
     #[test]
     fn recognizes_abl_and_sql_builtin_functions() {
@@ -386,4 +671,16 @@ mod tests {
         assert!(!is_builtin_variable_name("EXECNAME"));
         assert!(!is_builtin_variable_name("NOT_A_VARIABLE"));
     }
+
+    #[test]
+    fn documents_common_builtins_and_abbreviations() {
+        let (signature, description) = builtin_function_doc("SUBSTRING").expect("doc");
+        assert_eq!(signature, "SUBSTRING(source, position [, length [, type]])");
+        assert!(description.starts_with("Returns part of a string"));
+        assert_eq!(
+            builtin_function_doc("SUBSTR"),
+            builtin_function_doc("SUBSTRING")
+        );
+        assert!(builtin_function_doc("NOT-A-BUILTIN").is_none());
+    }
 }
@@ -0,0 +1,364 @@
+use std::sync::Arc;
+
+use tower_lsp::lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, Position, Range,
+    SymbolKind, Url,
+};
+use tree_sitter::{Node, Tree};
+
+use crate::analysis::signature::run_target_name;
+use crate::utils::position::byte_ranges_to_utf16;
+use crate::utils::ts::node_to_range;
+
+pub struct CallableDefinition {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub range: Range,
+    pub selection_range: Range,
+    pub start_byte: usize,
+    pub end_byte: usize,
+}
+
+pub struct CallReference {
+    pub name: String,
+    pub range: Range,
+    pub start_byte: usize,
+}
+
+/// Collects function, procedure and method definitions (forward declarations excluded).
+pub fn collect_callable_definitions(node: Node<'_>, src: &[u8], out: &mut Vec<CallableDefinition>) {
+    let kind = match node.kind() {
+        "function_definition" => Some(SymbolKind::FUNCTION),
+        "procedure_definition" => Some(SymbolKind::FUNCTION),
+        "method_definition" => Some(SymbolKind::METHOD),
+        _ => None,
+    };
+    if let Some(kind) = kind
+        && let Some(name_node) = node.child_by_field_name("name")
+        && let Ok(name) = name_node.utf8_text(src)
+    {
+        let name = name.trim().trim_matches('"');
+        if !name.is_empty() {
+            out.push(CallableDefinition {
+                name: name.to_string(),
+                kind,
+                range: node_to_range(node),
+                selection_range: node_to_range(name_node),
+                start_byte: node.start_byte(),
+                end_byte: node.end_byte(),
+            });
+        }
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_callable_definitions(ch, src, out);
+        }
+    }
+}
+
+/// Collects `function_call` and `RUN` targets within `[start, end)`.
+pub fn collect_call_references(
+    node: Node<'_>,
+    src: &[u8],
+    start: usize,
+    end: usize,
+    out: &mut Vec<CallReference>,
+) {
+    if node.end_byte() <= start || node.start_byte() >= end {
+        return;
+    }
+
+    match node.kind() {
+        "function_call" => {
+            if let Some(function) = node.child_by_field_name("function")
+                && let Ok(name) = function.utf8_text(src)
+                && !name.trim().is_empty()
+            {
+                out.push(CallReference {
+                    name: name.trim().to_string(),
+                    range: node_to_range(function),
+                    start_byte: function.start_byte(),
+                });
+            }
+        }
+        "run_statement" => {
+            if let Some(name) = run_target_name(node, src) {
+                out.push(CallReference {
+                    name,
+                    range: node_to_range(node),
+                    start_byte: node.start_byte(),
+                });
+            }
+        }
+        _ => {}
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_call_references(ch, src, start, end, out);
+        }
+    }
+}
+
+/// Returns the innermost callable definition containing `offset`.
+pub fn enclosing_callable(
+    definitions: &[CallableDefinition],
+    offset: usize,
+) -> Option<&CallableDefinition> {
+    definitions
+        .iter()
+        .filter(|def| def.start_byte <= offset && offset < def.end_byte)
+        .min_by_key(|def| def.end_byte - def.start_byte)
+}
+
+pub fn callable_names_match(a: &str, b: &str) -> bool {
+    a.trim()
+        .trim_matches('"')
+        .eq_ignore_ascii_case(b.trim().trim_matches('"'))
+}
+
+/// A parsed document searched for callables and calls.
+pub struct CallHierarchySource {
+    pub uri: Url,
+    pub text: Arc<String>,
+    pub tree: Tree,
+}
+
+impl CallHierarchySource {
+    /// Callables of this source, with ranges converted to UTF-16 columns.
+    pub fn callable_definitions(&self) -> Vec<CallableDefinition> {
+        let mut definitions = Vec::new();
+        collect_callable_definitions(
+            self.tree.root_node(),
+            self.text.as_bytes(),
+            &mut definitions,
+        );
+        byte_ranges_to_utf16(
+            &self.text,
+            definitions
+                .iter_mut()
+                .flat_map(|def| [&mut def.range, &mut def.selection_range]),
+        );
+        definitions
+    }
+
+    /// Calls between `start_byte` and `end_byte`, with ranges converted to UTF-16 columns.
+    pub fn call_references(&self, start_byte: usize, end_byte: usize) -> Vec<CallReference> {
+        let mut calls = Vec::new();
+        collect_call_references(
+            self.tree.root_node(),
+            self.text.as_bytes(),
+            start_byte,
+            end_byte,
+            &mut calls,
+        );
+        byte_ranges_to_utf16(&self.text, calls.iter_mut().map(|call| &mut call.range));
+        calls
+    }
+}
+
+/// The item for the first callable named `name` across `sources`.
+pub fn find_callable_item(
+    sources: &[CallHierarchySource],
+    name: &str,
+) -> Option<CallHierarchyItem> {
+    sources.iter().find_map(|source| {
+        source
+            .callable_definitions()
+            .iter()
+            .find(|def| callable_names_match(&def.name, name))
+            .map(|def| callable_item(&source.uri, def))
+    })
+}
+
+/// Calls of `target_name` in `sources`, grouped by the callable (or file) that makes them.
+pub fn collect_incoming_calls(
+    sources: &[CallHierarchySource],
+    target_name: &str,
+) -> Vec<CallHierarchyIncomingCall> {
+    let mut incoming = Vec::<CallHierarchyIncomingCall>::new();
+    for source in sources {
+        let mut calls = source.call_references(0, source.text.len());
+        calls.retain(|call| callable_names_match(&call.name, target_name));
+        if calls.is_empty() {
+            continue;
+        }
+
+        let definitions = source.callable_definitions();
+        for call in calls {
+            let from = match enclosing_callable(&definitions, call.start_byte) {
+                Some(def) => callable_item(&source.uri, def),
+                None => file_item(&source.uri),
+            };
+            match incoming
+                .iter_mut()
+                .find(|c| c.from.uri == from.uri && c.from.range == from.range)
+            {
+                Some(existing) => existing.from_ranges.push(call.range),
+                None => incoming.push(CallHierarchyIncomingCall {
+                    from,
+                    from_ranges: vec![call.range],
+                }),
+            }
+        }
+    }
+    incoming
+}
+
+/// Calls made from the body of `caller`, grouped by the callable they reach; `None` when
+/// `caller` is not defined in its source.
+pub fn collect_outgoing_calls(
+    sources: &[CallHierarchySource],
+    caller: &CallHierarchyItem,
+) -> Option<Vec<CallHierarchyOutgoingCall>> {
+    let caller_source = sources.iter().find(|s| s.uri == caller.uri)?;
+    let definitions = caller_source.callable_definitions();
+    let def = definitions
+        .iter()
+        .find(|def| def.selection_range == caller.selection_range)
+        .or_else(|| {
+            definitions
+                .iter()
+                .find(|def| callable_names_match(&def.name, &caller.name))
+        })?;
+
+    let mut outgoing = Vec::<CallHierarchyOutgoingCall>::new();
+    for call in caller_source.call_references(def.start_byte, def.end_byte) {
+        if let Some(existing) = outgoing
+            .iter_mut()
+            .find(|c| callable_names_match(&c.to.name, &call.name))
+        {
+            existing.from_ranges.push(call.range);
+            continue;
+        }
+        let Some(to) = find_callable_item(sources, &call.name) else {
+            continue;
+        };
+        outgoing.push(CallHierarchyOutgoingCall {
+            to,
+            from_ranges: vec![call.range],
+        });
+    }
+    Some(outgoing)
+}
+
+fn callable_item(uri: &Url, def: &CallableDefinition) -> CallHierarchyItem {
+    CallHierarchyItem {
+        name: def.name.clone(),
+        kind: def.kind,
+        tags: None,
+        detail: None,
+        uri: uri.clone(),
+        range: def.range,
+        selection_range: def.selection_range,
+        data: None,
+    }
+}
+
+fn file_item(uri: &Url) -> CallHierarchyItem {
+    let name = uri
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .unwrap_or(uri.as_str())
+        .to_string();
+    let start = Range::new(Position::new(0, 0), Position::new(0, 0));
+    CallHierarchyItem {
+        name,
+        kind: SymbolKind::FILE,
+        tags: None,
+        detail: None,
+        uri: uri.clone(),
+        range: start,
+        selection_range: start,
+        data: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CallHierarchySource, collect_call_references, collect_callable_definitions,
+        collect_incoming_calls, collect_outgoing_calls, enclosing_callable, find_callable_item,
+    };
+    use crate::analysis::parse_abl;
+    use std::sync::Arc;
+    use tower_lsp::lsp_types::{SymbolKind, Url};
+
+    #[test]
+    fn collects_calls_grouped_by_enclosing_callable() {
+        let src = r#"
+FUNCTION add_one RETURNS INTEGER (INPUT p AS INTEGER):
+  RETURN p + 1.
+END FUNCTION.
+
+PROCEDURE compute:
+  DEFINE VARIABLE x AS INTEGER NO-UNDO.
+  x = add_one(x).
+END PROCEDURE.
+
+RUN compute.
+"#;
+        let tree = parse_abl(src);
+        let mut definitions = Vec::new();
+        collect_callable_definitions(tree.root_node(), src.as_bytes(), &mut definitions);
+        let names = definitions
+            .iter()
+            .map(|d| d.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["add_one", "compute"]);
+
+        let mut calls = Vec::new();
+        collect_call_references(tree.root_node(), src.as_bytes(), 0, src.len(), &mut calls);
+        let add_one_call = calls
+            .iter()
+            .find(|c| c.name == "add_one")
+            .expect("function call");
+        assert_eq!(
+            enclosing_callable(&definitions, add_one_call.start_byte).map(|d| d.name.as_str()),
+            Some("compute")
+        );
+        let run_call = calls
+            .iter()
+            .find(|c| c.name == "compute")
+            .expect("run call");
+        assert!(enclosing_callable(&definitions, run_call.start_byte).is_none());
+    }
+
+    #[test]
+    fn groups_incoming_and_outgoing_calls() {
+        let src = r#"
+FUNCTION add_one RETURNS INTEGER (INPUT p AS INTEGER):
+  RETURN p + 1.
+END FUNCTION.
+
+PROCEDURE compute:
+  DEFINE VARIABLE x AS INTEGER NO-UNDO.
+  x = add_one(x).
+  x = add_one(x).
+END PROCEDURE.
+
+RUN compute.
+"#;
+        let sources = vec![CallHierarchySource {
+            uri: Url::parse("file:///tmp/calls.p").expect("uri"),
+            text: Arc::new(src.to_string()),
+            tree: parse_abl(src),
+        }];
+
+        let incoming = collect_incoming_calls(&sources, "add_one");
+        assert_eq!(incoming.len(), 1);
+        assert_eq!(incoming[0].from.name, "compute");
+        assert_eq!(incoming[0].from_ranges.len(), 2);
+
+        let from_file = collect_incoming_calls(&sources, "compute");
+        assert_eq!(from_file.len(), 1);
+        assert_eq!(from_file[0].from.kind, SymbolKind::FILE);
+
+        let compute = find_callable_item(&sources, "compute").expect("compute item");
+        let outgoing = collect_outgoing_calls(&sources, &compute).expect("outgoing calls");
+        assert_eq!(outgoing.len(), 1);
+        assert_eq!(outgoing[0].to.name, "add_one");
+        assert_eq!(outgoing[0].from_ranges.len(), 2);
+    }
+}
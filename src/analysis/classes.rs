@@ -0,0 +1,689 @@
+use tower_lsp::lsp_types::{CompletionItemKind, Range};
+use tree_sitter::Node;
+
+use crate::analysis::definitions::{AblDefinitionSite, AblSymbol, collect_definition_sites};
+use crate::analysis::diagnostics::functions::function_param_count;
+use crate::analysis::signature::split_argument_offsets;
+use crate::utils::position::utf8_byte_offset_to_lsp_pos;
+use crate::utils::ts::{collect_nodes_by_kind, first_descendant_by_kind, node_trimmed_text};
+
+/// Bound on `INHERITS` chains followed when looking up inherited members.
+pub const MAX_INHERITS_DEPTH: usize = 8;
+
+/// Method called at an offset: `name(...)`, `THIS-OBJECT:name(...)` or `SUPER:name(...)`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct MethodCallAtOffset {
+    pub name: String,
+    /// Argument count when the call has a parenthesized argument list.
+    pub arg_count: Option<usize>,
+    /// `SUPER:` calls skip the current class.
+    pub inherited_only: bool,
+}
+
+/// Returns the `class_definition` that contains `offset`, or the first one in the file.
+pub fn enclosing_class_definition(root: Node<'_>, offset: usize) -> Option<Node<'_>> {
+    let mut classes = Vec::new();
+    collect_nodes_by_kind(root, "class_definition", &mut classes);
+    classes
+        .iter()
+        .copied()
+        .filter(|class| class.start_byte() <= offset && offset <= class.end_byte())
+        .min_by_key(|class| class.end_byte() - class.start_byte())
+        .or_else(|| classes.first().copied())
+}
+
+/// Collects methods and properties declared directly in a class body.
+pub fn collect_class_members(class_node: Node<'_>, src: &[u8], out: &mut Vec<AblSymbol>) {
+    collect_members(class_node, src, true, out);
+}
+
+/// Collects the members of a parent class that subclasses can reach, skipping `PRIVATE` ones.
+pub fn collect_inherited_class_members(class_node: Node<'_>, src: &[u8], out: &mut Vec<AblSymbol>) {
+    collect_members(class_node, src, false, out);
+}
+
+fn collect_members(node: Node<'_>, src: &[u8], include_private: bool, out: &mut Vec<AblSymbol>) {
+    for i in 0..node.child_count() {
+        let Some(ch) = node.child(i as u32) else {
+            continue;
+        };
+        let (kind, detail) = match ch.kind() {
+            "method_definition" => (CompletionItemKind::METHOD, "ABL method"),
+            "property_definition" => (CompletionItemKind::PROPERTY, "ABL property"),
+            "event_definition" => (CompletionItemKind::EVENT, "ABL event"),
+            "class_definition" | "interface_definition" => continue,
+            _ => {
+                collect_members(ch, src, include_private, out);
+                continue;
+            }
+        };
+        let Some(name) = ch
+            .child_by_field_name("name")
+            .or_else(|| first_descendant_by_kind(ch, "identifier"))
+        else {
+            continue;
+        };
+        if !include_private && is_private_member(ch, name, src) {
+            continue;
+        }
+        let Some(label) = node_trimmed_text(name, src) else {
+            continue;
+        };
+        let detail = ch
+            .child_by_field_name("type")
+            .and_then(|n| node_trimmed_text(n, src))
+            .unwrap_or_else(|| detail.to_string());
+        out.push(AblSymbol {
+            label,
+            kind,
+            detail,
+            start_byte: name.start_byte(),
+        });
+    }
+}
+
+/// `METHOD PRIVATE ...` / `DEFINE PRIVATE PROPERTY ...`: the modifier sits before the name.
+fn is_private_member(member: Node<'_>, name: Node<'_>, src: &[u8]) -> bool {
+    src.get(member.start_byte()..name.start_byte())
+        .and_then(|header| std::str::from_utf8(header).ok())
+        .is_some_and(|header| {
+            header
+                .split_ascii_whitespace()
+                .any(|token| token.eq_ignore_ascii_case("PRIVATE"))
+        })
+}
+
+/// Returns the class name from the `INHERITS` clause of a class header.
+pub fn class_inherits_name(class_node: Node<'_>, src: &[u8]) -> Option<String> {
+    if let Some(node) = class_node.child_by_field_name("inherits")
+        && let Some(name) = node_trimmed_text(node, src)
+    {
+        return Some(name);
+    }
+
+    let text = class_node.utf8_text(src).ok()?;
+    let header = text.split(':').next().unwrap_or_default();
+    let mut tokens = header.split_ascii_whitespace();
+    while let Some(token) = tokens.next() {
+        if token.eq_ignore_ascii_case("INHERITS") {
+            return tokens
+                .next()
+                .map(|name| name.trim_end_matches(',').to_string())
+                .filter(|name| !name.is_empty());
+        }
+    }
+    None
+}
+
+/// Returns the interface names listed in a class header's `IMPLEMENTS` clause.
+pub fn class_implements_names(class_node: Node<'_>, src: &[u8]) -> Vec<String> {
+    let Ok(text) = class_node.utf8_text(src) else {
+        return Vec::new();
+    };
+    let header = text.split(':').next().unwrap_or_default();
+    let mut out = Vec::new();
+    let mut in_clause = false;
+    for token in header.split_ascii_whitespace() {
+        if token.eq_ignore_ascii_case("IMPLEMENTS") {
+            in_clause = true;
+            continue;
+        }
+        if !in_clause {
+            continue;
+        }
+        if [
+            "INHERITS",
+            "USE-WIDGET-POOL",
+            "ABSTRACT",
+            "FINAL",
+            "SERIALIZABLE",
+        ]
+        .iter()
+        .any(|k| token.eq_ignore_ascii_case(k))
+        {
+            in_clause = false;
+            continue;
+        }
+        out.extend(
+            token
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string),
+        );
+    }
+    out
+}
+
+/// Returns the name of the interface enclosing `offset` when it declares a method named `method`.
+pub fn interface_declaring_method(
+    root: Node<'_>,
+    src: &[u8],
+    offset: usize,
+    method: &str,
+) -> Option<String> {
+    let mut interfaces = Vec::new();
+    collect_nodes_by_kind(root, "interface_definition", &mut interfaces);
+    let interface = interfaces
+        .into_iter()
+        .filter(|node| node.start_byte() <= offset && offset <= node.end_byte())
+        .min_by_key(|node| node.end_byte() - node.start_byte())?;
+
+    let mut members = Vec::new();
+    collect_class_members(interface, src, &mut members);
+    if !members
+        .iter()
+        .any(|m| m.kind == CompletionItemKind::METHOD && m.label.eq_ignore_ascii_case(method))
+    {
+        return None;
+    }
+    interface
+        .child_by_field_name("name")
+        .and_then(|name| node_trimmed_text(name, src))
+        .or_else(|| {
+            let text = interface.utf8_text(src).ok()?;
+            let mut tokens = text.split(':').next()?.split_ascii_whitespace();
+            tokens.next();
+            tokens.next().map(str::to_string)
+        })
+}
+
+/// True when `reference`, written in a file with `imports`, names the class/interface `qualified`.
+pub fn class_reference_matches(reference: &str, imports: &[UsingImport], qualified: &str) -> bool {
+    if class_name_candidates(reference, imports)
+        .iter()
+        .any(|candidate| candidate.eq_ignore_ascii_case(qualified))
+    {
+        return true;
+    }
+    !qualified.contains('.')
+        && reference
+            .rsplit('.')
+            .next()
+            .is_some_and(|short| short.eq_ignore_ascii_case(qualified))
+}
+
+/// Ranges (UTF-16) of the methods named `method` in the classes of `class_text` that implement
+/// `interface`; the `IMPLEMENTS` names are resolved with the file's own `USING` imports.
+pub fn implementing_method_ranges(
+    class_root: Node<'_>,
+    class_text: &str,
+    interface: &str,
+    method: &str,
+) -> Vec<Range> {
+    let imports = collect_using_imports(class_text);
+    let mut classes = Vec::new();
+    collect_nodes_by_kind(class_root, "class_definition", &mut classes);
+
+    let mut out = Vec::new();
+    for class in classes {
+        if !class_implements_names(class, class_text.as_bytes())
+            .iter()
+            .any(|name| class_reference_matches(name, &imports, interface))
+        {
+            continue;
+        }
+        let mut members = Vec::new();
+        collect_class_members(class, class_text.as_bytes(), &mut members);
+        out.extend(
+            members
+                .iter()
+                .filter(|m| {
+                    m.kind == CompletionItemKind::METHOD && m.label.eq_ignore_ascii_case(method)
+                })
+                .map(|m| {
+                    Range::new(
+                        utf8_byte_offset_to_lsp_pos(class_text, m.start_byte),
+                        utf8_byte_offset_to_lsp_pos(class_text, m.start_byte + m.label.len()),
+                    )
+                }),
+        );
+    }
+    out
+}
+
+/// Maps a dotted class name (`pkg.sub.Class`) to its propath-relative `.cls` path.
+pub fn class_name_to_relative_path(class_name: &str) -> String {
+    let mut path = class_name.trim().replace('.', "/");
+    path.push_str(".cls");
+    path
+}
+
+/// A `USING pkg.*.` (package) or `USING pkg.ClassName.` directive.
+#[derive(Clone)]
+pub struct UsingImport {
+    pub name: String,
+    pub is_package: bool,
+}
+
+/// Collects `USING` directives from the source text, in file order.
+pub fn collect_using_imports(text: &str) -> Vec<UsingImport> {
+    let mut out = Vec::new();
+    for line in text.lines() {
+        let line = line.trim_start();
+        let Some(head) = line.get(..6) else {
+            continue;
+        };
+        if !head.eq_ignore_ascii_case("USING ") {
+            continue;
+        }
+        let Some(name) = line[6..].split_ascii_whitespace().next() else {
+            continue;
+        };
+        let name = name.strip_suffix('.').unwrap_or(name);
+        if let Some(package) = name.strip_suffix(".*") {
+            if !package.is_empty() {
+                out.push(UsingImport {
+                    name: package.to_string(),
+                    is_package: true,
+                });
+            }
+        } else if !name.is_empty() {
+            out.push(UsingImport {
+                name: name.to_string(),
+                is_package: false,
+            });
+        }
+    }
+    out
+}
+
+/// Short class names made available by `USING` imports, paired with their qualified names.
+///
+/// `package_classes` lists the classes found on disk for a package import.
+pub fn imported_class_names(
+    imports: &[UsingImport],
+    package_classes: impl Fn(&str) -> Vec<String>,
+) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    for import in imports {
+        if import.is_package {
+            for class in package_classes(&import.name) {
+                out.push((class.clone(), format!("{}.{class}", import.name)));
+            }
+        } else if let Some(short) = import.name.rsplit('.').next() {
+            out.push((short.to_string(), import.name.clone()));
+        }
+    }
+    out
+}
+
+/// Fully qualified names a (possibly short) class reference may stand for, most specific first.
+pub fn class_name_candidates(name: &str, imports: &[UsingImport]) -> Vec<String> {
+    if name.contains('.') {
+        return vec![name.to_string()];
+    }
+    let mut out = imports
+        .iter()
+        .filter(|import| {
+            !import.is_package
+                && import
+                    .name
+                    .rsplit('.')
+                    .next()
+                    .is_some_and(|short| short.eq_ignore_ascii_case(name))
+        })
+        .map(|import| import.name.clone())
+        .collect::<Vec<_>>();
+    out.extend(
+        imports
+            .iter()
+            .filter(|import| import.is_package)
+            .map(|import| format!("{}.{name}", import.name)),
+    );
+    out.push(name.to_string());
+    out
+}
+
+/// Returns the method call at `offset`; calls on other objects (`obj:m()`, `Cls.m()`) are skipped.
+pub fn method_call_at_offset(text: &str, offset: usize) -> Option<MethodCallAtOffset> {
+    let bytes = text.as_bytes();
+    let is_name = |b: u8| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-');
+    let offset = offset.min(bytes.len());
+
+    let mut start = offset;
+    while start > 0 && is_name(bytes[start - 1]) {
+        start -= 1;
+    }
+    let mut end = offset;
+    while end < bytes.len() && is_name(bytes[end]) {
+        end += 1;
+    }
+    if start == end || !bytes[start].is_ascii_alphabetic() {
+        return None;
+    }
+
+    let inherited_only = match bytes[..start].last() {
+        Some(b':') => {
+            let before = &text[..start - 1];
+            let qualifier_start = before
+                .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+                .map(|i| i + 1)
+                .unwrap_or(0);
+            match before[qualifier_start..].to_ascii_uppercase().as_str() {
+                "THIS-OBJECT" => false,
+                "SUPER" => true,
+                _ => return None,
+            }
+        }
+        Some(b'.') => return None,
+        _ => false,
+    };
+
+    let after = text[end..].trim_start();
+    let arg_count = after.starts_with('(').then(|| {
+        let open = text.len() - after.len();
+        split_argument_offsets(bytes, open, bytes.len()).len()
+    });
+    if arg_count.is_none() && bytes[..start].last() != Some(&b':') {
+        return None;
+    }
+
+    Some(MethodCallAtOffset {
+        name: text[start..end].to_string(),
+        arg_count,
+        inherited_only,
+    })
+}
+
+/// Name site of the method `name` declared in `class_node`, preferring the overload whose
+/// parameter count matches `arg_count`.
+pub fn find_class_method_site(
+    class_node: Node<'_>,
+    src: &[u8],
+    name: &str,
+    arg_count: Option<usize>,
+) -> Option<AblDefinitionSite> {
+    let mut methods = Vec::new();
+    collect_class_method_nodes(class_node, &mut methods);
+    let candidates = methods
+        .into_iter()
+        .filter_map(|method| {
+            let mut sites = Vec::new();
+            collect_definition_sites(method, src, &mut sites);
+            let site = sites.into_iter().next()?;
+            site.label
+                .eq_ignore_ascii_case(name)
+                .then(|| (function_param_count(method, src), site))
+        })
+        .collect::<Vec<_>>();
+
+    let matching =
+        arg_count.and_then(|count| candidates.iter().position(|(arity, _)| *arity == count));
+    let index = matching.unwrap_or(0);
+    candidates.into_iter().nth(index).map(|(_, site)| site)
+}
+
+fn collect_class_method_nodes<'a>(node: Node<'a>, out: &mut Vec<Node<'a>>) {
+    for i in 0..node.child_count() {
+        let Some(ch) = node.child(i as u32) else {
+            continue;
+        };
+        match ch.kind() {
+            "method_definition" => out.push(ch),
+            "class_definition" | "interface_definition" => {}
+            _ => collect_class_method_nodes(ch, out),
+        }
+    }
+}
+
+/// Returns the dotted class name at `offset` when it follows `NEW`, `AS`, `INHERITS` or `IMPLEMENTS`.
+pub fn class_type_reference_at_offset(text: &str, offset: usize) -> Option<String> {
+    let bytes = text.as_bytes();
+    let is_name = |b: u8| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'.');
+    let offset = offset.min(bytes.len());
+
+    let mut start = offset;
+    while start > 0 && is_name(bytes[start - 1]) {
+        start -= 1;
+    }
+    let mut end = offset;
+    while end < bytes.len() && is_name(bytes[end]) {
+        end += 1;
+    }
+    let name = text[start..end].trim_matches('.');
+    if name.is_empty() || !name.as_bytes()[0].is_ascii_alphabetic() {
+        return None;
+    }
+
+    let before = text[..start].trim_end();
+    let keyword_start = before
+        .rfind(|c: char| !c.is_ascii_alphabetic())
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let keyword = &before[keyword_start..];
+    ["NEW", "AS", "INHERITS", "IMPLEMENTS"]
+        .iter()
+        .any(|k| keyword.eq_ignore_ascii_case(k))
+        .then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        class_implements_names, class_inherits_name, class_name_candidates,
+        class_name_to_relative_path, class_reference_matches, class_type_reference_at_offset,
+        collect_class_members, collect_inherited_class_members, collect_using_imports,
+        enclosing_class_definition, find_class_method_site, implementing_method_ranges,
+        imported_class_names, interface_declaring_method, method_call_at_offset,
+    };
+    use crate::analysis::parse_abl;
+    use tower_lsp::lsp_types::CompletionItemKind;
+
+    #[test]
+    fn collects_class_members_and_parent_class() {
+        let src = r#"
+CLASS app.Child INHERITS app.Base:
+  DEFINE PUBLIC PROPERTY Name AS CHARACTER NO-UNDO GET. SET.
+  METHOD PUBLIC VOID Run():
+  END METHOD.
+END CLASS.
+"#;
+        let tree = parse_abl(src);
+        let class = enclosing_class_definition(tree.root_node(), src.find("Run").expect("method"))
+            .expect("class definition");
+
+        let mut members = Vec::new();
+        collect_class_members(class, src.as_bytes(), &mut members);
+        assert!(
+            members
+                .iter()
+                .any(|m| m.label == "Name" && m.kind == CompletionItemKind::PROPERTY)
+        );
+        assert!(
+            members
+                .iter()
+                .any(|m| m.label == "Run" && m.kind == CompletionItemKind::METHOD)
+        );
+        assert_eq!(
+            class_inherits_name(class, src.as_bytes()).as_deref(),
+            Some("app.Base")
+        );
+    }
+
+    #[test]
+    fn inherited_members_skip_private_ones() {
+        let src = r#"
+CLASS app.Base:
+  DEFINE PRIVATE PROPERTY Secret AS CHARACTER NO-UNDO GET. SET.
+  DEFINE PROTECTED PROPERTY Shared AS CHARACTER NO-UNDO GET. SET.
+  METHOD PRIVATE VOID Helper():
+  END METHOD.
+  METHOD PUBLIC VOID Run():
+  END METHOD.
+END CLASS.
+"#;
+        let tree = parse_abl(src);
+        let class = enclosing_class_definition(tree.root_node(), usize::MAX).expect("class");
+
+        let mut all = Vec::new();
+        collect_class_members(class, src.as_bytes(), &mut all);
+        assert_eq!(all.len(), 4);
+
+        let mut inherited = Vec::new();
+        collect_inherited_class_members(class, src.as_bytes(), &mut inherited);
+        let labels = inherited
+            .iter()
+            .map(|m| m.label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(labels, vec!["Shared", "Run"]);
+    }
+
+    #[test]
+    fn maps_class_name_to_relative_path() {
+        assert_eq!(
+            class_name_to_relative_path("app.util.Base"),
+            "app/util/Base.cls"
+        );
+    }
+
+    #[test]
+    fn finds_class_references_after_new_and_as() {
+        let src = "DEFINE VARIABLE o AS Progress.Json.ObjectModel.JsonArray NO-UNDO.\no = NEW JsonArray(x).\n";
+        assert_eq!(
+            class_type_reference_at_offset(src, src.find("ObjectModel").expect("as")).as_deref(),
+            Some("Progress.Json.ObjectModel.JsonArray")
+        );
+        assert_eq!(
+            class_type_reference_at_offset(src, src.find("JsonArray(").expect("new")).as_deref(),
+            Some("JsonArray")
+        );
+        assert_eq!(
+            class_type_reference_at_offset(src, src.find("x)").expect("arg")),
+            None
+        );
+    }
+
+    #[test]
+    fn expands_short_class_names_through_using_imports() {
+        let src = "USING Progress.Json.ObjectModel.*.\nusing app.util.Strings FROM PROPATH.\n";
+        let imports = collect_using_imports(src);
+        assert_eq!(imports.len(), 2);
+        assert!(imports[0].is_package);
+        assert_eq!(imports[1].name, "app.util.Strings");
+
+        assert_eq!(
+            class_name_candidates("JsonArray", &imports),
+            vec!["Progress.Json.ObjectModel.JsonArray", "JsonArray"]
+        );
+        assert_eq!(
+            class_name_candidates("strings", &imports),
+            vec![
+                "app.util.Strings",
+                "Progress.Json.ObjectModel.strings",
+                "strings"
+            ]
+        );
+        assert_eq!(class_name_candidates("a.B", &imports), vec!["a.B"]);
+
+        let names = imported_class_names(&imports, |package| {
+            assert_eq!(package, "Progress.Json.ObjectModel");
+            vec!["JsonObject".to_string()]
+        });
+        assert_eq!(
+            names,
+            vec![
+                (
+                    "JsonObject".to_string(),
+                    "Progress.Json.ObjectModel.JsonObject".to_string()
+                ),
+                ("Strings".to_string(), "app.util.Strings".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_interface_methods_and_implementers() {
+        let iface_src = r#"
+INTERFACE app.IShape:
+  METHOD PUBLIC DECIMAL Area().
+END INTERFACE.
+"#;
+        let tree = parse_abl(iface_src);
+        let offset = iface_src.find("Area").expect("method");
+        assert_eq!(
+            interface_declaring_method(tree.root_node(), iface_src.as_bytes(), offset, "area")
+                .as_deref(),
+            Some("app.IShape")
+        );
+        assert_eq!(
+            interface_declaring_method(tree.root_node(), iface_src.as_bytes(), offset, "Perimeter"),
+            None
+        );
+
+        let class_src = r#"
+USING app.*.
+CLASS app.Circle INHERITS app.Base IMPLEMENTS IShape, app.IDrawable FINAL:
+  METHOD PUBLIC DECIMAL Area():
+  END METHOD.
+END CLASS.
+"#;
+        let tree = parse_abl(class_src);
+        let class = enclosing_class_definition(tree.root_node(), 0).expect("class");
+        let implements = class_implements_names(class, class_src.as_bytes());
+        assert_eq!(implements, vec!["IShape", "app.IDrawable"]);
+
+        let imports = collect_using_imports(class_src);
+        assert!(class_reference_matches(
+            &implements[0],
+            &imports,
+            "app.IShape"
+        ));
+        assert!(!class_reference_matches(
+            &implements[1],
+            &imports,
+            "app.IShape"
+        ));
+        assert!(class_reference_matches("app.IShape", &[], "IShape"));
+
+        let ranges = implementing_method_ranges(tree.root_node(), class_src, "app.IShape", "area");
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0].start.line, 3);
+        assert!(
+            implementing_method_ranges(tree.root_node(), class_src, "app.IOther", "Area")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn finds_method_calls_on_the_current_object() {
+        let text = "x = THIS-OBJECT:getName(1, 2). SUPER:init(). obj:run(). calc(3).";
+        let call = method_call_at_offset(text, text.find("getName").expect("call") + 2)
+            .expect("this-object call");
+        assert_eq!(call.name, "getName");
+        assert_eq!(call.arg_count, Some(2));
+        assert!(!call.inherited_only);
+
+        let call = method_call_at_offset(text, text.find("init").expect("call")).expect("super");
+        assert_eq!(call.arg_count, Some(0));
+        assert!(call.inherited_only);
+
+        assert!(method_call_at_offset(text, text.find("run").expect("call")).is_none());
+        let call = method_call_at_offset(text, text.find("calc").expect("call")).expect("plain");
+        assert_eq!(call.arg_count, Some(1));
+        assert!(method_call_at_offset(text, text.find("x =").expect("var")).is_none());
+    }
+
+    #[test]
+    fn picks_the_overload_matching_the_argument_count() {
+        let src = r#"
+CLASS app.Calc:
+  METHOD PUBLIC INTEGER add(INPUT a AS INTEGER):
+    RETURN a.
+  END METHOD.
+  METHOD PUBLIC INTEGER add(INPUT a AS INTEGER, INPUT b AS INTEGER):
+    RETURN a + b.
+  END METHOD.
+END CLASS.
+"#;
+        let tree = parse_abl(src);
+        let class = enclosing_class_definition(tree.root_node(), 0).expect("class");
+        let second = src.rfind("add(").expect("second overload");
+
+        let site = find_class_method_site(class, src.as_bytes(), "ADD", Some(2)).expect("site");
+        assert_eq!(site.start_byte, second);
+        let site = find_class_method_site(class, src.as_bytes(), "add", Some(5)).expect("site");
+        assert_eq!(site.start_byte, src.find("add(").expect("first overload"));
+        assert!(find_class_method_site(class, src.as_bytes(), "sub", None).is_none());
+    }
+}
@@ -0,0 +1,312 @@
+use tower_lsp::lsp_types::{Range, TextEdit};
+use tree_sitter::Node;
+
+use crate::analysis::diagnostics::no_undo::{has_no_undo, is_no_undo_definition};
+use crate::analysis::diagnostics::types::{first_assignment_rhs, infer_first_assignment_type};
+use crate::analysis::scopes::{containing_scope_node, is_scope_node};
+use crate::analysis::types::BasicType;
+use crate::utils::position::utf8_byte_offset_to_lsp_pos;
+
+/// Extracts `X` from an `Unknown variable 'X'` diagnostic message.
+pub fn unknown_variable_name_from_message(message: &str) -> Option<&str> {
+    message
+        .strip_prefix("Unknown variable '")?
+        .strip_suffix('\'')
+        .filter(|name| !name.is_empty())
+}
+
+/// Whether a diagnostic message comes from the `require_no_undo` lint.
+pub fn is_missing_no_undo_message(message: &str) -> bool {
+    message.ends_with("' is defined without NO-UNDO")
+}
+
+/// Builds the edit adding `NO-UNDO` to the variable/temp-table definition named at `offset`.
+///
+/// Temp-tables get it after their name, variables before the terminating period.
+pub fn insert_no_undo_edit(root: Node<'_>, text: &str, offset: usize) -> Option<TextEdit> {
+    let mut definition = root.named_descendant_for_byte_range(offset, offset)?;
+    while !is_no_undo_definition(definition.kind()) {
+        definition = definition.parent()?;
+    }
+    if has_no_undo(definition, text.as_bytes()) {
+        return None;
+    }
+
+    let insert_at = if definition.kind() == "temp_table_definition" {
+        definition.child_by_field_name("name")?.end_byte()
+    } else {
+        let statement = text.get(definition.start_byte()..definition.end_byte())?;
+        let body = statement.trim_end();
+        definition.start_byte() + body.strip_suffix('.').unwrap_or(body).trim_end().len()
+    };
+    let pos = utf8_byte_offset_to_lsp_pos(text, insert_at);
+    Some(TextEdit {
+        range: Range::new(pos, pos),
+        new_text: " NO-UNDO".to_string(),
+    })
+}
+
+/// Builds the edit inserting `DEFINE VARIABLE <name> ...` at the top of the enclosing scope.
+pub fn create_variable_edit(root: Node<'_>, text: &str, name: &str, offset: usize) -> TextEdit {
+    let name_upper = name.to_ascii_uppercase();
+    let data_type = match infer_first_assignment_type(root, text.as_bytes(), &name_upper) {
+        Some(BasicType::Numeric) => numeric_definition_type(root, text, &name_upper),
+        Some(BasicType::Logical) => "LOGICAL",
+        Some(BasicType::DateLike) => "DATE",
+        Some(BasicType::Handle) => "HANDLE",
+        Some(BasicType::Character) | None => "CHARACTER",
+    };
+
+    let insert_at = definition_insert_offset(root, text, offset);
+    let indent = line_indent_at(text, insert_at);
+    TextEdit {
+        range: Range::new(
+            utf8_byte_offset_to_lsp_pos(text, insert_at),
+            utf8_byte_offset_to_lsp_pos(text, insert_at),
+        ),
+        new_text: format!("{indent}DEFINE VARIABLE {name} AS {data_type} NO-UNDO.\n"),
+    }
+}
+
+/// Returns the start of the line after a scope header, or the start of the file.
+fn definition_insert_offset(root: Node<'_>, text: &str, offset: usize) -> usize {
+    let Some(scope) = containing_scope_node(root, offset) else {
+        return 0;
+    };
+    if scope == root {
+        return 0;
+    }
+
+    // The header ends at the scope's own `:` token; colons in strings and comments are not
+    // children of the scope node.
+    let mut cursor = scope.walk();
+    let Some(colon) = scope.children(&mut cursor).find(|ch| ch.kind() == ":") else {
+        return scope.start_byte();
+    };
+    text[colon.end_byte()..]
+        .find('\n')
+        .map(|nl| colon.end_byte() + nl + 1)
+        .unwrap_or(text.len())
+}
+
+/// Moves `DEFINE VARIABLE`/`DEFINE BUFFER` statements of the scope at `offset` ahead of its code.
+///
+/// Returns the replaced byte range and its new text, or `None` when nothing moves or the move is unsafe.
+pub fn organize_defines_edit(
+    root: Node<'_>,
+    text: &str,
+    offset: usize,
+) -> Option<(usize, usize, String)> {
+    let scope = containing_scope_node(root, offset)?;
+
+    let mut defines = Vec::new();
+    collect_scope_defines(scope, true, &mut defines);
+    let parent = defines.first()?.parent()?;
+    if defines.iter().any(|d| d.parent() != Some(parent)) {
+        return None;
+    }
+
+    let mut siblings = Vec::new();
+    for i in 0..parent.named_child_count() {
+        if let Some(ch) = parent.named_child(i as u32) {
+            siblings.push(ch);
+        }
+    }
+    let first_code = siblings
+        .iter()
+        .position(|n| !is_movable_define(n.kind()) && n.kind() != "comment")?;
+    let last_define = siblings
+        .iter()
+        .rposition(|n| is_movable_define(n.kind()))
+        .filter(|idx| *idx > first_code)?;
+
+    // Definitions must not jump over other definitions or preprocessor state they may depend on.
+    let crossed = &siblings[first_code..=last_define];
+    if crossed.iter().any(|n| {
+        n.kind().contains("preprocessor")
+            || (n.kind().ends_with("_definition") && !is_movable_define(n.kind()))
+    }) {
+        return None;
+    }
+
+    let (region_start, _) = owned_line_span(text, crossed[0])?;
+    let (_, region_end) = owned_line_span(text, siblings[last_define])?;
+    if text[region_start..region_end]
+        .lines()
+        .any(|line| line.trim_start().starts_with('&'))
+    {
+        return None;
+    }
+
+    let mut moved = String::new();
+    let mut rest = String::new();
+    let mut cursor = region_start;
+    for node in crossed.iter().filter(|n| is_movable_define(n.kind())) {
+        let (start, end) = owned_line_span(text, *node)?;
+        if text[start..end].contains('{') {
+            return None;
+        }
+        rest.push_str(&text[cursor..start]);
+        moved.push_str(&text[start..end]);
+        if !moved.ends_with('\n') {
+            moved.push('\n');
+        }
+        cursor = end;
+    }
+    rest.push_str(&text[cursor..region_end]);
+    moved.push_str(&rest);
+
+    Some((region_start, region_end, moved))
+}
+
+fn is_movable_define(kind: &str) -> bool {
+    matches!(kind, "variable_definition" | "buffer_definition")
+}
+
+fn collect_scope_defines<'tree>(node: Node<'tree>, is_root: bool, out: &mut Vec<Node<'tree>>) {
+    if !is_root && is_scope_node(node.kind()) {
+        return;
+    }
+    if is_movable_define(node.kind()) {
+        out.push(node);
+        return;
+    }
+    for i in 0..node.named_child_count() {
+        if let Some(ch) = node.named_child(i as u32) {
+            collect_scope_defines(ch, false, out);
+        }
+    }
+}
+
+/// Returns the full-line span of `node` if it does not share its lines with other code.
+fn owned_line_span(text: &str, node: Node<'_>) -> Option<(usize, usize)> {
+    let start = text[..node.start_byte()]
+        .rfind('\n')
+        .map(|nl| nl + 1)
+        .unwrap_or(0);
+    let end = text[node.end_byte()..]
+        .find('\n')
+        .map(|nl| node.end_byte() + nl + 1)
+        .unwrap_or(text.len());
+    let owned = text[start..node.start_byte()].trim().is_empty()
+        && text[node.end_byte()..end].trim().is_empty();
+    owned.then_some((start, end))
+}
+
+fn line_indent_at(text: &str, line_start: usize) -> String {
+    text.get(line_start..)
+        .and_then(|rest| rest.lines().find(|line| !line.trim().is_empty()))
+        .map(|line| {
+            line.chars()
+                .take_while(|c| *c == ' ' || *c == '\t')
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn numeric_definition_type(root: Node<'_>, text: &str, name_upper: &str) -> &'static str {
+    let rhs_is_integer_literal = first_assignment_rhs(root, text.as_bytes(), name_upper)
+        .and_then(|right| Some((right, right.utf8_text(text.as_bytes()).ok()?)))
+        .is_some_and(|(right, raw)| right.kind() == "number_literal" && !raw.contains('.'));
+    if rhs_is_integer_literal {
+        "INTEGER"
+    } else {
+        "DECIMAL"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        create_variable_edit, insert_no_undo_edit, is_missing_no_undo_message,
+        organize_defines_edit, unknown_variable_name_from_message,
+    };
+    use crate::analysis::parse_abl;
+    use tower_lsp::lsp_types::Position;
+
+    #[test]
+    fn extracts_unknown_variable_name() {
+        assert_eq!(
+            unknown_variable_name_from_message("Unknown variable 'lv_total'"),
+            Some("lv_total")
+        );
+        assert_eq!(
+            unknown_variable_name_from_message("Unknown table 'x'"),
+            None
+        );
+    }
+
+    #[test]
+    fn inserts_typed_definition_at_top_of_procedure() {
+        let src = "PROCEDURE calc:\n  lv_total = 5.\nEND PROCEDURE.\n";
+        let tree = parse_abl(src);
+        let offset = src.find("lv_total").expect("ref");
+
+        let edit = create_variable_edit(tree.root_node(), src, "lv_total", offset);
+        assert_eq!(edit.range.start, Position::new(1, 0));
+        assert_eq!(
+            edit.new_text,
+            "  DEFINE VARIABLE lv_total AS INTEGER NO-UNDO.\n"
+        );
+    }
+
+    #[test]
+    fn skips_quoted_and_commented_colons_in_scope_headers() {
+        let src = "FUNCTION fmt RETURNS CHARACTER /* 'a:b' */ (INPUT p AS CHARACTER):\n  lv_total = 5.\n  RETURN p.\nEND FUNCTION.\n";
+        let tree = parse_abl(src);
+        let offset = src.find("lv_total").expect("ref");
+
+        let edit = create_variable_edit(tree.root_node(), src, "lv_total", offset);
+        assert_eq!(edit.range.start, Position::new(1, 0));
+    }
+
+    #[test]
+    fn inserts_no_undo_into_definitions() {
+        assert!(is_missing_no_undo_message(
+            "'lvName' is defined without NO-UNDO"
+        ));
+        assert!(!is_missing_no_undo_message("Unknown variable 'lvName'"));
+
+        let src = "DEFINE VARIABLE lvName AS CHARACTER INITIAL \"x\".\nDEFINE TEMP-TABLE ttItem\n  FIELD id AS INTEGER.\nDEFINE VARIABLE lvDone AS LOGICAL NO-UNDO.\n";
+        let tree = parse_abl(src);
+
+        let edit = insert_no_undo_edit(tree.root_node(), src, src.find("lvName").expect("var"))
+            .expect("variable edit");
+        assert_eq!(edit.range.start, Position::new(0, 47));
+        assert_eq!(edit.new_text, " NO-UNDO");
+
+        let edit = insert_no_undo_edit(tree.root_node(), src, src.find("ttItem").expect("tt"))
+            .expect("temp-table edit");
+        assert_eq!(edit.range.start, Position::new(1, 24));
+
+        assert!(
+            insert_no_undo_edit(tree.root_node(), src, src.find("lvDone").expect("var")).is_none()
+        );
+    }
+
+    #[test]
+    fn moves_defines_ahead_of_code_in_procedure() {
+        let src = "PROCEDURE calc:\n  DEFINE VARIABLE a AS INTEGER NO-UNDO.\n  a = 1.\n  DEFINE BUFFER bCust FOR Customer.\n  DEFINE VARIABLE b AS INTEGER NO-UNDO.\n  b = a.\nEND PROCEDURE.\n";
+        let tree = parse_abl(src);
+        let offset = src.find("b = a").expect("offset");
+
+        let (start, end, new_text) =
+            organize_defines_edit(tree.root_node(), src, offset).expect("edit");
+        let mut organized = src.to_string();
+        organized.replace_range(start..end, &new_text);
+        assert_eq!(
+            organized,
+            "PROCEDURE calc:\n  DEFINE VARIABLE a AS INTEGER NO-UNDO.\n  DEFINE BUFFER bCust FOR Customer.\n  DEFINE VARIABLE b AS INTEGER NO-UNDO.\n  a = 1.\n  b = a.\nEND PROCEDURE.\n"
+        );
+
+        let tree = parse_abl(&organized);
+        assert!(organize_defines_edit(tree.root_node(), &organized, 0).is_none());
+    }
+
+    #[test]
+    fn keeps_defines_behind_preprocessor_directives() {
+        let src = "x = 1.\n&SCOPED-DEFINE T INTEGER\nDEFINE VARIABLE y AS {&T} NO-UNDO.\n";
+        let tree = parse_abl(src);
+        assert!(organize_defines_edit(tree.root_node(), src, 0).is_none());
+    }
+}
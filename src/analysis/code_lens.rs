@@ -0,0 +1,107 @@
+use tower_lsp::lsp_types::Range;
+use tree_sitter::Node;
+
+use crate::analysis::diagnostics::functions::{FunctionCallSite, collect_function_calls};
+use crate::analysis::functions::{normalize_function_name, procedure_arity_key};
+use crate::utils::ts::node_to_range;
+
+/// A function/procedure definition and the call sites that target it.
+pub struct ReferenceLensSite {
+    pub name: String,
+    pub name_range: Range,
+    pub references: Vec<Range>,
+}
+
+/// Function/procedure definitions of a document with the calls made in that document.
+pub fn document_reference_lens_sites(root: Node<'_>, src: &[u8]) -> Vec<ReferenceLensSite> {
+    let mut calls = Vec::new();
+    collect_function_calls(root, src, &mut calls);
+    let mut sites = Vec::new();
+    collect_reference_lens_sites(root, src, &calls, &mut sites);
+    sites
+}
+
+/// Pairs each `FUNCTION`/`PROCEDURE` definition with its calls from `collect_function_calls`.
+pub fn collect_reference_lens_sites(
+    node: Node<'_>,
+    src: &[u8],
+    calls: &[FunctionCallSite],
+    out: &mut Vec<ReferenceLensSite>,
+) {
+    if matches!(node.kind(), "function_definition" | "procedure_definition")
+        && let Some(name_node) = node.child_by_field_name("name")
+        && let Ok(name) = name_node.utf8_text(src)
+        && !name.trim().is_empty()
+    {
+        let key = if node.kind() == "function_definition" {
+            normalize_function_name(name)
+        } else {
+            procedure_arity_key(name)
+        };
+        out.push(ReferenceLensSite {
+            name: name.trim().to_string(),
+            name_range: node_to_range(name_node),
+            references: calls
+                .iter()
+                .filter(|call| call.name_upper == key)
+                .map(|call| call.range)
+                .collect(),
+        });
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_reference_lens_sites(ch, src, calls, out);
+        }
+    }
+}
+
+pub fn reference_lens_title(count: usize) -> String {
+    if count == 1 {
+        "1 reference".to_string()
+    } else {
+        format!("{count} references")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{document_reference_lens_sites, reference_lens_title};
+    use crate::analysis::parse_abl;
+
+    #[test]
+    fn counts_calls_of_functions_and_procedures() {
+        let src = r#"
+FUNCTION add_one RETURNS INTEGER (INPUT p AS INTEGER):
+  RETURN p + 1.
+END FUNCTION.
+
+PROCEDURE compute:
+  DEFINE VARIABLE x AS INTEGER NO-UNDO.
+  x = add_one(x).
+  x = add_one(add_one(x)).
+END PROCEDURE.
+
+PROCEDURE unused:
+END PROCEDURE.
+
+RUN compute.
+"#;
+        let tree = parse_abl(src);
+        let sites = document_reference_lens_sites(tree.root_node(), src.as_bytes());
+
+        let counts = sites
+            .iter()
+            .map(|site| (site.name.as_str(), site.references.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, vec![("add_one", 3), ("compute", 1), ("unused", 0)]);
+        assert_eq!(sites[1].references[0].start.line, 14);
+    }
+
+    #[test]
+    fn pluralizes_lens_title() {
+        assert_eq!(reference_lens_title(0), "0 references");
+        assert_eq!(reference_lens_title(1), "1 reference");
+        assert_eq!(reference_lens_title(4), "4 references");
+    }
+}
@@ -3,13 +3,50 @@ use tower_lsp::lsp_types::Documentation;
 use tree_sitter::Node;
 
 pub fn qualifier_before_dot(text: &str, offset: usize, prefix: &str) -> Option<String> {
+    qualifier_before_separator(text, offset, prefix, b'.')
+}
+
+pub fn qualifier_before_colon(text: &str, offset: usize, prefix: &str) -> Option<String> {
+    qualifier_before_separator(text, offset, prefix, b':')
+}
+
+/// Like [`qualifier_before_colon`], but keeps a package prefix (`Acme.Color:` -> `Acme.Color`).
+pub fn type_qualifier_before_colon(text: &str, offset: usize, prefix: &str) -> Option<String> {
+    let short = qualifier_before_colon(text, offset, prefix)?;
+    let end = offset - prefix.len() - 1;
+    let bytes = text.as_bytes();
+    let is_ident = |c: u8| c.is_ascii_alphanumeric() || c == b'_' || c == b'-';
+    let mut start = end - short.len();
+    while start > 1 && bytes[start - 1] == b'.' && is_ident(bytes[start - 2]) {
+        start -= 1;
+        while start > 0 && is_ident(bytes[start - 1]) {
+            start -= 1;
+        }
+    }
+    Some(text[start..end].to_string())
+}
+
+/// True for symbol details naming a handle type (`HANDLE`, `WIDGET-HANDLE`, `SHARED HANDLE NO-UNDO`, ...).
+pub fn is_handle_type_detail(detail: &str) -> bool {
+    detail.split_ascii_whitespace().any(|token| {
+        let upper = token.to_ascii_uppercase();
+        upper == "HANDLE" || upper.ends_with("-HANDLE")
+    })
+}
+
+fn qualifier_before_separator(
+    text: &str,
+    offset: usize,
+    prefix: &str,
+    separator: u8,
+) -> Option<String> {
     let bytes = text.as_bytes();
     let prefix_len = prefix.len();
     if offset < prefix_len + 1 {
         return None;
     }
     let dot_pos = offset - prefix_len - 1;
-    if bytes.get(dot_pos).copied() != Some(b'.') {
+    if bytes.get(dot_pos).copied() != Some(separator) {
         return None;
     }
 
@@ -29,6 +66,62 @@ pub fn qualifier_before_dot(text: &str, offset: usize, prefix: &str) -> Option<S
     Some(text[start..dot_pos].to_string())
 }
 
+/// Returns the partially typed path when the cursor is inside `{path` (not `{&macro`).
+pub fn include_path_prefix(text: &str, offset: usize) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut start = offset.min(bytes.len());
+    while start > 0 {
+        let c = bytes[start - 1];
+        let is_path = c.is_ascii_alphanumeric() || matches!(c, b'_' | b'-' | b'.' | b'/' | b'\\');
+        if !is_path {
+            break;
+        }
+        start -= 1;
+    }
+    if start == 0 || bytes[start - 1] != b'{' {
+        return None;
+    }
+    Some(text[start..offset].to_string())
+}
+
+/// Returns the partially typed name when the cursor is inside `{&name`.
+pub fn preprocessor_reference_prefix(text: &str, offset: usize) -> Option<String> {
+    let bytes = text.as_bytes();
+    let offset = offset.min(bytes.len());
+    let mut start = offset;
+    while start > 0 {
+        let c = bytes[start - 1];
+        if !(c.is_ascii_alphanumeric() || matches!(c, b'_' | b'-')) {
+            break;
+        }
+        start -= 1;
+    }
+    if start < 2 || &bytes[start - 2..start] != b"{&" {
+        return None;
+    }
+    Some(text[start..offset].to_string())
+}
+
+/// Distinct identifier-like words of `text` extending `prefix` (case-insensitive), sorted and capped.
+/// A tree-free fallback for documents too large to analyze.
+pub fn document_words_with_prefix(text: &str, prefix: &str, limit: usize) -> Vec<String> {
+    let pref_up = prefix.to_ascii_uppercase();
+    let mut seen = std::collections::HashSet::new();
+    let mut words = text
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        .filter(|word| {
+            word.len() > prefix.len()
+                && word.starts_with(|c: char| c.is_ascii_alphabetic())
+                && word.to_ascii_uppercase().starts_with(&pref_up)
+        })
+        .filter(|word| seen.insert(word.to_ascii_uppercase()))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    words.sort_by_key(|word| word.to_ascii_uppercase());
+    words.truncate(limit);
+    words
+}
+
 pub fn text_has_dot_before_cursor(text: &str, offset: usize) -> bool {
     if offset == 0 {
         return false;
@@ -36,70 +129,40 @@ pub fn text_has_dot_before_cursor(text: &str, offset: usize) -> bool {
     text.as_bytes().get(offset - 1).copied() == Some(b'.')
 }
 
+/// Fields of a table; `db_fields_by_table` is keyed by upper-cased table names at load.
 pub fn lookup_case_insensitive_fields(
     map: &dashmap::DashMap<String, Vec<DbFieldInfo>>,
     key: &str,
 ) -> Option<Vec<DbFieldInfo>> {
-    map.get(key)
+    map.get(&key.trim().to_ascii_uppercase())
         .map(|fields| fields.value().clone())
-        .or_else(|| {
-            map.iter().find_map(|entry| {
-                if entry.key().eq_ignore_ascii_case(key) {
-                    Some(entry.value().clone())
-                } else {
-                    None
-                }
-            })
-        })
 }
 
+/// Like `lookup_case_insensitive_fields`, also accepting a `dbname.table` symbol.
 pub fn lookup_case_insensitive_fields_by_table_symbol(
     map: &dashmap::DashMap<String, Vec<DbFieldInfo>>,
     key: &str,
 ) -> Option<Vec<DbFieldInfo>> {
-    lookup_case_insensitive_fields(map, key).or_else(|| {
-        let key_tail = unqualified_table_name(key);
-        map.iter().find_map(|entry| {
-            if unqualified_table_name(entry.key()).eq_ignore_ascii_case(key_tail) {
-                Some(entry.value().clone())
-            } else {
-                None
-            }
-        })
-    })
+    lookup_case_insensitive_fields(map, key)
+        .or_else(|| lookup_case_insensitive_fields(map, unqualified_table_name(key)))
 }
 
+/// Indexes of a table; `db_indexes_by_table` is keyed by upper-cased table names at load.
 pub fn lookup_case_insensitive_indexes_by_table(
     map: &dashmap::DashMap<String, Vec<String>>,
     key: &str,
 ) -> Option<Vec<String>> {
-    map.get(key)
+    map.get(&key.trim().to_ascii_uppercase())
         .map(|indexes| indexes.value().clone())
-        .or_else(|| {
-            map.iter().find_map(|entry| {
-                if entry.key().eq_ignore_ascii_case(key) {
-                    Some(entry.value().clone())
-                } else {
-                    None
-                }
-            })
-        })
 }
 
+/// Like `lookup_case_insensitive_indexes_by_table`, also accepting a `dbname.table` symbol.
 pub fn lookup_case_insensitive_indexes_by_table_symbol(
     map: &dashmap::DashMap<String, Vec<String>>,
     key: &str,
 ) -> Option<Vec<String>> {
-    lookup_case_insensitive_indexes_by_table(map, key).or_else(|| {
-        let key_tail = unqualified_table_name(key);
-        map.iter().find_map(|entry| {
-            if unqualified_table_name(entry.key()).eq_ignore_ascii_case(key_tail) {
-                Some(entry.value().clone())
-            } else {
-                None
-            }
-        })
-    })
+    lookup_case_insensitive_indexes_by_table(map, key)
+        .or_else(|| lookup_case_insensitive_indexes_by_table(map, unqualified_table_name(key)))
 }
 
 fn unqualified_table_name(name: &str) -> &str {
@@ -249,10 +312,12 @@ pub fn field_documentation(field: &DbFieldInfo) -> Option<Documentation> {
 #[cfg(test)]
 mod tests {
     use super::{
-        field_detail, field_documentation, lookup_case_insensitive_fields,
+        document_words_with_prefix, field_detail, field_documentation, include_path_prefix,
+        is_handle_type_detail, lookup_case_insensitive_fields,
         lookup_case_insensitive_fields_by_table_symbol, lookup_case_insensitive_indexes_by_table,
-        lookup_case_insensitive_indexes_by_table_symbol, qualifier_before_dot,
-        text_has_dot_before_cursor, use_index_table_symbol_at_offset,
+        lookup_case_insensitive_indexes_by_table_symbol, preprocessor_reference_prefix,
+        qualifier_before_colon, qualifier_before_dot, text_has_dot_before_cursor,
+        type_qualifier_before_colon, use_index_table_symbol_at_offset,
         use_index_table_symbol_in_statement_prefix,
     };
     use crate::analysis::parse_abl;
@@ -260,6 +325,20 @@ mod tests {
     use dashmap::DashMap;
     use tower_lsp::lsp_types::Documentation;
 
+    #[test]
+    fn keeps_package_prefix_for_type_qualifiers() {
+        let text = "x = Acme.Util.Color:Re";
+        assert_eq!(
+            type_qualifier_before_colon(text, text.len(), "Re").as_deref(),
+            Some("Acme.Util.Color")
+        );
+        let text = "END. Color:";
+        assert_eq!(
+            type_qualifier_before_colon(text, text.len(), "").as_deref(),
+            Some("Color")
+        );
+    }
+
     #[test]
     fn finds_qualifier_before_dot_with_dash() {
         let text = "f-lpd_det.";
@@ -272,6 +351,40 @@ mod tests {
         assert!(text_has_dot_before_cursor(text, offset));
     }
 
+    #[test]
+    fn detects_include_path_prefix_after_brace() {
+        let text = "{inc/com";
+        assert_eq!(
+            include_path_prefix(text, text.len()).as_deref(),
+            Some("inc/com")
+        );
+        assert_eq!(include_path_prefix("{", 1).as_deref(), Some(""));
+        assert!(include_path_prefix("{&Mode", 6).is_none());
+        assert!(include_path_prefix("x = abc", 7).is_none());
+    }
+
+    #[test]
+    fn detects_preprocessor_reference_prefix() {
+        let text = "MESSAGE {&Mode";
+        assert_eq!(
+            preprocessor_reference_prefix(text, text.len()).as_deref(),
+            Some("Mode")
+        );
+        assert_eq!(preprocessor_reference_prefix("{&", 2).as_deref(), Some(""));
+        assert!(preprocessor_reference_prefix("{inc", 4).is_none());
+        assert!(preprocessor_reference_prefix("a & b", 3).is_none());
+    }
+
+    #[test]
+    fn finds_qualifier_before_colon() {
+        let text = "THIS-OBJECT:Ru";
+        assert_eq!(
+            qualifier_before_colon(text, text.len(), "Ru").as_deref(),
+            Some("THIS-OBJECT")
+        );
+        assert_eq!(qualifier_before_dot(text, text.len(), "Ru"), None);
+    }
+
     #[test]
     fn renders_field_detail_and_docs() {
         let field = DbFieldInfo {
@@ -328,7 +441,7 @@ END.
     fn looks_up_fields_case_insensitively() {
         let map = DashMap::<String, Vec<DbFieldInfo>>::new();
         map.insert(
-            "Customer".to_string(),
+            "CUSTOMER".to_string(),
             vec![DbFieldInfo {
                 name: "Name".to_string(),
                 field_type: Some("CHARACTER".to_string()),
@@ -338,7 +451,7 @@ END.
             }],
         );
 
-        let hit = lookup_case_insensitive_fields(&map, "CUSTOMER").expect("fields");
+        let hit = lookup_case_insensitive_fields(&map, "Customer").expect("fields");
         assert_eq!(hit.len(), 1);
         assert_eq!(hit[0].name, "Name");
         assert!(lookup_case_insensitive_fields(&map, "ORDER").is_none());
@@ -347,7 +460,7 @@ END.
     #[test]
     fn looks_up_indexes_case_insensitively() {
         let map = DashMap::<String, Vec<String>>::new();
-        map.insert("Customer".to_string(), vec!["CustNum".to_string()]);
+        map.insert("CUSTOMER".to_string(), vec!["CustNum".to_string()]);
 
         let hit = lookup_case_insensitive_indexes_by_table(&map, "customer").expect("indexes");
         assert_eq!(hit, vec!["CustNum".to_string()]);
@@ -355,10 +468,10 @@ END.
     }
 
     #[test]
-    fn looks_up_fields_by_unqualified_table_name() {
+    fn looks_up_fields_by_qualified_table_symbol() {
         let map = DashMap::<String, Vec<DbFieldInfo>>::new();
         map.insert(
-            "sports.Customer".to_string(),
+            "CUSTOMER".to_string(),
             vec![DbFieldInfo {
                 name: "Name".to_string(),
                 field_type: Some("CHARACTER".to_string()),
@@ -368,18 +481,42 @@ END.
             }],
         );
 
-        let hit = lookup_case_insensitive_fields_by_table_symbol(&map, "customer").expect("fields");
+        let hit = lookup_case_insensitive_fields_by_table_symbol(&map, "sports.customer")
+            .expect("fields");
         assert_eq!(hit.len(), 1);
         assert_eq!(hit[0].name, "Name");
     }
 
     #[test]
-    fn looks_up_indexes_by_unqualified_table_name() {
+    fn looks_up_indexes_by_qualified_table_symbol() {
         let map = DashMap::<String, Vec<String>>::new();
-        map.insert("sports.Customer".to_string(), vec!["CustNum".to_string()]);
+        map.insert("CUSTOMER".to_string(), vec!["CustNum".to_string()]);
 
-        let hit = lookup_case_insensitive_indexes_by_table_symbol(&map, "customer")
+        let hit = lookup_case_insensitive_indexes_by_table_symbol(&map, "sports.customer")
             .expect("indexes by table symbol");
         assert_eq!(hit, vec!["CustNum".to_string()]);
     }
+
+    #[test]
+    fn detects_handle_type_details() {
+        assert!(is_handle_type_detail("HANDLE"));
+        assert!(is_handle_type_detail("HANDLE NO-UNDO"));
+        assert!(is_handle_type_detail("SHARED WIDGET-HANDLE"));
+        assert!(!is_handle_type_detail("CHARACTER NO-UNDO"));
+        assert!(!is_handle_type_detail("BUFFER Customer"));
+    }
+
+    #[test]
+    fn collects_distinct_document_words_by_prefix() {
+        let text =
+            "DEFINE VARIABLE cust-name AS CHARACTER.\ncust-num = 1. CUST-NAME = 'x'. cu 2cust";
+        assert_eq!(
+            document_words_with_prefix(text, "cu", 10),
+            vec!["cust-name", "cust-num"]
+        );
+        assert_eq!(
+            document_words_with_prefix(text, "", 2),
+            vec!["AS", "CHARACTER"]
+        );
+    }
 }
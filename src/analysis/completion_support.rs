@@ -3,7 +3,7 @@ use tower_lsp::lsp_types::{
 };
 use tree_sitter::Node;
 
-use crate::analysis::completion::{field_detail, field_documentation};
+use crate::analysis::completion::field_detail;
 use crate::analysis::scopes::{ByteScope, containing_scope};
 use crate::backend::DbFieldInfo;
 
@@ -18,6 +18,19 @@ pub fn completion_response(items: Vec<CompletionItem>, is_incomplete: bool) -> C
     }
 }
 
+/// `completion.min_prefix_length`: suppress completion while fewer characters have been typed.
+/// An empty prefix (explicit invocation) and member access after `.`/`:` are never suppressed.
+pub fn is_below_min_prefix_length(text: &str, offset: usize, prefix: &str, min: usize) -> bool {
+    if prefix.is_empty() || prefix.len() >= min {
+        return false;
+    }
+    let before = offset.saturating_sub(prefix.len());
+    !matches!(
+        before.checked_sub(1).and_then(|i| text.as_bytes().get(i)),
+        Some(b'.' | b':')
+    )
+}
+
 pub fn build_field_completion_items(
     fields: &[DbFieldInfo],
     table_key: &str,
@@ -31,7 +44,8 @@ pub fn build_field_completion_items(
             label: f.name.clone(),
             kind: Some(CompletionItemKind::FIELD),
             detail: Some(field_detail(f, table_key)),
-            documentation: field_documentation(f),
+            // Documented on `completionItem/resolve`, keyed by the table.
+            data: Some(serde_json::json!({ "table": table_key })),
             insert_text: Some(f.name.clone()),
             insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
             ..Default::default()
@@ -47,6 +61,182 @@ pub fn build_field_completion_items(
     items
 }
 
+const ABL_COMPLETION_KEYWORDS: &[&str] = &[
+    "ASSIGN",
+    "AVAILABLE",
+    "BUFFER",
+    "CASE",
+    "CATCH",
+    "CLASS",
+    "CREATE",
+    "DEFINE",
+    "DELETE",
+    "DISPLAY",
+    "DO",
+    "ELSE",
+    "END",
+    "FIND",
+    "FINALLY",
+    "FIRST",
+    "FOR EACH",
+    "FUNCTION",
+    "IF",
+    "INPUT",
+    "INPUT-OUTPUT",
+    "LAST",
+    "LEAVE",
+    "MESSAGE",
+    "METHOD",
+    "NEXT",
+    "NO-ERROR",
+    "NO-LOCK",
+    "NO-UNDO",
+    "OUTPUT",
+    "PARAMETER",
+    "PROCEDURE",
+    "REPEAT",
+    "RETURN",
+    "RETURNS",
+    "RUN",
+    "TEMP-TABLE",
+    "THEN",
+    "UNDO",
+    "VARIABLE",
+    "WHERE",
+    "WHEN",
+];
+
+/// Completion sort buckets; lower ranks are listed first, alphabetical within a bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CompletionRank {
+    /// Locals, parameters and fields of buffers in scope.
+    Local,
+    /// Symbols from include files and imported classes.
+    Include,
+    DbTable,
+    /// Keywords and snippets.
+    Keyword,
+}
+
+pub fn ranked_sort_text(rank: CompletionRank, label: &str) -> String {
+    format!("{}_{}", rank as u8, label.to_ascii_uppercase())
+}
+
+/// Builds keyword items matching `prefix`, sorted after symbol candidates.
+pub fn build_keyword_completion_items(prefix: &str) -> Vec<CompletionItem> {
+    let pref_up = prefix.to_ascii_uppercase();
+    ABL_COMPLETION_KEYWORDS
+        .iter()
+        .filter(|kw| kw.starts_with(&pref_up))
+        .map(|kw| CompletionItem {
+            label: kw.to_string(),
+            kind: Some(CompletionItemKind::KEYWORD),
+            detail: Some("ABL keyword".to_string()),
+            sort_text: Some(ranked_sort_text(CompletionRank::Keyword, kw)),
+            insert_text: Some(kw.to_string()),
+            insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Builds attribute (PROPERTY) and method (METHOD) items for a colon-qualified handle.
+pub fn build_member_completion_items(
+    attributes: &[&str],
+    methods: &[&str],
+    owner: &str,
+    prefix: &str,
+) -> Vec<CompletionItem> {
+    let pref_up = prefix.to_ascii_uppercase();
+    attributes
+        .iter()
+        .map(|name| (name, CompletionItemKind::PROPERTY, "attribute"))
+        .chain(
+            methods
+                .iter()
+                .map(|name| (name, CompletionItemKind::METHOD, "method")),
+        )
+        .filter(|(name, _, _)| name.starts_with(&pref_up))
+        .map(|(name, kind, role)| CompletionItem {
+            label: name.to_string(),
+            kind: Some(kind),
+            detail: Some(format!("{owner} {role}")),
+            insert_text: Some(name.to_string()),
+            insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Parameter names of the called procedure (`INPUT pName: CHARACTER` -> `pName`), listed first;
+/// the parameter at the cursor's argument position is preselected.
+pub fn build_parameter_name_completion_items(
+    params: &[String],
+    active_param: usize,
+    prefix: &str,
+) -> Vec<CompletionItem> {
+    let pref_up = prefix.to_ascii_uppercase();
+    params
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, param)| {
+            let head = param.split(':').next().unwrap_or(param).trim();
+            let name = head.rsplit(' ').next().unwrap_or(head);
+            name.to_ascii_uppercase()
+                .starts_with(&pref_up)
+                .then(|| CompletionItem {
+                    label: name.to_string(),
+                    kind: Some(CompletionItemKind::VALUE),
+                    detail: Some(format!("Parameter {}: {param}", idx + 1)),
+                    sort_text: Some(format!("0_{idx:03}")),
+                    preselect: (idx == active_param).then_some(true),
+                    insert_text: Some(name.to_string()),
+                    insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+                    ..Default::default()
+                })
+        })
+        .collect()
+}
+
+const ABL_COMPLETION_SNIPPETS: &[(&str, &str)] = &[
+    (
+        "FOR EACH ... END",
+        "FOR EACH ${1:table} NO-LOCK:\n\t$0\nEND.",
+    ),
+    ("DO ... END", "DO:\n\t$0\nEND."),
+    (
+        "DEFINE VARIABLE",
+        "DEFINE VARIABLE ${1:name} AS ${2:CHARACTER} NO-UNDO.$0",
+    ),
+    ("IF ... THEN DO", "IF ${1:condition} THEN DO:\n\t$0\nEND."),
+    (
+        "PROCEDURE ... END",
+        "PROCEDURE ${1:name}:\n\t$0\nEND PROCEDURE.",
+    ),
+    (
+        "FUNCTION ... END",
+        "FUNCTION ${1:name} RETURNS ${2:LOGICAL} ():\n\t$0\nEND FUNCTION.",
+    ),
+];
+
+/// Builds snippet items whose label matches `prefix`, sorted after symbol candidates.
+pub fn build_snippet_completion_items(prefix: &str) -> Vec<CompletionItem> {
+    let pref_up = prefix.to_ascii_uppercase();
+    ABL_COMPLETION_SNIPPETS
+        .iter()
+        .filter(|(label, _)| label.starts_with(&pref_up))
+        .map(|(label, body)| CompletionItem {
+            label: label.to_string(),
+            kind: Some(CompletionItemKind::SNIPPET),
+            detail: Some("ABL snippet".to_string()),
+            sort_text: Some(ranked_sort_text(CompletionRank::Keyword, label)),
+            insert_text: Some(body.to_string()),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            ..Default::default()
+        })
+        .collect()
+}
+
 pub fn is_parameter_symbol_at_byte(root: Node<'_>, start_byte: usize) -> bool {
     let Some(mut node) = root.named_descendant_for_byte_range(start_byte, start_byte) else {
         return false;
@@ -79,13 +269,15 @@ pub fn symbol_is_in_current_scope(
 #[cfg(test)]
 mod tests {
     use super::{
-        build_field_completion_items, completion_response, is_parameter_symbol_at_byte,
-        symbol_is_in_current_scope,
+        CompletionRank, build_field_completion_items, build_keyword_completion_items,
+        build_member_completion_items, build_parameter_name_completion_items,
+        build_snippet_completion_items, completion_response, is_below_min_prefix_length,
+        is_parameter_symbol_at_byte, ranked_sort_text, symbol_is_in_current_scope,
     };
     use crate::analysis::parse_abl;
     use crate::analysis::scopes::containing_scope;
     use crate::backend::DbFieldInfo;
-    use tower_lsp::lsp_types::CompletionResponse;
+    use tower_lsp::lsp_types::{CompletionItemKind, CompletionResponse, InsertTextFormat};
 
     #[test]
     fn builds_completion_response_variants() {
@@ -127,6 +319,39 @@ mod tests {
         assert_eq!(labels, vec!["Name".to_string()]);
     }
 
+    #[test]
+    fn ranks_buckets_before_labels() {
+        let local = ranked_sort_text(CompletionRank::Local, "zzLocal");
+        let include = ranked_sort_text(CompletionRank::Include, "aInclude");
+        let table = ranked_sort_text(CompletionRank::DbTable, "Customer");
+        let keyword = ranked_sort_text(CompletionRank::Keyword, "ASSIGN");
+        assert!(local < include && include < table && table < keyword);
+        assert!(
+            ranked_sort_text(CompletionRank::DbTable, "benefits")
+                < ranked_sort_text(CompletionRank::DbTable, "Customer")
+        );
+    }
+
+    #[test]
+    fn builds_keyword_items_after_symbols() {
+        let items = build_keyword_completion_items("fo");
+        let labels = items.iter().map(|i| i.label.as_str()).collect::<Vec<_>>();
+        assert_eq!(labels, vec!["FOR EACH"]);
+        assert_eq!(items[0].sort_text.as_deref(), Some("3_FOR EACH"));
+        assert!(build_keyword_completion_items("zz").is_empty());
+    }
+
+    #[test]
+    fn builds_snippet_items_with_tabstops() {
+        let items = build_snippet_completion_items("for");
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].insert_text_format, Some(InsertTextFormat::SNIPPET));
+        assert_eq!(
+            items[0].insert_text.as_deref(),
+            Some("FOR EACH ${1:table} NO-LOCK:\n\t$0\nEND.")
+        );
+    }
+
     #[test]
     fn detects_parameter_symbols_and_scope_membership() {
         let src = r#"
@@ -159,4 +384,55 @@ END FUNCTION.
             Some(current_scope)
         ));
     }
+
+    #[test]
+    fn builds_member_items_filtered_by_prefix() {
+        let items = build_member_completion_items(
+            &["NUM-MESSAGES", "ERROR"],
+            &["GET-MESSAGE"],
+            "ERROR-STATUS",
+            "n",
+        );
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].label, "NUM-MESSAGES");
+        assert_eq!(items[0].kind, Some(CompletionItemKind::PROPERTY));
+        assert_eq!(items[0].detail.as_deref(), Some("ERROR-STATUS attribute"));
+
+        let items = build_member_completion_items(&[], &["GET-MESSAGE"], "ERROR-STATUS", "");
+        assert_eq!(items[0].kind, Some(CompletionItemKind::METHOD));
+        assert_eq!(items[0].detail.as_deref(), Some("ERROR-STATUS method"));
+    }
+
+    #[test]
+    fn builds_parameter_name_items_with_active_preselected() {
+        let params = vec![
+            "INPUT pCustNum: INTEGER".to_string(),
+            "OUTPUT pName: CHARACTER".to_string(),
+            "pNote: CHARACTER".to_string(),
+        ];
+        let items = build_parameter_name_completion_items(&params, 1, "");
+        let labels = items.iter().map(|i| i.label.as_str()).collect::<Vec<_>>();
+        assert_eq!(labels, vec!["pCustNum", "pName", "pNote"]);
+        assert_eq!(items[0].kind, Some(CompletionItemKind::VALUE));
+        assert_eq!(
+            items[1].detail.as_deref(),
+            Some("Parameter 2: OUTPUT pName: CHARACTER")
+        );
+        assert_eq!(items[1].preselect, Some(true));
+        assert_eq!(items[0].preselect, None);
+
+        let items = build_parameter_name_completion_items(&params, 0, "pn");
+        let labels = items.iter().map(|i| i.label.as_str()).collect::<Vec<_>>();
+        assert_eq!(labels, vec!["pName", "pNote"]);
+    }
+
+    #[test]
+    fn min_prefix_length_skips_short_prefixes_outside_member_access() {
+        assert!(!is_below_min_prefix_length("DISPLAY c", 9, "c", 0));
+        assert!(is_below_min_prefix_length("DISPLAY c", 9, "c", 2));
+        assert!(!is_below_min_prefix_length("DISPLAY cu", 10, "cu", 2));
+        assert!(!is_below_min_prefix_length("DISPLAY ", 8, "", 2));
+        assert!(!is_below_min_prefix_length("Customer.N", 10, "N", 3));
+        assert!(!is_below_min_prefix_length("hWin:V", 6, "V", 3));
+    }
 }
@@ -0,0 +1,105 @@
+use tree_sitter::Node;
+
+pub struct DatasetDefinition {
+    pub name: String,
+    pub is_data_source: bool,
+    pub members: Vec<String>,
+}
+
+/// Collects `DEFINE DATASET`/`DEFINE DATA-SOURCE` names with the buffers listed in their `FOR` clause.
+pub fn collect_dataset_definitions(node: Node<'_>, src: &[u8], out: &mut Vec<DatasetDefinition>) {
+    if matches!(node.kind(), "dataset_definition" | "data_source_definition")
+        && let Some(name_node) = node.child_by_field_name("name")
+        && let Ok(name) = name_node.utf8_text(src)
+        && let Some(rest) = src
+            .get(name_node.end_byte()..node.end_byte())
+            .and_then(|rest| std::str::from_utf8(rest).ok())
+    {
+        let name = name.trim();
+        if !name.is_empty() {
+            out.push(DatasetDefinition {
+                name: name.to_string(),
+                is_data_source: node.kind() == "data_source_definition",
+                members: parse_for_clause_members(rest),
+            });
+        }
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_dataset_definitions(ch, src, out);
+        }
+    }
+}
+
+/// Parses `FOR buf1, buf2 ...` (stopping at the first non-comma continuation).
+fn parse_for_clause_members(text: &str) -> Vec<String> {
+    let mut tokens = text.split_ascii_whitespace();
+    if !tokens.any(|token| token.eq_ignore_ascii_case("FOR")) {
+        return Vec::new();
+    }
+    let rest = tokens.collect::<Vec<_>>().join(" ");
+
+    let mut members = Vec::new();
+    let mut chars = rest.char_indices().peekable();
+    loop {
+        while chars.next_if(|(_, c)| c.is_ascii_whitespace()).is_some() {}
+        let start = match chars.peek() {
+            Some((i, _)) => *i,
+            None => break,
+        };
+        let mut end = start;
+        while let Some((i, c)) =
+            chars.next_if(|(_, c)| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        {
+            end = i + c.len_utf8();
+        }
+        let member = rest[start..end].trim_end_matches('.');
+        if member.is_empty() {
+            break;
+        }
+        members.push(member.to_string());
+
+        while chars.next_if(|(_, c)| c.is_ascii_whitespace()).is_some() {}
+        if chars.next_if(|(_, c)| *c == ',').is_none() {
+            break;
+        }
+    }
+    members
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_dataset_definitions, parse_for_clause_members};
+    use crate::analysis::parse_abl;
+
+    #[test]
+    fn parses_for_clause_member_list() {
+        assert_eq!(
+            parse_for_clause_members(" FOR ttOrder, ttOrderLine DATA-RELATION r1 FOR ttOrder."),
+            vec!["ttOrder", "ttOrderLine"]
+        );
+        assert_eq!(parse_for_clause_members(" FOR bCust."), vec!["bCust"]);
+        assert!(parse_for_clause_members(" LIKE dsOther.").is_empty());
+    }
+
+    #[test]
+    fn collects_dataset_members() {
+        let src = r#"
+DEFINE TEMP-TABLE ttOrder NO-UNDO FIELD ordNo AS INTEGER.
+DEFINE TEMP-TABLE ttLine NO-UNDO FIELD ordNo AS INTEGER.
+DEFINE DATASET dsOrders FOR ttOrder, ttLine
+  DATA-RELATION rLine FOR ttOrder, ttLine RELATION-FIELDS (ordNo, ordNo).
+"#;
+        let tree = parse_abl(src);
+        let mut defs = Vec::new();
+        collect_dataset_definitions(tree.root_node(), src.as_bytes(), &mut defs);
+
+        let ds = defs
+            .iter()
+            .find(|d| d.name.eq_ignore_ascii_case("dsOrders"))
+            .expect("dataset definition");
+        assert!(!ds.is_data_source);
+        assert_eq!(ds.members, vec!["ttOrder", "ttLine"]);
+    }
+}
@@ -1,17 +1,25 @@
 use crate::analysis::buffers::collect_buffer_mappings;
+use crate::analysis::classes::{
+    MAX_INHERITS_DEPTH, class_inherits_name, class_name_to_relative_path,
+    enclosing_class_definition, find_class_method_site, method_call_at_offset,
+};
+use crate::analysis::completion::lookup_case_insensitive_fields_by_table_symbol;
 use crate::analysis::definitions::{
     AblDefinitionSite, PreprocessorDefineSite, collect_definition_sites,
     collect_global_preprocessor_define_sites, collect_local_table_field_sites,
-    collect_preprocessor_define_sites,
+    collect_preprocessor_define_sites, completion_kind_for_node,
 };
 use crate::analysis::includes::{
-    collect_include_sites_from_tree, include_site_matches_file_offset, resolve_include_site_path,
+    collect_include_sites_from_tree, collect_resolved_include_parses,
+    include_site_matches_file_offset, resolve_include_site_path,
 };
+use crate::analysis::local_tables::collect_local_table_definitions;
 use crate::analysis::schema::normalize_lookup_key;
 use crate::analysis::schema_lookup::pick_single_location;
 use crate::analysis::scopes::containing_scope;
-use crate::backend::Backend;
-use std::collections::HashMap;
+use crate::analysis::signature::run_program_target;
+use crate::backend::{Backend, table_field_key};
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use tower_lsp::lsp_types::{Location, Position, Range, Url};
 use tree_sitter::Node;
@@ -161,6 +169,240 @@ pub fn resolve_buffer_alias_table_location(
     None
 }
 
+/// Returns `(qualifier, field)` when `offset` sits on the field part of a `table.field` name.
+pub fn qualified_field_at_offset(
+    root: Node<'_>,
+    src: &[u8],
+    offset: usize,
+) -> Option<(String, String)> {
+    let mut node = root.named_descendant_for_byte_range(offset, offset)?;
+    while node.kind() != "qualified_name" {
+        node = node.parent()?;
+    }
+    let text = node.utf8_text(src).ok()?.trim_end();
+    let (qualifier, field) = text.rsplit_once('.')?;
+    let field_start = node.start_byte() + qualifier.len() + 1;
+    if offset < field_start || qualifier.trim().is_empty() || field.trim().is_empty() {
+        return None;
+    }
+    Some((qualifier.trim().to_string(), field.trim().to_string()))
+}
+
+/// Resolves `table.field` (or `buffer.field`) to the matching `ADD FIELD ... OF table` site.
+pub fn resolve_qualified_field_location(
+    backend: &Backend,
+    root: Node<'_>,
+    src: &[u8],
+    qualifier: &str,
+    field: &str,
+    offset: usize,
+) -> Option<Location> {
+    let table =
+        if lookup_case_insensitive_fields_by_table_symbol(&backend.db_fields_by_table, qualifier)
+            .is_some()
+        {
+            qualifier.to_string()
+        } else {
+            nearest_buffer_table(root, src, qualifier, offset)?
+        };
+    let table_tail = table.rsplit('.').next().unwrap_or(&table);
+    let locations = backend
+        .db_field_definitions_by_table
+        .get(&table_field_key(table_tail, field))?;
+    pick_single_location(locations.value())
+}
+
+/// Resolves `tt.field` to the `FIELD` declaration of a local temp-table, falling back to
+/// the `.df` field of its `LIKE` table.
+pub fn resolve_local_table_field_location(
+    backend: &Backend,
+    uri: &Url,
+    root: Node<'_>,
+    src: &[u8],
+    qualifier: &str,
+    field: &str,
+    offset: usize,
+) -> Option<Location> {
+    let table_node = find_local_table_node(root, src, qualifier).or_else(|| {
+        let table = nearest_buffer_table(root, src, qualifier, offset)?;
+        find_local_table_node(root, src, table.rsplit('.').next().unwrap_or(&table))
+    })?;
+
+    let mut sites = Vec::new();
+    collect_local_table_field_sites(table_node, src, &mut sites);
+    if let Some(site) = sites
+        .into_iter()
+        .find(|site| site.label.eq_ignore_ascii_case(field))
+    {
+        return Some(Location {
+            uri: uri.clone(),
+            range: site.range,
+        });
+    }
+
+    let mut defs = Vec::new();
+    collect_local_table_definitions(table_node, src, &mut defs);
+    let like_table = defs.into_iter().next()?.like_table_upper?;
+    let locations = backend
+        .db_field_definitions_by_table
+        .get(&table_field_key(&like_table, field))?;
+    pick_single_location(locations.value())
+}
+
+/// Resolves `tt.field` to the `FIELD` declaration of a temp-table defined in a resolved include.
+pub async fn resolve_include_table_field_location(
+    backend: &Backend,
+    uri: &Url,
+    text: &str,
+    root: Node<'_>,
+    qualifier: &str,
+    field: &str,
+    offset: usize,
+) -> Option<Location> {
+    if !text.as_bytes().contains(&b'{') {
+        return None;
+    }
+    let current_path = uri.to_file_path().ok()?;
+    let version = backend.get_document_version(uri)?;
+    let table = nearest_buffer_table(root, text.as_bytes(), qualifier, offset)
+        .map(|table| table.rsplit('.').next().unwrap_or(&table).to_string())
+        .unwrap_or_else(|| qualifier.to_string());
+
+    let include_parses =
+        collect_resolved_include_parses(backend, uri, version, &current_path, text, root).await?;
+    include_parses
+        .into_iter()
+        .find_map(|(include_path, include_text, include_tree)| {
+            let include_uri = Url::from_file_path(&include_path).ok()?;
+            resolve_local_table_field_location(
+                backend,
+                &include_uri,
+                include_tree.root_node(),
+                include_text.as_bytes(),
+                &table,
+                field,
+                0,
+            )
+        })
+}
+
+fn find_local_table_node<'a>(node: Node<'a>, src: &[u8], name: &str) -> Option<Node<'a>> {
+    if matches!(
+        node.kind(),
+        "temp_table_definition" | "work_table_definition" | "workfile_definition"
+    ) && node
+        .child_by_field_name("name")
+        .and_then(|n| n.utf8_text(src).ok())
+        .is_some_and(|n| n.trim().eq_ignore_ascii_case(name))
+    {
+        return Some(node);
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32)
+            && let Some(found) = find_local_table_node(ch, src, name)
+        {
+            return Some(found);
+        }
+    }
+    None
+}
+
+fn nearest_buffer_table(root: Node<'_>, src: &[u8], alias: &str, offset: usize) -> Option<String> {
+    let mut mappings = Vec::new();
+    collect_buffer_mappings(root, src, &mut mappings);
+    mappings.retain(|m| m.alias.eq_ignore_ascii_case(alias));
+    mappings
+        .iter()
+        .filter(|m| m.start_byte <= offset)
+        .max_by_key(|m| m.start_byte)
+        .or_else(|| mappings.iter().min_by_key(|m| m.start_byte))
+        .map(|m| m.table.clone())
+}
+
+/// Resolves a call to a method of the enclosing class, following `INHERITS` when the class
+/// does not declare it.
+pub async fn resolve_class_method_location(
+    backend: &Backend,
+    uri: &Url,
+    text: &str,
+    root: Node<'_>,
+    offset: usize,
+) -> Option<Location> {
+    let call = method_call_at_offset(text, offset)?;
+    let class_node = enclosing_class_definition(root, offset)?;
+    if !call.inherited_only
+        && let Some(site) =
+            find_class_method_site(class_node, text.as_bytes(), &call.name, call.arg_count)
+    {
+        return Some(Location {
+            uri: uri.clone(),
+            range: site.range,
+        });
+    }
+
+    let current_path = uri.to_file_path().ok()?;
+    let mut parent = class_inherits_name(class_node, text.as_bytes());
+    let mut seen = HashSet::new();
+    while let Some(parent_name) = parent.take()
+        && seen.len() < MAX_INHERITS_DEPTH
+        && seen.insert(parent_name.to_ascii_uppercase())
+    {
+        let parent_path = backend
+            .resolve_include_path_for(&current_path, &class_name_to_relative_path(&parent_name))
+            .await?;
+        let (parent_text, parent_tree) = backend.get_cached_include_parse(&parent_path).await?;
+        let parent_class = enclosing_class_definition(parent_tree.root_node(), usize::MAX)?;
+        if let Some(site) = find_class_method_site(
+            parent_class,
+            parent_text.as_bytes(),
+            &call.name,
+            call.arg_count,
+        ) {
+            return Some(Location {
+                uri: Url::from_file_path(&parent_path).ok()?,
+                range: site.range,
+            });
+        }
+        parent = class_inherits_name(parent_class, parent_text.as_bytes());
+    }
+    None
+}
+
+/// Declared `AS` type of the variable/parameter/property named `symbol` nearest `offset`,
+/// without a leading `CLASS` keyword.
+pub fn declared_type_name_at(
+    root: Node<'_>,
+    src: &[u8],
+    symbol: &str,
+    offset: usize,
+) -> Option<String> {
+    let mut sites = Vec::new();
+    collect_definition_sites(root, src, &mut sites);
+    // Definitions inside another procedure/function/method are not visible here.
+    let matching = sites.iter().filter(|site| {
+        site.label.eq_ignore_ascii_case(symbol)
+            && containing_scope(root, site.start_byte)
+                .is_none_or(|scope| scope.start <= offset && offset <= scope.end)
+    });
+    let site = matching
+        .clone()
+        .filter(|site| site.start_byte <= offset)
+        .max_by_key(|site| site.start_byte)
+        .or_else(|| matching.min_by_key(|site| site.start_byte))?;
+
+    let mut def = root.descendant_for_byte_range(site.start_byte, site.start_byte)?;
+    while completion_kind_for_node(def.kind()).is_none() {
+        def = def.parent()?;
+    }
+    let raw = def.child_by_field_name("type")?.utf8_text(src).ok()?.trim();
+    let ty = match raw.split_once(char::is_whitespace) {
+        Some((head, rest)) if head.eq_ignore_ascii_case("CLASS") => rest.trim(),
+        _ => raw,
+    };
+    (!ty.is_empty()).then(|| ty.to_string())
+}
+
 pub fn resolve_local_definition_location(
     uri: &Url,
     root: Node<'_>,
@@ -310,6 +552,32 @@ pub async fn resolve_include_definition_location(
         .map(|(_, location)| location)
 }
 
+/// Start of the program file named by the `RUN "prog.p"` statement under the cursor.
+pub async fn resolve_run_program_location(
+    backend: &Backend,
+    uri: &Url,
+    text: &str,
+    root: Node<'_>,
+    offset: usize,
+) -> Option<Location> {
+    let mut node = root.descendant_for_byte_range(offset, offset)?;
+    while node.kind() != "run_statement" {
+        node = node.parent()?;
+    }
+    let (program, start, end) = run_program_target(node, text.as_bytes())?;
+    if offset < start || offset > end {
+        return None;
+    }
+    let current_path = uri.to_file_path().ok()?;
+    let path = backend
+        .resolve_run_program_path(&current_path, &program)
+        .await?;
+    Some(Location {
+        uri: Url::from_file_path(path).ok()?,
+        range: Range::new(Position::new(0, 0), Position::new(0, 0)),
+    })
+}
+
 pub struct PreprocessorDefineMatch {
     pub name: String,
     pub value: Option<String>,
@@ -435,6 +703,91 @@ pub async fn resolve_preprocessor_define_match(
     include_before.or(include_after).map(|(_, m)| m)
 }
 
+const MAX_PREPROCESSOR_EXPANSION_DEPTH: usize = 16;
+const MAX_PREPROCESSOR_EXPANSION_NAMES: usize = 64;
+
+/// Fully expands `{&NAME}` references in a define's value as seen from `offset`.
+pub async fn expand_preprocessor_define_value(
+    backend: &Backend,
+    uri: &Url,
+    text: &str,
+    root: Node<'_>,
+    value: &str,
+    offset: usize,
+) -> String {
+    let mut values: HashMap<String, Option<String>> = HashMap::new();
+    let mut pending = preprocessor_references(value);
+    while let Some(name) = pending.pop() {
+        let key = name.to_ascii_uppercase();
+        if values.contains_key(&key) || values.len() >= MAX_PREPROCESSOR_EXPANSION_NAMES {
+            continue;
+        }
+        let resolved = resolve_preprocessor_define_match(backend, uri, text, root, &name, offset)
+            .await
+            .map(|m| m.value.unwrap_or_default());
+        if let Some(resolved) = &resolved {
+            pending.extend(preprocessor_references(resolved));
+        }
+        values.insert(key, resolved);
+    }
+    expand_preprocessor_references(value, &values, &mut Vec::new())
+}
+
+/// Names referenced as `{&NAME}` in `value`, in order of appearance.
+fn preprocessor_references(value: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("{&") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+        let name = after[..end].trim();
+        if !name.is_empty() {
+            out.push(name.to_string());
+        }
+        rest = &after[end + 1..];
+    }
+    out
+}
+
+/// Substitutes resolved references recursively; unresolved ones are kept as written.
+fn expand_preprocessor_references(
+    value: &str,
+    values: &HashMap<String, Option<String>>,
+    stack: &mut Vec<String>,
+) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("{&") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let reference = &rest[start..start + end + 3];
+        let key = after[..end].trim().to_ascii_uppercase();
+        rest = &after[end + 1..];
+
+        if stack.contains(&key) || stack.len() >= MAX_PREPROCESSOR_EXPANSION_DEPTH {
+            out.push_str("… (recursive)");
+            continue;
+        }
+        match values.get(&key) {
+            Some(Some(resolved)) => {
+                stack.push(key);
+                out.push_str(
+                    expand_preprocessor_references(resolved.trim(), values, stack).as_str(),
+                );
+                stack.pop();
+            }
+            _ => out.push_str(reference),
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
 fn pick_best_preprocessor_site<'a>(
     sites: &'a [PreprocessorDefineSite],
     symbol: &str,
@@ -470,17 +823,16 @@ fn pick_best_preprocessor_site<'a>(
 #[cfg(test)]
 mod tests {
     use super::{
-        pick_best_preprocessor_site, resolve_buffer_alias_table_location,
-        resolve_local_definition_location,
+        declared_type_name_at, expand_preprocessor_references, pick_best_preprocessor_site,
+        preprocessor_references, qualified_field_at_offset, resolve_buffer_alias_table_location,
+        resolve_include_table_field_location, resolve_local_definition_location,
+        resolve_local_table_field_location, resolve_qualified_field_location,
     };
     use crate::analysis::definitions::PreprocessorDefineSite;
     use crate::analysis::parse_abl;
-    use crate::backend::{Backend, BackendState};
-    use dashmap::{DashMap, DashSet};
-    use std::sync::Arc;
-    use tokio::sync::Mutex as AsyncMutex;
+    use crate::backend::Backend;
+    use std::collections::HashMap;
     use tower_lsp::lsp_types::{Position, Range};
-    use tower_lsp::{Client, LspService};
 
     fn site(label: &str, start_byte: usize, line: u32) -> PreprocessorDefineSite {
         PreprocessorDefineSite {
@@ -492,38 +844,33 @@ mod tests {
         }
     }
 
-    fn test_backend() -> Backend {
-        let (service, _socket) = LspService::build(|client: Client| Backend {
-            client,
-            state: Arc::new(BackendState {
-                abl_language: tree_sitter_abl::LANGUAGE.into(),
-                df_parser: AsyncMutex::new({
-                    let mut p = tree_sitter::Parser::new();
-                    p.set_language(&tree_sitter_df::LANGUAGE.into())
-                        .expect("set df language");
-                    p
-                }),
-                documents: DashMap::new(),
-                workspace_root: AsyncMutex::new(None),
-                config: AsyncMutex::new(crate::config::AblConfig::default()),
-                db_tables: DashSet::new(),
-                db_sequences: DashSet::new(),
-                db_table_labels: DashMap::new(),
-                db_table_definitions: DashMap::new(),
-                db_sequence_definitions: DashMap::new(),
-                db_field_definitions: DashMap::new(),
-                db_index_definitions: DashMap::new(),
-                db_indexes_by_table: DashMap::new(),
-                db_index_fields_by_table_index: DashMap::new(),
-                db_fields_by_table: DashMap::new(),
-                include_completion_cache: DashMap::new(),
-                include_parse_cache: DashMap::new(),
-            }),
-        })
-        .finish();
-        let backend = service.inner().clone();
-        drop(service);
-        backend
+    #[test]
+    fn finds_declared_type_of_nearest_definition() {
+        let src = r#"
+DEFINE VARIABLE oCust AS CLASS app.Customer NO-UNDO.
+DEFINE VARIABLE n AS INTEGER NO-UNDO.
+PROCEDURE p:
+  DEFINE VARIABLE oCust AS app.Order NO-UNDO.
+  oCust = ?.
+END PROCEDURE.
+oCust = ?.
+"#;
+        let tree = parse_abl(src);
+        let inner = src.find("oCust = ?").expect("inner use");
+        let outer = src.rfind("oCust = ?").expect("outer use");
+        let n = src.find("n AS").expect("n");
+        assert_eq!(
+            declared_type_name_at(tree.root_node(), src.as_bytes(), "oCust", inner).as_deref(),
+            Some("app.Order")
+        );
+        assert_eq!(
+            declared_type_name_at(tree.root_node(), src.as_bytes(), "ocust", outer).as_deref(),
+            Some("app.Customer")
+        );
+        assert_eq!(
+            declared_type_name_at(tree.root_node(), src.as_bytes(), "n", n).as_deref(),
+            Some("INTEGER")
+        );
     }
 
     #[test]
@@ -540,6 +887,26 @@ mod tests {
         assert_eq!(picked.start_byte, 40);
     }
 
+    #[test]
+    fn expands_nested_preprocessor_references() {
+        assert_eq!(preprocessor_references("{&B} + {& C }"), vec!["B", "C"]);
+
+        let values = HashMap::from([
+            ("A".to_string(), Some("{&B}".to_string())),
+            ("B".to_string(), Some("\"x\"".to_string())),
+            ("LOOP".to_string(), Some("1 + {&LOOP}".to_string())),
+        ]);
+        let mut stack = vec!["A".to_string()];
+        assert_eq!(
+            expand_preprocessor_references("{&B}", &values, &mut stack),
+            "\"x\""
+        );
+        assert_eq!(
+            expand_preprocessor_references("{&LOOP} {&MISSING}", &values, &mut Vec::new()),
+            "1 + … (recursive) {&MISSING}"
+        );
+    }
+
     #[test]
     fn resolves_local_definition_to_nearest_previous_site() {
         let src = r#"
@@ -574,7 +941,7 @@ FOR EACH bCust:
 END.
 "#;
         let tree = parse_abl(src);
-        let backend = test_backend();
+        let backend = Backend::for_tests();
         let uri = tower_lsp::lsp_types::Url::parse("file:///tmp/test.p").expect("uri");
         let offset = src.find("bCust.id").expect("buffer usage");
 
@@ -591,4 +958,137 @@ END.
         assert_eq!(location.uri, uri);
         assert_eq!(location.range.start.line, 1);
     }
+
+    #[test]
+    fn resolves_qualified_field_through_buffer_to_table_field_site() {
+        let src = r#"
+DEFINE BUFFER bCust FOR Customer.
+FIND FIRST bCust NO-LOCK.
+DISPLAY bCust.Name.
+"#;
+        let tree = parse_abl(src);
+        let backend = Backend::for_tests();
+        let df_uri = tower_lsp::lsp_types::Url::parse("file:///tmp/schema.df").expect("uri");
+        let field_location = |line| tower_lsp::lsp_types::Location {
+            uri: df_uri.clone(),
+            range: Range::new(Position::new(line, 10), Position::new(line, 16)),
+        };
+        backend.db_field_definitions_by_table.insert(
+            crate::backend::table_field_key("Customer", "Name"),
+            vec![field_location(4)],
+        );
+        backend.db_field_definitions_by_table.insert(
+            crate::backend::table_field_key("Supplier", "Name"),
+            vec![field_location(9)],
+        );
+
+        let offset = src.find("Name.").expect("field usage") + 1;
+        let (qualifier, field) =
+            qualified_field_at_offset(tree.root_node(), src.as_bytes(), offset)
+                .expect("qualified field");
+        assert_eq!((qualifier.as_str(), field.as_str()), ("bCust", "Name"));
+
+        let location = resolve_qualified_field_location(
+            &backend,
+            tree.root_node(),
+            src.as_bytes(),
+            &qualifier,
+            &field,
+            offset,
+        )
+        .expect("location");
+        assert_eq!(location.range.start.line, 4);
+    }
+
+    #[test]
+    fn resolves_temp_table_field_and_like_fallback() {
+        let src = r#"
+DEFINE TEMP-TABLE ttCust NO-UNDO LIKE Customer
+  FIELD extraFlag AS LOGICAL.
+
+DISPLAY ttCust.extraFlag ttCust.Name.
+"#;
+        let tree = parse_abl(src);
+        let backend = Backend::for_tests();
+        let uri = tower_lsp::lsp_types::Url::parse("file:///tmp/test.p").expect("uri");
+        let df_uri = tower_lsp::lsp_types::Url::parse("file:///tmp/schema.df").expect("uri");
+        backend.db_field_definitions_by_table.insert(
+            crate::backend::table_field_key("CUSTOMER", "Name"),
+            vec![tower_lsp::lsp_types::Location {
+                uri: df_uri.clone(),
+                range: Range::new(Position::new(7, 10), Position::new(7, 16)),
+            }],
+        );
+
+        let offset = src.find("extraFlag ttCust").expect("local field usage") + 1;
+        let location = resolve_local_table_field_location(
+            &backend,
+            &uri,
+            tree.root_node(),
+            src.as_bytes(),
+            "ttCust",
+            "extraFlag",
+            offset,
+        )
+        .expect("local field location");
+        assert_eq!(location.uri, uri);
+        assert_eq!(location.range.start.line, 2);
+
+        let offset = src.find("Name.").expect("like field usage") + 1;
+        let location = resolve_local_table_field_location(
+            &backend,
+            &uri,
+            tree.root_node(),
+            src.as_bytes(),
+            "ttCust",
+            "Name",
+            offset,
+        )
+        .expect("like field location");
+        assert_eq!(location.uri, df_uri);
+    }
+
+    #[tokio::test]
+    async fn resolves_temp_table_field_defined_in_include() {
+        let dir = std::env::temp_dir().join(format!(
+            "abl-ls-include-tt-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("clock")
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let include_path = dir.join("tt.i");
+        std::fs::write(
+            &include_path,
+            "DEFINE TEMP-TABLE ttOrder NO-UNDO\n  FIELD qty AS INTEGER.\n",
+        )
+        .expect("write include");
+
+        let backend = Backend::for_tests();
+        let uri = tower_lsp::lsp_types::Url::from_file_path(dir.join("main.p")).expect("uri");
+        let src = "{tt.i}\nDISPLAY ttOrder.qty.\n";
+        backend.set_document_text_version(&uri, 1, src.to_string(), true);
+        let tree = parse_abl(src);
+        let offset = src.find("qty").expect("field usage");
+
+        let location = resolve_include_table_field_location(
+            &backend,
+            &uri,
+            src,
+            tree.root_node(),
+            "ttOrder",
+            "qty",
+            offset,
+        )
+        .await
+        .expect("location");
+        assert_eq!(
+            location.uri,
+            tower_lsp::lsp_types::Url::from_file_path(&include_path).expect("include uri")
+        );
+        assert_eq!(location.range.start.line, 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -1,7 +1,15 @@
 use tower_lsp::lsp_types::{CompletionItemKind, Range};
 use tree_sitter::Node;
 
-use crate::utils::ts::{first_descendant_by_kind, node_to_range, node_trimmed_text};
+use crate::analysis::enums::collect_enum_definitions;
+use crate::analysis::preprocessor::retain_reachable_definitions;
+use crate::analysis::scopes::containing_scope;
+use crate::utils::ts::{
+    collect_nodes_by_kind, first_descendant_by_kind, node_to_range, node_trimmed_text,
+};
+
+/// Detail prefix marking `DEFINE [NEW [GLOBAL]] SHARED VARIABLE` symbols.
+pub const SHARED_DETAIL_PREFIX: &str = "SHARED ";
 
 pub struct AblSymbol {
     pub label: String,
@@ -25,7 +33,7 @@ pub struct PreprocessorDefineSite {
     pub is_global: bool,
 }
 
-fn completion_kind_for_node(node_kind: &str) -> Option<(CompletionItemKind, &'static str)> {
+pub fn completion_kind_for_node(node_kind: &str) -> Option<(CompletionItemKind, &'static str)> {
     use CompletionItemKind as Kind;
 
     let entry = match node_kind {
@@ -39,15 +47,16 @@ fn completion_kind_for_node(node_kind: &str) -> Option<(CompletionItemKind, &'st
         "destructor_definition" => (Kind::METHOD, "ABL destructor"),
         "class_definition" => (Kind::CLASS, "ABL class"),
         "interface_definition" => (Kind::INTERFACE, "ABL interface"),
+        "enum_definition" => (Kind::ENUM, "ABL enum"),
         "property_definition" => (Kind::PROPERTY, "ABL property"),
         "event_definition" => (Kind::EVENT, "ABL event"),
         "buffer_definition" => (Kind::VARIABLE, "ABL buffer"),
-        "dataset_definition"
-        | "temp_table_definition"
+        "dataset_definition" => (Kind::STRUCT, "ABL dataset"),
+        "data_source_definition" => (Kind::STRUCT, "ABL data-source"),
+        "temp_table_definition"
         | "work_table_definition"
         | "workfile_definition"
-        | "query_definition"
-        | "data_source_definition" => (Kind::STRUCT, "ABL data definition"),
+        | "query_definition" => (Kind::STRUCT, "ABL data definition"),
         "stream_definition" => (Kind::VARIABLE, "ABL stream"),
         "browse_definition"
         | "button_definition"
@@ -82,11 +91,52 @@ pub fn collect_definition_symbols(node: Node, src: &[u8], out: &mut Vec<AblSymbo
             collect_definition_symbols(ch, src, out);
         }
     }
+
+    if node.parent().is_none()
+        && let Ok(text) = std::str::from_utf8(src)
+    {
+        push_enum_symbols(node, src, out);
+        retain_reachable_definitions(text, out, |s| (s.label.as_str(), s.start_byte));
+    }
+}
+
+/// Adds `ENUM` types and their members; symbols the tree already produced at those names are retyped.
+fn push_enum_symbols(root: Node<'_>, src: &[u8], out: &mut Vec<AblSymbol>) {
+    for definition in collect_enum_definitions(root, src) {
+        let members = definition.members.iter().map(|member| {
+            (
+                &member.name,
+                member.start_byte,
+                CompletionItemKind::ENUM_MEMBER,
+                format!("{} member", definition.name),
+            )
+        });
+        let header = (
+            &definition.name,
+            definition.start_byte,
+            CompletionItemKind::ENUM,
+            "ABL enum".to_string(),
+        );
+        for (label, start_byte, kind, detail) in std::iter::once(header).chain(members) {
+            if let Some(existing) = out.iter_mut().find(|s| s.start_byte == start_byte) {
+                existing.kind = kind;
+                existing.detail = detail;
+            } else {
+                out.push(AblSymbol {
+                    label: label.clone(),
+                    kind,
+                    detail,
+                    start_byte,
+                });
+            }
+        }
+    }
 }
 
 /// Walks the syntax tree and extracts names from preprocessor define directives.
 pub fn collect_preprocessor_define_symbols(node: Node, src: &[u8], out: &mut Vec<AblSymbol>) {
     collect_preprocessor_define_symbols_internal(node, src, out, true);
+    retain_reachable_defines(node, src, out, |s| (s.label.as_str(), s.start_byte));
 }
 
 pub fn collect_global_preprocessor_define_symbols(
@@ -95,6 +145,7 @@ pub fn collect_global_preprocessor_define_symbols(
     out: &mut Vec<AblSymbol>,
 ) {
     collect_preprocessor_define_symbols_internal(node, src, out, false);
+    retain_reachable_defines(node, src, out, |s| (s.label.as_str(), s.start_byte));
 }
 
 pub fn collect_preprocessor_define_sites(
@@ -103,6 +154,7 @@ pub fn collect_preprocessor_define_sites(
     out: &mut Vec<PreprocessorDefineSite>,
 ) {
     collect_preprocessor_define_sites_internal(node, src, out, true);
+    retain_reachable_defines(node, src, out, |s| (s.label.as_str(), s.start_byte));
 }
 
 pub fn collect_global_preprocessor_define_sites(
@@ -111,6 +163,21 @@ pub fn collect_global_preprocessor_define_sites(
     out: &mut Vec<PreprocessorDefineSite>,
 ) {
     collect_preprocessor_define_sites_internal(node, src, out, false);
+    retain_reachable_defines(node, src, out, |s| (s.label.as_str(), s.start_byte));
+}
+
+/// Applies `&IF` branch filtering once the whole document has been walked.
+fn retain_reachable_defines<T>(
+    node: Node,
+    src: &[u8],
+    out: &mut Vec<T>,
+    key: impl Fn(&T) -> (&str, usize),
+) {
+    if node.parent().is_none()
+        && let Ok(text) = std::str::from_utf8(src)
+    {
+        retain_reachable_definitions(text, out, key);
+    }
 }
 
 fn collect_preprocessor_define_symbols_internal(
@@ -235,16 +302,164 @@ fn push_site(name_node: Node, src: &[u8], out: &mut Vec<AblDefinitionSite>) {
 }
 
 fn symbol_detail(node: Node, src: &[u8], default_detail: &'static str) -> String {
-    if let Some(type_node) = node.child_by_field_name("type")
-        && let Ok(ty) = type_node.utf8_text(src)
-    {
-        let ty = ty.trim();
-        if !ty.is_empty() {
-            return ty.to_string();
+    let detail = node
+        .child_by_field_name("type")
+        .and_then(|type_node| type_node.utf8_text(src).ok())
+        .map(str::trim)
+        .filter(|ty| !ty.is_empty())
+        .unwrap_or(default_detail);
+
+    let mut detail = detail.to_string();
+    if node.kind() == "variable_definition" {
+        for modifier in variable_modifiers(node, src) {
+            detail.push(' ');
+            detail.push_str(&modifier);
+        }
+        if is_shared_definition(node, src) {
+            return format!("{SHARED_DETAIL_PREFIX}{detail}");
+        }
+    }
+    detail
+}
+
+/// Renders `DEFINE VARIABLE x AS T NO-UNDO INITIAL ... EXTENT n` for the variable named `symbol`
+/// visible at `offset`, when it has modifiers.
+pub fn find_variable_declaration(
+    root: Node<'_>,
+    src: &[u8],
+    symbol: &str,
+    offset: usize,
+) -> Option<String> {
+    let mut defs = Vec::new();
+    collect_nodes_by_kind(root, "variable_definition", &mut defs);
+    let name_of = |node: Node<'_>| {
+        node.child_by_field_name("name")
+            .and_then(|n| node_trimmed_text(n, src))
+            .filter(|name| name.eq_ignore_ascii_case(symbol))
+    };
+    // Definitions inside another procedure/function/method are not visible here.
+    let matching = defs.into_iter().filter(|def| {
+        name_of(*def).is_some()
+            && containing_scope(root, def.start_byte())
+                .is_none_or(|scope| scope.start <= offset && offset <= scope.end)
+    });
+    let def = matching
+        .clone()
+        .filter(|def| def.start_byte() <= offset)
+        .max_by_key(|def| def.start_byte())
+        .or_else(|| matching.min_by_key(|def| def.start_byte()))?;
+
+    let modifiers = variable_modifiers(def, src);
+    if modifiers.is_empty() {
+        return None;
+    }
+    let name = name_of(def)?;
+    let ty = def
+        .child_by_field_name("type")
+        .and_then(|n| node_trimmed_text(n, src))?;
+    Some(format!(
+        "DEFINE VARIABLE {name} AS {ty} {}",
+        modifiers.join(" ")
+    ))
+}
+
+/// Collects `NO-UNDO`, `INITIAL <value>` and `EXTENT [n]` from a variable definition, in that order.
+fn variable_modifiers(node: Node, src: &[u8]) -> Vec<String> {
+    let tail_start = node
+        .child_by_field_name("type")
+        .or_else(|| node.child_by_field_name("name"))
+        .map(|n| n.end_byte())
+        .unwrap_or(node.start_byte());
+    let Some(tail) = src
+        .get(tail_start..node.end_byte())
+        .and_then(|t| std::str::from_utf8(t).ok())
+    else {
+        return Vec::new();
+    };
+    let tokens = definition_tokens(tail);
+
+    let mut no_undo = false;
+    let mut initial = None;
+    let mut extent = None;
+    let mut i = 0;
+    while i < tokens.len() {
+        let upper = tokens[i].to_ascii_uppercase();
+        match upper.as_str() {
+            "NO-UNDO" => no_undo = true,
+            "INITIAL" | "INIT" => {
+                if let Some(value) = tokens.get(i + 1) {
+                    initial = Some(format!("INITIAL {value}"));
+                    i += 1;
+                }
+            }
+            "EXTENT" => match tokens.get(i + 1) {
+                Some(size) if size.bytes().all(|b| b.is_ascii_digit()) => {
+                    extent = Some(format!("EXTENT {size}"));
+                    i += 1;
+                }
+                _ => extent = Some("EXTENT".to_string()),
+            },
+            _ => {}
         }
+        i += 1;
     }
 
-    default_detail.to_string()
+    let mut out = Vec::new();
+    if no_undo {
+        out.push("NO-UNDO".to_string());
+    }
+    out.extend(initial);
+    out.extend(extent);
+    out
+}
+
+/// Splits definition text on whitespace, keeping quoted strings and `[...]` lists whole.
+fn definition_tokens(text: &str) -> Vec<&str> {
+    let text = text.trim_end().trim_end_matches('.');
+    let bytes = text.as_bytes();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i].is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let mut quote = None;
+        let mut depth = 0usize;
+        while i < bytes.len() {
+            let b = bytes[i];
+            match quote {
+                Some(q) if b == q => quote = None,
+                Some(_) => {}
+                None => match b {
+                    b'"' | b'\'' => quote = Some(b),
+                    b'[' => depth += 1,
+                    b']' => depth = depth.saturating_sub(1),
+                    _ if b.is_ascii_whitespace() && depth == 0 => break,
+                    _ => {}
+                },
+            }
+            i += 1;
+        }
+        out.push(&text[start..i]);
+    }
+    out
+}
+
+/// Returns true when the definition header (before its name) contains `SHARED`.
+fn is_shared_definition(node: Node, src: &[u8]) -> bool {
+    let header_end = node
+        .child_by_field_name("name")
+        .map(|name| name.start_byte())
+        .unwrap_or(node.end_byte());
+    src.get(node.start_byte()..header_end)
+        .and_then(|header| std::str::from_utf8(header).ok())
+        .is_some_and(|header| {
+            header
+                .split_ascii_whitespace()
+                .any(|token| token.eq_ignore_ascii_case("SHARED"))
+        })
 }
 
 #[cfg(test)]
@@ -252,9 +467,58 @@ mod tests {
     use super::{
         collect_definition_symbols, collect_global_preprocessor_define_sites,
         collect_global_preprocessor_define_symbols, collect_local_table_field_sites,
-        collect_preprocessor_define_sites, collect_preprocessor_define_symbols,
+        collect_preprocessor_define_sites, collect_preprocessor_define_symbols, definition_tokens,
+        find_variable_declaration,
     };
     use crate::analysis::parse_abl;
+    use tower_lsp::lsp_types::CompletionItemKind;
+
+    #[test]
+    fn collects_enum_members_as_symbols() {
+        let src = "ENUM Acme.Color:\n  DEFINE ENUM Red Green.\nEND ENUM.\n";
+        let tree = parse_abl(src);
+        let mut symbols = Vec::new();
+        collect_definition_symbols(tree.root_node(), src.as_bytes(), &mut symbols);
+
+        let green = symbols
+            .iter()
+            .find(|s| s.label == "Green")
+            .expect("enum member symbol");
+        assert_eq!(green.kind, CompletionItemKind::ENUM_MEMBER);
+        assert_eq!(green.detail, "Acme.Color member");
+        assert!(
+            symbols
+                .iter()
+                .any(|s| s.label == "Acme.Color" && s.kind == CompletionItemKind::ENUM)
+        );
+    }
+
+    #[test]
+    fn keeps_only_the_taken_branch_of_define_guarded_declarations() {
+        let src = r#"
+&GLOBAL-DEFINE USE-LONG YES
+&IF DEFINED(USE-LONG) &THEN
+DEFINE VARIABLE lvValue AS INT64 NO-UNDO.
+&ELSE
+DEFINE VARIABLE lvValue AS INTEGER NO-UNDO.
+&SCOPED-DEFINE NARROW YES
+&ENDIF
+"#;
+        let tree = parse_abl(src);
+        let mut symbols = Vec::new();
+        collect_definition_symbols(tree.root_node(), src.as_bytes(), &mut symbols);
+        let values = symbols
+            .iter()
+            .filter(|s| s.label == "lvValue")
+            .collect::<Vec<_>>();
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].start_byte, src.find("lvValue").expect("first"));
+
+        let mut sites = Vec::new();
+        collect_preprocessor_define_sites(tree.root_node(), src.as_bytes(), &mut sites);
+        let labels = sites.iter().map(|s| s.label.as_str()).collect::<Vec<_>>();
+        assert_eq!(labels, vec!["USE-LONG"]);
+    }
 
     #[test]
     fn collects_function_parameters_as_symbols() {
@@ -273,6 +537,96 @@ END FUNCTION.
         assert!(symbols.iter().any(|s| s.label.eq_ignore_ascii_case("p_b")));
     }
 
+    #[test]
+    fn marks_shared_variables_in_symbol_detail() {
+        let src = r#"
+DEFINE NEW SHARED VARIABLE gv_user AS CHARACTER NO-UNDO.
+DEFINE SHARED VARIABLE gv_site AS CHARACTER NO-UNDO.
+DEFINE VARIABLE lv_local AS CHARACTER NO-UNDO.
+"#;
+
+        let tree = parse_abl(src);
+
+        let mut symbols = Vec::new();
+        collect_definition_symbols(tree.root_node(), src.as_bytes(), &mut symbols);
+
+        let detail = |label: &str| {
+            symbols
+                .iter()
+                .find(|s| s.label.eq_ignore_ascii_case(label))
+                .map(|s| s.detail.clone())
+                .expect("symbol")
+        };
+        assert_eq!(detail("gv_user"), "SHARED CHARACTER NO-UNDO");
+        assert_eq!(detail("gv_site"), "SHARED CHARACTER NO-UNDO");
+        assert_eq!(detail("lv_local"), "CHARACTER NO-UNDO");
+    }
+
+    #[test]
+    fn renders_variable_declaration_with_modifiers() {
+        let src = r#"
+DEFINE VARIABLE lv_name AS CHARACTER NO-UNDO INITIAL "a b".
+DEFINE VARIABLE lv_days AS INTEGER EXTENT 7 INITIAL [1, 2] NO-UNDO.
+DEFINE VARIABLE lv_bare AS DECIMAL.
+"#;
+
+        let tree = parse_abl(src);
+        let decl = |name: &str| {
+            find_variable_declaration(tree.root_node(), src.as_bytes(), name, src.len())
+        };
+
+        assert_eq!(
+            decl("lv_name").as_deref(),
+            Some(r#"DEFINE VARIABLE lv_name AS CHARACTER NO-UNDO INITIAL "a b""#)
+        );
+        assert_eq!(
+            decl("LV_DAYS").as_deref(),
+            Some("DEFINE VARIABLE lv_days AS INTEGER NO-UNDO INITIAL [1, 2] EXTENT 7")
+        );
+        assert_eq!(decl("lv_bare"), None);
+    }
+
+    #[test]
+    fn renders_the_variable_declaration_visible_at_the_offset() {
+        let src = r#"
+DEFINE VARIABLE lv_count AS INTEGER NO-UNDO.
+PROCEDURE calc:
+  DEFINE VARIABLE lv_count AS DECIMAL NO-UNDO INITIAL 1.5.
+  lv_count = 2.
+END PROCEDURE.
+PROCEDURE other:
+  DEFINE VARIABLE lv_other AS CHARACTER NO-UNDO.
+END PROCEDURE.
+lv_count = 3.
+"#;
+
+        let tree = parse_abl(src);
+        let decl = |name: &str, at: &str| {
+            let offset = src.find(at).expect("offset");
+            find_variable_declaration(tree.root_node(), src.as_bytes(), name, offset)
+        };
+
+        assert_eq!(
+            decl("lv_count", "lv_count = 2").as_deref(),
+            Some("DEFINE VARIABLE lv_count AS DECIMAL NO-UNDO INITIAL 1.5")
+        );
+        assert_eq!(
+            decl("lv_count", "lv_count = 3").as_deref(),
+            Some("DEFINE VARIABLE lv_count AS INTEGER NO-UNDO")
+        );
+        assert_eq!(decl("lv_other", "lv_count = 3"), None);
+    }
+
+    #[test]
+    fn tokenizes_definition_text_with_strings_and_lists() {
+        assert_eq!(
+            definition_tokens(r#" NO-UNDO INIT "x y" EXTENT 2 INITIAL [1, 2]."#),
+            vec![
+                "NO-UNDO", "INIT", r#""x y""#, "EXTENT", "2", "INITIAL", "[1, 2]"
+            ]
+        );
+    }
+
     #[test]
     fn collects_preprocessor_define_symbols_for_completion() {
         let src = r#"
@@ -48,6 +48,7 @@ pub fn collect_df_table_sites(node: Node, src: &[u8], out: &mut Vec<DfTableSite>
 
 pub struct DfFieldSite {
     pub name: String,
+    pub table: Option<String>,
     pub range: Range,
 }
 
@@ -58,8 +59,14 @@ pub fn collect_df_field_sites(node: Node, src: &[u8], out: &mut Vec<DfFieldSite>
         && let Ok(raw) = field_node.utf8_text(src)
         && let Some(name) = unquote(raw)
     {
+        let table = node
+            .child_by_field_name("table")
+            .and_then(|t| t.utf8_text(src).ok())
+            .and_then(unquote)
+            .map(str::to_string);
         out.push(DfFieldSite {
             name: name.to_string(),
+            table,
             range: node_to_range(field_node),
         });
     }
@@ -187,10 +194,17 @@ pub fn collect_df_sequence_sites(node: Node, src: &[u8], out: &mut Vec<DfSequenc
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DfIndexFlags {
+    pub unique: bool,
+    pub primary: bool,
+}
+
 pub struct DfTableIndex {
     pub table: String,
     pub index: String,
     pub fields: Vec<String>,
+    pub flags: DfIndexFlags,
 }
 
 /// Collects `(table, index)` pairs from `ADD INDEX "index" ON "table"`.
@@ -204,16 +218,12 @@ pub fn collect_df_table_indexes(node: Node, src: &[u8], out: &mut Vec<DfTableInd
             (index_node.utf8_text(src), table_node.utf8_text(src))
         && let (Some(index), Some(table)) = (unquote(index_raw), unquote(table_raw))
     {
-        let fields = node
-            .utf8_text(src)
-            .ok()
-            .map(extract_index_field_names)
-            .unwrap_or_default();
-
+        let raw = node.utf8_text(src).unwrap_or_default();
         out.push(DfTableIndex {
             table: table.to_string(),
             index: index.to_string(),
-            fields,
+            fields: extract_index_field_names(raw),
+            flags: extract_index_flags(raw),
         });
     }
 
@@ -278,12 +288,28 @@ fn extract_index_field_names(raw: &str) -> Vec<String> {
     out
 }
 
+/// Reads the `UNIQUE` / `PRIMARY` option lines of an `ADD INDEX` block.
+fn extract_index_flags(raw: &str) -> DfIndexFlags {
+    let mut flags = DfIndexFlags::default();
+    for line in raw.lines() {
+        let Some(first) = line.split_ascii_whitespace().next() else {
+            continue;
+        };
+        if first.eq_ignore_ascii_case("UNIQUE") {
+            flags.unique = true;
+        } else if first.eq_ignore_ascii_case("PRIMARY") {
+            flags.primary = true;
+        }
+    }
+    flags
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
         collect_df_field_sites, collect_df_index_sites, collect_df_sequence_sites,
         collect_df_table_indexes, collect_df_table_names, collect_df_table_sites,
-        extract_first_quoted, extract_index_field_names, unquote,
+        extract_first_quoted, extract_index_field_names, extract_index_flags, unquote,
     };
     use std::collections::HashSet;
 
@@ -330,6 +356,7 @@ ADD INDEX "z9zw_idx" ON "z9zw_mstr"
                 .iter()
                 .any(|s| s.name.eq_ignore_ascii_case("Z9ZW_ID"))
         );
+        assert_eq!(field_sites[0].table.as_deref(), Some("z9zw_mstr"));
 
         let mut index_sites = Vec::new();
         collect_df_index_sites(tree.root_node(), src.as_bytes(), &mut index_sites);
@@ -357,6 +384,8 @@ ADD INDEX "z9zw_idx" ON "z9zw_mstr"
             .find(|i| i.index.eq_ignore_ascii_case("Z9ZW_IDX"))
             .expect("index fields");
         assert_eq!(idx.fields, vec!["z9zw_id"]);
+        assert!(idx.flags.unique);
+        assert!(!idx.flags.primary);
     }
 
     #[test]
@@ -386,4 +415,20 @@ ADD INDEX "idx" ON "tt"
 ."#;
         assert_eq!(extract_index_field_names(raw), vec!["a", "b"]);
     }
+
+    #[test]
+    fn extracts_index_flags_from_option_lines() {
+        let raw = r#"
+ADD INDEX "idx" ON "tt"
+  AREA "Index Area"
+  UNIQUE
+  PRIMARY
+  INDEX-FIELD "a" ASC
+."#;
+        let flags = extract_index_flags(raw);
+        assert!(flags.unique && flags.primary);
+
+        let plain = extract_index_flags("ADD INDEX \"idx\" ON \"tt\"\n  INDEX-FIELD \"a\" ASC\n.");
+        assert!(!plain.unique && !plain.primary);
+    }
 }
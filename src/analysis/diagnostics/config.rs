@@ -1,8 +1,8 @@
 use std::path::Path;
 
-use tower_lsp::lsp_types::Url;
+use tower_lsp::lsp_types::{DiagnosticSeverity, Url};
 
-use crate::config::DiagnosticFeatureConfig;
+use crate::config::{ClassFileBehavior, DiagnosticFeatureConfig, UnknownFunctionsConfig};
 use crate::utils::paths::uri_matches_any_path_pattern;
 
 pub fn diagnostics_feature_enabled_for_uri(
@@ -19,12 +19,32 @@ pub fn diagnostics_feature_enabled_for_uri(
     !uri_matches_any_path_pattern(uri, workspace_root, &feature.exclude)
 }
 
+/// Severity of unknown-function diagnostics in `uri`; `None` when `.cls` files have them off.
+pub fn unknown_function_severity_for_uri(
+    uri: &Url,
+    config: &UnknownFunctionsConfig,
+) -> Option<DiagnosticSeverity> {
+    let is_class_file = Path::new(uri.path())
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("cls"));
+    if !is_class_file {
+        return Some(config.feature.severity.to_lsp());
+    }
+    match config.class_file_behavior {
+        ClassFileBehavior::Error => Some(DiagnosticSeverity::ERROR),
+        ClassFileBehavior::Warning => Some(DiagnosticSeverity::WARNING),
+        ClassFileBehavior::Off => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::diagnostics_feature_enabled_for_uri;
-    use crate::config::DiagnosticFeatureConfig;
+    use super::{diagnostics_feature_enabled_for_uri, unknown_function_severity_for_uri};
+    use crate::config::{
+        ClassFileBehavior, DiagnosticFeatureConfig, SeverityLevel, UnknownFunctionsConfig,
+    };
     use std::path::Path;
-    use tower_lsp::lsp_types::Url;
+    use tower_lsp::lsp_types::{DiagnosticSeverity, Url};
 
     #[test]
     fn disables_feature_when_flag_is_false() {
@@ -33,6 +53,7 @@ mod tests {
             enabled: false,
             exclude: Vec::new(),
             ignore: Vec::new(),
+            ..Default::default()
         };
 
         assert!(!diagnostics_feature_enabled_for_uri(
@@ -49,6 +70,7 @@ mod tests {
             enabled: true,
             exclude: Vec::new(),
             ignore: Vec::new(),
+            ..Default::default()
         };
 
         assert!(diagnostics_feature_enabled_for_uri(
@@ -65,6 +87,7 @@ mod tests {
             enabled: true,
             exclude: vec!["legacy/*".to_string()],
             ignore: Vec::new(),
+            ..Default::default()
         };
 
         assert!(!diagnostics_feature_enabled_for_uri(
@@ -73,4 +96,58 @@ mod tests {
             &feature
         ));
     }
+
+    #[test]
+    fn applies_recursive_exclusions_per_feature_independently() {
+        let uri = Url::parse("file:///tmp/project/tmp/nested/deep/job.p").expect("uri");
+        let unknown_variables = DiagnosticFeatureConfig {
+            enabled: true,
+            exclude: vec!["tmp/**/*.p".to_string()],
+            ignore: Vec::new(),
+            ..Default::default()
+        };
+        let unknown_functions = DiagnosticFeatureConfig {
+            enabled: true,
+            exclude: vec!["legacy/*.p".to_string()],
+            ignore: Vec::new(),
+            ..Default::default()
+        };
+        let root = Some(Path::new("/tmp/project"));
+
+        assert!(!diagnostics_feature_enabled_for_uri(
+            &uri,
+            root,
+            &unknown_variables
+        ));
+        assert!(diagnostics_feature_enabled_for_uri(
+            &uri,
+            root,
+            &unknown_functions
+        ));
+    }
+
+    #[test]
+    fn applies_class_file_behavior_only_to_cls_files() {
+        let procedure = Url::parse("file:///tmp/project/src/main.p").expect("uri");
+        let class = Url::parse("file:///tmp/project/src/Order.CLS").expect("uri");
+        let mut config = UnknownFunctionsConfig::default();
+        config.feature.severity = SeverityLevel::Hint;
+
+        assert_eq!(
+            unknown_function_severity_for_uri(&procedure, &config),
+            Some(DiagnosticSeverity::HINT)
+        );
+        assert_eq!(unknown_function_severity_for_uri(&class, &config), None);
+
+        config.class_file_behavior = ClassFileBehavior::Warning;
+        assert_eq!(
+            unknown_function_severity_for_uri(&class, &config),
+            Some(DiagnosticSeverity::WARNING)
+        );
+        config.class_file_behavior = ClassFileBehavior::Error;
+        assert_eq!(
+            unknown_function_severity_for_uri(&class, &config),
+            Some(DiagnosticSeverity::ERROR)
+        );
+    }
 }
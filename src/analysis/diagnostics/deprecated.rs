@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag};
+use tree_sitter::Node;
+
+use crate::utils::ts::node_to_range;
+
+/// Built-in `diagnostics.deprecated_statements` entries, keyed by node kind or leading keyword.
+pub const DEFAULT_DEPRECATED_STATEMENTS: &[(&str, &str)] = &[
+    (
+        "work_table_definition",
+        "DEFINE WORK-TABLE is deprecated; use DEFINE TEMP-TABLE",
+    ),
+    (
+        "workfile_definition",
+        "DEFINE WORKFILE is deprecated; use DEFINE TEMP-TABLE",
+    ),
+    ("CHOOSE", "CHOOSE is a legacy character-mode statement"),
+    ("STATUS", "STATUS is a legacy character-mode statement"),
+];
+
+/// Warns on statements whose node kind, or leading keyword for `*_statement` nodes, is listed.
+pub fn collect_deprecated_statement_diags(
+    node: Node<'_>,
+    src: &[u8],
+    deprecated: &HashMap<String, String>,
+    out: &mut Vec<Diagnostic>,
+) {
+    if let Some(message) = deprecated_message(node, src, deprecated) {
+        let target = node
+            .child_by_field_name("name")
+            .or_else(|| node.child(0))
+            .unwrap_or(node);
+        out.push(Diagnostic {
+            range: node_to_range(target),
+            severity: Some(DiagnosticSeverity::WARNING),
+            source: Some("abl-semantic".into()),
+            message: message.clone(),
+            tags: Some(vec![DiagnosticTag::DEPRECATED]),
+            ..Default::default()
+        });
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_deprecated_statement_diags(ch, src, deprecated, out);
+        }
+    }
+}
+
+fn deprecated_message<'a>(
+    node: Node<'_>,
+    src: &[u8],
+    deprecated: &'a HashMap<String, String>,
+) -> Option<&'a String> {
+    if let Some(message) = deprecated.get(node.kind()) {
+        return Some(message);
+    }
+    if !node.kind().ends_with("_statement") {
+        return None;
+    }
+    let keyword = node
+        .child(0)?
+        .utf8_text(src)
+        .ok()?
+        .trim()
+        .to_ascii_uppercase();
+    deprecated
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(&keyword))
+        .map(|(_, message)| message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::collect_deprecated_statement_diags;
+    use crate::analysis::parse_abl;
+    use tower_lsp::lsp_types::{DiagnosticSeverity, DiagnosticTag};
+
+    #[test]
+    fn warns_on_configured_node_kinds_and_keywords() {
+        let src = r#"
+DEFINE WORK-TABLE wtItem
+  FIELD id AS INTEGER.
+DEFINE TEMP-TABLE ttItem NO-UNDO
+  FIELD id AS INTEGER.
+RUN legacy.p.
+"#;
+        let tree = parse_abl(src);
+        let deprecated = HashMap::from([
+            (
+                "work_table_definition".to_string(),
+                "use a temp-table".to_string(),
+            ),
+            ("run".to_string(), "no RUN".to_string()),
+        ]);
+        let mut diags = Vec::new();
+        collect_deprecated_statement_diags(
+            tree.root_node(),
+            src.as_bytes(),
+            &deprecated,
+            &mut diags,
+        );
+
+        let messages = diags.iter().map(|d| d.message.as_str()).collect::<Vec<_>>();
+        assert_eq!(messages, vec!["use a temp-table", "no RUN"]);
+        assert!(diags.iter().all(|d| {
+            d.severity == Some(DiagnosticSeverity::WARNING)
+                && d.tags == Some(vec![DiagnosticTag::DEPRECATED])
+        }));
+    }
+}
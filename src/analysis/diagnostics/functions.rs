@@ -1,9 +1,14 @@
 use std::collections::HashMap;
 
-use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Range};
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, Range, Url,
+};
 use tree_sitter::Node;
 
-use crate::analysis::functions::normalize_function_name;
+use crate::analysis::functions::{
+    normalize_function_name, persistent_procedure_key, procedure_arity_key,
+};
+use crate::analysis::signature::{run_program_target, run_target_name, split_argument_spans};
 use crate::utils::ts::{count_nodes_by_kind, direct_child_by_kind, node_to_range};
 
 #[derive(Clone)]
@@ -14,29 +19,73 @@ pub struct FunctionCallSite {
     pub range: Range,
 }
 
-pub fn collect_function_arities(node: Node<'_>, src: &[u8], out: &mut HashMap<String, Vec<usize>>) {
+#[derive(Clone, PartialEq)]
+pub struct FunctionArity {
+    pub arity: usize,
+    pub location: Location,
+}
+
+pub fn collect_function_arities(
+    node: Node<'_>,
+    src: &[u8],
+    uri: &Url,
+    out: &mut HashMap<String, Vec<FunctionArity>>,
+) {
     if matches!(
         node.kind(),
         "function_definition" | "function_forward_definition"
-    ) {
-        let name = node
-            .child_by_field_name("name")
-            .and_then(|n| n.utf8_text(src).ok())
-            .map(normalize_function_name);
-        if let Some(name_upper) = name {
-            let arity = function_param_count(node, src);
-            out.entry(name_upper).or_default().push(arity);
-        }
+    ) && let Some(name_node) = node.child_by_field_name("name")
+        && let Ok(name) = name_node.utf8_text(src)
+    {
+        let arity = function_param_count(node, src);
+        out.entry(normalize_function_name(name))
+            .or_default()
+            .push(FunctionArity {
+                arity,
+                location: Location {
+                    uri: uri.clone(),
+                    range: node_to_range(name_node),
+                },
+            });
+    }
+
+    if matches!(
+        node.kind(),
+        "procedure_definition" | "procedure_forward_definition"
+    ) && let Some(name_node) = node.child_by_field_name("name")
+        && let Ok(name) = name_node.utf8_text(src)
+    {
+        let arity = function_param_count(node, src);
+        out.entry(procedure_arity_key(name))
+            .or_default()
+            .push(FunctionArity {
+                arity,
+                location: Location {
+                    uri: uri.clone(),
+                    range: node_to_range(name_node),
+                },
+            });
     }
 
     for i in 0..node.child_count() {
         if let Some(ch) = node.child(i as u32) {
-            collect_function_arities(ch, src, out);
+            collect_function_arities(ch, src, uri, out);
         }
     }
 }
 
 pub fn collect_function_calls(node: Node<'_>, src: &[u8], out: &mut Vec<FunctionCallSite>) {
+    let mut handles = HashMap::new();
+    collect_persistent_run_handles(node, src, &mut handles);
+    collect_function_calls_in_node(node, src, &handles, out);
+}
+
+fn collect_function_calls_in_node(
+    node: Node<'_>,
+    src: &[u8],
+    handles: &HashMap<String, String>,
+    out: &mut Vec<FunctionCallSite>,
+) {
     if node.kind() == "function_call" {
         let function_node = node.child_by_field_name("function");
         let display_name = function_node
@@ -61,45 +110,213 @@ pub fn collect_function_calls(node: Node<'_>, src: &[u8], out: &mut Vec<Function
         }
     }
 
+    if node.kind() == "run_statement"
+        && let Some(call) = run_call_site(node, src, handles)
+    {
+        out.push(call);
+    }
+
     for i in 0..node.child_count() {
         if let Some(ch) = node.child(i as u32) {
-            collect_function_calls(ch, src, out);
+            collect_function_calls_in_node(ch, src, handles, out);
         }
     }
 }
 
+/// Handle variables set by `RUN prog.p PERSISTENT SET hProc`, mapped to the program they run.
+pub fn collect_persistent_run_handles(
+    node: Node<'_>,
+    src: &[u8],
+    out: &mut HashMap<String, String>,
+) {
+    if node.kind() == "run_statement"
+        && let Some((program, _, end)) = run_program_target(node, src)
+        && let Some(tail) = src
+            .get(end..node.end_byte())
+            .and_then(|b| std::str::from_utf8(b).ok())
+    {
+        let mut tokens = tail
+            .split(|c: char| c.is_ascii_whitespace() || c == '(' || c == ')')
+            .filter(|t| !t.is_empty());
+        if tokens
+            .next()
+            .is_some_and(|t| t.eq_ignore_ascii_case("PERSISTENT"))
+            && tokens.next().is_some_and(|t| t.eq_ignore_ascii_case("SET"))
+            && let Some(handle) = tokens.next()
+        {
+            let handle = handle.trim_end_matches('.');
+            if !handle.is_empty() {
+                out.insert(handle.to_ascii_uppercase(), program);
+            }
+        }
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_persistent_run_handles(ch, src, out);
+        }
+    }
+}
+
+/// `RUN name (args)` / `RUN name.` against an internal procedure; other RUN forms are skipped.
+fn run_call_site(
+    node: Node<'_>,
+    src: &[u8],
+    handles: &HashMap<String, String>,
+) -> Option<FunctionCallSite> {
+    let (display_name, name_upper, arguments) = run_call_arguments(node, src, handles)?;
+    let name_node = node
+        .child_by_field_name("procedure")
+        .or_else(|| node.child_by_field_name("name"))?;
+    Some(FunctionCallSite {
+        name_upper,
+        display_name,
+        arg_count: arguments.len(),
+        range: node_to_range(name_node),
+    })
+}
+
+/// Display name, arity key and argument byte spans of a `RUN` against an internal procedure.
+/// `RUN name IN hProc` targets this file for `THIS-PROCEDURE`, or the program `hProc` was
+/// started from by a `RUN ... PERSISTENT SET hProc` in `handles`.
+pub fn run_call_arguments(
+    node: Node<'_>,
+    src: &[u8],
+    handles: &HashMap<String, String>,
+) -> Option<(String, String, Vec<(usize, usize)>)> {
+    let display_name = run_target_name(node, src)?;
+    let name_node = node
+        .child_by_field_name("procedure")
+        .or_else(|| node.child_by_field_name("name"))?;
+    let tail = src.get(name_node.end_byte()..node.end_byte())?;
+    let mut tail = std::str::from_utf8(tail).ok()?.trim_start();
+    let mut name_upper = procedure_arity_key(&display_name);
+
+    if let Some(rest) = tail
+        .get(..2)
+        .filter(|kw| kw.eq_ignore_ascii_case("IN"))
+        .and(tail.get(2..))
+        .filter(|rest| rest.starts_with(|c: char| c.is_ascii_whitespace()))
+    {
+        let rest = rest.trim_start();
+        let handle_len = rest
+            .find(|c: char| c.is_ascii_whitespace() || c == '(' || c == '.')
+            .unwrap_or(rest.len());
+        let handle = rest[..handle_len].to_ascii_uppercase();
+        if handle != "THIS-PROCEDURE" {
+            name_upper = persistent_procedure_key(handles.get(&handle)?, &display_name);
+        }
+        tail = rest[handle_len..].trim_start();
+    }
+
+    let arguments = if tail.starts_with('(') {
+        let open = node.end_byte() - tail.len();
+        split_argument_spans(src, open, node.end_byte())
+    } else if tail.trim().trim_end_matches('.').trim().is_empty() {
+        Vec::new()
+    } else {
+        return None;
+    };
+
+    Some((display_name, name_upper, arguments))
+}
+
 pub fn append_function_arity_mismatch_diags(
-    signatures: &HashMap<String, Vec<usize>>,
+    signatures: &HashMap<String, Vec<FunctionArity>>,
     calls: &[FunctionCallSite],
+    severity: DiagnosticSeverity,
     out: &mut Vec<Diagnostic>,
 ) {
     for call in calls {
-        let Some(expected_set) = signatures.get(&call.name_upper) else {
+        let Some(candidates) = signatures.get(&call.name_upper) else {
             continue;
         };
-        if expected_set.contains(&call.arg_count) {
+        if candidates.iter().any(|c| c.arity == call.arg_count) {
             continue;
         }
 
+        let mut expected_set = candidates.iter().map(|c| c.arity).collect::<Vec<_>>();
+        expected_set.sort_unstable();
+        expected_set.dedup();
         let expected = expected_set
             .iter()
             .map(|v| v.to_string())
             .collect::<Vec<_>>()
             .join(" or ");
+        let related_information = candidates
+            .iter()
+            .map(|c| DiagnosticRelatedInformation {
+                location: c.location.clone(),
+                message: format!(
+                    "Candidate '{}' with {} parameter(s)",
+                    call.display_name, c.arity
+                ),
+            })
+            .collect::<Vec<_>>();
+        out.push(Diagnostic {
+            range: call.range,
+            severity: Some(severity),
+            source: Some("abl-semantic".into()),
+            message: format!(
+                "{} '{}' expects {} argument(s), got {}",
+                if call.name_upper.starts_with("RUN ") {
+                    "Procedure"
+                } else {
+                    "Function"
+                },
+                call.display_name,
+                expected,
+                call.arg_count
+            ),
+            related_information: Some(related_information),
+            ..Default::default()
+        });
+    }
+}
+
+/// Warns about function calls that precede every in-file definition and `FORWARD` declaration.
+/// Names also provided by an include are skipped: the include may sit above the call.
+pub fn append_call_before_definition_diags(
+    signatures: &HashMap<String, Vec<FunctionArity>>,
+    uri: &Url,
+    calls: &[FunctionCallSite],
+    out: &mut Vec<Diagnostic>,
+) {
+    for call in calls {
+        if call.name_upper.starts_with("RUN ") {
+            continue;
+        }
+        let Some(candidates) = signatures.get(&call.name_upper) else {
+            continue;
+        };
+        if candidates.iter().any(|c| &c.location.uri != uri) {
+            continue;
+        }
+        let Some(first) = candidates.iter().min_by_key(|c| c.location.range.start) else {
+            continue;
+        };
+        if call.range.start >= first.location.range.start {
+            continue;
+        }
         out.push(Diagnostic {
             range: call.range,
-            severity: Some(DiagnosticSeverity::ERROR),
+            severity: Some(DiagnosticSeverity::WARNING),
             source: Some("abl-semantic".into()),
             message: format!(
-                "Function '{}' expects {} argument(s), got {}",
-                call.display_name, expected, call.arg_count
+                "Function '{}' is called before its definition or FORWARD declaration",
+                call.display_name
             ),
+            related_information: Some(vec![DiagnosticRelatedInformation {
+                location: first.location.clone(),
+                message: format!("'{}' is defined here", call.display_name),
+            }]),
             ..Default::default()
         });
     }
 }
 
-fn function_param_count(function_node: Node<'_>, src: &[u8]) -> usize {
+/// Declared parameter count of a function, procedure or method definition.
+pub fn function_param_count(function_node: Node<'_>, src: &[u8]) -> usize {
     if let Some(parameters_node) = direct_child_by_kind(function_node, "parameters") {
         let count = count_nodes_by_kind(parameters_node, "parameter");
         if count > 0 {
@@ -120,6 +337,7 @@ fn count_parameter_definitions(node: Node<'_>, out: &mut usize, is_root: bool) {
             "function_definition"
                 | "function_forward_definition"
                 | "procedure_definition"
+                | "procedure_forward_definition"
                 | "method_definition"
                 | "constructor_definition"
                 | "destructor_definition"
@@ -152,9 +370,13 @@ fn count_argument_nodes(arguments_node: Node<'_>) -> usize {
 
 #[cfg(test)]
 mod tests {
-    use super::{collect_function_arities, collect_function_calls};
+    use super::{
+        FunctionArity, append_call_before_definition_diags, append_function_arity_mismatch_diags,
+        collect_function_arities, collect_function_calls,
+    };
     use crate::analysis::parse_abl;
     use std::collections::HashMap;
+    use tower_lsp::lsp_types::{DiagnosticSeverity, Url};
 
     #[test]
     fn extracts_function_arities_and_call_arg_counts() {
@@ -170,9 +392,15 @@ x = foo().
 
         let tree = parse_abl(src);
 
-        let mut signatures = HashMap::<String, Vec<usize>>::new();
-        collect_function_arities(tree.root_node(), src.as_bytes(), &mut signatures);
-        assert_eq!(signatures.get("FOO").cloned(), Some(vec![2]));
+        let uri = Url::parse("file:///tmp/main.p").expect("uri");
+        let mut signatures = HashMap::<String, Vec<FunctionArity>>::new();
+        collect_function_arities(tree.root_node(), src.as_bytes(), &uri, &mut signatures);
+        assert_eq!(
+            signatures
+                .get("FOO")
+                .map(|c| c.iter().map(|a| a.arity).collect::<Vec<_>>()),
+            Some(vec![2])
+        );
 
         let mut calls = Vec::new();
         collect_function_calls(tree.root_node(), src.as_bytes(), &mut calls);
@@ -207,4 +435,155 @@ y = foo(INTEGER(pzd_linia)).
             .collect::<Vec<_>>();
         assert_eq!(foo_calls, vec![1]);
     }
+
+    #[test]
+    fn checks_run_arity_against_forward_only_procedure() {
+        let src = r#"
+PROCEDURE calcTotal IN SUPER:
+  DEFINE INPUT PARAMETER pOrder AS INTEGER NO-UNDO.
+  DEFINE OUTPUT PARAMETER pTotal AS DECIMAL NO-UNDO.
+END PROCEDURE.
+
+DEFINE VARIABLE dTotal AS DECIMAL NO-UNDO.
+RUN calcTotal (INPUT 1, OUTPUT dTotal).
+RUN calcTotal (INPUT 1).
+"#;
+
+        let tree = parse_abl(src);
+        let uri = Url::parse("file:///tmp/main.p").expect("uri");
+        let mut signatures = HashMap::<String, Vec<FunctionArity>>::new();
+        collect_function_arities(tree.root_node(), src.as_bytes(), &uri, &mut signatures);
+        assert_eq!(
+            signatures
+                .get("RUN CALCTOTAL")
+                .map(|c| c.iter().map(|a| a.arity).collect::<Vec<_>>()),
+            Some(vec![2])
+        );
+        assert!(!signatures.contains_key("CALCTOTAL"));
+
+        let mut calls = Vec::new();
+        collect_function_calls(tree.root_node(), src.as_bytes(), &mut calls);
+        let mut diags = Vec::new();
+        append_function_arity_mismatch_diags(
+            &signatures,
+            &calls,
+            DiagnosticSeverity::ERROR,
+            &mut diags,
+        );
+        assert_eq!(diags.len(), 1);
+        assert_eq!(
+            diags[0].message,
+            "Procedure 'calcTotal' expects 2 argument(s), got 1"
+        );
+    }
+
+    #[test]
+    fn attaches_candidate_locations_to_arity_mismatches() {
+        let src = r#"
+FUNCTION foo RETURNS LOGICAL FORWARD.
+FUNCTION foo RETURNS LOGICAL (INPUT p1 AS INTEGER):
+  RETURN TRUE.
+END FUNCTION.
+foo(1, 2).
+"#;
+
+        let tree = parse_abl(src);
+        let uri = Url::parse("file:///tmp/main.p").expect("uri");
+        let mut signatures = HashMap::<String, Vec<FunctionArity>>::new();
+        collect_function_arities(tree.root_node(), src.as_bytes(), &uri, &mut signatures);
+        let mut calls = Vec::new();
+        collect_function_calls(tree.root_node(), src.as_bytes(), &mut calls);
+
+        let mut diags = Vec::new();
+        append_function_arity_mismatch_diags(
+            &signatures,
+            &calls,
+            DiagnosticSeverity::ERROR,
+            &mut diags,
+        );
+        assert_eq!(diags.len(), 1);
+        assert_eq!(
+            diags[0].message,
+            "Function 'foo' expects 0 or 1 argument(s), got 2"
+        );
+        let related = diags[0].related_information.as_ref().expect("related");
+        assert_eq!(related.len(), 2);
+        assert!(related.iter().all(|r| r.location.uri == uri));
+    }
+
+    #[test]
+    fn resolves_run_in_persistent_handle_calls() {
+        let src = r#"
+DEFINE VARIABLE hLib AS HANDLE NO-UNDO.
+DEFINE VARIABLE hOther AS HANDLE NO-UNDO.
+RUN lib/util.p PERSISTENT SET hLib.
+RUN calc IN hLib (1, 2).
+RUN local IN THIS-PROCEDURE.
+RUN calc IN hOther (1).
+
+PROCEDURE local:
+END PROCEDURE.
+"#;
+        let tree = parse_abl(src);
+        let mut calls = Vec::new();
+        collect_function_calls(tree.root_node(), src.as_bytes(), &mut calls);
+        let run_calls = calls
+            .iter()
+            .filter(|c| c.name_upper.starts_with("RUN "))
+            .map(|c| (c.name_upper.as_str(), c.arg_count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            run_calls,
+            vec![("RUN LIB/UTIL.P::CALC", 2), ("RUN LOCAL", 0)]
+        );
+    }
+
+    #[test]
+    fn warns_when_function_is_called_before_its_definition() {
+        let src = r#"
+DEFINE VARIABLE x AS INTEGER NO-UNDO.
+x = late(1).
+
+FUNCTION early RETURNS INTEGER (INPUT p AS INTEGER) FORWARD.
+x = early(1).
+
+FUNCTION late RETURNS INTEGER (INPUT p AS INTEGER):
+  RETURN p.
+END FUNCTION.
+
+FUNCTION early RETURNS INTEGER (INPUT p AS INTEGER):
+  RETURN late(p).
+END FUNCTION.
+"#;
+        let tree = parse_abl(src);
+        let uri = Url::parse("file:///tmp/main.p").expect("uri");
+        let mut signatures = HashMap::<String, Vec<FunctionArity>>::new();
+        collect_function_arities(tree.root_node(), src.as_bytes(), &uri, &mut signatures);
+        let mut calls = Vec::new();
+        collect_function_calls(tree.root_node(), src.as_bytes(), &mut calls);
+
+        let mut diags = Vec::new();
+        append_call_before_definition_diags(&signatures, &uri, &calls, &mut diags);
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].range.start.line, 2);
+        assert_eq!(
+            diags[0].message,
+            "Function 'late' is called before its definition or FORWARD declaration"
+        );
+
+        let include_uri = Url::parse("file:///tmp/lib.i").expect("uri");
+        signatures
+            .get_mut("LATE")
+            .expect("late")
+            .push(FunctionArity {
+                arity: 1,
+                location: tower_lsp::lsp_types::Location {
+                    uri: include_uri,
+                    range: Default::default(),
+                },
+            });
+        let mut diags = Vec::new();
+        append_call_before_definition_diags(&signatures, &uri, &calls, &mut diags);
+        assert!(diags.is_empty());
+    }
 }
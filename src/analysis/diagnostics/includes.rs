@@ -0,0 +1,251 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Range, Url};
+use tree_sitter::Node;
+
+use crate::analysis::definitions::collect_preprocessor_define_sites;
+use crate::analysis::diagnostics::config::diagnostics_feature_enabled_for_uri;
+use crate::analysis::includes::{collect_include_sites_from_tree, resolve_include_site_path};
+use crate::analysis::signature::{
+    collect_run_program_sites, run_program_target, run_statement_is_remote,
+};
+use crate::backend::Backend;
+use crate::config::{DiagnosticFeatureConfig, DiagnosticsConfig};
+use crate::utils::position::utf8_byte_offset_to_lsp_pos;
+
+/// Reports `{file.i}` references that cannot be resolved via the current dir, PROPATH or workspace.
+/// `ignored` holds upper-cased include paths that are never reported.
+pub async fn collect_unresolved_include_diags(
+    backend: &Backend,
+    uri: &Url,
+    text: &str,
+    root: Node<'_>,
+    severity: DiagnosticSeverity,
+    ignored: &HashSet<String>,
+    out: &mut Vec<Diagnostic>,
+) {
+    let Ok(current_path) = uri.to_file_path() else {
+        return;
+    };
+    let mut define_sites = Vec::new();
+    collect_preprocessor_define_sites(root, text.as_bytes(), &mut define_sites);
+
+    for include in collect_include_sites_from_tree(root, text.as_bytes()) {
+        // A prefix macro defined elsewhere (e.g. in a parent include) cannot be expanded here.
+        if let Some(prefix_macro) = include.prefix_macro.as_deref()
+            && !define_sites
+                .iter()
+                .any(|d| d.label.eq_ignore_ascii_case(prefix_macro))
+        {
+            continue;
+        }
+        let include_path = resolve_include_site_path(&include, &define_sites);
+        if ignored.contains(&include.path.to_ascii_uppercase())
+            || ignored.contains(&include_path.to_ascii_uppercase())
+        {
+            continue;
+        }
+        if backend
+            .resolve_include_path_for(&current_path, &include_path)
+            .await
+            .is_some()
+        {
+            continue;
+        }
+
+        out.push(Diagnostic {
+            range: Range::new(
+                utf8_byte_offset_to_lsp_pos(text, include.start_offset),
+                utf8_byte_offset_to_lsp_pos(text, include.end_offset),
+            ),
+            severity: Some(severity),
+            source: Some("abl-include".into()),
+            message: format!("Include file '{}' not found on PROPATH", include.path),
+            ..Default::default()
+        });
+    }
+}
+
+/// Reports `RUN "prog.p"` targets that cannot be found; `RUN ... ON <server>` is run remotely and skipped.
+/// `ignored` holds upper-cased program paths that are never reported.
+pub async fn collect_unresolved_run_program_diags(
+    backend: &Backend,
+    uri: &Url,
+    text: &str,
+    root: Node<'_>,
+    severity: DiagnosticSeverity,
+    ignored: &HashSet<String>,
+    out: &mut Vec<Diagnostic>,
+) {
+    let Ok(current_path) = uri.to_file_path() else {
+        return;
+    };
+    let mut sites = Vec::new();
+    collect_run_program_sites(root, text.as_bytes(), &mut sites);
+
+    for site in sites {
+        let Some((program, start, end)) = run_program_target(site, text.as_bytes()) else {
+            continue;
+        };
+        if run_statement_is_remote(site, text.as_bytes())
+            || ignored.contains(&program.to_ascii_uppercase())
+            || backend
+                .resolve_run_program_path(&current_path, &program)
+                .await
+                .is_some()
+        {
+            continue;
+        }
+
+        out.push(Diagnostic {
+            range: Range::new(
+                utf8_byte_offset_to_lsp_pos(text, start),
+                utf8_byte_offset_to_lsp_pos(text, end),
+            ),
+            severity: Some(severity),
+            source: Some("abl-run".into()),
+            message: format!("Program '{program}' not found on PROPATH"),
+            ..Default::default()
+        });
+    }
+}
+
+/// Unresolved includes and `RUN` targets, each gated by its own `exclude` patterns. These hit the
+/// disk, so callers run them on open/save only. Ranges are already UTF-16.
+pub async fn collect_unresolved_path_diags(
+    backend: &Backend,
+    uri: &Url,
+    text: &str,
+    root: Node<'_>,
+    config: &DiagnosticsConfig,
+    workspace_root: Option<&Path>,
+    out: &mut Vec<Diagnostic>,
+) {
+    if diagnostics_feature_enabled_for_uri(uri, workspace_root, &config.unknown_includes) {
+        collect_unresolved_include_diags(
+            backend,
+            uri,
+            text,
+            root,
+            config.unknown_includes.severity.to_lsp(),
+            &ignored_paths(&config.unknown_includes),
+            out,
+        )
+        .await;
+    }
+    if diagnostics_feature_enabled_for_uri(uri, workspace_root, &config.unknown_programs) {
+        collect_unresolved_run_program_diags(
+            backend,
+            uri,
+            text,
+            root,
+            config.unknown_programs.severity.to_lsp(),
+            &ignored_paths(&config.unknown_programs),
+            out,
+        )
+        .await;
+    }
+}
+
+/// Upper-cased `ignore` entries of a path-based check (includes, RUN programs).
+fn ignored_paths(feature: &DiagnosticFeatureConfig) -> HashSet<String> {
+    feature
+        .ignore
+        .iter()
+        .map(|path| path.to_ascii_uppercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_unresolved_include_diags, collect_unresolved_run_program_diags};
+    use crate::analysis::parse_abl;
+    use crate::backend::Backend;
+    use std::collections::HashSet;
+    use std::fs;
+    use tower_lsp::lsp_types::{DiagnosticSeverity, Url};
+
+    #[tokio::test]
+    async fn reports_only_missing_and_not_ignored_includes() {
+        let base = std::env::temp_dir().join(format!(
+            "abl_ls_unresolved_include_test_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("epoch")
+                .as_nanos()
+        ));
+        fs::create_dir_all(&base).expect("create dir");
+        fs::write(
+            base.join("found.i"),
+            "DEFINE VARIABLE x AS INTEGER NO-UNDO.\n",
+        )
+        .expect("write include");
+        let path = base.join("main.p");
+        let src = "{found.i}\n{missing.i}\n{legacy/gone.i}\n";
+        fs::write(&path, src).expect("write main");
+
+        let backend = Backend::for_tests();
+        let uri = Url::from_file_path(&path).expect("uri");
+        let tree = parse_abl(src);
+        let mut diags = Vec::new();
+        collect_unresolved_include_diags(
+            &backend,
+            &uri,
+            src,
+            tree.root_node(),
+            DiagnosticSeverity::WARNING,
+            &HashSet::from(["LEGACY/GONE.I".to_string()]),
+            &mut diags,
+        )
+        .await;
+
+        assert_eq!(diags.len(), 1);
+        assert_eq!(
+            diags[0].message,
+            "Include file 'missing.i' not found on PROPATH"
+        );
+        assert_eq!(diags[0].severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diags[0].range.start.line, 1);
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[tokio::test]
+    async fn reports_missing_local_programs_but_not_remote_runs() {
+        let base = std::env::temp_dir().join(format!(
+            "abl_ls_unresolved_run_test_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("epoch")
+                .as_nanos()
+        ));
+        fs::create_dir_all(&base).expect("create dir");
+        fs::write(base.join("found.p"), "MESSAGE 1.\n").expect("write program");
+        let path = base.join("main.p");
+        let src = "RUN found.p.\nRUN missing.p.\nRUN remote.p ON SERVER hServer.\n";
+        fs::write(&path, src).expect("write main");
+
+        let backend = Backend::for_tests();
+        let uri = Url::from_file_path(&path).expect("uri");
+        let tree = parse_abl(src);
+        let mut diags = Vec::new();
+        collect_unresolved_run_program_diags(
+            &backend,
+            &uri,
+            src,
+            tree.root_node(),
+            DiagnosticSeverity::ERROR,
+            &HashSet::new(),
+            &mut diags,
+        )
+        .await;
+
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].message, "Program 'missing.p' not found on PROPATH");
+        assert_eq!(diags[0].source.as_deref(), Some("abl-run"));
+        assert_eq!(diags[0].range.start.line, 1);
+
+        let _ = fs::remove_dir_all(&base);
+    }
+}
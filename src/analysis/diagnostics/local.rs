@@ -0,0 +1,106 @@
+use dashmap::DashSet;
+use tower_lsp::lsp_types::{Diagnostic, Url};
+use tree_sitter::Node;
+
+use crate::analysis::diagnostics::deprecated::collect_deprecated_statement_diags;
+use crate::analysis::diagnostics::no_undo::collect_missing_no_undo_diags;
+use crate::analysis::diagnostics::params::{
+    collect_forward_param_mismatch_diags, collect_input_param_write_diags,
+    collect_output_argument_diags,
+};
+use crate::analysis::diagnostics::record_scope::collect_unscoped_db_field_assign_diags;
+use crate::analysis::diagnostics::types::{
+    collect_assignment_type_diags, collect_comparison_type_diags,
+    collect_function_call_arg_type_diags, collect_return_type_diags,
+};
+use crate::analysis::diagnostics::unknown_value::collect_unknown_value_diags;
+use crate::analysis::diagnostics::unreachable::collect_unreachable_code_diags;
+use crate::analysis::diagnostics::unused::collect_unused_variable_diags;
+use crate::config::DiagnosticsConfig;
+
+/// Checks that need nothing but the file's own tree and the schema, shared by open documents
+/// and the workspace pass. Type checks are cheap and run on every change; the other passes
+/// only when `include_semantic_diags` is set (open/save and unopened files).
+pub fn collect_file_local_diags(
+    root: Node<'_>,
+    src: &[u8],
+    uri: &Url,
+    config: &DiagnosticsConfig,
+    db_tables: &DashSet<String>,
+    include_semantic_diags: bool,
+    out: &mut Vec<Diagnostic>,
+) {
+    if config.type_checks.enabled {
+        let severity = config.type_checks.severity.to_lsp();
+        collect_assignment_type_diags(root, src, severity, out);
+        collect_function_call_arg_type_diags(root, src, severity, out);
+        collect_comparison_type_diags(root, src, severity, out);
+        collect_return_type_diags(root, src, severity, out);
+    }
+    if !include_semantic_diags {
+        return;
+    }
+    collect_unreachable_code_diags(root, out);
+    if config.input_param_writes {
+        collect_input_param_write_diags(root, src, out);
+    }
+    collect_output_argument_diags(root, src, out);
+    collect_forward_param_mismatch_diags(root, src, uri, out);
+    if config.unused_variables {
+        collect_unused_variable_diags(root, src, out);
+    }
+    if config.require_no_undo {
+        collect_missing_no_undo_diags(root, src, out);
+    }
+    if config.unknown_value_checks {
+        collect_unknown_value_diags(root, src, out);
+    }
+    if config.record_scope_checks {
+        collect_unscoped_db_field_assign_diags(root, src, db_tables, out);
+    }
+    collect_deprecated_statement_diags(root, src, &config.deprecated_statements, out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::collect_file_local_diags;
+    use crate::analysis::parse_abl;
+    use crate::config::DiagnosticsConfig;
+    use dashmap::DashSet;
+    use tower_lsp::lsp_types::Url;
+
+    #[test]
+    fn runs_semantic_passes_only_when_requested() {
+        let src = r#"
+PROCEDURE calc:
+  DEFINE VARIABLE x AS INTEGER NO-UNDO.
+  RETURN.
+  x = "text".
+END PROCEDURE.
+"#;
+        let tree = parse_abl(src);
+        let uri = Url::parse("file:///tmp/project/calc.p").expect("uri");
+        let config = DiagnosticsConfig::default();
+        let collect = |include_semantic_diags| {
+            let mut diags = Vec::new();
+            collect_file_local_diags(
+                tree.root_node(),
+                src.as_bytes(),
+                &uri,
+                &config,
+                &DashSet::new(),
+                include_semantic_diags,
+                &mut diags,
+            );
+            diags.into_iter().map(|d| d.message).collect::<Vec<_>>()
+        };
+
+        let on_change = collect(false);
+        assert!(on_change.iter().all(|m| m != "Unreachable code"));
+        assert!(!on_change.is_empty(), "type checks run on every change");
+
+        let on_save = collect(true);
+        assert!(on_save.iter().any(|m| m == "Unreachable code"));
+        assert!(on_save.len() > on_change.len());
+    }
+}
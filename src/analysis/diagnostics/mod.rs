@@ -1,6 +1,15 @@
 pub mod config;
+pub mod deprecated;
 pub mod functions;
+pub mod includes;
+pub mod local;
+pub mod no_undo;
+pub mod params;
+pub mod record_scope;
 pub mod semantic;
 pub mod symbols;
 pub mod syntax;
 pub mod types;
+pub mod unknown_value;
+pub mod unreachable;
+pub mod unused;
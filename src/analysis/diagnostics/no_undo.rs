@@ -0,0 +1,88 @@
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+use tree_sitter::Node;
+
+use crate::utils::ts::node_to_range;
+
+/// Definitions the `require_no_undo` lint applies to.
+pub fn is_no_undo_definition(kind: &str) -> bool {
+    matches!(kind, "variable_definition" | "temp_table_definition")
+}
+
+/// Hints `DEFINE VARIABLE`/`DEFINE TEMP-TABLE` statements without a `NO-UNDO` modifier.
+pub fn collect_missing_no_undo_diags(node: Node<'_>, src: &[u8], out: &mut Vec<Diagnostic>) {
+    if is_no_undo_definition(node.kind())
+        && !has_no_undo(node, src)
+        && let Some(name) = node.child_by_field_name("name")
+        && let Ok(label) = name.utf8_text(src)
+    {
+        out.push(Diagnostic {
+            range: node_to_range(name),
+            severity: Some(DiagnosticSeverity::HINT),
+            source: Some("abl-semantic".into()),
+            message: format!("'{}' is defined without NO-UNDO", label.trim()),
+            ..Default::default()
+        });
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_missing_no_undo_diags(ch, src, out);
+        }
+    }
+}
+
+/// Whether the definition carries a `NO-UNDO` token (fields of a temp-table are not searched).
+pub fn has_no_undo(definition: Node<'_>, src: &[u8]) -> bool {
+    for i in 0..definition.child_count() {
+        let Some(ch) = definition.child(i as u32) else {
+            continue;
+        };
+        if matches!(ch.kind(), "temp_table_field" | "field" | "temp_table_index") {
+            continue;
+        }
+        if ch.child_count() == 0 {
+            if ch
+                .utf8_text(src)
+                .is_ok_and(|t| t.trim().eq_ignore_ascii_case("NO-UNDO"))
+            {
+                return true;
+            }
+        } else if has_no_undo(ch, src) {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::collect_missing_no_undo_diags;
+    use crate::analysis::parse_abl;
+    use tower_lsp::lsp_types::DiagnosticSeverity;
+
+    #[test]
+    fn hints_definitions_without_no_undo() {
+        let src = r#"
+DEFINE VARIABLE lvGood AS INTEGER NO-UNDO.
+DEFINE VARIABLE lvBad AS CHARACTER INITIAL "x".
+DEFINE TEMP-TABLE ttGood NO-UNDO
+  FIELD f1 AS CHARACTER.
+DEFINE TEMP-TABLE ttBad
+  FIELD f1 AS CHARACTER.
+"#;
+        let tree = parse_abl(src);
+        let mut diags = Vec::new();
+        collect_missing_no_undo_diags(tree.root_node(), src.as_bytes(), &mut diags);
+
+        let messages = diags.iter().map(|d| d.message.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "'lvBad' is defined without NO-UNDO",
+                "'ttBad' is defined without NO-UNDO",
+            ]
+        );
+        assert_eq!(diags[0].severity, Some(DiagnosticSeverity::HINT));
+        assert_eq!(diags[1].range.start.line, 5);
+    }
+}
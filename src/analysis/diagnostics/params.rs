@@ -0,0 +1,558 @@
+use std::collections::{HashMap, HashSet};
+
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, Range, Url,
+};
+use tree_sitter::Node;
+
+use crate::analysis::diagnostics::functions::run_call_arguments;
+use crate::analysis::diagnostics::symbols::assignment_target_identifier;
+use crate::analysis::diagnostics::types::argument_exprs;
+use crate::analysis::functions::{normalize_function_name, procedure_arity_key};
+use crate::analysis::scopes::is_scope_node;
+use crate::analysis::types::canonical_type_name;
+use crate::utils::position::utf8_byte_offset_to_byte_pos;
+use crate::utils::ts::{direct_child_by_kind, node_to_range};
+
+/// Warns about assignments to `INPUT` parameters of the enclosing procedure/function/method;
+/// ABL allows them, but the new value never reaches the caller.
+pub fn collect_input_param_write_diags(node: Node<'_>, src: &[u8], out: &mut Vec<Diagnostic>) {
+    if node.parent().is_none() || is_scope_node(node.kind()) {
+        let input_params = param_nodes(node)
+            .into_iter()
+            .filter(|param| {
+                let mode = param_mode(*param, src);
+                mode.scalar && mode.keyword == "INPUT"
+            })
+            .filter_map(|param| param.child_by_field_name("name"))
+            .filter_map(|name| name.utf8_text(src).ok())
+            .map(|name| name.trim().to_ascii_uppercase())
+            .collect::<HashSet<_>>();
+        if !input_params.is_empty() {
+            collect_scope_param_writes(node, src, true, &input_params, out);
+        }
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_input_param_write_diags(ch, src, out);
+        }
+    }
+}
+
+fn collect_scope_param_writes(
+    node: Node<'_>,
+    src: &[u8],
+    is_root: bool,
+    input_params: &HashSet<String>,
+    out: &mut Vec<Diagnostic>,
+) {
+    if !is_root && is_scope_node(node.kind()) {
+        return;
+    }
+    if let Some(left) = assignment_target_identifier(node)
+        && let Ok(name) = left.utf8_text(src)
+        && input_params.contains(&name.trim().to_ascii_uppercase())
+    {
+        out.push(Diagnostic {
+            range: node_to_range(left),
+            severity: Some(DiagnosticSeverity::WARNING),
+            source: Some("abl-semantic".into()),
+            message: format!(
+                "Assignment to INPUT parameter '{}' is not returned to the caller",
+                name.trim()
+            ),
+            ..Default::default()
+        });
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_scope_param_writes(ch, src, false, input_params, out);
+        }
+    }
+}
+
+/// Reports implementation parameters whose mode or type differs from the `FORWARD` declaration.
+pub fn collect_forward_param_mismatch_diags(
+    root: Node<'_>,
+    src: &[u8],
+    uri: &Url,
+    out: &mut Vec<Diagnostic>,
+) {
+    let mut forwards = HashMap::<String, Node<'_>>::new();
+    let mut implementations = Vec::new();
+    collect_function_definitions(root, src, &mut forwards, &mut implementations);
+
+    for (key, implementation) in implementations {
+        let Some(forward) = forwards.get(&key) else {
+            continue;
+        };
+        let display_name = implementation
+            .child_by_field_name("name")
+            .and_then(|n| n.utf8_text(src).ok())
+            .unwrap_or_default()
+            .trim();
+        let forward_params = param_nodes(*forward);
+        let impl_params = param_nodes(implementation);
+
+        for (index, (impl_param, forward_param)) in
+            impl_params.iter().zip(forward_params.iter()).enumerate()
+        {
+            let impl_mode = param_mode(*impl_param, src).keyword;
+            let forward_mode = param_mode(*forward_param, src).keyword;
+            let impl_type = declared_param_type(*impl_param, src);
+            let forward_type = declared_param_type(*forward_param, src);
+            let (here, there) = if impl_mode != forward_mode {
+                (impl_mode, forward_mode)
+            } else if let (Some(impl_type), Some(forward_type)) = (impl_type, forward_type)
+                && canonical_type_name(impl_type) != canonical_type_name(forward_type)
+            {
+                (impl_type.to_string(), forward_type.to_string())
+            } else {
+                continue;
+            };
+            let message = format!(
+                "Parameter {} of '{}' is {} here but {} in the FORWARD declaration",
+                index + 1,
+                display_name,
+                here,
+                there
+            );
+            out.push(Diagnostic {
+                range: node_to_range(*impl_param),
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some("abl-semantic".into()),
+                message,
+                related_information: Some(vec![DiagnosticRelatedInformation {
+                    location: Location {
+                        uri: uri.clone(),
+                        range: node_to_range(*forward_param),
+                    },
+                    message: "FORWARD declaration of this parameter".into(),
+                }]),
+                ..Default::default()
+            });
+        }
+    }
+}
+
+fn collect_function_definitions<'tree>(
+    node: Node<'tree>,
+    src: &[u8],
+    forwards: &mut HashMap<String, Node<'tree>>,
+    implementations: &mut Vec<(String, Node<'tree>)>,
+) {
+    if matches!(
+        node.kind(),
+        "function_definition" | "function_forward_definition"
+    ) && let Some(name) = node
+        .child_by_field_name("name")
+        .and_then(|n| n.utf8_text(src).ok())
+    {
+        let key = normalize_function_name(name);
+        if node.kind() == "function_forward_definition" {
+            forwards.entry(key).or_insert(node);
+        } else {
+            implementations.push((key, node));
+        }
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_function_definitions(ch, src, forwards, implementations);
+        }
+    }
+}
+
+/// `(p AS ...)` header parameters, or the `DEFINE PARAMETER` statements of the body.
+fn param_nodes(def_node: Node<'_>) -> Vec<Node<'_>> {
+    let mut out = Vec::new();
+    if let Some(parameters_node) = direct_child_by_kind(def_node, "parameters") {
+        collect_param_nodes(parameters_node, "parameter", true, &mut out);
+        if !out.is_empty() {
+            return out;
+        }
+    }
+    collect_param_nodes(def_node, "parameter_definition", true, &mut out);
+    out
+}
+
+fn collect_param_nodes<'tree>(
+    node: Node<'tree>,
+    target_kind: &str,
+    is_root: bool,
+    out: &mut Vec<Node<'tree>>,
+) {
+    if !is_root && is_scope_node(node.kind()) {
+        return;
+    }
+    if node.kind() == target_kind {
+        out.push(node);
+        return;
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_param_nodes(ch, target_kind, false, out);
+        }
+    }
+}
+
+/// How a parameter is passed.
+#[derive(Debug, PartialEq, Eq)]
+struct ParamMode {
+    /// Upper-cased `INPUT` / `OUTPUT` / `INPUT-OUTPUT` / `RETURN`, or `BUFFER`.
+    keyword: String,
+    /// `false` for `BUFFER`, `TABLE` and `DATASET` parameters, which take a name, not a value.
+    scalar: bool,
+}
+
+/// Mode of a header parameter or `DEFINE PARAMETER`; parameters without a mode are `INPUT`.
+fn param_mode(node: Node<'_>, src: &[u8]) -> ParamMode {
+    const MODES: &[&str] = &["INPUT", "OUTPUT", "INPUT-OUTPUT", "RETURN"];
+    const NON_SCALAR: &[&str] = &["BUFFER", "TABLE", "DATASET"];
+
+    let raw = node.utf8_text(src).unwrap_or_default();
+    let mut tokens = raw
+        .split_ascii_whitespace()
+        .map(str::to_ascii_uppercase)
+        .peekable();
+    if node.kind() == "parameter_definition" {
+        tokens.next_if(|token| token == "DEFINE");
+    }
+    let mode = tokens.next_if(|token| MODES.contains(&token.as_str()));
+    tokens.next_if(|token| token == "PARAMETER");
+    let non_scalar = tokens.next_if(|token| NON_SCALAR.contains(&token.as_str()));
+    let keyword = match (mode, &non_scalar) {
+        (Some(mode), _) => mode,
+        (None, Some(kind)) if kind == "BUFFER" => kind.clone(),
+        (None, _) => "INPUT".to_string(),
+    };
+    ParamMode {
+        keyword,
+        scalar: non_scalar.is_none(),
+    }
+}
+
+/// Type text after `AS` / `LIKE`, as written.
+fn declared_param_type<'a>(node: Node<'_>, src: &'a [u8]) -> Option<&'a str> {
+    node.child_by_field_name("type")
+        .and_then(|n| n.utf8_text(src).ok())
+        .map(str::trim)
+        .filter(|ty| !ty.is_empty())
+}
+
+/// Reports literals/expressions passed where an `OUTPUT`/`INPUT-OUTPUT` parameter needs a variable.
+pub fn collect_output_argument_diags(root: Node<'_>, src: &[u8], out: &mut Vec<Diagnostic>) {
+    let mut signatures = HashMap::<String, Vec<Vec<bool>>>::new();
+    collect_output_param_signatures(root, src, &mut signatures);
+    if !signatures
+        .values()
+        .flatten()
+        .any(|modes| modes.contains(&true))
+    {
+        return;
+    }
+    collect_output_argument_diags_in_node(root, src, &signatures, out);
+}
+
+fn collect_output_param_signatures(
+    node: Node<'_>,
+    src: &[u8],
+    out: &mut HashMap<String, Vec<Vec<bool>>>,
+) {
+    let key = match node.kind() {
+        "function_definition" | "function_forward_definition" => node
+            .child_by_field_name("name")
+            .and_then(|n| n.utf8_text(src).ok())
+            .map(normalize_function_name),
+        "procedure_definition" | "procedure_forward_definition" => node
+            .child_by_field_name("name")
+            .and_then(|n| n.utf8_text(src).ok())
+            .map(procedure_arity_key),
+        _ => None,
+    };
+    if let Some(key) = key {
+        out.entry(key)
+            .or_default()
+            .push(param_output_modes(node, src));
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_output_param_signatures(ch, src, out);
+        }
+    }
+}
+
+/// One flag per parameter, in declaration order: `true` for scalar `OUTPUT` / `INPUT-OUTPUT`.
+fn param_output_modes(def_node: Node<'_>, src: &[u8]) -> Vec<bool> {
+    param_nodes(def_node)
+        .into_iter()
+        .map(|param| {
+            let mode = param_mode(param, src);
+            mode.scalar && matches!(mode.keyword.as_str(), "OUTPUT" | "INPUT-OUTPUT")
+        })
+        .collect()
+}
+
+fn collect_output_argument_diags_in_node(
+    node: Node<'_>,
+    src: &[u8],
+    signatures: &HashMap<String, Vec<Vec<bool>>>,
+    out: &mut Vec<Diagnostic>,
+) {
+    let call = match node.kind() {
+        "function_call" => node
+            .child_by_field_name("function")
+            .and_then(|n| n.utf8_text(src).ok())
+            .map(|name| {
+                let args = node
+                    .children(&mut node.walk())
+                    .find(|n| n.kind() == "arguments")
+                    .map(argument_exprs)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|arg| (arg.start_byte(), arg.end_byte()))
+                    .collect::<Vec<_>>();
+                (name.trim().to_string(), normalize_function_name(name), args)
+            }),
+        // Only same-file procedures have signatures here, so `IN hProc` targets are skipped.
+        "run_statement" => run_call_arguments(node, src, &HashMap::new()),
+        _ => None,
+    };
+
+    if let Some((display_name, key, args)) = call
+        && let Some(modes) = signatures
+            .get(&key)
+            .and_then(|sigs| sigs.iter().find(|modes| modes.len() == args.len()))
+    {
+        for (idx, (start, end)) in args.into_iter().enumerate() {
+            if !modes[idx] {
+                continue;
+            }
+            let Some(arg_text) = src
+                .get(start..end)
+                .and_then(|b| std::str::from_utf8(b).ok())
+            else {
+                continue;
+            };
+            if is_writable_argument(arg_text) {
+                continue;
+            }
+            let text = std::str::from_utf8(src).unwrap_or_default();
+            out.push(Diagnostic {
+                range: Range::new(
+                    utf8_byte_offset_to_byte_pos(text, start),
+                    utf8_byte_offset_to_byte_pos(text, end),
+                ),
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some("abl-semantic".into()),
+                message: format!(
+                    "Argument {} of '{}' is an OUTPUT parameter and must be a variable or field",
+                    idx + 1,
+                    display_name.trim_matches('"')
+                ),
+                ..Default::default()
+            });
+        }
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_output_argument_diags_in_node(ch, src, signatures, out);
+        }
+    }
+}
+
+/// Variable, field or array element, optionally prefixed by its call-site mode keyword.
+fn is_writable_argument(raw: &str) -> bool {
+    let mut text = raw.trim();
+    for mode in ["INPUT-OUTPUT", "OUTPUT"] {
+        if text.len() > mode.len()
+            && text[..mode.len()].eq_ignore_ascii_case(mode)
+            && text.as_bytes()[mode.len()].is_ascii_whitespace()
+        {
+            text = text[mode.len()..].trim_start();
+            break;
+        }
+    }
+    let text = match text.split_once('[') {
+        Some((base, subscript)) if subscript.ends_with(']') => base,
+        Some(_) => return false,
+        None => text,
+    };
+    !text.is_empty()
+        && text.split('.').all(|part| {
+            part.chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && part.chars().all(|c| {
+                    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '#' | '$' | '%' | '&')
+                })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        collect_forward_param_mismatch_diags, collect_input_param_write_diags,
+        collect_output_argument_diags, is_writable_argument, param_mode, param_nodes,
+    };
+    use crate::analysis::parse_abl;
+    use crate::utils::ts::collect_nodes_by_kind;
+    use tower_lsp::lsp_types::{DiagnosticSeverity, Url};
+
+    #[test]
+    fn recognizes_parameter_modes() {
+        let src = r#"
+PROCEDURE p:
+  DEFINE INPUT PARAMETER a AS INTEGER NO-UNDO.
+  DEFINE INPUT-OUTPUT PARAMETER b AS INTEGER NO-UNDO.
+  DEFINE OUTPUT PARAMETER TABLE FOR ttItem.
+  DEFINE PARAMETER BUFFER bCust FOR Customer.
+END PROCEDURE.
+
+FUNCTION f RETURNS INTEGER (c AS INTEGER, OUTPUT d AS CHARACTER, TABLE FOR ttItem):
+  RETURN 0.
+END FUNCTION.
+"#;
+        let tree = parse_abl(src);
+        let mut defs = Vec::new();
+        collect_nodes_by_kind(tree.root_node(), "procedure_definition", &mut defs);
+        collect_nodes_by_kind(tree.root_node(), "function_definition", &mut defs);
+        let modes = defs
+            .iter()
+            .flat_map(|def| param_nodes(*def))
+            .map(|param| {
+                let mode = param_mode(param, src.as_bytes());
+                (mode.keyword, mode.scalar)
+            })
+            .collect::<Vec<_>>();
+
+        let expected = [
+            ("INPUT", true),
+            ("INPUT-OUTPUT", true),
+            ("OUTPUT", false),
+            ("BUFFER", false),
+            ("INPUT", true),
+            ("OUTPUT", true),
+            ("INPUT", false),
+        ];
+        assert_eq!(
+            modes,
+            expected
+                .iter()
+                .map(|(keyword, scalar)| (keyword.to_string(), *scalar))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn flags_writes_to_input_parameters_only() {
+        let src = r#"
+PROCEDURE calc:
+  DEFINE INPUT PARAMETER pIn AS INTEGER NO-UNDO.
+  DEFINE INPUT-OUTPUT PARAMETER pBoth AS INTEGER NO-UNDO.
+  DEFINE OUTPUT PARAMETER pOut AS INTEGER NO-UNDO.
+  pIn = 1.
+  pBoth = pIn.
+  pOut = pIn.
+END PROCEDURE.
+
+FUNCTION twice RETURNS INTEGER (INPUT n AS INTEGER):
+  n = n * 2.
+  RETURN n.
+END FUNCTION.
+"#;
+        let tree = parse_abl(src);
+        let mut diags = Vec::new();
+        collect_input_param_write_diags(tree.root_node(), src.as_bytes(), &mut diags);
+
+        assert_eq!(diags.len(), 2);
+        assert_eq!(
+            diags[0].message,
+            "Assignment to INPUT parameter 'pIn' is not returned to the caller"
+        );
+        assert_eq!(diags[0].severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diags[0].range.start.line, 5);
+        assert_eq!(diags[1].range.start.line, 11);
+    }
+
+    #[test]
+    fn recognizes_writable_arguments() {
+        assert!(is_writable_argument("myVar"));
+        assert!(is_writable_argument("OUTPUT myVar"));
+        assert!(is_writable_argument("input-output Customer.Name"));
+        assert!(is_writable_argument("arr[2]"));
+        assert!(!is_writable_argument("1"));
+        assert!(!is_writable_argument("\"text\""));
+        assert!(!is_writable_argument("OUTPUT a + 1"));
+    }
+
+    #[test]
+    fn flags_non_variable_output_arguments() {
+        let src = r#"
+DEFINE VARIABLE v AS INTEGER NO-UNDO.
+
+PROCEDURE calc:
+  DEFINE INPUT PARAMETER pIn AS INTEGER NO-UNDO.
+  DEFINE OUTPUT PARAMETER pOut AS INTEGER NO-UNDO.
+END PROCEDURE.
+
+RUN calc (1, OUTPUT v).
+RUN calc (v + 1, OUTPUT 5).
+"#;
+        let tree = parse_abl(src);
+        let mut diags = Vec::new();
+        collect_output_argument_diags(tree.root_node(), src.as_bytes(), &mut diags);
+
+        assert_eq!(diags.len(), 1);
+        assert_eq!(
+            diags[0].message,
+            "Argument 2 of 'calc' is an OUTPUT parameter and must be a variable or field"
+        );
+        assert_eq!(diags[0].range.start.line, 9);
+        assert_eq!(diags[0].range.start.character, 17);
+        assert_eq!(diags[0].range.end.character, 25);
+    }
+
+    #[test]
+    fn flags_parameter_mode_and_type_mismatch_with_forward() {
+        let src = r#"
+FUNCTION calc RETURNS INTEGER (INPUT a AS INTEGER, b AS CHARACTER, OUTPUT c AS LOGICAL) FORWARD.
+FUNCTION same RETURNS INTEGER (INPUT a AS INTEGER) FORWARD.
+FUNCTION ratio RETURNS DECIMAL (a AS INTEGER) FORWARD.
+
+FUNCTION calc RETURNS INTEGER (OUTPUT a AS INTEGER, INPUT b AS INTEGER, OUTPUT c AS LOGICAL):
+  RETURN 0.
+END FUNCTION.
+
+FUNCTION same RETURNS INTEGER (a AS INT):
+  RETURN a.
+END FUNCTION.
+
+FUNCTION ratio RETURNS DECIMAL (a AS DECIMAL):
+  RETURN a / 2.
+END FUNCTION.
+"#;
+        let tree = parse_abl(src);
+        let uri = Url::parse("file:///tmp/forward.p").expect("uri");
+        let mut diags = Vec::new();
+        collect_forward_param_mismatch_diags(tree.root_node(), src.as_bytes(), &uri, &mut diags);
+
+        let messages = diags.iter().map(|d| d.message.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "Parameter 1 of 'calc' is OUTPUT here but INPUT in the FORWARD declaration",
+                "Parameter 2 of 'calc' is INTEGER here but CHARACTER in the FORWARD declaration",
+                "Parameter 1 of 'ratio' is DECIMAL here but INTEGER in the FORWARD declaration",
+            ]
+        );
+        assert_eq!(diags[0].range.start.line, 5);
+        let related = diags[0].related_information.as_ref().expect("related");
+        assert_eq!(related[0].location.range.start.line, 1);
+    }
+}
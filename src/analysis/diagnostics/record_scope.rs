@@ -0,0 +1,116 @@
+use dashmap::DashSet;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+use tree_sitter::Node;
+
+use crate::analysis::diagnostics::symbols::{ActiveBuffer, collect_active_buffer_like_names};
+use crate::utils::ts::node_to_range;
+
+/// Flags `table.field = ...` on a DB table with no `FOR`/`FIND`/`CREATE` of that table in scope.
+///
+/// This is a lexical check only: a record found in another procedure or made available through
+/// a shared buffer is not seen, so the diagnostic is informational.
+pub fn collect_unscoped_db_field_assign_diags(
+    root: Node<'_>,
+    src: &[u8],
+    db_tables: &DashSet<String>,
+    out: &mut Vec<Diagnostic>,
+) {
+    if db_tables.is_empty() {
+        return;
+    }
+    let buffers = collect_active_buffer_like_names(root, src);
+    collect_unscoped_assign_diags(root, src, db_tables, &buffers, out);
+}
+
+fn collect_unscoped_assign_diags(
+    node: Node<'_>,
+    src: &[u8],
+    db_tables: &DashSet<String>,
+    buffers: &[ActiveBuffer],
+    out: &mut Vec<Diagnostic>,
+) {
+    if node.kind() == "assignment_statement"
+        && let Some(left) = node.child_by_field_name("left")
+        && left.kind() == "qualified_name"
+        && let Ok(target) = left.utf8_text(src).map(str::trim)
+        && let Some(table_upper) = qualified_field_table(target)
+        && db_tables.contains(&table_upper)
+        && !buffers.iter().any(|buffer| {
+            buffer.name_upper == table_upper
+                && buffer.start_byte <= left.start_byte()
+                && left.end_byte() <= buffer.end_byte
+        })
+    {
+        out.push(Diagnostic {
+            range: node_to_range(left),
+            severity: Some(DiagnosticSeverity::INFORMATION),
+            source: Some("abl-semantic".into()),
+            message: format!(
+                "No '{}' record is in scope here; FIND, FOR EACH or CREATE it before assigning '{}'",
+                target.rsplit_once('.').map_or(target, |(table, _)| table),
+                target
+            ),
+            ..Default::default()
+        });
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_unscoped_assign_diags(ch, src, db_tables, buffers, out);
+        }
+    }
+}
+
+/// Upper-cased table of `table.field` / `db.table.field`.
+fn qualified_field_table(target: &str) -> Option<String> {
+    let parts = target.split('.').collect::<Vec<_>>();
+    match parts.as_slice() {
+        [table, _] | [_, table, _] if !table.is_empty() => Some(table.to_ascii_uppercase()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::collect_unscoped_db_field_assign_diags;
+    use crate::analysis::parse_abl;
+    use dashmap::DashSet;
+
+    #[test]
+    fn flags_db_field_assignment_without_record_in_scope() {
+        let src = r#"
+PROCEDURE touch:
+  Customer.Name = "missing".
+  CREATE Order.
+  Order.OrderNum = 1.
+END PROCEDURE.
+
+FIND FIRST Customer NO-ERROR.
+Customer.Name = "found".
+
+FOR EACH Item:
+  Item.Price = 0.
+END.
+Item.Price = 1.
+"#;
+        let tree = parse_abl(src);
+        let db_tables = DashSet::new();
+        for table in ["CUSTOMER", "ORDER", "ITEM"] {
+            db_tables.insert(table.to_string());
+        }
+        let mut diags = Vec::new();
+        collect_unscoped_db_field_assign_diags(
+            tree.root_node(),
+            src.as_bytes(),
+            &db_tables,
+            &mut diags,
+        );
+
+        let lines = diags.iter().map(|d| d.range.start.line).collect::<Vec<_>>();
+        assert_eq!(lines, vec![2, 13]);
+        assert_eq!(
+            diags[0].message,
+            "No 'Customer' record is in scope here; FIND, FOR EACH or CREATE it before assigning 'Customer.Name'"
+        );
+    }
+}
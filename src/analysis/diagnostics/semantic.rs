@@ -1,17 +1,11 @@
 use std::collections::{HashMap, HashSet};
-use std::path::{Path, PathBuf};
-use std::sync::Arc;
-
-use tower_lsp::lsp_types::{Diagnostic, Url};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Url};
 use tree_sitter::Node;
 
-use crate::analysis::definitions::{
-    PreprocessorDefineSite, collect_global_preprocessor_define_sites,
-    collect_preprocessor_define_sites,
-};
 use crate::analysis::diagnostics::functions::{
-    FunctionCallSite, append_function_arity_mismatch_diags, collect_function_arities,
-    collect_function_calls,
+    FunctionArity, FunctionCallSite, append_call_before_definition_diags,
+    append_function_arity_mismatch_diags, collect_function_arities, collect_function_calls,
+    collect_persistent_run_handles,
 };
 use crate::analysis::diagnostics::symbols::{
     IdentifierRef, TableRef, UnknownSymbolDiagInputs, append_unknown_symbol_diags,
@@ -20,7 +14,9 @@ use crate::analysis::diagnostics::symbols::{
     collect_local_table_field_symbols, collect_table_refs_for_unknown_table_diag,
     normalize_identifier_refs,
 };
-use crate::analysis::includes::{collect_include_sites_from_tree, resolve_include_site_path};
+use crate::analysis::enums::collect_enum_access_types;
+use crate::analysis::functions::persistent_procedure_key;
+use crate::analysis::includes::collect_resolved_include_parses;
 use crate::backend::Backend;
 
 pub fn should_accept_version(backend: &Backend, uri: &Url, version: i32) -> bool {
@@ -47,36 +43,103 @@ pub async fn collect_function_call_arity_diags(
         return false;
     }
 
-    let mut signatures = HashMap::<String, Vec<usize>>::new();
-    collect_function_arities(root, text.as_bytes(), &mut signatures);
+    let mut signatures = HashMap::<String, Vec<FunctionArity>>::new();
+    collect_function_arities(root, text.as_bytes(), uri, &mut signatures);
 
     if include_from_includes && let Ok(current_path) = uri.to_file_path() {
-        let include_parses =
-            collect_resolved_include_parses(backend, &current_path, text, root).await;
-        for (_, include_text, include_tree) in include_parses {
+        let Some(include_parses) =
+            collect_resolved_include_parses(backend, uri, version, &current_path, text, root).await
+        else {
+            return false;
+        };
+        for (include_path, include_text, include_tree) in include_parses {
             if !is_latest_version(backend, uri, version) {
                 return false;
             }
+            let Ok(include_uri) = Url::from_file_path(&include_path) else {
+                continue;
+            };
             collect_function_arities(
                 include_tree.root_node(),
                 include_text.as_bytes(),
+                &include_uri,
                 &mut signatures,
             );
         }
     }
 
+    // Reading the programs behind persistent handles is disk work, so like includes it waits
+    // for full passes; keystroke passes skip the cross-file handle checks.
+    if include_from_includes && let Ok(current_path) = uri.to_file_path() {
+        let mut handles = HashMap::<String, String>::new();
+        collect_persistent_run_handles(root, text.as_bytes(), &mut handles);
+        let programs = handles.into_values().collect::<HashSet<_>>();
+        for program in programs {
+            let Some(program_path) = backend
+                .resolve_run_program_path(&current_path, &program)
+                .await
+            else {
+                continue;
+            };
+            let Some((program_text, program_tree)) =
+                backend.get_cached_include_parse(&program_path).await
+            else {
+                continue;
+            };
+            let Ok(program_uri) = Url::from_file_path(&program_path) else {
+                continue;
+            };
+            let mut program_signatures = HashMap::<String, Vec<FunctionArity>>::new();
+            collect_function_arities(
+                program_tree.root_node(),
+                program_text.as_bytes(),
+                &program_uri,
+                &mut program_signatures,
+            );
+            for (key, arities) in program_signatures {
+                if let Some(name) = key.strip_prefix("RUN ") {
+                    signatures
+                        .entry(persistent_procedure_key(&program, name))
+                        .or_default()
+                        .extend(arities);
+                }
+            }
+        }
+    }
+
     if !is_latest_version(backend, uri, version) {
         return false;
     }
 
     for arities in signatures.values_mut() {
-        arities.sort_unstable();
+        arities.sort_by_key(|a| a.arity);
         arities.dedup();
     }
 
     let mut calls = Vec::<FunctionCallSite>::new();
     collect_function_calls(root, text.as_bytes(), &mut calls);
-    append_function_arity_mismatch_diags(&signatures, &calls, out);
+    // Arity mismatches are type checks: same switch, same severity.
+    let type_checks = backend.config.lock().await.diagnostics.type_checks.clone();
+    if type_checks.enabled {
+        append_function_arity_mismatch_diags(
+            &signatures,
+            &calls,
+            type_checks.severity.to_lsp(),
+            out,
+        );
+    }
+    // Include signatures are only known on full passes; without them an include-provided name
+    // would look like a call before its in-file definition.
+    if include_from_includes
+        && backend
+            .config
+            .lock()
+            .await
+            .diagnostics
+            .call_before_definition
+    {
+        append_call_before_definition_diags(&signatures, uri, &calls, out);
+    }
 
     true
 }
@@ -96,7 +159,7 @@ pub async fn collect_unknown_symbol_diags(
 
     let mut known_variables = HashSet::<String>::new();
     let mut known_functions = HashSet::<String>::new();
-    let mut known_function_signatures = HashMap::<String, Vec<usize>>::new();
+    let mut known_function_signatures = HashMap::<String, Vec<FunctionArity>>::new();
     collect_known_symbols(
         params.root,
         params.text.as_bytes(),
@@ -106,6 +169,7 @@ pub async fn collect_unknown_symbol_diags(
     collect_function_arities(
         params.root,
         params.text.as_bytes(),
+        params.uri,
         &mut known_function_signatures,
     );
     collect_local_table_field_symbols(
@@ -118,9 +182,19 @@ pub async fn collect_unknown_symbol_diags(
     if params.include_semantic_diags
         && let Ok(current_path) = params.uri.to_file_path()
     {
-        let include_parses =
-            collect_resolved_include_parses(backend, &current_path, params.text, params.root).await;
-        for (_, include_text, include_tree) in include_parses {
+        let Some(include_parses) = collect_resolved_include_parses(
+            backend,
+            params.uri,
+            params.version,
+            &current_path,
+            params.text,
+            params.root,
+        )
+        .await
+        else {
+            return false;
+        };
+        for (include_path, include_text, include_tree) in include_parses {
             if !is_latest_version(backend, params.uri, params.version) {
                 return false;
             }
@@ -136,25 +210,44 @@ pub async fn collect_unknown_symbol_diags(
                 include_text.as_bytes(),
                 &mut known_variables,
             );
-            collect_function_arities(
-                include_tree.root_node(),
-                include_text.as_bytes(),
-                &mut known_function_signatures,
-            );
+            if let Ok(include_uri) = Url::from_file_path(&include_path) {
+                collect_function_arities(
+                    include_tree.root_node(),
+                    include_text.as_bytes(),
+                    &include_uri,
+                    &mut known_function_signatures,
+                );
+            }
         }
     }
 
     known_functions.extend(known_function_signatures.into_keys());
+    known_variables.extend(
+        backend
+            .imported_class_names_for(params.uri, params.text)
+            .await
+            .into_iter()
+            .map(|(short, _)| short.to_ascii_uppercase()),
+    );
 
     let mut refs = Vec::<IdentifierRef>::new();
     let mut table_refs = Vec::<TableRef>::new();
-    collect_identifier_refs_for_unknown_symbol_diag(params.root, params.text.as_bytes(), &mut refs);
+    let enum_types = collect_enum_access_types(backend, params.uri, params.text, params.root).await;
+    collect_identifier_refs_for_unknown_symbol_diag(
+        params.root,
+        params.text.as_bytes(),
+        &enum_types,
+        &mut refs,
+    );
     collect_table_refs_for_unknown_table_diag(params.root, params.text.as_bytes(), &mut table_refs);
     normalize_identifier_refs(&mut refs);
-    let active_buffer_like_names =
-        collect_active_buffer_like_names(params.root, params.text.as_bytes(), backend);
-    let active_table_fields =
-        collect_active_db_table_field_symbols(backend, &active_buffer_like_names);
+    let active_buffers = collect_active_buffer_like_names(params.root, params.text.as_bytes());
+    let active_table_fields = collect_active_db_table_field_symbols(
+        backend,
+        params.root,
+        params.text.as_bytes(),
+        &active_buffers,
+    );
 
     let mut calls = Vec::<FunctionCallSite>::new();
     collect_function_calls(params.root, params.text.as_bytes(), &mut calls);
@@ -169,10 +262,12 @@ pub async fn collect_unknown_symbol_diags(
             unknown_functions_ignored: params.unknown_functions_ignored,
             db_tables: &backend.db_tables,
             db_sequences: &backend.db_sequences,
+            db_name_by_table: &backend.db_name_by_table,
             active_table_fields: &active_table_fields,
-            active_buffer_like_names: &active_buffer_like_names,
             unknown_variables_enabled: params.unknown_variables_enabled,
             unknown_functions_enabled: params.unknown_functions_enabled,
+            unknown_variables_severity: params.unknown_variables_severity,
+            unknown_functions_severity: params.unknown_functions_severity,
         },
         out,
     );
@@ -188,195 +283,21 @@ pub struct UnknownSymbolDiagParams<'a> {
     pub include_semantic_diags: bool,
     pub unknown_variables_enabled: bool,
     pub unknown_functions_enabled: bool,
+    pub unknown_variables_severity: DiagnosticSeverity,
+    pub unknown_functions_severity: DiagnosticSeverity,
     pub unknown_variables_ignored: &'a HashSet<String>,
     pub unknown_functions_ignored: &'a HashSet<String>,
 }
 
-async fn collect_resolved_include_parses(
-    backend: &Backend,
-    current_path: &Path,
-    text: &str,
-    root: Node<'_>,
-) -> Vec<(PathBuf, Arc<String>, tree_sitter::Tree)> {
-    let mut state = IncludeCollectState {
-        seen: HashSet::new(),
-        out: Vec::new(),
-        pending: Vec::new(),
-    };
-
-    collect_resolved_includes_for_file(backend, current_path, text, root, &[], &mut state).await;
-
-    while let Some(next) = state.pending.pop() {
-        collect_resolved_includes_for_file(
-            backend,
-            &next.path,
-            next.text.as_str(),
-            next.tree.root_node(),
-            &next.inherited_globals,
-            &mut state,
-        )
-        .await;
-    }
-
-    state.out
-}
-
-async fn collect_resolved_includes_for_file(
-    backend: &Backend,
-    file_path: &Path,
-    file_text: &str,
-    file_root: Node<'_>,
-    inherited_globals: &[PreprocessorDefineSite],
-    state: &mut IncludeCollectState,
-) {
-    let include_sites = collect_include_sites_from_tree(file_root, file_text.as_bytes());
-    let mut available_define_sites = inherited_globals.to_vec();
-    collect_preprocessor_define_sites(file_root, file_text.as_bytes(), &mut available_define_sites);
-
-    for include in include_sites {
-        let include_path_value = resolve_include_site_path(&include, &available_define_sites);
-        let Some(resolved_path) = backend
-            .resolve_include_path_for(file_path, &include_path_value)
-            .await
-        else {
-            continue;
-        };
-
-        if let Some((include_text, include_tree)) =
-            backend.get_cached_include_parse(&resolved_path).await
-        {
-            let mut include_global_defines = Vec::new();
-            collect_global_preprocessor_define_sites(
-                include_tree.root_node(),
-                include_text.as_bytes(),
-                &mut include_global_defines,
-            );
-            for mut define in include_global_defines {
-                define.start_byte = include.start_offset;
-                available_define_sites.push(define);
-            }
-
-            if state.seen.insert(resolved_path.clone()) {
-                state.out.push((
-                    resolved_path.clone(),
-                    include_text.clone(),
-                    include_tree.clone(),
-                ));
-                state.pending.push(PendingInclude {
-                    path: resolved_path,
-                    text: include_text,
-                    tree: include_tree,
-                    inherited_globals: globals_visible_at_offset(
-                        &available_define_sites,
-                        include.start_offset,
-                    ),
-                });
-            }
-        }
-    }
-}
-
-#[derive(Clone)]
-struct PendingInclude {
-    path: PathBuf,
-    text: Arc<String>,
-    tree: tree_sitter::Tree,
-    inherited_globals: Vec<PreprocessorDefineSite>,
-}
-
-struct IncludeCollectState {
-    seen: HashSet<PathBuf>,
-    out: Vec<(PathBuf, Arc<String>, tree_sitter::Tree)>,
-    pending: Vec<PendingInclude>,
-}
-
-fn globals_visible_at_offset(
-    available_define_sites: &[PreprocessorDefineSite],
-    offset: usize,
-) -> Vec<PreprocessorDefineSite> {
-    available_define_sites
-        .iter()
-        .filter(|d| d.is_global && d.start_byte <= offset)
-        .cloned()
-        .map(|mut d| {
-            d.start_byte = 0;
-            d
-        })
-        .collect()
-}
-
 #[cfg(test)]
 mod tests {
-    use super::{globals_visible_at_offset, is_latest_version, should_accept_version};
-    use crate::analysis::definitions::PreprocessorDefineSite;
-    use crate::backend::{Backend, BackendState};
-    use dashmap::{DashMap, DashSet};
-    use std::sync::Arc;
-    use tokio::sync::Mutex as AsyncMutex;
-    use tower_lsp::lsp_types::{Position, Range};
-    use tower_lsp::{Client, LspService};
-
-    fn define(label: &str, start_byte: usize, is_global: bool) -> PreprocessorDefineSite {
-        PreprocessorDefineSite {
-            label: label.to_string(),
-            value: Some("v".to_string()),
-            range: Range::new(Position::new(0, 0), Position::new(0, 1)),
-            start_byte,
-            is_global,
-        }
-    }
-
-    #[test]
-    fn keeps_only_global_defines_visible_at_offset_and_resets_start_byte() {
-        let defs = vec![
-            define("A", 5, true),
-            define("B", 10, false),
-            define("C", 20, true),
-        ];
-
-        let visible = globals_visible_at_offset(&defs, 12);
-        assert_eq!(visible.len(), 1);
-        assert_eq!(visible[0].label, "A");
-        assert_eq!(visible[0].start_byte, 0);
-    }
-
-    fn test_backend() -> Backend {
-        let (service, _socket) = LspService::build(|client: Client| Backend {
-            client,
-            state: Arc::new(BackendState {
-                abl_language: tree_sitter_abl::LANGUAGE.into(),
-                df_parser: AsyncMutex::new({
-                    let mut p = tree_sitter::Parser::new();
-                    p.set_language(&tree_sitter_df::LANGUAGE.into())
-                        .expect("set df language");
-                    p
-                }),
-                documents: DashMap::new(),
-                workspace_root: AsyncMutex::new(None),
-                config: AsyncMutex::new(crate::config::AblConfig::default()),
-                db_tables: DashSet::new(),
-                db_sequences: DashSet::new(),
-                db_table_labels: DashMap::new(),
-                db_table_definitions: DashMap::new(),
-                db_sequence_definitions: DashMap::new(),
-                db_field_definitions: DashMap::new(),
-                db_index_definitions: DashMap::new(),
-                db_indexes_by_table: DashMap::new(),
-                db_index_fields_by_table_index: DashMap::new(),
-                db_fields_by_table: DashMap::new(),
-                include_completion_cache: DashMap::new(),
-                include_parse_cache: DashMap::new(),
-            }),
-        })
-        .finish();
-        let backend = service.inner().clone();
-        drop(service);
-        backend
-    }
+    use super::{collect_function_call_arity_diags, is_latest_version, should_accept_version};
+    use crate::analysis::parse_abl;
+    use crate::backend::Backend;
 
     #[test]
     fn accepts_only_non_stale_versions() {
-        let backend = test_backend();
+        let backend = Backend::for_tests();
         let uri = tower_lsp::lsp_types::Url::parse("file:///tmp/doc.p").expect("uri");
         backend.set_document_text_version(&uri, 3, "MESSAGE x.".to_string(), true);
 
@@ -387,7 +308,7 @@ mod tests {
 
     #[test]
     fn checks_latest_version_exact_match() {
-        let backend = test_backend();
+        let backend = Backend::for_tests();
         let uri = tower_lsp::lsp_types::Url::parse("file:///tmp/doc2.p").expect("uri");
         backend.set_document_text_version(&uri, 7, "MESSAGE y.".to_string(), true);
 
@@ -395,4 +316,136 @@ mod tests {
         assert!(!is_latest_version(&backend, &uri, 6));
         assert!(!is_latest_version(&backend, &uri, 8));
     }
+
+    #[tokio::test]
+    async fn arity_mismatches_follow_the_type_checks_switch() {
+        let backend = Backend::for_tests();
+        let uri = tower_lsp::lsp_types::Url::parse("file:///tmp/arity.p").expect("uri");
+        let src = "FUNCTION foo RETURNS LOGICAL (a AS INTEGER):\n  RETURN TRUE.\nEND FUNCTION.\nfoo(1, 2).\n";
+        backend.set_document_text_version(&uri, 1, src.to_string(), true);
+        let tree = parse_abl(src);
+
+        let mut diags = Vec::new();
+        assert!(
+            collect_function_call_arity_diags(
+                &backend,
+                &uri,
+                1,
+                src,
+                tree.root_node(),
+                false,
+                &mut diags
+            )
+            .await
+        );
+        assert_eq!(diags.len(), 1);
+
+        backend.config.lock().await.diagnostics.type_checks.enabled = false;
+        let mut diags = Vec::new();
+        assert!(
+            collect_function_call_arity_diags(
+                &backend,
+                &uri,
+                1,
+                src,
+                tree.root_node(),
+                false,
+                &mut diags
+            )
+            .await
+        );
+        assert!(diags.is_empty());
+    }
+
+    #[tokio::test]
+    async fn persistent_handle_arity_waits_for_full_passes() {
+        let base = std::env::temp_dir().join(format!(
+            "abl_ls_persistent_arity_test_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("epoch")
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(&base).expect("create base");
+        std::fs::write(
+            base.join("util.p"),
+            "PROCEDURE calc:\n  DEFINE INPUT PARAMETER a AS INTEGER NO-UNDO.\nEND PROCEDURE.\n",
+        )
+        .expect("write util");
+        let main = base.join("main.p");
+        let src = "DEFINE VARIABLE hUtil AS HANDLE NO-UNDO.\nRUN util.p PERSISTENT SET hUtil.\nRUN calc IN hUtil (1, 2).\n";
+        std::fs::write(&main, src).expect("write main");
+
+        let backend = Backend::for_tests();
+        *backend.workspace_roots.lock().await = vec![base.clone()];
+        let uri = tower_lsp::lsp_types::Url::from_file_path(&main).expect("uri");
+        backend.set_document_text_version(&uri, 1, src.to_string(), true);
+        let tree = parse_abl(src);
+
+        for (include_from_includes, expected) in [(false, 0), (true, 1)] {
+            let mut diags = Vec::new();
+            assert!(
+                collect_function_call_arity_diags(
+                    &backend,
+                    &uri,
+                    1,
+                    src,
+                    tree.root_node(),
+                    include_from_includes,
+                    &mut diags
+                )
+                .await
+            );
+            assert_eq!(diags.len(), expected);
+        }
+
+        let _ = std::fs::remove_dir_all(base);
+    }
+
+    #[tokio::test]
+    async fn call_before_definition_is_opt_in_and_waits_for_includes() {
+        let backend = Backend::for_tests();
+        let uri = tower_lsp::lsp_types::Url::parse("file:///tmp/late.p").expect("uri");
+        let src = "late().\nFUNCTION late RETURNS LOGICAL ():\n  RETURN TRUE.\nEND FUNCTION.\n";
+        backend.set_document_text_version(&uri, 1, src.to_string(), true);
+        let tree = parse_abl(src);
+
+        let run = |include_from_includes: bool| {
+            let backend = &backend;
+            let uri = &uri;
+            let tree = &tree;
+            async move {
+                let mut diags = Vec::new();
+                assert!(
+                    collect_function_call_arity_diags(
+                        backend,
+                        uri,
+                        1,
+                        src,
+                        tree.root_node(),
+                        include_from_includes,
+                        &mut diags
+                    )
+                    .await
+                );
+                diags
+            }
+        };
+
+        assert!(run(true).await.is_empty());
+
+        backend
+            .config
+            .lock()
+            .await
+            .diagnostics
+            .call_before_definition = true;
+        assert!(run(false).await.is_empty());
+        let diags = run(true).await;
+        assert_eq!(diags.len(), 1);
+        assert_eq!(
+            diags[0].message,
+            "Function 'late' is called before its definition or FORWARD declaration"
+        );
+    }
 }
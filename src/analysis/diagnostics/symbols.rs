@@ -1,17 +1,23 @@
 use std::collections::HashSet;
 
-use dashmap::DashSet;
-use tower_lsp::lsp_types::{CompletionItemKind, Diagnostic, DiagnosticSeverity, Range};
+use dashmap::{DashMap, DashSet};
+use tower_lsp::lsp_types::{CompletionItemKind, Diagnostic, DiagnosticSeverity, Position, Range};
 use tree_sitter::Node;
 
-use crate::analysis::buffers::collect_buffer_mappings;
+use crate::analysis::buffers::{
+    block_for_buffers, buffer_parameter, collect_buffer_mappings, record_phrase_of_table,
+    resolve_buffer_table,
+};
 use crate::analysis::builtins::{is_builtin_function_name, is_builtin_variable_name};
 use crate::analysis::definitions::collect_definition_symbols;
 use crate::analysis::diagnostics::functions::FunctionCallSite;
-use crate::analysis::functions::normalize_function_name;
+use crate::analysis::enums::colon_access_qualifier;
+use crate::analysis::functions::{is_persistent_procedure_key, normalize_function_name};
 use crate::analysis::local_tables::collect_local_table_definitions;
+use crate::analysis::schema::{is_logical_db_name, strip_db_qualifier};
+use crate::analysis::scopes::is_scope_node;
 use crate::backend::Backend;
-use crate::utils::ts::{collect_nodes_by_kind, node_to_range};
+use crate::utils::ts::{node_to_range, point_to_position};
 
 #[derive(Clone)]
 pub struct IdentifierRef {
@@ -53,15 +59,16 @@ pub fn collect_known_symbols(
     }
 }
 
+/// `enum_types` are upper-cased `ENUM` names whose `Type:Member` accesses are not variable refs.
 pub fn collect_identifier_refs_for_unknown_symbol_diag(
     node: Node<'_>,
     src: &[u8],
+    enum_types: &HashSet<String>,
     out: &mut Vec<IdentifierRef>,
 ) {
     match node.kind() {
         "assignment_statement" => {
-            if let Some(left) = node.child_by_field_name("left")
-                && left.kind() == "identifier"
+            if let Some(left) = assignment_target_identifier(node)
                 && let Ok(name_raw) = left.utf8_text(src)
             {
                 let display_name = name_raw.trim().to_string();
@@ -74,7 +81,7 @@ pub fn collect_identifier_refs_for_unknown_symbol_diag(
                 }
             }
             if let Some(right) = node.child_by_field_name("right") {
-                collect_identifier_refs_from_expression(right, src, out);
+                collect_identifier_refs_from_expression(right, src, enum_types, out);
             }
         }
         "return_statement" => {
@@ -82,12 +89,12 @@ pub fn collect_identifier_refs_for_unknown_symbol_diag(
                 .child_by_field_name("value")
                 .or_else(|| node.named_child(0))
             {
-                collect_identifier_refs_from_expression(value, src, out);
+                collect_identifier_refs_from_expression(value, src, enum_types, out);
             }
         }
         "expression_statement" => {
             if let Some(expr) = node.named_child(0) {
-                collect_identifier_refs_from_expression(expr, src, out);
+                collect_identifier_refs_from_expression(expr, src, enum_types, out);
             }
         }
         _ => {}
@@ -95,11 +102,20 @@ pub fn collect_identifier_refs_for_unknown_symbol_diag(
 
     for i in 0..node.child_count() {
         if let Some(ch) = node.child(i as u32) {
-            collect_identifier_refs_for_unknown_symbol_diag(ch, src, out);
+            collect_identifier_refs_for_unknown_symbol_diag(ch, src, enum_types, out);
         }
     }
 }
 
+/// Returns the plain identifier assigned by an `assignment_statement`, if any.
+pub fn assignment_target_identifier(node: Node<'_>) -> Option<Node<'_>> {
+    if node.kind() != "assignment_statement" {
+        return None;
+    }
+    node.child_by_field_name("left")
+        .filter(|left| left.kind() == "identifier")
+}
+
 pub fn collect_table_refs_for_unknown_table_diag(
     node: Node<'_>,
     src: &[u8],
@@ -109,7 +125,7 @@ pub fn collect_table_refs_for_unknown_table_diag(
         && let Some(record) = node
             .child_by_field_name("record")
             .or_else(|| node.child_by_field_name("table"))
-        && record.kind() == "identifier"
+        && matches!(record.kind(), "identifier" | "qualified_name")
         && let Ok(name_raw) = record.utf8_text(src)
     {
         let display_name = name_raw.trim().to_string();
@@ -132,6 +148,7 @@ pub fn collect_table_refs_for_unknown_table_diag(
 fn collect_identifier_refs_from_expression(
     expr: Node<'_>,
     src: &[u8],
+    enum_types: &HashSet<String>,
     out: &mut Vec<IdentifierRef>,
 ) {
     match expr.kind() {
@@ -142,6 +159,11 @@ fn collect_identifier_refs_from_expression(
             return;
         }
         "identifier" => {
+            if colon_access_qualifier(expr, src)
+                .is_some_and(|qualifier| enum_types.contains(&qualifier.to_ascii_uppercase()))
+            {
+                return;
+            }
             if let Ok(name_raw) = expr.utf8_text(src) {
                 let display_name = name_raw.trim().to_string();
                 if !display_name.is_empty() {
@@ -161,7 +183,7 @@ fn collect_identifier_refs_from_expression(
                 .find(|n| n.kind() == "arguments")
             {
                 for arg in argument_exprs(args) {
-                    collect_identifier_refs_from_expression(arg, src, out);
+                    collect_identifier_refs_from_expression(arg, src, enum_types, out);
                 }
             }
             return;
@@ -172,7 +194,7 @@ fn collect_identifier_refs_from_expression(
                 .find(|n| n.kind() == "arguments")
             {
                 for arg in argument_exprs(args) {
-                    collect_identifier_refs_from_expression(arg, src, out);
+                    collect_identifier_refs_from_expression(arg, src, enum_types, out);
                 }
             }
             return;
@@ -182,7 +204,7 @@ fn collect_identifier_refs_from_expression(
 
     for i in 0..expr.child_count() {
         if let Some(ch) = expr.child(i as u32) {
-            collect_identifier_refs_from_expression(ch, src, out);
+            collect_identifier_refs_from_expression(ch, src, enum_types, out);
         }
     }
 }
@@ -231,115 +253,183 @@ pub fn collect_local_table_field_symbols(
     }
 }
 
-pub fn collect_active_buffer_like_names(
-    root: Node<'_>,
-    src: &[u8],
-    backend: &Backend,
-) -> HashSet<String> {
-    let mut out = HashSet::<String>::new();
+/// A table or buffer name brought into scope by a record phrase, `FIND`, `CREATE`, a `DO FOR` /
+/// `REPEAT FOR` block or a buffer parameter.
+pub struct ActiveBuffer {
+    pub name_upper: String,
+    pub range: Range,
+    pub start_byte: usize,
+    pub end_byte: usize,
+}
 
-    let mut buffer_mappings = Vec::new();
-    collect_buffer_mappings(root, src, &mut buffer_mappings);
-    for mapping in buffer_mappings {
-        let alias_upper = mapping.alias.trim().to_ascii_uppercase();
-        if !alias_upper.is_empty() {
-            out.insert(alias_upper);
+/// Collects the buffers in use with the range over which their fields may be referenced bare.
+///
+/// `FOR EACH`, `CAN-FIND`, `DO FOR` and `REPEAT FOR` scope the buffer to themselves; other
+/// record phrases, `FIND` and `CREATE` scope it to the rest of the enclosing procedure, function
+/// or method, and buffer parameters to all of it.
+pub fn collect_active_buffer_like_names(root: Node<'_>, src: &[u8]) -> Vec<ActiveBuffer> {
+    let mut stack = Vec::new();
+    collect_active_buffers(root, src, &mut stack);
+    stack
+}
+
+fn collect_active_buffers(node: Node<'_>, src: &[u8], out: &mut Vec<ActiveBuffer>) {
+    match node.kind() {
+        "record_phrase" | "find_statement" => {
+            if let Some(record) = node
+                .child_by_field_name("record")
+                .or_else(|| node.child_by_field_name("table"))
+                && record.kind() == "identifier"
+                && let Ok(name_raw) = record.utf8_text(src)
+            {
+                let scope = buffer_scope(node);
+                // `order OF customer` brings the joined table into the same scope.
+                let of_table = (node.kind() == "record_phrase")
+                    .then(|| record_phrase_of_table(node, src))
+                    .flatten();
+                for name in std::iter::once(name_raw.to_string()).chain(of_table) {
+                    push_active_buffer(&name, scope, out);
+                }
+            }
         }
-        let table_upper = mapping.table.trim().to_ascii_uppercase();
-        if !table_upper.is_empty() {
-            out.insert(table_upper);
+        "create_statement" => {
+            if let Some(table) = create_statement_table(node, src) {
+                push_active_buffer(&table, rest_of_scope(node), out);
+            }
+        }
+        "parameter" | "parameter_definition" => {
+            if let Some((alias, _)) = buffer_parameter(node, src) {
+                let scope = enclosing_scope(node);
+                let range = Range::new(
+                    point_to_position(node.start_position()),
+                    point_to_position(scope.end_position()),
+                );
+                push_active_buffer(&alias, (range, node.start_byte(), scope.end_byte()), out);
+            }
         }
+        kind if kind.starts_with("do") || kind.starts_with("repeat") => {
+            let scope = (node_to_range(node), node.start_byte(), node.end_byte());
+            for name in block_for_buffers(node, src) {
+                push_active_buffer(&name, scope, out);
+            }
+        }
+        _ => {}
     }
 
-    let mut local_table_defs = Vec::new();
-    collect_local_table_definitions(root, src, &mut local_table_defs);
-    for def in local_table_defs {
-        if !def.name_upper.is_empty() {
-            out.insert(def.name_upper);
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_active_buffers(ch, src, out);
         }
     }
+}
 
-    let mut identifiers = Vec::<Node>::new();
-    collect_nodes_by_kind(root, "identifier", &mut identifiers);
-    for ident in identifiers {
-        let Ok(name_raw) = ident.utf8_text(src) else {
-            continue;
-        };
-        let name_upper = name_raw.trim().to_ascii_uppercase();
-        if name_upper.is_empty() {
-            continue;
-        }
-        if backend.db_tables.contains(&name_upper) {
-            out.insert(name_upper);
-        }
+fn push_active_buffer(name: &str, scope: (Range, usize, usize), out: &mut Vec<ActiveBuffer>) {
+    let name_upper = name.trim().to_ascii_uppercase();
+    if name_upper.is_empty() {
+        return;
     }
+    let (range, start_byte, end_byte) = scope;
+    out.push(ActiveBuffer {
+        name_upper,
+        range,
+        start_byte,
+        end_byte,
+    });
+}
 
-    out
+/// Table of `CREATE table.` / `CREATE db.table NO-ERROR.`.
+fn create_statement_table(node: Node<'_>, src: &[u8]) -> Option<String> {
+    if let Some(record) = node
+        .child_by_field_name("record")
+        .or_else(|| node.child_by_field_name("table"))
+    {
+        return record.utf8_text(src).ok().map(str::to_string);
+    }
+    let raw = node.utf8_text(src).ok()?;
+    let table = raw.split_ascii_whitespace().nth(1)?.trim_end_matches('.');
+    let table = table.rsplit('.').next().unwrap_or(table);
+    (!table.is_empty()).then(|| table.to_string())
 }
 
-pub fn collect_active_db_table_field_symbols(
-    backend: &Backend,
-    active_table_like_names: &HashSet<String>,
-) -> HashSet<String> {
-    let mut out = HashSet::<String>::new();
-    for table_like in active_table_like_names {
-        let Some(fields) = backend.db_fields_by_table.get(table_like) else {
-            continue;
-        };
-        for field in fields.value().iter() {
-            let upper = field.name.trim().to_ascii_uppercase();
-            if !upper.is_empty() {
-                out.insert(upper);
-            }
+fn buffer_scope(node: Node<'_>) -> (Range, usize, usize) {
+    let mut current = node;
+    while let Some(parent) = current.parent() {
+        if matches!(parent.kind(), "for_statement" | "can_find_expression") {
+            return (
+                node_to_range(parent),
+                parent.start_byte(),
+                parent.end_byte(),
+            );
+        }
+        if is_scope_node(parent.kind()) || parent.parent().is_none() {
+            break;
         }
+        current = parent;
     }
-    out
+    rest_of_scope(node)
 }
 
-pub fn looks_like_table_field_reference(
-    name_upper: &str,
-    active_buffers: &HashSet<String>,
-) -> bool {
-    if name_upper.is_empty() || active_buffers.is_empty() {
-        return false;
-    }
-    for buffer in active_buffers {
-        if looks_like_prefixed_field(name_upper, buffer)
-            || table_field_prefix_from_table_like_name(buffer)
-                .is_some_and(|prefix| looks_like_prefixed_field(name_upper, &prefix))
-        {
-            return true;
+/// From `node` to the end of its procedure, function, method or file.
+fn rest_of_scope(node: Node<'_>) -> (Range, usize, usize) {
+    let scope = enclosing_scope(node);
+    let range = Range::new(
+        point_to_position(node.start_position()),
+        point_to_position(scope.end_position()),
+    );
+    (range, node.start_byte(), scope.end_byte())
+}
+
+fn enclosing_scope(node: Node<'_>) -> Node<'_> {
+    let mut scope = node;
+    while let Some(parent) = scope.parent() {
+        scope = parent;
+        if is_scope_node(scope.kind()) {
+            break;
         }
     }
-    false
+    scope
 }
 
-fn table_field_prefix_from_table_like_name(name_upper: &str) -> Option<String> {
-    let trimmed = name_upper.trim();
-    if trimmed.is_empty() {
-        return None;
-    }
-    for sep in ['_', '-'] {
-        if let Some(idx) = trimmed.find(sep)
-            && idx > 0
-        {
-            let mut prefix = trimmed[..idx].to_string();
-            prefix.push('_');
-            return Some(prefix);
-        }
+/// Field names of the active buffers, each valid only within its buffer's scope.
+pub struct ActiveTableFields {
+    scopes: Vec<(Range, HashSet<String>)>,
+}
+
+impl ActiveTableFields {
+    pub fn contains_at(&self, name_upper: &str, position: Position) -> bool {
+        self.scopes.iter().any(|(range, fields)| {
+            range.start <= position && position <= range.end && fields.contains(name_upper)
+        })
     }
-    None
 }
 
-fn looks_like_prefixed_field(name_upper: &str, prefix_upper: &str) -> bool {
-    if !name_upper.starts_with(prefix_upper) || name_upper.len() <= prefix_upper.len() {
-        return false;
+pub fn collect_active_db_table_field_symbols(
+    backend: &Backend,
+    root: Node<'_>,
+    src: &[u8],
+    active_buffers: &[ActiveBuffer],
+) -> ActiveTableFields {
+    let mut buffer_mappings = Vec::new();
+    collect_buffer_mappings(root, src, &mut buffer_mappings);
+
+    let mut scopes = Vec::new();
+    for buffer in active_buffers {
+        let table_upper =
+            resolve_buffer_table(&buffer_mappings, &buffer.name_upper, buffer.start_byte)
+                .map(str::to_ascii_uppercase)
+                .unwrap_or_else(|| buffer.name_upper.clone());
+        let Some(fields) = backend.db_fields_by_table.get(&table_upper) else {
+            continue;
+        };
+        let names = fields
+            .value()
+            .iter()
+            .map(|field| field.name.trim().to_ascii_uppercase())
+            .filter(|upper| !upper.is_empty())
+            .collect::<HashSet<_>>();
+        scopes.push((buffer.range, names));
     }
-    let suffix = &name_upper[prefix_upper.len()..];
-    let Some(first) = suffix.chars().next() else {
-        return false;
-    };
-    first.is_ascii_alphabetic() || first == '_'
+    ActiveTableFields { scopes }
 }
 
 pub fn normalize_identifier_refs(refs: &mut Vec<IdentifierRef>) {
@@ -364,16 +454,25 @@ pub struct UnknownSymbolDiagInputs<'a> {
     pub unknown_functions_ignored: &'a HashSet<String>,
     pub db_tables: &'a DashSet<String>,
     pub db_sequences: &'a DashSet<String>,
-    pub active_table_fields: &'a HashSet<String>,
-    pub active_buffer_like_names: &'a HashSet<String>,
+    pub db_name_by_table: &'a DashMap<String, String>,
+    pub active_table_fields: &'a ActiveTableFields,
     pub unknown_variables_enabled: bool,
     pub unknown_functions_enabled: bool,
+    pub unknown_variables_severity: DiagnosticSeverity,
+    pub unknown_functions_severity: DiagnosticSeverity,
 }
 
 pub fn append_unknown_symbol_diags(inputs: UnknownSymbolDiagInputs<'_>, out: &mut Vec<Diagnostic>) {
     for table_ref in inputs.table_refs {
         if inputs.known_variables.contains(&table_ref.name_upper)
             || inputs.db_tables.contains(&table_ref.name_upper)
+            || strip_db_qualifier(inputs.db_name_by_table, &table_ref.name_upper).is_some()
+        {
+            continue;
+        }
+        // Other `dbname.table` qualifiers are only checked for `[[database]]` logical names.
+        if let Some((db_name, _)) = table_ref.name_upper.split_once('.')
+            && !is_logical_db_name(inputs.db_name_by_table, db_name)
         {
             continue;
         }
@@ -392,16 +491,17 @@ pub fn append_unknown_symbol_diags(inputs: UnknownSymbolDiagInputs<'_>, out: &mu
                 || inputs.unknown_variables_ignored.contains(&r.name_upper)
                 || inputs.db_tables.contains(&r.name_upper)
                 || inputs.db_sequences.contains(&r.name_upper)
-                || inputs.active_table_fields.contains(&r.name_upper)
+                || inputs
+                    .active_table_fields
+                    .contains_at(&r.name_upper, r.range.start)
                 || is_builtin_variable_name(&r.name_upper)
                 || is_builtin_function_name(&r.name_upper)
-                || looks_like_table_field_reference(&r.name_upper, inputs.active_buffer_like_names)
             {
                 continue;
             }
             out.push(Diagnostic {
                 range: r.range,
-                severity: Some(DiagnosticSeverity::ERROR),
+                severity: Some(inputs.unknown_variables_severity),
                 source: Some("abl-semantic".into()),
                 message: format!("Unknown variable '{}'", r.display_name),
                 ..Default::default()
@@ -414,6 +514,7 @@ pub fn append_unknown_symbol_diags(inputs: UnknownSymbolDiagInputs<'_>, out: &mu
             if inputs.known_functions.contains(&call.name_upper)
                 || inputs.unknown_functions_ignored.contains(&call.name_upper)
                 || is_builtin_function_name(&call.name_upper)
+                || is_persistent_procedure_key(&call.name_upper)
                 || call.display_name.contains('.')
                 || call.display_name.contains(':')
             {
@@ -421,7 +522,7 @@ pub fn append_unknown_symbol_diags(inputs: UnknownSymbolDiagInputs<'_>, out: &mu
             }
             out.push(Diagnostic {
                 range: call.range,
-                severity: Some(DiagnosticSeverity::ERROR),
+                severity: Some(inputs.unknown_functions_severity),
                 source: Some("abl-semantic".into()),
                 message: format!("Unknown function '{}'", call.display_name),
                 ..Default::default()
@@ -433,13 +534,18 @@ pub fn append_unknown_symbol_diags(inputs: UnknownSymbolDiagInputs<'_>, out: &mu
 #[cfg(test)]
 mod tests {
     use super::{
-        IdentifierRef, TableRef, UnknownSymbolDiagInputs, append_unknown_symbol_diags,
+        ActiveTableFields, IdentifierRef, TableRef, UnknownSymbolDiagInputs,
+        append_unknown_symbol_diags, collect_active_buffer_like_names,
         collect_identifier_refs_for_unknown_symbol_diag,
     };
     use crate::analysis::parse_abl;
-    use dashmap::DashSet;
+    use dashmap::{DashMap, DashSet};
     use std::collections::HashSet;
-    use tower_lsp::lsp_types::Range;
+    use tower_lsp::lsp_types::{DiagnosticSeverity, Position, Range};
+
+    fn no_active_fields() -> ActiveTableFields {
+        ActiveTableFields { scopes: Vec::new() }
+    }
 
     #[test]
     fn ignores_preprocessor_references_for_unknown_variable_refs() {
@@ -451,12 +557,51 @@ mod tests {
         collect_identifier_refs_for_unknown_symbol_diag(
             tree.root_node(),
             src.as_bytes(),
+            &HashSet::new(),
             &mut refs,
         );
 
         assert!(refs.is_empty());
     }
 
+    #[test]
+    fn ignores_enum_member_access_for_unknown_variable_refs() {
+        let src = "DEFINE VARIABLE c AS Color NO-UNDO.\nc = Color:Red.\n";
+
+        let tree = parse_abl(src);
+
+        let mut refs = Vec::new();
+        collect_identifier_refs_for_unknown_symbol_diag(
+            tree.root_node(),
+            src.as_bytes(),
+            &HashSet::from(["COLOR".to_string()]),
+            &mut refs,
+        );
+
+        assert!(
+            !refs
+                .iter()
+                .any(|r| r.name_upper == "COLOR" || r.name_upper == "RED")
+        );
+    }
+
+    #[test]
+    fn keeps_colon_access_refs_of_non_enum_qualifiers() {
+        let src = "DEFINE VARIABLE c AS Color NO-UNDO.\nc = Color:Red.\n";
+
+        let tree = parse_abl(src);
+
+        let mut refs = Vec::new();
+        collect_identifier_refs_for_unknown_symbol_diag(
+            tree.root_node(),
+            src.as_bytes(),
+            &HashSet::new(),
+            &mut refs,
+        );
+
+        assert!(refs.iter().any(|r| r.name_upper == "COLOR"));
+    }
+
     #[test]
     fn ignores_new_expression_type_identifier_for_unknown_variable_refs() {
         let src = r#"
@@ -471,6 +616,7 @@ a = NEW JsonArray(x).
         collect_identifier_refs_for_unknown_symbol_diag(
             tree.root_node(),
             src.as_bytes(),
+            &HashSet::new(),
             &mut refs,
         );
 
@@ -490,6 +636,7 @@ a = NEW JsonArray(x).
         collect_identifier_refs_for_unknown_symbol_diag(
             tree.root_node(),
             src.as_bytes(),
+            &HashSet::new(),
             &mut refs,
         );
 
@@ -519,10 +666,12 @@ a = NEW JsonArray(x).
                 unknown_functions_ignored: &HashSet::new(),
                 db_tables: &db_tables,
                 db_sequences: &db_sequences,
-                active_table_fields: &HashSet::new(),
-                active_buffer_like_names: &HashSet::new(),
+                db_name_by_table: &DashMap::new(),
+                active_table_fields: &no_active_fields(),
                 unknown_variables_enabled: true,
                 unknown_functions_enabled: true,
+                unknown_variables_severity: DiagnosticSeverity::ERROR,
+                unknown_functions_severity: DiagnosticSeverity::ERROR,
             },
             &mut diags,
         );
@@ -552,10 +701,12 @@ a = NEW JsonArray(x).
                 unknown_functions_ignored: &HashSet::new(),
                 db_tables: &db_tables,
                 db_sequences: &db_sequences,
-                active_table_fields: &HashSet::new(),
-                active_buffer_like_names: &HashSet::new(),
+                db_name_by_table: &DashMap::new(),
+                active_table_fields: &no_active_fields(),
                 unknown_variables_enabled: true,
                 unknown_functions_enabled: true,
+                unknown_variables_severity: DiagnosticSeverity::ERROR,
+                unknown_functions_severity: DiagnosticSeverity::ERROR,
             },
             &mut diags,
         );
@@ -563,4 +714,224 @@ a = NEW JsonArray(x).
         assert_eq!(diags.len(), 1);
         assert_eq!(diags[0].message, "Unknown table 'xaaa'");
     }
+
+    #[test]
+    fn resolves_logical_db_qualifiers_in_table_refs() {
+        let table_ref = |name: &str| TableRef {
+            name_upper: name.to_ascii_uppercase(),
+            display_name: name.to_string(),
+            range: Range::default(),
+        };
+        let table_refs = vec![
+            table_ref("sports.customer"),
+            table_ref("sports.order"),
+            table_ref("other.invoice"),
+        ];
+        let db_tables = DashSet::new();
+        db_tables.insert("CUSTOMER".to_string());
+        let db_name_by_table = DashMap::new();
+        db_name_by_table.insert("CUSTOMER".to_string(), "sports".to_string());
+        let mut diags = Vec::new();
+
+        append_unknown_symbol_diags(
+            UnknownSymbolDiagInputs {
+                refs: &[],
+                table_refs: &table_refs,
+                calls: &[],
+                known_variables: &HashSet::new(),
+                known_functions: &HashSet::new(),
+                unknown_variables_ignored: &HashSet::new(),
+                unknown_functions_ignored: &HashSet::new(),
+                db_tables: &db_tables,
+                db_sequences: &DashSet::new(),
+                db_name_by_table: &db_name_by_table,
+                active_table_fields: &no_active_fields(),
+                unknown_variables_enabled: true,
+                unknown_functions_enabled: true,
+                unknown_variables_severity: DiagnosticSeverity::ERROR,
+                unknown_functions_severity: DiagnosticSeverity::ERROR,
+            },
+            &mut diags,
+        );
+
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].message, "Unknown table 'sports.order'");
+    }
+
+    #[test]
+    fn skips_ignored_unknown_symbols_but_reports_others() {
+        let refs = vec![
+            IdentifierRef {
+                name_upper: "BATCHRUN".to_string(),
+                display_name: "BatchRun".to_string(),
+                range: Range::default(),
+            },
+            IdentifierRef {
+                name_upper: "MISSING_VAR".to_string(),
+                display_name: "missing_var".to_string(),
+                range: Range::default(),
+            },
+        ];
+        let unknown_variables_ignored = HashSet::from(["BATCHRUN".to_string()]);
+        let db_tables = DashSet::new();
+        let db_sequences = DashSet::new();
+        let mut diags = Vec::new();
+
+        append_unknown_symbol_diags(
+            UnknownSymbolDiagInputs {
+                refs: &refs,
+                table_refs: &[],
+                calls: &[],
+                known_variables: &HashSet::new(),
+                known_functions: &HashSet::new(),
+                unknown_variables_ignored: &unknown_variables_ignored,
+                unknown_functions_ignored: &HashSet::new(),
+                db_tables: &db_tables,
+                db_sequences: &db_sequences,
+                db_name_by_table: &DashMap::new(),
+                active_table_fields: &no_active_fields(),
+                unknown_variables_enabled: true,
+                unknown_functions_enabled: true,
+                unknown_variables_severity: DiagnosticSeverity::ERROR,
+                unknown_functions_severity: DiagnosticSeverity::ERROR,
+            },
+            &mut diags,
+        );
+
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].message, "Unknown variable 'missing_var'");
+    }
+
+    #[test]
+    fn scopes_for_each_buffer_fields_to_the_loop() {
+        let src = r#"
+FOR EACH customer NO-LOCK:
+  x = name.
+END.
+y = name.
+"#;
+        let tree = parse_abl(src);
+        let buffers = collect_active_buffer_like_names(tree.root_node(), src.as_bytes());
+        let customer = buffers
+            .iter()
+            .find(|b| b.name_upper == "CUSTOMER")
+            .expect("customer buffer");
+
+        let fields = ActiveTableFields {
+            scopes: vec![(customer.range, HashSet::from(["NAME".to_string()]))],
+        };
+        assert!(fields.contains_at("NAME", Position::new(2, 6)));
+        assert!(!fields.contains_at("NAME", Position::new(4, 4)));
+    }
+
+    #[test]
+    fn scopes_create_do_for_and_buffer_parameters() {
+        let src = r#"
+PROCEDURE p:
+  DEFINE PARAMETER BUFFER bCust FOR customer.
+  CREATE order.
+  DO FOR item:
+  END.
+END PROCEDURE.
+"#;
+        let tree = parse_abl(src);
+        let buffers = collect_active_buffer_like_names(tree.root_node(), src.as_bytes());
+        let scope_of = |name: &str| {
+            buffers
+                .iter()
+                .find(|b| b.name_upper == name)
+                .map(|b| &src[b.start_byte..b.end_byte])
+                .unwrap_or_else(|| panic!("{name} buffer"))
+        };
+
+        assert!(scope_of("BCUST").starts_with("DEFINE PARAMETER BUFFER"));
+        assert!(scope_of("BCUST").ends_with("END PROCEDURE."));
+        assert!(scope_of("ORDER").starts_with("CREATE order."));
+        assert!(scope_of("ORDER").ends_with("END PROCEDURE."));
+        assert!(scope_of("ITEM").starts_with("DO FOR item:"));
+        assert!(scope_of("ITEM").ends_with("END."));
+    }
+
+    #[test]
+    fn reports_bare_field_outside_of_its_buffer_scope() {
+        let refs = vec![
+            IdentifierRef {
+                name_upper: "NAME".to_string(),
+                display_name: "name".to_string(),
+                range: Range::new(Position::new(2, 6), Position::new(2, 10)),
+            },
+            IdentifierRef {
+                name_upper: "NAME".to_string(),
+                display_name: "name".to_string(),
+                range: Range::new(Position::new(4, 4), Position::new(4, 8)),
+            },
+        ];
+        let fields = ActiveTableFields {
+            scopes: vec![(
+                Range::new(Position::new(1, 0), Position::new(3, 4)),
+                HashSet::from(["NAME".to_string()]),
+            )],
+        };
+        let db_tables = DashSet::new();
+        let db_sequences = DashSet::new();
+        let mut diags = Vec::new();
+
+        append_unknown_symbol_diags(
+            UnknownSymbolDiagInputs {
+                refs: &refs,
+                table_refs: &[],
+                calls: &[],
+                known_variables: &HashSet::new(),
+                known_functions: &HashSet::new(),
+                unknown_variables_ignored: &HashSet::new(),
+                unknown_functions_ignored: &HashSet::new(),
+                db_tables: &db_tables,
+                db_sequences: &db_sequences,
+                db_name_by_table: &DashMap::new(),
+                active_table_fields: &fields,
+                unknown_variables_enabled: true,
+                unknown_functions_enabled: true,
+                unknown_variables_severity: DiagnosticSeverity::ERROR,
+                unknown_functions_severity: DiagnosticSeverity::ERROR,
+            },
+            &mut diags,
+        );
+
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].range.start, Position::new(4, 4));
+    }
+
+    #[test]
+    fn of_clause_brings_join_table_into_scope() {
+        let src = r#"
+FOR EACH order OF customer NO-LOCK:
+  x = order-num.
+  y = name.
+END.
+"#;
+        let tree = parse_abl(src);
+        let buffers = collect_active_buffer_like_names(tree.root_node(), src.as_bytes());
+        let names = buffers
+            .iter()
+            .map(|b| b.name_upper.as_str())
+            .collect::<Vec<_>>();
+        assert!(names.contains(&"ORDER"));
+        assert!(names.contains(&"CUSTOMER"));
+
+        let fields = ActiveTableFields {
+            scopes: buffers
+                .iter()
+                .map(|b| {
+                    let field = if b.name_upper == "ORDER" {
+                        "ORDER-NUM"
+                    } else {
+                        "NAME"
+                    };
+                    (b.range, HashSet::from([field.to_string()]))
+                })
+                .collect(),
+        };
+        assert!(fields.contains_at("ORDER-NUM", Position::new(2, 6)));
+        assert!(fields.contains_at("NAME", Position::new(3, 6)));
+    }
 }
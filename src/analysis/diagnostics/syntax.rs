@@ -1,18 +1,48 @@
-use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
 use tree_sitter::Node;
 
-use crate::utils::ts::node_to_range;
+use crate::utils::ts::{node_to_range, point_to_position};
+
+/// `source` of parser-level diagnostics; everything else comes from the semantic passes.
+pub const SYNTAX_DIAGNOSTIC_SOURCE: &str = "tree-sitter";
+
+const BLOCK_OPENER_KEYWORDS: &[&str] = &[
+    "DO",
+    "FOR",
+    "REPEAT",
+    "PROCEDURE",
+    "FUNCTION",
+    "METHOD",
+    "CONSTRUCTOR",
+    "DESTRUCTOR",
+    "CASE",
+    "CLASS",
+    "INTERFACE",
+    "CATCH",
+    "FINALLY",
+];
 
 pub fn collect_ts_error_diags(node: Node<'_>, out: &mut Vec<Diagnostic>, limit: usize) {
+    collect_ts_error_diags_except(node, &[], out, limit);
+}
+
+/// Like [`collect_ts_error_diags`], without a generic "Syntax error" for the ERROR nodes in
+/// `explained` (ids of nodes a more specific check already reported).
+fn collect_ts_error_diags_except(
+    node: Node<'_>,
+    explained: &[usize],
+    out: &mut Vec<Diagnostic>,
+    limit: usize,
+) {
     if out.len() >= limit {
         return;
     }
 
-    if node.is_error() || node.is_missing() {
+    if (node.is_error() && !explained.contains(&node.id())) || node.is_missing() {
         out.push(Diagnostic {
             range: node_to_range(node),
             severity: Some(DiagnosticSeverity::ERROR),
-            source: Some("tree-sitter".into()),
+            source: Some(SYNTAX_DIAGNOSTIC_SOURCE.into()),
             message: if node.is_missing() {
                 "Missing token".into()
             } else {
@@ -27,7 +57,7 @@ pub fn collect_ts_error_diags(node: Node<'_>, out: &mut Vec<Diagnostic>, limit:
 
     for i in 0..node.child_count() {
         if let Some(ch) = node.child(i as u32) {
-            collect_ts_error_diags(ch, out, limit);
+            collect_ts_error_diags_except(ch, explained, out, limit);
             if out.len() >= limit {
                 return;
             }
@@ -35,10 +65,232 @@ pub fn collect_ts_error_diags(node: Node<'_>, out: &mut Vec<Diagnostic>, limit:
     }
 }
 
+/// Reports `"`/`'` strings that never close at their opening quote, instead of leaving only the
+/// generic "Syntax error" somewhere after it.
+pub fn collect_unterminated_string_diags(
+    node: Node<'_>,
+    src: &[u8],
+    out: &mut Vec<Diagnostic>,
+    limit: usize,
+) {
+    if out.len() >= limit {
+        return;
+    }
+
+    let suspicious = node.is_error()
+        || (node.kind() == "string_literal" && (node.has_error() || node.is_missing()));
+    if suspicious
+        && let Ok(text) = node.utf8_text(src)
+        && let Some(quote_offset) = unterminated_quote_offset(text)
+    {
+        let start = offset_to_position(node, text, quote_offset);
+        out.push(Diagnostic {
+            range: Range::new(start, Position::new(start.line, start.character + 1)),
+            severity: Some(DiagnosticSeverity::ERROR),
+            source: Some(SYNTAX_DIAGNOSTIC_SOURCE.into()),
+            message: "Unterminated string literal".into(),
+            ..Default::default()
+        });
+        // Everything after the quote is string content; nested nodes would only repeat it.
+        return;
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_unterminated_string_diags(ch, src, out, limit);
+            if out.len() >= limit {
+                return;
+            }
+        }
+    }
+}
+
+/// Byte offset of the opening quote of a string still open at the end of `text`.
+///
+/// Follows ABL quoting: `~` escapes the next character, a doubled quote is a literal quote,
+/// and quotes inside `/* */` or `//` comments are ignored.
+fn unterminated_quote_offset(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut open: Option<(u8, usize)> = None;
+    let mut comment_depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let next = bytes.get(i + 1).copied();
+        match open {
+            Some((quote, _)) => {
+                if b == b'~' {
+                    i += 1;
+                } else if b == quote {
+                    if next == Some(quote) {
+                        i += 1;
+                    } else {
+                        open = None;
+                    }
+                }
+            }
+            None if comment_depth > 0 => {
+                if b == b'*' && next == Some(b'/') {
+                    comment_depth -= 1;
+                    i += 1;
+                } else if b == b'/' && next == Some(b'*') {
+                    comment_depth += 1;
+                    i += 1;
+                }
+            }
+            None => match b {
+                b'/' if next == Some(b'*') => {
+                    comment_depth = 1;
+                    i += 1;
+                }
+                b'/' if next == Some(b'/') => {
+                    while i < bytes.len() && bytes[i] != b'\n' {
+                        i += 1;
+                    }
+                }
+                b'"' | b'\'' => open = Some((b, i)),
+                _ => {}
+            },
+        }
+        i += 1;
+    }
+    open.map(|(_, start)| start)
+}
+
+/// Byte-column position of `offset` within `node`'s `text`.
+fn offset_to_position(node: Node<'_>, text: &str, offset: usize) -> Position {
+    let start = point_to_position(node.start_position());
+    let before = &text.as_bytes()[..offset];
+    match before.iter().rposition(|b| *b == b'\n') {
+        Some(newline) => {
+            let lines = before.iter().filter(|b| **b == b'\n').count() as u32;
+            Position::new(start.line + lines, (offset - newline - 1) as u32)
+        }
+        None => Position::new(start.line, start.character + offset as u32),
+    }
+}
+
+/// Reports unclosed blocks (`DO:`/`FOR EACH`/`PROCEDURE` ...) at the block opener.
+///
+/// Returns the ids of the ERROR nodes reported this way.
+pub fn collect_missing_end_diags(
+    node: Node<'_>,
+    src: &[u8],
+    out: &mut Vec<Diagnostic>,
+    limit: usize,
+) -> Vec<usize> {
+    let mut explained = Vec::new();
+    collect_missing_end_diags_into(node, src, out, &mut explained, limit);
+    explained
+}
+
+fn collect_missing_end_diags_into(
+    node: Node<'_>,
+    src: &[u8],
+    out: &mut Vec<Diagnostic>,
+    explained: &mut Vec<usize>,
+    limit: usize,
+) {
+    if out.len() >= limit {
+        return;
+    }
+
+    if node.is_error() {
+        let opener = node
+            .prev_sibling()
+            .filter(|prev| !prev.is_error() && block_opener_keyword(*prev, src).is_some())
+            .or_else(|| block_opener_keyword(node, src).map(|_| node));
+        if let Some(opener) = opener
+            && let Some(keyword) = block_opener_keyword(opener, src)
+            && !block_text_is_closed(opener, src)
+        {
+            out.push(Diagnostic {
+                range: node_to_range(opener_anchor(opener)),
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some(SYNTAX_DIAGNOSTIC_SOURCE.into()),
+                message: format!("Missing END for {keyword} block started here"),
+                ..Default::default()
+            });
+            explained.push(node.id());
+            if out.len() >= limit {
+                return;
+            }
+        }
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_missing_end_diags_into(ch, src, out, explained, limit);
+            if out.len() >= limit {
+                return;
+            }
+        }
+    }
+}
+
+fn block_opener_keyword(node: Node<'_>, src: &[u8]) -> Option<String> {
+    let text = node.utf8_text(src).ok()?.trim_start();
+    let first = text
+        .split(|c: char| c.is_ascii_whitespace() || c == ':')
+        .next()?
+        .to_ascii_uppercase();
+    if !BLOCK_OPENER_KEYWORDS.contains(&first.as_str()) {
+        return None;
+    }
+    // Block headers always end with a colon; `FOR` without one is e.g. a FIND-like statement.
+    if !text.contains(':') {
+        return None;
+    }
+    Some(first)
+}
+
+fn block_text_is_closed(node: Node<'_>, src: &[u8]) -> bool {
+    let Ok(text) = node.utf8_text(src) else {
+        return false;
+    };
+    let upper = text.trim_end().trim_end_matches('.').to_ascii_uppercase();
+    let last_statement = upper.rsplit('.').next().unwrap_or_default().trim();
+    last_statement == "END" || last_statement.starts_with("END ")
+}
+
+fn opener_anchor(node: Node<'_>) -> Node<'_> {
+    let mut current = node;
+    while let Some(first) = current.child(0) {
+        current = first;
+    }
+    current
+}
+
+/// Every syntax check (unclosed blocks, unterminated strings, parse errors), each capped at `limit`.
+pub fn collect_syntax_diags(root: Node<'_>, src: &[u8], out: &mut Vec<Diagnostic>, limit: usize) {
+    let explained = collect_missing_end_diags(root, src, out, limit);
+    collect_unterminated_string_diags(root, src, out, limit);
+    collect_ts_error_diags_except(root, &explained, out, limit);
+}
+
+/// Fresh syntax errors plus the semantic results already on screen, for the early publish that
+/// precedes the semantic passes of a change.
+pub fn with_previous_semantic_diags(
+    mut syntax: Vec<Diagnostic>,
+    previous: Vec<Diagnostic>,
+) -> Vec<Diagnostic> {
+    syntax.extend(
+        previous
+            .into_iter()
+            .filter(|diag| diag.source.as_deref() != Some(SYNTAX_DIAGNOSTIC_SOURCE)),
+    );
+    syntax
+}
+
 #[cfg(test)]
 mod tests {
-    use super::collect_ts_error_diags;
+    use super::{
+        SYNTAX_DIAGNOSTIC_SOURCE, collect_missing_end_diags, collect_syntax_diags,
+        collect_ts_error_diags, collect_unterminated_string_diags, unterminated_quote_offset,
+        with_previous_semantic_diags,
+    };
     use crate::analysis::parse_abl;
+    use tower_lsp::lsp_types::Diagnostic;
 
     #[test]
     fn collects_syntax_errors_with_limit() {
@@ -54,4 +306,91 @@ END FUNCTION
         assert_eq!(out.len(), 1);
         assert!(out[0].message == "Syntax error" || out[0].message == "Missing token");
     }
+
+    #[test]
+    fn reports_missing_end_for_unclosed_do_block() {
+        let src = r#"
+DEFINE VARIABLE i AS INTEGER NO-UNDO.
+DO:
+  i = 1.
+"#;
+        let tree = parse_abl(src);
+
+        let mut out = Vec::new();
+        collect_missing_end_diags(tree.root_node(), src.as_bytes(), &mut out, 64);
+        assert!(out.iter().any(
+            |d| d.message == "Missing END for DO block started here" && d.range.start.line == 2
+        ));
+    }
+
+    #[test]
+    fn missing_end_replaces_the_generic_syntax_error() {
+        let src = r#"
+DEFINE VARIABLE i AS INTEGER NO-UNDO.
+DO:
+  i = 1.
+"#;
+        let tree = parse_abl(src);
+
+        let mut out = Vec::new();
+        collect_syntax_diags(tree.root_node(), src.as_bytes(), &mut out, 64);
+        let missing_end = out
+            .iter()
+            .filter(|d| d.message.starts_with("Missing END"))
+            .count();
+        assert_eq!(missing_end, 1);
+        assert!(!out.iter().any(|d| d.message == "Syntax error"));
+    }
+
+    #[test]
+    fn does_not_report_missing_end_for_closed_blocks() {
+        let src = r#"
+DO:
+  MESSAGE "x".
+END.
+"#;
+        let tree = parse_abl(src);
+
+        let mut out = Vec::new();
+        collect_missing_end_diags(tree.root_node(), src.as_bytes(), &mut out, 64);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn reports_unterminated_string_at_opening_quote() {
+        let src = "DEFINE VARIABLE c AS CHARACTER NO-UNDO.\nc = \"abc.\nMESSAGE c.\n";
+        let tree = parse_abl(src);
+
+        let mut out = Vec::new();
+        collect_unterminated_string_diags(tree.root_node(), src.as_bytes(), &mut out, 64);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].message, "Unterminated string literal");
+        assert_eq!(out[0].range.start.line, 1);
+        assert_eq!(out[0].range.start.character, 4);
+
+        assert_eq!(unterminated_quote_offset(r#"x = "a""b" + 'c~'d'."#), None);
+        assert_eq!(unterminated_quote_offset("/* don't */ x = 1."), None);
+        assert_eq!(unterminated_quote_offset(r#"x = 'ok' + "open"#), Some(11));
+    }
+
+    #[test]
+    fn early_publish_replaces_stale_syntax_errors_and_keeps_semantic_ones() {
+        let diag = |source: &str, message: &str| Diagnostic {
+            source: Some(source.to_string()),
+            message: message.to_string(),
+            ..Default::default()
+        };
+        let merged = with_previous_semantic_diags(
+            vec![diag(SYNTAX_DIAGNOSTIC_SOURCE, "new syntax")],
+            vec![
+                diag(SYNTAX_DIAGNOSTIC_SOURCE, "old syntax"),
+                diag("abl-semantic", "unknown variable"),
+            ],
+        );
+        let messages = merged
+            .iter()
+            .map(|d| d.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(messages, vec!["new syntax", "unknown variable"]);
+    }
 }
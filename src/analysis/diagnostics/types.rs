@@ -13,12 +13,22 @@ struct TypedBinding {
     start_byte: usize,
 }
 
+enum ReturnExpectation {
+    Function { name: String, ty: BasicType },
+    Procedure,
+}
+
 #[derive(Clone)]
 struct FunctionTypeSignature {
     param_types: Vec<Option<BasicType>>,
 }
 
-pub fn collect_assignment_type_diags(root: Node<'_>, src: &[u8], out: &mut Vec<Diagnostic>) {
+pub fn collect_assignment_type_diags(
+    root: Node<'_>,
+    src: &[u8],
+    severity: DiagnosticSeverity,
+    out: &mut Vec<Diagnostic>,
+) {
     let mut bindings = Vec::<TypedBinding>::new();
     collect_typed_bindings(root, src, &mut bindings);
 
@@ -29,10 +39,15 @@ pub fn collect_assignment_type_diags(root: Node<'_>, src: &[u8], out: &mut Vec<D
     let mut function_returns = HashMap::<String, BasicType>::new();
     collect_function_return_types(root, src, &mut function_returns);
 
-    collect_assignment_type_diags_in_node(root, src, &bindings, &function_returns, out);
+    collect_assignment_type_diags_in_node(root, src, &bindings, &function_returns, severity, out);
 }
 
-pub fn collect_function_call_arg_type_diags(root: Node<'_>, src: &[u8], out: &mut Vec<Diagnostic>) {
+pub fn collect_function_call_arg_type_diags(
+    root: Node<'_>,
+    src: &[u8],
+    severity: DiagnosticSeverity,
+    out: &mut Vec<Diagnostic>,
+) {
     let mut bindings = Vec::<TypedBinding>::new();
     collect_typed_bindings(root, src, &mut bindings);
 
@@ -48,10 +63,112 @@ pub fn collect_function_call_arg_type_diags(root: Node<'_>, src: &[u8], out: &mu
         &bindings,
         &function_returns,
         &signatures,
+        severity,
+        out,
+    );
+}
+
+/// Reports `=`/`<>`/`<`/`>` (and `EQ`/`NE`/...) comparisons between operands of different types.
+pub fn collect_comparison_type_diags(
+    root: Node<'_>,
+    src: &[u8],
+    severity: DiagnosticSeverity,
+    out: &mut Vec<Diagnostic>,
+) {
+    let mut bindings = Vec::<TypedBinding>::new();
+    collect_typed_bindings(root, src, &mut bindings);
+
+    if bindings.is_empty() {
+        return;
+    }
+
+    let mut function_returns = HashMap::<String, BasicType>::new();
+    collect_function_return_types(root, src, &mut function_returns);
+
+    collect_comparison_type_diags_in_node(root, src, &bindings, &function_returns, severity, out);
+}
+
+/// Reports `RETURN <expr>` values that do not match the enclosing function's declared type;
+/// procedures (and the main block) may only return CHARACTER values.
+pub fn collect_return_type_diags(
+    root: Node<'_>,
+    src: &[u8],
+    severity: DiagnosticSeverity,
+    out: &mut Vec<Diagnostic>,
+) {
+    let mut bindings = Vec::<TypedBinding>::new();
+    collect_typed_bindings(root, src, &mut bindings);
+
+    let mut function_returns = HashMap::<String, BasicType>::new();
+    collect_function_return_types(root, src, &mut function_returns);
+
+    collect_return_type_diags_in_node(
+        root,
+        src,
+        &bindings,
+        &function_returns,
+        Some(&ReturnExpectation::Procedure),
+        severity,
         out,
     );
 }
 
+/// Declared type of `name_upper` from the nearest definition before `at_byte`.
+pub fn binding_type_at(
+    root: Node<'_>,
+    src: &[u8],
+    name_upper: &str,
+    at_byte: usize,
+) -> Option<BasicType> {
+    let mut bindings = Vec::<TypedBinding>::new();
+    collect_typed_bindings(root, src, &mut bindings);
+    resolve_binding_type(&bindings, name_upper, at_byte)
+}
+
+/// Infers the type of the first value assigned to `name_upper` (used to seed new definitions).
+pub fn infer_first_assignment_type(
+    root: Node<'_>,
+    src: &[u8],
+    name_upper: &str,
+) -> Option<BasicType> {
+    let mut bindings = Vec::<TypedBinding>::new();
+    collect_typed_bindings(root, src, &mut bindings);
+    let mut function_returns = HashMap::<String, BasicType>::new();
+    collect_function_return_types(root, src, &mut function_returns);
+
+    let right = first_assignment_rhs(root, src, name_upper)?;
+    infer_expr_type(right, src, &bindings, &function_returns)
+}
+
+/// Right-hand side of the first `name = ...` assignment under `node`.
+pub fn first_assignment_rhs<'tree>(
+    node: Node<'tree>,
+    src: &[u8],
+    name_upper: &str,
+) -> Option<Node<'tree>> {
+    if node.kind() == "assignment_statement"
+        && let (Some(left), Some(right)) = (
+            node.child_by_field_name("left"),
+            node.child_by_field_name("right"),
+        )
+        && left.kind() == "identifier"
+        && left
+            .utf8_text(src)
+            .is_ok_and(|name| name.trim().eq_ignore_ascii_case(name_upper))
+    {
+        return Some(right);
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32)
+            && let Some(found) = first_assignment_rhs(ch, src, name_upper)
+        {
+            return Some(found);
+        }
+    }
+    None
+}
+
 fn collect_typed_bindings(node: Node<'_>, src: &[u8], out: &mut Vec<TypedBinding>) {
     if matches!(node.kind(), "variable_definition" | "parameter_definition")
         && let (Some(name_node), Some(type_node)) = (
@@ -124,6 +241,7 @@ fn collect_assignment_type_diags_in_node(
     src: &[u8],
     bindings: &[TypedBinding],
     function_returns: &HashMap<String, BasicType>,
+    severity: DiagnosticSeverity,
     out: &mut Vec<Diagnostic>,
 ) {
     if node.kind() == "assignment_statement"
@@ -141,7 +259,7 @@ fn collect_assignment_type_diags_in_node(
         {
             out.push(Diagnostic {
                 range: node_to_range(right),
-                severity: Some(DiagnosticSeverity::ERROR),
+                severity: Some(severity),
                 source: Some("abl-semantic".into()),
                 message: format!(
                     "Type mismatch: cannot assign {} to {} variable '{}'",
@@ -156,7 +274,148 @@ fn collect_assignment_type_diags_in_node(
 
     for i in 0..node.child_count() {
         if let Some(ch) = node.child(i as u32) {
-            collect_assignment_type_diags_in_node(ch, src, bindings, function_returns, out);
+            collect_assignment_type_diags_in_node(
+                ch,
+                src,
+                bindings,
+                function_returns,
+                severity,
+                out,
+            );
+        }
+    }
+}
+
+fn collect_return_type_diags_in_node(
+    node: Node<'_>,
+    src: &[u8],
+    bindings: &[TypedBinding],
+    function_returns: &HashMap<String, BasicType>,
+    expected: Option<&ReturnExpectation>,
+    severity: DiagnosticSeverity,
+    out: &mut Vec<Diagnostic>,
+) {
+    let scoped;
+    let expected = match node.kind() {
+        "function_definition" => {
+            scoped = node
+                .child_by_field_name("name")
+                .and_then(|n| n.utf8_text(src).ok())
+                .map(normalize_function_name)
+                .and_then(|name| {
+                    let ty = *function_returns.get(&name)?;
+                    Some(ReturnExpectation::Function { name, ty })
+                });
+            scoped.as_ref()
+        }
+        "procedure_definition" => Some(&ReturnExpectation::Procedure),
+        "method_definition"
+        | "constructor_definition"
+        | "destructor_definition"
+        | "function_forward_definition"
+        | "class_definition"
+        | "interface_definition" => None,
+        _ => expected,
+    };
+
+    if node.kind() == "return_statement"
+        && let Some(expected) = expected
+        && let Some(value) = node
+            .child_by_field_name("value")
+            .or_else(|| node.named_child(0))
+        && let Some(actual) = infer_expr_type(value, src, bindings, function_returns)
+    {
+        let message = match expected {
+            ReturnExpectation::Function { name, ty } if *ty != actual => Some(format!(
+                "Function '{}' returns {}, got {}",
+                name,
+                ty.label(),
+                actual.label()
+            )),
+            ReturnExpectation::Procedure if actual != BasicType::Character => Some(format!(
+                "Procedure RETURN value must be CHARACTER, got {}",
+                actual.label()
+            )),
+            _ => None,
+        };
+        if let Some(message) = message {
+            out.push(Diagnostic {
+                range: node_to_range(value),
+                severity: Some(severity),
+                source: Some("abl-semantic".into()),
+                message,
+                ..Default::default()
+            });
+        }
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_return_type_diags_in_node(
+                ch,
+                src,
+                bindings,
+                function_returns,
+                expected,
+                severity,
+                out,
+            );
+        }
+    }
+}
+
+const COMPARISON_OPERATORS: &[&str] = &[
+    "=", "<>", "<", ">", "<=", ">=", "EQ", "NE", "LT", "GT", "LE", "GE",
+];
+
+fn collect_comparison_type_diags_in_node(
+    node: Node<'_>,
+    src: &[u8],
+    bindings: &[TypedBinding],
+    function_returns: &HashMap<String, BasicType>,
+    severity: DiagnosticSeverity,
+    out: &mut Vec<Diagnostic>,
+) {
+    if matches!(node.kind(), "comparison_expression" | "binary_expression")
+        && let (Some(left), Some(right)) = (
+            node.child_by_field_name("left"),
+            node.child_by_field_name("right"),
+        )
+        && let Some(operator) = src
+            .get(left.end_byte()..right.start_byte())
+            .and_then(|op| std::str::from_utf8(op).ok())
+            .map(str::trim)
+        && COMPARISON_OPERATORS
+            .iter()
+            .any(|op| op.eq_ignore_ascii_case(operator))
+        && let Some(left_ty) = infer_expr_type(left, src, bindings, function_returns)
+        && let Some(right_ty) = infer_expr_type(right, src, bindings, function_returns)
+        // INTEGER/DECIMAL/INT64 share NUMERIC, so only genuinely incompatible kinds remain.
+        && left_ty != right_ty
+    {
+        out.push(Diagnostic {
+            range: node_to_range(node),
+            severity: Some(severity),
+            source: Some("abl-semantic".into()),
+            message: format!(
+                "Type mismatch: cannot compare {} with {}",
+                left_ty.label(),
+                right_ty.label()
+            ),
+            ..Default::default()
+        });
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_comparison_type_diags_in_node(
+                ch,
+                src,
+                bindings,
+                function_returns,
+                severity,
+                out,
+            );
         }
     }
 }
@@ -208,6 +467,7 @@ fn collect_function_call_arg_type_diags_in_node(
     bindings: &[TypedBinding],
     function_returns: &HashMap<String, BasicType>,
     signatures: &HashMap<String, Vec<FunctionTypeSignature>>,
+    severity: DiagnosticSeverity,
     out: &mut Vec<Diagnostic>,
 ) {
     if node.kind() == "function_call" {
@@ -238,7 +498,7 @@ fn collect_function_call_arg_type_diags_in_node(
                     {
                         out.push(Diagnostic {
                             range: node_to_range(arg_expr),
-                            severity: Some(DiagnosticSeverity::ERROR),
+                            severity: Some(severity),
                             source: Some("abl-semantic".into()),
                             message: format!(
                                 "Function '{}' argument {} expects {}, got {}",
@@ -263,6 +523,7 @@ fn collect_function_call_arg_type_diags_in_node(
                 bindings,
                 function_returns,
                 signatures,
+                severity,
                 out,
             );
         }
@@ -285,6 +546,7 @@ fn unify_expected_param_type(
     expected
 }
 
+/// Declared builtin type of each parameter; `None` for class, table and unknown types.
 fn function_param_types(function_node: Node<'_>, src: &[u8]) -> Vec<Option<BasicType>> {
     if let Some(parameters_node) = direct_child_by_kind(function_node, "parameters") {
         let mut header_param_types = Vec::new();
@@ -333,6 +595,7 @@ fn collect_param_types_recursive(
             "function_definition"
                 | "function_forward_definition"
                 | "procedure_definition"
+                | "procedure_forward_definition"
                 | "method_definition"
                 | "constructor_definition"
                 | "destructor_definition"
@@ -357,7 +620,7 @@ fn collect_param_types_recursive(
     }
 }
 
-fn argument_exprs(arguments_node: Node<'_>) -> Vec<Node<'_>> {
+pub fn argument_exprs(arguments_node: Node<'_>) -> Vec<Node<'_>> {
     let mut out = Vec::new();
     for i in 0..arguments_node.child_count() {
         let Some(ch) = arguments_node.child(i as u32) else {
@@ -375,8 +638,100 @@ fn argument_exprs(arguments_node: Node<'_>) -> Vec<Node<'_>> {
 
 #[cfg(test)]
 mod tests {
-    use super::{collect_assignment_type_diags, collect_function_call_arg_type_diags};
+    use super::{
+        binding_type_at, collect_assignment_type_diags, collect_comparison_type_diags,
+        collect_function_call_arg_type_diags, collect_return_type_diags,
+    };
     use crate::analysis::parse_abl;
+    use crate::analysis::types::BasicType;
+    use tower_lsp::lsp_types::DiagnosticSeverity;
+
+    #[test]
+    fn reports_comparisons_between_incompatible_types() {
+        let src = r#"
+DEFINE VARIABLE c AS CHARACTER NO-UNDO.
+DEFINE VARIABLE i AS INTEGER NO-UNDO.
+DEFINE VARIABLE d AS DECIMAL NO-UNDO.
+IF c = 5 THEN MESSAGE "x".
+IF i < d THEN MESSAGE "y".
+IF c EQ "a" THEN MESSAGE "z".
+"#;
+        let tree = parse_abl(src);
+        let mut diags = Vec::new();
+        collect_comparison_type_diags(
+            tree.root_node(),
+            src.as_bytes(),
+            DiagnosticSeverity::WARNING,
+            &mut diags,
+        );
+
+        assert_eq!(diags.len(), 1);
+        assert_eq!(
+            diags[0].message,
+            "Type mismatch: cannot compare CHARACTER with NUMERIC"
+        );
+        assert_eq!(diags[0].range.start.line, 4);
+    }
+
+    #[test]
+    fn reports_return_values_of_the_wrong_type() {
+        let src = r#"
+DEFINE VARIABLE n AS INTEGER NO-UNDO.
+FUNCTION greet RETURNS CHARACTER ():
+  RETURN n.
+END FUNCTION.
+FUNCTION total RETURNS INTEGER ():
+  RETURN n.
+END FUNCTION.
+PROCEDURE calc:
+  RETURN 5.
+END PROCEDURE.
+PROCEDURE ok:
+  RETURN "done".
+END PROCEDURE.
+"#;
+        let tree = parse_abl(src);
+        let mut diags = Vec::new();
+        collect_return_type_diags(
+            tree.root_node(),
+            src.as_bytes(),
+            DiagnosticSeverity::ERROR,
+            &mut diags,
+        );
+
+        let messages = diags.iter().map(|d| d.message.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "Function 'GREET' returns CHARACTER, got NUMERIC",
+                "Procedure RETURN value must be CHARACTER, got NUMERIC",
+            ]
+        );
+        assert_eq!(diags[0].range.start.line, 3);
+    }
+
+    #[test]
+    fn resolves_binding_type_at_offset() {
+        let src = r#"
+DEFINE VARIABLE hWidget AS HANDLE NO-UNDO.
+DEFINE VARIABLE cName AS CHARACTER NO-UNDO.
+hWidget:SCREEN-VALUE = cName.
+"#;
+        let tree = parse_abl(src);
+        let at = src.find("hWidget:").expect("use");
+        assert_eq!(
+            binding_type_at(tree.root_node(), src.as_bytes(), "HWIDGET", at),
+            Some(BasicType::Handle)
+        );
+        assert_eq!(
+            binding_type_at(tree.root_node(), src.as_bytes(), "CNAME", at),
+            Some(BasicType::Character)
+        );
+        assert_eq!(
+            binding_type_at(tree.root_node(), src.as_bytes(), "HWIDGET", 0),
+            None
+        );
+    }
 
     #[test]
     fn reports_assignment_type_mismatches_for_variables_and_function_returns() {
@@ -399,7 +754,12 @@ i = 42.
         let tree = parse_abl(src);
 
         let mut diags = Vec::new();
-        collect_assignment_type_diags(tree.root_node(), src.as_bytes(), &mut diags);
+        collect_assignment_type_diags(
+            tree.root_node(),
+            src.as_bytes(),
+            DiagnosticSeverity::ERROR,
+            &mut diags,
+        );
 
         assert_eq!(diags.len(), 3);
         let messages = diags.into_iter().map(|d| d.message).collect::<Vec<_>>();
@@ -433,7 +793,12 @@ local_mul("5", 1).
         let tree = parse_abl(src);
 
         let mut diags = Vec::new();
-        collect_function_call_arg_type_diags(tree.root_node(), src.as_bytes(), &mut diags);
+        collect_function_call_arg_type_diags(
+            tree.root_node(),
+            src.as_bytes(),
+            DiagnosticSeverity::ERROR,
+            &mut diags,
+        );
 
         assert_eq!(diags.len(), 1);
         assert!(
@@ -0,0 +1,91 @@
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+use tree_sitter::Node;
+
+use crate::utils::ts::node_to_range;
+
+/// Relational operators that yield `?` (not FALSE) when an operand is the unknown value.
+const ORDERING_OPERATORS: &[&str] = &["<", ">", "<=", ">=", "LT", "GT", "LE", "GE"];
+const ARITHMETIC_OPERATORS: &[&str] = &["+", "-", "*", "/"];
+
+/// Flags uses of the unknown value `?` whose result is always `?`; `x = ?` and `x <> ?` are fine.
+pub fn collect_unknown_value_diags(node: Node<'_>, src: &[u8], out: &mut Vec<Diagnostic>) {
+    if matches!(node.kind(), "comparison_expression" | "binary_expression")
+        && let (Some(left), Some(right)) = (
+            node.child_by_field_name("left"),
+            node.child_by_field_name("right"),
+        )
+        && (is_unknown_value(left, src) || is_unknown_value(right, src))
+        && let Some(operator) = src
+            .get(left.end_byte()..right.start_byte())
+            .and_then(|op| std::str::from_utf8(op).ok())
+            .map(str::trim)
+    {
+        let is_op = |ops: &[&str]| ops.iter().any(|op| op.eq_ignore_ascii_case(operator));
+        let message = if is_op(ORDERING_OPERATORS) {
+            Some(format!(
+                "Comparing with the unknown value (?) using '{operator}' yields ?; use = ? or <> ? to test for unknown"
+            ))
+        } else if is_op(ARITHMETIC_OPERATORS) {
+            Some(format!(
+                "'{operator}' with the unknown value (?) always yields ?"
+            ))
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            out.push(Diagnostic {
+                range: node_to_range(node),
+                severity: Some(DiagnosticSeverity::INFORMATION),
+                source: Some("abl-semantic".into()),
+                message,
+                ..Default::default()
+            });
+        }
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_unknown_value_diags(ch, src, out);
+        }
+    }
+}
+
+fn is_unknown_value(node: Node<'_>, src: &[u8]) -> bool {
+    if node.kind() == "parenthesized_expression" {
+        return node
+            .named_child(0)
+            .is_some_and(|inner| is_unknown_value(inner, src));
+    }
+    node.utf8_text(src).is_ok_and(|text| text.trim() == "?")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::collect_unknown_value_diags;
+    use crate::analysis::parse_abl;
+    use tower_lsp::lsp_types::DiagnosticSeverity;
+
+    #[test]
+    fn flags_ordering_and_arithmetic_with_unknown_value() {
+        let src = r#"
+DEFINE VARIABLE i AS INTEGER NO-UNDO.
+DEFINE VARIABLE c AS CHARACTER NO-UNDO.
+IF i = ? OR i <> ? THEN i = ?.
+IF i > ? THEN MESSAGE "never".
+c = "a" + ?.
+"#;
+        let tree = parse_abl(src);
+        let mut diags = Vec::new();
+        collect_unknown_value_diags(tree.root_node(), src.as_bytes(), &mut diags);
+
+        assert_eq!(diags.len(), 2);
+        assert_eq!(diags[0].range.start.line, 4);
+        assert!(diags[0].message.contains("using '>'"));
+        assert_eq!(diags[0].severity, Some(DiagnosticSeverity::INFORMATION));
+        assert_eq!(diags[1].range.start.line, 5);
+        assert_eq!(
+            diags[1].message,
+            "'+' with the unknown value (?) always yields ?"
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag, Range};
+use tree_sitter::Node;
+
+use crate::utils::ts::node_to_range;
+
+/// Reports statements following an unconditional `RETURN`/`LEAVE`/`NEXT` in the same block.
+pub fn collect_unreachable_code_diags(node: Node<'_>, out: &mut Vec<Diagnostic>) {
+    if is_statement_container(node) {
+        let mut exited = false;
+        let mut unreachable: Option<Range> = None;
+        for i in 0..node.named_child_count() {
+            let Some(ch) = node.named_child(i as u32) else {
+                continue;
+            };
+            // `CATCH`/`FINALLY` still run after the block exits, and close its statement list.
+            if is_catch_or_finally(ch.kind()) {
+                break;
+            }
+            if !ch.kind().ends_with("_statement") {
+                continue;
+            }
+            if exited {
+                let range = node_to_range(ch);
+                unreachable = Some(match unreachable {
+                    Some(first) => Range::new(first.start, range.end),
+                    None => range,
+                });
+            } else if matches!(
+                ch.kind(),
+                "return_statement" | "leave_statement" | "next_statement"
+            ) {
+                exited = true;
+            }
+        }
+        if let Some(range) = unreachable {
+            out.push(Diagnostic {
+                range,
+                severity: Some(DiagnosticSeverity::WARNING),
+                source: Some("abl-semantic".into()),
+                message: "Unreachable code".into(),
+                tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+                ..Default::default()
+            });
+        }
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_unreachable_code_diags(ch, out);
+        }
+    }
+}
+
+fn is_catch_or_finally(kind: &str) -> bool {
+    matches!(
+        kind,
+        "catch_block" | "finally_block" | "catch_statement" | "finally_statement"
+    )
+}
+
+/// Statement lists that execute sequentially; `IF`/`CASE` branches are deliberately excluded.
+fn is_statement_container(node: Node<'_>) -> bool {
+    node.parent().is_none() || node.kind() == "body" || node.kind().ends_with("_definition")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::collect_unreachable_code_diags;
+    use crate::analysis::parse_abl;
+    use tower_lsp::lsp_types::DiagnosticTag;
+
+    #[test]
+    fn flags_statements_after_unconditional_return() {
+        let src = r#"
+PROCEDURE calc:
+  DEFINE VARIABLE x AS INTEGER NO-UNDO.
+  IF x > 0 THEN RETURN.
+  x = 1.
+  RETURN.
+  x = 2.
+  MESSAGE x.
+END PROCEDURE.
+"#;
+        let tree = parse_abl(src);
+        let mut diags = Vec::new();
+        collect_unreachable_code_diags(tree.root_node(), &mut diags);
+
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].range.start.line, 6);
+        assert_eq!(diags[0].range.end.line, 7);
+        assert_eq!(diags[0].tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+    }
+
+    #[test]
+    fn does_not_flag_catch_after_return() {
+        let src = r#"
+PROCEDURE calc:
+  RETURN.
+  CATCH e AS Progress.Lang.Error:
+    MESSAGE e:GetMessage(1).
+  END CATCH.
+END PROCEDURE.
+"#;
+        let tree = parse_abl(src);
+        let mut diags = Vec::new();
+        collect_unreachable_code_diags(tree.root_node(), &mut diags);
+
+        assert!(diags.is_empty());
+    }
+
+    #[test]
+    fn does_not_flag_finally_after_leave() {
+        let src = r#"
+DO ON ERROR UNDO, LEAVE:
+  LEAVE.
+  FINALLY:
+    MESSAGE "cleanup".
+  END FINALLY.
+END.
+"#;
+        let tree = parse_abl(src);
+        let mut diags = Vec::new();
+        collect_unreachable_code_diags(tree.root_node(), &mut diags);
+
+        assert!(diags.is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashSet;
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag};
+use tree_sitter::Node;
+
+use crate::analysis::highlights::is_write_site;
+use crate::analysis::scopes::is_scope_node;
+use crate::utils::ts::node_to_range;
+
+/// Hints `DEFINE VARIABLE`s that are never read in their scope (nested scopes included).
+pub fn collect_unused_variable_diags(node: Node<'_>, src: &[u8], out: &mut Vec<Diagnostic>) {
+    if node.parent().is_none() || is_scope_node(node.kind()) {
+        let mut variables = Vec::new();
+        collect_scope_variables(node, src, true, &mut variables);
+        if !variables.is_empty() {
+            let mut reads = HashSet::new();
+            collect_scope_reads(node, src, true, &mut reads);
+            for (name, name_node) in variables {
+                if reads.contains(&name.to_ascii_uppercase()) {
+                    continue;
+                }
+                out.push(Diagnostic {
+                    range: node_to_range(name_node),
+                    severity: Some(DiagnosticSeverity::HINT),
+                    source: Some("abl-semantic".into()),
+                    message: format!("Variable '{name}' is never read"),
+                    tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_unused_variable_diags(ch, src, out);
+        }
+    }
+}
+
+fn collect_scope_variables<'a>(
+    node: Node<'a>,
+    src: &[u8],
+    is_root: bool,
+    out: &mut Vec<(String, Node<'a>)>,
+) {
+    if !is_root && is_scope_node(node.kind()) {
+        return;
+    }
+    if node.kind() == "variable_definition" {
+        if let Some(name_node) = node.child_by_field_name("name")
+            && let Ok(name) = name_node.utf8_text(src)
+            && !is_visible_outside_scope(node, name_node, src)
+        {
+            out.push((name.trim().to_string(), name_node));
+        }
+        return;
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_scope_variables(ch, src, false, out);
+        }
+    }
+}
+
+/// `SHARED` variables and `PUBLIC`/`PROTECTED` class members may be read from other files.
+fn is_visible_outside_scope(definition: Node<'_>, name: Node<'_>, src: &[u8]) -> bool {
+    src.get(definition.start_byte()..name.start_byte())
+        .and_then(|header| std::str::from_utf8(header).ok())
+        .is_some_and(|header| {
+            header.split_ascii_whitespace().any(|token| {
+                ["SHARED", "PUBLIC", "PROTECTED"]
+                    .iter()
+                    .any(|m| token.eq_ignore_ascii_case(m))
+            })
+        })
+}
+
+/// Upper-cased names read in this scope; nested scopes contribute reads of names they do not redefine.
+/// `OUTPUT` arguments are not assignment targets, so passing a variable that way counts as a use.
+fn collect_scope_reads(node: Node<'_>, src: &[u8], is_root: bool, out: &mut HashSet<String>) {
+    if !is_root && is_scope_node(node.kind()) {
+        let mut own = HashSet::new();
+        collect_scope_defined_names(node, src, true, &mut own);
+        let mut nested = HashSet::new();
+        collect_scope_reads(node, src, true, &mut nested);
+        out.extend(nested.into_iter().filter(|name| !own.contains(name)));
+        return;
+    }
+    if node.kind() == "identifier" {
+        if !is_write_site(node)
+            && let Ok(name) = node.utf8_text(src)
+        {
+            out.insert(name.trim().to_ascii_uppercase());
+        }
+        return;
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_scope_reads(ch, src, false, out);
+        }
+    }
+}
+
+fn collect_scope_defined_names(
+    node: Node<'_>,
+    src: &[u8],
+    is_root: bool,
+    out: &mut HashSet<String>,
+) {
+    if !is_root && is_scope_node(node.kind()) {
+        return;
+    }
+    if (node.kind().ends_with("_definition") || node.kind() == "parameter")
+        && !is_scope_node(node.kind())
+        && let Some(name) = node.child_by_field_name("name")
+        && let Ok(name) = name.utf8_text(src)
+    {
+        out.insert(name.trim().to_ascii_uppercase());
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_scope_defined_names(ch, src, false, out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::collect_unused_variable_diags;
+    use crate::analysis::parse_abl;
+    use tower_lsp::lsp_types::{DiagnosticSeverity, DiagnosticTag};
+
+    #[test]
+    fn hints_variables_that_are_never_read() {
+        let src = r#"
+DEFINE VARIABLE lvRead AS INTEGER NO-UNDO.
+DEFINE VARIABLE lvWriteOnly AS INTEGER NO-UNDO.
+DEFINE VARIABLE lvOut AS INTEGER NO-UNDO.
+DEFINE NEW SHARED VARIABLE gvShared AS INTEGER NO-UNDO.
+lvWriteOnly = 1.
+RUN calc (OUTPUT lvOut).
+MESSAGE lvRead.
+
+PROCEDURE calc:
+  DEFINE OUTPUT PARAMETER pOut AS INTEGER NO-UNDO.
+  DEFINE VARIABLE lvRead AS INTEGER NO-UNDO.
+  pOut = 1.
+END PROCEDURE.
+"#;
+        let tree = parse_abl(src);
+        let mut diags = Vec::new();
+        collect_unused_variable_diags(tree.root_node(), src.as_bytes(), &mut diags);
+
+        let messages = diags.iter().map(|d| d.message.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "Variable 'lvWriteOnly' is never read",
+                "Variable 'lvRead' is never read",
+            ]
+        );
+        assert_eq!(diags[0].severity, Some(DiagnosticSeverity::HINT));
+        assert_eq!(diags[0].tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+        assert_eq!(diags[1].range.start.line, 11);
+    }
+}
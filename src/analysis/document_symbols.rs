@@ -0,0 +1,274 @@
+use serde::Serialize;
+use tower_lsp::lsp_types::{CompletionItemKind, DocumentSymbol, SymbolKind};
+use tree_sitter::Node;
+
+use crate::analysis::definitions::{collect_definition_symbols, completion_kind_for_node};
+use crate::utils::ts::{first_descendant_by_kind, node_to_range, node_trimmed_text};
+
+const ACCESS_MODIFIERS: &[&str] = &[
+    "PUBLIC",
+    "PROTECTED",
+    "PRIVATE",
+    "PACKAGE-PRIVATE",
+    "PACKAGE-PROTECTED",
+];
+
+/// Builds the document outline; classes and interfaces nest their members.
+pub fn collect_document_symbols(node: Node<'_>, src: &[u8], out: &mut Vec<DocumentSymbol>) {
+    for i in 0..node.child_count() {
+        let Some(ch) = node.child(i as u32) else {
+            continue;
+        };
+        if matches!(
+            ch.kind(),
+            "parameter"
+                | "parameter_definition"
+                | "function_forward_definition"
+                | "procedure_forward_definition"
+        ) {
+            continue;
+        }
+        let Some((kind, fallback_detail)) = completion_kind_for_node(ch.kind()) else {
+            collect_document_symbols(ch, src, out);
+            continue;
+        };
+        let Some(name) = ch
+            .child_by_field_name("name")
+            .or_else(|| first_descendant_by_kind(ch, "identifier"))
+        else {
+            continue;
+        };
+        let Some(label) = node_trimmed_text(name, src) else {
+            continue;
+        };
+
+        let children =
+            matches!(ch.kind(), "class_definition" | "interface_definition").then(|| {
+                let mut members = Vec::new();
+                collect_document_symbols(ch, src, &mut members);
+                members
+            });
+        let detail = member_detail(ch, name, src).unwrap_or_else(|| fallback_detail.to_string());
+        #[allow(deprecated)]
+        out.push(DocumentSymbol {
+            name: label,
+            detail: Some(detail),
+            kind: symbol_kind(kind),
+            tags: None,
+            deprecated: None,
+            range: node_to_range(ch),
+            selection_range: node_to_range(name),
+            children,
+        });
+    }
+}
+
+fn symbol_kind(kind: CompletionItemKind) -> SymbolKind {
+    match kind {
+        CompletionItemKind::CLASS => SymbolKind::CLASS,
+        CompletionItemKind::INTERFACE => SymbolKind::INTERFACE,
+        CompletionItemKind::METHOD => SymbolKind::METHOD,
+        CompletionItemKind::CONSTRUCTOR => SymbolKind::CONSTRUCTOR,
+        CompletionItemKind::FUNCTION => SymbolKind::FUNCTION,
+        CompletionItemKind::PROPERTY => SymbolKind::PROPERTY,
+        CompletionItemKind::EVENT => SymbolKind::EVENT,
+        CompletionItemKind::STRUCT => SymbolKind::STRUCT,
+        _ => SymbolKind::VARIABLE,
+    }
+}
+
+/// `PUBLIC CHARACTER (GET, PRIVATE SET)`: access modifier, declared type and property accessors.
+fn member_detail(node: Node<'_>, name: Node<'_>, src: &[u8]) -> Option<String> {
+    let header = src
+        .get(node.start_byte()..name.start_byte())
+        .and_then(|h| std::str::from_utf8(h).ok())
+        .unwrap_or_default();
+    let mut parts = header
+        .split_ascii_whitespace()
+        .filter(|token| is_access_modifier(token))
+        .map(str::to_ascii_uppercase)
+        .take(1)
+        .collect::<Vec<_>>();
+    if let Some(ty) = node
+        .child_by_field_name("type")
+        .and_then(|n| node_trimmed_text(n, src))
+    {
+        parts.push(ty);
+    }
+    if node.kind() == "property_definition" {
+        let accessors = property_accessors(node, name, src);
+        if !accessors.is_empty() {
+            parts.push(format!("({})", accessors.join(", ")));
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join(" "))
+}
+
+fn property_accessors(node: Node<'_>, name: Node<'_>, src: &[u8]) -> Vec<String> {
+    let Some(tail) = src
+        .get(name.end_byte()..node.end_byte())
+        .and_then(|t| std::str::from_utf8(t).ok())
+    else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    let mut previous: Option<&str> = None;
+    for token in tail.split_ascii_whitespace() {
+        let word = token.trim_end_matches(|c: char| !c.is_ascii_alphabetic());
+        if (word.eq_ignore_ascii_case("GET") || word.eq_ignore_ascii_case("SET"))
+            && !out
+                .iter()
+                .any(|a: &String| a.ends_with(&word.to_ascii_uppercase()))
+        {
+            let accessor = match previous.filter(|p| is_access_modifier(p)) {
+                Some(modifier) => format!(
+                    "{} {}",
+                    modifier.to_ascii_uppercase(),
+                    word.to_ascii_uppercase()
+                ),
+                None => word.to_ascii_uppercase(),
+            };
+            out.push(accessor);
+        }
+        previous = Some(word);
+    }
+    out
+}
+
+fn is_access_modifier(token: &str) -> bool {
+    ACCESS_MODIFIERS
+        .iter()
+        .any(|m| m.eq_ignore_ascii_case(token))
+}
+
+/// Entry of the flat `abl/documentSymbols` listing.
+#[derive(Debug, Serialize)]
+pub struct FlatSymbol {
+    pub name: String,
+    /// Definition node kind without `_definition`: `variable`, `procedure`, `temp_table`, ...
+    pub kind: String,
+    /// Zero-based line of the symbol name.
+    pub line: u32,
+    #[serde(rename = "type")]
+    pub ty: Option<String>,
+}
+
+/// Every definition in the document, in source order, without outline nesting.
+pub fn collect_flat_symbols(root: Node<'_>, src: &[u8]) -> Vec<FlatSymbol> {
+    let mut symbols = Vec::new();
+    collect_definition_symbols(root, src, &mut symbols);
+    symbols
+        .into_iter()
+        .filter_map(|symbol| {
+            let name_node = root.descendant_for_byte_range(symbol.start_byte, symbol.start_byte)?;
+            let mut def = name_node;
+            while completion_kind_for_node(def.kind()).is_none() {
+                def = def.parent()?;
+            }
+            Some(FlatSymbol {
+                name: symbol.label,
+                kind: def.kind().trim_end_matches("_definition").to_string(),
+                line: name_node.start_position().row as u32,
+                ty: def
+                    .child_by_field_name("type")
+                    .and_then(|n| node_trimmed_text(n, src))
+                    .filter(|ty| !ty.is_empty()),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_document_symbols, collect_flat_symbols};
+    use crate::analysis::parse_abl;
+    use tower_lsp::lsp_types::SymbolKind;
+
+    #[test]
+    fn nests_class_members_under_the_class() {
+        let src = r#"
+CLASS app.Customer INHERITS app.Base:
+  DEFINE PUBLIC PROPERTY Name AS CHARACTER NO-UNDO
+    GET.
+    PRIVATE SET.
+  DEFINE PRIVATE VARIABLE cache AS CHARACTER NO-UNDO.
+  CONSTRUCTOR PUBLIC Customer():
+  END CONSTRUCTOR.
+  METHOD PUBLIC LOGICAL Save(INPUT pForce AS LOGICAL):
+    DEFINE VARIABLE ok AS LOGICAL NO-UNDO.
+    RETURN ok.
+  END METHOD.
+END CLASS.
+"#;
+        let tree = parse_abl(src);
+        let mut symbols = Vec::new();
+        collect_document_symbols(tree.root_node(), src.as_bytes(), &mut symbols);
+
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].kind, SymbolKind::CLASS);
+        let members = symbols[0].children.as_deref().expect("members");
+        let outline = members
+            .iter()
+            .map(|s| {
+                (
+                    s.name.as_str(),
+                    s.kind,
+                    s.detail.as_deref().unwrap_or_default(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outline,
+            vec![
+                (
+                    "Name",
+                    SymbolKind::PROPERTY,
+                    "PUBLIC CHARACTER (GET, PRIVATE SET)"
+                ),
+                ("cache", SymbolKind::VARIABLE, "PRIVATE CHARACTER"),
+                ("Customer", SymbolKind::CONSTRUCTOR, "PUBLIC"),
+                ("Save", SymbolKind::METHOD, "PUBLIC LOGICAL"),
+            ]
+        );
+        assert!(members.iter().all(|m| m.children.is_none()));
+    }
+
+    #[test]
+    fn lists_procedures_and_top_level_definitions_flat() {
+        let src = r#"
+DEFINE VARIABLE lv_total AS INTEGER NO-UNDO.
+PROCEDURE calc:
+  DEFINE INPUT PARAMETER p AS INTEGER NO-UNDO.
+END PROCEDURE.
+"#;
+        let tree = parse_abl(src);
+        let mut symbols = Vec::new();
+        collect_document_symbols(tree.root_node(), src.as_bytes(), &mut symbols);
+
+        let names = symbols.iter().map(|s| s.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["lv_total", "calc"]);
+        assert_eq!(symbols[1].kind, SymbolKind::FUNCTION);
+    }
+
+    #[test]
+    fn lists_definitions_flat_with_kind_line_and_type() {
+        let src = r#"
+DEFINE VARIABLE counter AS INTEGER NO-UNDO.
+PROCEDURE calc:
+  DEFINE INPUT PARAMETER pValue AS CHARACTER NO-UNDO.
+END PROCEDURE.
+"#;
+        let tree = parse_abl(src);
+        let symbols = collect_flat_symbols(tree.root_node(), src.as_bytes());
+        let json = serde_json::to_value(&symbols).expect("serialize");
+
+        assert_eq!(
+            json,
+            serde_json::json!([
+                { "name": "counter", "kind": "variable", "line": 1, "type": "INTEGER" },
+                { "name": "calc", "kind": "procedure", "line": 2, "type": null },
+                { "name": "pValue", "kind": "parameter", "line": 3, "type": "CHARACTER" },
+            ])
+        );
+    }
+}
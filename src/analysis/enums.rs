@@ -0,0 +1,210 @@
+use std::collections::HashSet;
+
+use tower_lsp::lsp_types::Url;
+use tree_sitter::Node;
+
+use crate::analysis::classes::{class_name_candidates, class_name_to_relative_path};
+use crate::analysis::definitions::collect_preprocessor_define_sites;
+use crate::analysis::includes::{collect_include_sites_from_tree, resolve_include_site_path};
+use crate::backend::Backend;
+use crate::utils::ts::collect_nodes_by_kind;
+
+/// An OO `ENUM` type and the members declared by its `DEFINE ENUM` statements.
+pub struct EnumDefinition {
+    pub name: String,
+    pub start_byte: usize,
+    pub members: Vec<EnumMember>,
+}
+
+pub struct EnumMember {
+    pub name: String,
+    pub start_byte: usize,
+}
+
+/// Collects `ENUM <name> [FLAGS]: DEFINE ENUM ... END ENUM.` blocks from the syntax tree.
+pub fn collect_enum_definitions(root: Node<'_>, src: &[u8]) -> Vec<EnumDefinition> {
+    let mut nodes = Vec::new();
+    collect_nodes_by_kind(root, "enum_definition", &mut nodes);
+    nodes
+        .into_iter()
+        .filter_map(|node| {
+            let name = node.child_by_field_name("name")?;
+            // Only the `name` of each member; `= value` and `= Read,Write` live in other fields.
+            let mut member_nodes = Vec::new();
+            collect_nodes_by_kind(node, "enum_member", &mut member_nodes);
+            let members = member_nodes
+                .into_iter()
+                .filter_map(|member| {
+                    let member_name = member.child_by_field_name("name")?;
+                    Some(EnumMember {
+                        name: member_name.utf8_text(src).ok()?.trim().to_string(),
+                        start_byte: member_name.start_byte(),
+                    })
+                })
+                .collect();
+            Some(EnumDefinition {
+                name: name.utf8_text(src).ok()?.trim().to_string(),
+                start_byte: name.start_byte(),
+                members,
+            })
+        })
+        .collect()
+}
+
+/// Whether `definition_name` (possibly package-qualified) is named by `reference`.
+pub fn enum_name_matches(definition_name: &str, reference: &str) -> bool {
+    definition_name.eq_ignore_ascii_case(reference)
+        || (!reference.contains('.')
+            && definition_name
+                .rsplit('.')
+                .next()
+                .is_some_and(|short| short.eq_ignore_ascii_case(reference)))
+}
+
+/// Finds an `ENUM` by name in this file, its includes, or as a `.cls` on PROPATH.
+pub async fn resolve_enum_definition(
+    backend: &Backend,
+    uri: &Url,
+    text: &str,
+    root: Node<'_>,
+    name: &str,
+) -> Option<EnumDefinition> {
+    let find = |root: Node<'_>, src: &str, name: &str| {
+        collect_enum_definitions(root, src.as_bytes())
+            .into_iter()
+            .find(|definition| enum_name_matches(&definition.name, name))
+    };
+    if let Some(found) = find(root, text, name) {
+        return Some(found);
+    }
+
+    let current_path = uri.to_file_path().ok()?;
+    let mut define_sites = Vec::new();
+    collect_preprocessor_define_sites(root, text.as_bytes(), &mut define_sites);
+    for include in collect_include_sites_from_tree(root, text.as_bytes()) {
+        let include_path_value = resolve_include_site_path(&include, &define_sites);
+        if let Some(path) = backend
+            .resolve_include_path_for(&current_path, &include_path_value)
+            .await
+            && let Some((include_text, include_tree)) =
+                backend.get_cached_include_parse(&path).await
+            && let Some(found) = find(include_tree.root_node(), &include_text, name)
+        {
+            return Some(found);
+        }
+    }
+
+    let imports = backend.using_imports_for(uri, text);
+    for candidate in class_name_candidates(name, &imports) {
+        if let Some(path) = backend
+            .resolve_include_path_for(&current_path, &class_name_to_relative_path(&candidate))
+            .await
+            && let Some((class_text, class_tree)) = backend.get_cached_include_parse(&path).await
+            && let Some(found) = find(class_tree.root_node(), &class_text, &candidate)
+        {
+            return Some(found);
+        }
+    }
+    None
+}
+
+/// Upper-cased `Type` qualifiers of `Type:Member` accesses that resolve to an `ENUM`.
+pub async fn collect_enum_access_types(
+    backend: &Backend,
+    uri: &Url,
+    text: &str,
+    root: Node<'_>,
+) -> HashSet<String> {
+    let mut identifiers = Vec::new();
+    collect_nodes_by_kind(root, "identifier", &mut identifiers);
+    let qualifiers = identifiers
+        .into_iter()
+        .filter_map(|ident| colon_access_qualifier(ident, text.as_bytes()))
+        .map(|qualifier| qualifier.to_ascii_uppercase())
+        .collect::<HashSet<_>>();
+
+    let mut out = HashSet::new();
+    for qualifier in qualifiers {
+        if resolve_enum_definition(backend, uri, text, root, &qualifier)
+            .await
+            .is_some()
+        {
+            out.insert(qualifier);
+        }
+    }
+    out
+}
+
+/// The `Type` of a `Type:Member` access that `ident` is either side of.
+pub fn colon_access_qualifier<'a>(ident: Node<'_>, src: &'a [u8]) -> Option<&'a str> {
+    let is_name_byte = |b: u8| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'.');
+    let (start, end) = (ident.start_byte(), ident.end_byte());
+    let qualifier = if src.get(end) == Some(&b':')
+        && src
+            .get(end + 1)
+            .is_some_and(|b| b.is_ascii_alphabetic() || *b == b'_')
+    {
+        start..end
+    } else if start > 0 && src.get(start - 1) == Some(&b':') {
+        let colon = start - 1;
+        let qualifier_start = src[..colon]
+            .iter()
+            .rposition(|b| !is_name_byte(*b))
+            .map_or(0, |i| i + 1);
+        qualifier_start..colon
+    } else {
+        return None;
+    };
+    std::str::from_utf8(&src[qualifier])
+        .ok()
+        .filter(|q| !q.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_enum_definitions, enum_name_matches};
+    use crate::analysis::parse_abl;
+
+    #[test]
+    fn collects_enum_members() {
+        let src = r#"
+ENUM Acme.Permission FLAGS: /* access. rights */
+  DEFINE ENUM None = 0
+              Read
+              Write
+              ReadWrite = Read,Write
+              Admin. /* everything */
+END ENUM.
+"#;
+        let tree = parse_abl(src);
+        let enums = collect_enum_definitions(tree.root_node(), src.as_bytes());
+        assert_eq!(enums.len(), 1);
+        assert_eq!(enums[0].name, "Acme.Permission");
+        assert_eq!(enums[0].start_byte, src.find("Acme.Permission").unwrap());
+        let members = enums[0]
+            .members
+            .iter()
+            .map(|m| m.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(members, vec!["None", "Read", "Write", "ReadWrite", "Admin"]);
+        assert_eq!(enums[0].members[1].start_byte, src.find("Read\n").unwrap());
+    }
+
+    #[test]
+    fn ignores_sources_without_enums() {
+        for src in [
+            "DEFINE VARIABLE x AS INTEGER NO-UNDO.\n",
+            "MESSAGE \"ENUM Foo:\".\n",
+        ] {
+            let tree = parse_abl(src);
+            assert!(collect_enum_definitions(tree.root_node(), src.as_bytes()).is_empty());
+        }
+    }
+
+    #[test]
+    fn matches_short_and_qualified_names() {
+        assert!(enum_name_matches("Acme.Permission", "permission"));
+        assert!(enum_name_matches("Acme.Permission", "Acme.Permission"));
+        assert!(!enum_name_matches("Acme.Permission", "Other.Permission"));
+    }
+}
@@ -1,5 +1,8 @@
 use tree_sitter::{Node, Parser};
 
+use crate::utils::source::parse_source;
+use crate::utils::ts::is_keyword_leaf;
+
 #[derive(Debug, Clone, Copy)]
 pub struct IndentOptions {
     pub indent_size: usize,
@@ -21,6 +24,7 @@ pub fn autoindent_text(text: &str, options: IndentOptions) -> String {
 
     if let Some(tree) = parse_abl_tree(text) {
         collect_line_indents(tree.root_node(), text, &mut line_indents);
+        align_block_closers(tree.root_node(), text, &mut line_indents);
     }
 
     for (idx, raw_line) in text.split_inclusive('\n').enumerate() {
@@ -40,11 +44,245 @@ pub fn autoindent_text(text: &str, options: IndentOptions) -> String {
     out
 }
 
+/// Trims trailing blanks (outside multi-line strings) and/or ensures a final line ending.
+pub fn normalize_line_endings_whitespace(
+    text: &str,
+    trim_trailing_whitespace: bool,
+    insert_final_newline: bool,
+) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    if trim_trailing_whitespace {
+        let mut string_spans = Vec::new();
+        if let Some(tree) = parse_abl_tree(text) {
+            collect_multiline_string_spans(tree.root_node(), &mut string_spans);
+        }
+        let mut line_start = 0usize;
+        for raw_line in text.split_inclusive('\n') {
+            let (line, newline) = split_line_ending(raw_line);
+            let line_end = line_start + line.len();
+            let inside_string = string_spans
+                .iter()
+                .any(|(start, end)| *start < line_end && line_end < *end);
+            if inside_string {
+                out.push_str(line);
+            } else {
+                out.push_str(line.trim_end_matches([' ', '\t']));
+            }
+            out.push_str(newline);
+            line_start += raw_line.len();
+        }
+    } else {
+        out.push_str(text);
+    }
+
+    if insert_final_newline && !out.is_empty() && !out.ends_with('\n') {
+        out.push_str(if text.contains("\r\n") { "\r\n" } else { "\n" });
+    }
+    out
+}
+
+fn collect_multiline_string_spans(node: Node<'_>, out: &mut Vec<(usize, usize)>) {
+    if node.kind() == "string_literal" {
+        if node.start_position().row != node.end_position().row {
+            out.push((node.start_byte(), node.end_byte()));
+        }
+        return;
+    }
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_multiline_string_spans(ch, out);
+        }
+    }
+}
+
+/// Rewrites keyword tokens to upper or lower case; identifiers, strings and comments are left as-is.
+pub fn apply_keyword_case(text: &str, to_upper: bool) -> String {
+    let Some(tree) = parse_abl_tree(text) else {
+        return text.to_string();
+    };
+    let mut keywords = Vec::new();
+    collect_keyword_spans(tree.root_node(), text.as_bytes(), &mut keywords);
+
+    let mut out = text.to_string();
+    for (start, end) in keywords {
+        let cased = if to_upper {
+            text[start..end].to_ascii_uppercase()
+        } else {
+            text[start..end].to_ascii_lowercase()
+        };
+        out.replace_range(start..end, &cased);
+    }
+    out
+}
+
+fn collect_keyword_spans(node: Node<'_>, src: &[u8], out: &mut Vec<(usize, usize)>) {
+    if is_keyword_leaf(node, src) {
+        out.push((node.start_byte(), node.end_byte()));
+        return;
+    }
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_keyword_spans(ch, src, out);
+        }
+    }
+}
+
+/// Runs whose aligned `AS` column would exceed this are left untouched.
+pub const ALIGN_DEFINE_AS_MAX_COLUMN: usize = 60;
+
+/// Pads consecutive single-line `DEFINE VARIABLE` statements so their `AS` keywords share a column.
+pub fn align_define_as(text: &str, max_column: usize) -> String {
+    let Some(tree) = parse_abl_tree(text) else {
+        return text.to_string();
+    };
+    let mut edits = Vec::new();
+    collect_define_as_alignment(tree.root_node(), text, max_column, &mut edits);
+    edits.sort_by_key(|(start, _, _)| std::cmp::Reverse(*start));
+
+    let mut out = text.to_string();
+    for (start, end, padding) in edits {
+        out.replace_range(start..end, &" ".repeat(padding));
+    }
+    out
+}
+
+fn collect_define_as_alignment(
+    node: Node<'_>,
+    text: &str,
+    max_column: usize,
+    edits: &mut Vec<(usize, usize, usize)>,
+) {
+    // (gap start byte, gap end byte, column of the gap start)
+    let mut run: Vec<(usize, usize, usize)> = Vec::new();
+    let mut last_row = None;
+    for i in 0..node.child_count() {
+        let Some(ch) = node.child(i as u32) else {
+            continue;
+        };
+        let row = ch.start_position().row;
+        let gap = (ch.kind() == "variable_definition" && row == ch.end_position().row)
+            .then(|| define_as_gap(ch, text))
+            .flatten();
+        match gap {
+            Some(gap) if last_row.is_some_and(|last| last + 1 == row) => run.push(gap),
+            Some(gap) => {
+                push_define_as_run(&run, max_column, edits);
+                run = vec![gap];
+            }
+            None => {
+                push_define_as_run(&run, max_column, edits);
+                run.clear();
+                collect_define_as_alignment(ch, text, max_column, edits);
+            }
+        }
+        last_row = gap.map(|_| row);
+    }
+    push_define_as_run(&run, max_column, edits);
+}
+
+fn push_define_as_run(
+    run: &[(usize, usize, usize)],
+    max_column: usize,
+    edits: &mut Vec<(usize, usize, usize)>,
+) {
+    if run.len() < 2 {
+        return;
+    }
+    let Some(target) = run.iter().map(|(_, _, col)| col + 1).max() else {
+        return;
+    };
+    if target > max_column {
+        return;
+    }
+    edits.extend(
+        run.iter()
+            .map(|(start, end, col)| (*start, *end, target - col)),
+    );
+}
+
+/// Whitespace between the token before `AS` and `AS` itself, with the column where it starts.
+fn define_as_gap(definition: Node<'_>, text: &str) -> Option<(usize, usize, usize)> {
+    let type_start = definition
+        .child_by_field_name("type")
+        .map(|ty| ty.start_byte())?;
+    let mut as_kw = None;
+    for i in 0..definition.child_count() {
+        if let Some(ch) = definition.child(i as u32)
+            && ch.end_byte() <= type_start
+            && text
+                .get(ch.start_byte()..ch.end_byte())
+                .is_some_and(|t| t.eq_ignore_ascii_case("AS"))
+        {
+            as_kw = Some(ch);
+        }
+    }
+    let before = as_kw?.prev_sibling()?;
+    let (start, end) = (before.end_byte(), as_kw?.start_byte());
+    if !text
+        .get(start..end)?
+        .bytes()
+        .all(|b| b == b' ' || b == b'\t')
+    {
+        return None;
+    }
+    let line_start = text[..start].rfind('\n').map_or(0, |idx| idx + 1);
+    Some((start, end, text[line_start..start].chars().count()))
+}
+
+/// Puts each assignment of a multi-target `ASSIGN` on its own line; indentation is left to
+/// [`autoindent_text`].
+pub fn expand_assign(text: &str) -> String {
+    let Some(tree) = parse_abl_tree(text) else {
+        return text.to_string();
+    };
+    let mut gaps = Vec::new();
+    collect_assign_gaps(tree.root_node(), text, &mut gaps);
+    gaps.sort_by_key(|(start, _)| std::cmp::Reverse(*start));
+
+    let mut out = text.to_string();
+    for (start, end) in gaps {
+        out.replace_range(start..end, "\n");
+    }
+    out
+}
+
+/// Same-line blanks before every assignment of `ASSIGN` statements with more than one target.
+fn collect_assign_gaps(node: Node<'_>, text: &str, out: &mut Vec<(usize, usize)>) {
+    if node.kind() == "assign_statement" {
+        let assignments = (0..node.named_child_count())
+            .filter_map(|i| node.named_child(i as u32))
+            .filter(|ch| ch.kind() == "assignment")
+            .collect::<Vec<_>>();
+        if assignments.len() > 1 {
+            for assignment in assignments {
+                let Some(prev) = assignment.prev_sibling() else {
+                    continue;
+                };
+                let (start, end) = (prev.end_byte(), assignment.start_byte());
+                if start < end
+                    && text
+                        .get(start..end)
+                        .is_some_and(|gap| gap.bytes().all(|b| b == b' ' || b == b'\t'))
+                {
+                    out.push((start, end));
+                }
+            }
+        }
+        return;
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_assign_gaps(ch, text, out);
+        }
+    }
+}
+
 pub fn preserves_ast_shape(original: &str, formatted: &str, parser: &mut Parser) -> bool {
-    let Some(before) = parser.parse(original, None) else {
+    let Some(before) = parse_source(parser, original, None) else {
         return false;
     };
-    let Some(after) = parser.parse(formatted, None) else {
+    let Some(after) = parse_source(parser, formatted, None) else {
         return false;
     };
 
@@ -57,6 +295,39 @@ pub fn preserves_ast_shape(original: &str, formatted: &str, parser: &mut Parser)
     before_root.to_sexp() == after_root.to_sexp()
 }
 
+/// Like [`preserves_ast_shape`], but allows siblings to be reordered within their parent.
+pub fn preserves_ast_shape_ignoring_order(
+    original: &str,
+    rewritten: &str,
+    parser: &mut Parser,
+) -> bool {
+    let Some(before) = parse_source(parser, original, None) else {
+        return false;
+    };
+    let Some(after) = parse_source(parser, rewritten, None) else {
+        return false;
+    };
+
+    let before_root = before.root_node();
+    let after_root = after.root_node();
+    if before_root.has_error() || after_root.has_error() {
+        return false;
+    }
+
+    unordered_shape(before_root) == unordered_shape(after_root)
+}
+
+fn unordered_shape(node: Node<'_>) -> String {
+    let mut children = Vec::new();
+    for i in 0..node.named_child_count() {
+        if let Some(ch) = node.named_child(i as u32) {
+            children.push(unordered_shape(ch));
+        }
+    }
+    children.sort_unstable();
+    format!("({} {})", node.kind(), children.join(" "))
+}
+
 fn split_line_ending(raw_line: &str) -> (&str, &str) {
     if let Some(stripped) = raw_line.strip_suffix("\r\n") {
         return (stripped, "\r\n");
@@ -79,7 +350,7 @@ fn parse_abl_tree(text: &str) -> Option<tree_sitter::Tree> {
     parser
         .set_language(&tree_sitter_abl::LANGUAGE.into())
         .ok()?;
-    parser.parse(text, None)
+    parse_source(&mut parser, text, None)
 }
 
 fn collect_line_indents(node: Node<'_>, text: &str, line_indents: &mut [usize]) {
@@ -96,6 +367,84 @@ fn collect_line_indents(node: Node<'_>, text: &str, line_indents: &mut [usize])
     }
 }
 
+/// Puts the `END.` / `END PROCEDURE.` line of a block at the indent of the statement that opened
+/// it, whatever the body ranges above added to that line.
+fn align_block_closers(node: Node<'_>, text: &str, line_indents: &mut [usize]) {
+    let opener_row = node.start_position().row;
+    let mut cursor = node.walk();
+    let bodies = node
+        .children(&mut cursor)
+        .filter(|child| child.is_named() && child.kind() == "body")
+        .collect::<Vec<_>>();
+    let mut closer_rows = bodies
+        .iter()
+        .filter_map(|body| block_closer_row(*body, node, text))
+        .collect::<Vec<_>>();
+    if node.kind() == "function_definition"
+        && let Some(row) = last_statement_row(node)
+            .and_then(|row| first_end_row(text, row.saturating_add(1), node_end_row(node)))
+    {
+        closer_rows.push(row);
+    }
+    if let Some(opener_indent) = line_indents.get(opener_row).copied() {
+        for row in closer_rows {
+            if row > opener_row
+                && let Some(indent) = line_indents.get_mut(row)
+            {
+                *indent = opener_indent;
+            }
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.is_named() {
+            align_block_closers(child, text, line_indents);
+        }
+    }
+}
+
+/// Row of the `END` that closes `body`: the first `END` line after its last statement.
+fn block_closer_row(body: Node<'_>, owner: Node<'_>, text: &str) -> Option<usize> {
+    let last = body
+        .named_child_count()
+        .checked_sub(1)
+        .and_then(|i| body.named_child(i as u32));
+    let from = match last {
+        Some(last) => node_end_row(last).saturating_add(1),
+        None => body.start_position().row,
+    };
+    first_end_row(text, from, node_end_row(owner))
+}
+
+fn first_end_row(text: &str, from: usize, to: usize) -> Option<usize> {
+    text.lines()
+        .enumerate()
+        .skip(from)
+        .take(to.saturating_add(1).saturating_sub(from))
+        .find(|(_, line)| is_end_keyword_line(line))
+        .map(|(row, _)| row)
+}
+
+fn is_end_keyword_line(line: &str) -> bool {
+    let trimmed = line.trim_start_matches([' ', '\t']);
+    trimmed
+        .get(..3)
+        .is_some_and(|word| word.eq_ignore_ascii_case("END"))
+        && !trimmed
+            .as_bytes()
+            .get(3)
+            .is_some_and(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-'))
+}
+
+fn node_end_row(node: Node<'_>) -> usize {
+    let mut end_row = node.end_position().row;
+    if node.end_position().column == 0 && end_row > 0 {
+        end_row -= 1;
+    }
+    end_row
+}
+
 fn apply_case_indent(node: Node<'_>, line_indents: &mut [usize]) {
     match node.kind() {
         "case_when_phrase" | "case_otherwise_phrase" => {
@@ -338,7 +687,10 @@ fn push_indent(out: &mut String, level: usize, options: IndentOptions) {
 
 #[cfg(test)]
 mod tests {
-    use super::{IndentOptions, autoindent_text, collect_line_indents, preserves_ast_shape};
+    use super::{
+        IndentOptions, align_define_as, apply_keyword_case, autoindent_text, collect_line_indents,
+        expand_assign, normalize_line_endings_whitespace, preserves_ast_shape,
+    };
     use tree_sitter::Parser;
 
     fn parse_abl(src: &str) -> tree_sitter::Tree {
@@ -381,6 +733,25 @@ mod tests {
         assert_eq!(got, expected);
     }
 
+    #[test]
+    fn aligns_end_with_the_block_opener() {
+        let input = "DO:\nDO:\nMESSAGE \"x\".\nEND. /* inner */\nEND.\n";
+        let got = autoindent_text(input, IndentOptions::default());
+        let expected = "DO:\n  DO:\n    MESSAGE \"x\".\n  END. /* inner */\nEND.\n";
+        assert_eq!(got, expected);
+
+        let input = "IF a THEN DO:\nx = 1.\nEND.\nELSE DO:\nx = 2.\nEND.\n";
+        let got = autoindent_text(input, IndentOptions::default());
+        let expected = "IF a THEN DO:\n  x = 1.\nEND.\nELSE DO:\n  x = 2.\nEND.\n";
+        assert_eq!(got, expected);
+
+        let input =
+            "PROCEDURE p:\nIF a THEN DO:\nx = 1.\nEND.\nELSE DO:\nx = 2.\n  END.\nEND PROCEDURE.\n";
+        let got = autoindent_text(input, IndentOptions::default());
+        let expected = "PROCEDURE p:\n  IF a THEN DO:\n    x = 1.\n  END.\n  ELSE DO:\n    x = 2.\n  END.\nEND PROCEDURE.\n";
+        assert_eq!(got, expected);
+    }
+
     #[test]
     fn indents_assign_continuation_lines() {
         let input = "ASSIGN\nx = 1\ny = 2.\n";
@@ -389,6 +760,14 @@ mod tests {
         assert_eq!(got, expected);
     }
 
+    #[test]
+    fn expands_multi_target_assign_one_per_line() {
+        let input = "ASSIGN a = 1 b = 2 c = 3.\nASSIGN x = 1.\n";
+        let got = autoindent_text(&expand_assign(input), IndentOptions::default());
+        let expected = "ASSIGN\n  a = 1\n  b = 2\n  c = 3.\nASSIGN x = 1.\n";
+        assert_eq!(got, expected);
+    }
+
     #[test]
     fn indents_multiline_put_stream_unformatted_items() {
         let input = "PUT STREAM estr UNFORMATTED\n\"lvc_execname\"   \" \" lvc_execname   skip\n\"lvc_key1\"       \" \" lvc_key1       skip\n\"kod_kk\"         \" \" kod_kk\n.\n";
@@ -476,4 +855,52 @@ mod tests {
         let expected = "DEFINE TEMP-TABLE tt NO-UNDO\n  FIELD id AS CHARACTER\n  INDEX idx IS PRIMARY UNIQUE id.";
         assert_eq!(got, expected);
     }
+
+    #[test]
+    fn changes_keyword_case_but_not_identifiers_or_strings() {
+        let input = "define variable For_x as character no-undo.\nFor_x = \"for each\".\n";
+        let upper = apply_keyword_case(input, true);
+        assert_eq!(
+            upper,
+            "DEFINE VARIABLE For_x AS CHARACTER NO-UNDO.\nFor_x = \"for each\".\n"
+        );
+        assert_eq!(apply_keyword_case(&upper, false), input);
+    }
+
+    #[test]
+    fn trims_trailing_whitespace_and_keeps_crlf() {
+        let input = "x = 1.  \r\ny = \"a  \r\nb\".\t\r\nz = 3.";
+        let got = normalize_line_endings_whitespace(input, true, true);
+        assert_eq!(got, "x = 1.\r\ny = \"a  \r\nb\".\r\nz = 3.\r\n");
+
+        assert_eq!(
+            normalize_line_endings_whitespace("x = 1.  ", false, true),
+            "x = 1.  \n"
+        );
+        assert_eq!(normalize_line_endings_whitespace("", true, true), "");
+    }
+
+    #[test]
+    fn aligns_as_in_adjacent_variable_definitions() {
+        let input = "DEFINE VARIABLE i AS INTEGER NO-UNDO.\nDEFINE VARIABLE lvName AS CHARACTER NO-UNDO.\n\nDEFINE VARIABLE x AS LOGICAL NO-UNDO.\n";
+        let got = align_define_as(input, 60);
+        assert_eq!(
+            got,
+            "DEFINE VARIABLE i      AS INTEGER NO-UNDO.\nDEFINE VARIABLE lvName AS CHARACTER NO-UNDO.\n\nDEFINE VARIABLE x AS LOGICAL NO-UNDO.\n"
+        );
+        assert_eq!(align_define_as(&got, 60), got);
+
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_abl::LANGUAGE.into())
+            .expect("set abl language");
+        assert!(preserves_ast_shape(input, &got, &mut parser));
+    }
+
+    #[test]
+    fn skips_alignment_past_max_column() {
+        let input =
+            "DEFINE VARIABLE i AS INTEGER NO-UNDO.\nDEFINE VARIABLE lvName AS CHARACTER NO-UNDO.\n";
+        assert_eq!(align_define_as(input, 20), input);
+    }
 }
@@ -16,6 +16,7 @@ pub struct FunctionSignature {
     pub name: String,
     pub params: Vec<String>,
     pub return_type: Option<String>,
+    pub is_procedure: bool,
     is_forward: bool,
 }
 
@@ -49,6 +50,7 @@ fn collect_function_signatures(
             name: name.to_string(),
             params,
             return_type,
+            is_procedure: false,
             is_forward: node.kind() == "function_forward_definition",
         });
     }
@@ -60,6 +62,46 @@ fn collect_function_signatures(
     }
 }
 
+pub fn find_procedure_signature(root: Node, src: &[u8], symbol: &str) -> Option<FunctionSignature> {
+    let mut matches = Vec::new();
+    collect_procedure_signatures(root, src, symbol, &mut matches);
+    matches.into_iter().max_by_key(signature_score)
+}
+
+fn collect_procedure_signatures(
+    node: Node,
+    src: &[u8],
+    symbol: &str,
+    out: &mut Vec<FunctionSignature>,
+) {
+    if matches!(
+        node.kind(),
+        "procedure_definition" | "procedure_forward_definition"
+    ) && let Some(name_node) = node.child_by_field_name("name")
+        && let Ok(name) = name_node.utf8_text(src)
+        && name
+            .trim()
+            .trim_matches('"')
+            .eq_ignore_ascii_case(symbol.trim_matches('"'))
+    {
+        let mut params = Vec::new();
+        collect_params_recursive(node, src, &mut params, true);
+        out.push(FunctionSignature {
+            name: name.trim().trim_matches('"').to_string(),
+            params,
+            return_type: None,
+            is_procedure: true,
+            is_forward: node.kind() == "procedure_forward_definition",
+        });
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_procedure_signatures(ch, src, symbol, out);
+        }
+    }
+}
+
 fn collect_function_params(function_node: Node, src: &[u8]) -> Vec<String> {
     if let Some(parameters_node) = direct_child_by_kind(function_node, "parameters") {
         let mut header_params = Vec::new();
@@ -81,6 +123,7 @@ fn collect_params_recursive(node: Node, src: &[u8], out: &mut Vec<String>, is_ro
             "function_definition"
                 | "function_forward_definition"
                 | "procedure_definition"
+                | "procedure_forward_definition"
                 | "method_definition"
                 | "constructor_definition"
                 | "destructor_definition"
@@ -181,6 +224,28 @@ pub fn normalize_function_name(name: &str) -> String {
         .to_ascii_uppercase()
 }
 
+/// Arity-map key for internal procedures, kept apart from function names.
+pub fn procedure_arity_key(name: &str) -> String {
+    format!("RUN {}", name.trim().trim_matches('"').to_ascii_uppercase())
+}
+
+/// Arity-map key for an internal procedure of a persistent program (`RUN name IN hProc`).
+pub fn persistent_procedure_key(program: &str, name: &str) -> String {
+    format!(
+        "RUN {}::{}",
+        program
+            .trim()
+            .trim_matches(['"', '\''])
+            .replace('\\', "/")
+            .to_ascii_uppercase(),
+        name.trim().trim_matches('"').to_ascii_uppercase()
+    )
+}
+
+pub fn is_persistent_procedure_key(key: &str) -> bool {
+    key.starts_with("RUN ") && key.contains("::")
+}
+
 pub async fn find_function_signature_from_includes(
     backend: &Backend,
     uri: &Url,
@@ -188,6 +253,47 @@ pub async fn find_function_signature_from_includes(
     root: Node<'_>,
     offset: usize,
     symbol: &str,
+) -> Option<FunctionSignature> {
+    find_signature_from_includes(
+        backend,
+        uri,
+        text,
+        root,
+        offset,
+        symbol,
+        find_function_signature,
+    )
+    .await
+}
+
+pub async fn find_procedure_signature_from_includes(
+    backend: &Backend,
+    uri: &Url,
+    text: &str,
+    root: Node<'_>,
+    offset: usize,
+    symbol: &str,
+) -> Option<FunctionSignature> {
+    find_signature_from_includes(
+        backend,
+        uri,
+        text,
+        root,
+        offset,
+        symbol,
+        find_procedure_signature,
+    )
+    .await
+}
+
+async fn find_signature_from_includes(
+    backend: &Backend,
+    uri: &Url,
+    text: &str,
+    root: Node<'_>,
+    offset: usize,
+    symbol: &str,
+    lookup: fn(Node, &[u8], &str) -> Option<FunctionSignature>,
 ) -> Option<FunctionSignature> {
     let scope = containing_scope(root, offset)?;
     let current_path = uri.to_file_path().ok()?;
@@ -216,9 +322,7 @@ pub async fn find_function_signature_from_includes(
         else {
             continue;
         };
-        if let Some(sig) =
-            find_function_signature(include_tree.root_node(), include_text.as_bytes(), symbol)
-        {
+        if let Some(sig) = lookup(include_tree.root_node(), include_text.as_bytes(), symbol) {
             return Some(sig);
         }
         let mut include_global_defines = Vec::new();
@@ -238,7 +342,7 @@ pub async fn find_function_signature_from_includes(
 
 #[cfg(test)]
 mod tests {
-    use super::find_function_signature;
+    use super::{find_function_signature, find_procedure_signature};
     use crate::analysis::parse_abl;
 
     #[test]
@@ -263,4 +367,23 @@ END FUNCTION.
         assert!(sig.params[1].contains("OUTPUT"));
         assert!(sig.params[1].contains("p2"));
     }
+
+    #[test]
+    fn finds_signature_of_forward_only_procedure() {
+        let src = r#"
+PROCEDURE calcTotal IN SUPER:
+  DEFINE INPUT PARAMETER pOrder AS INTEGER NO-UNDO.
+  DEFINE OUTPUT PARAMETER pTotal AS DECIMAL NO-UNDO.
+END PROCEDURE.
+"#;
+
+        let tree = parse_abl(src);
+
+        let sig = find_procedure_signature(tree.root_node(), src.as_bytes(), "calcTotal")
+            .expect("procedure signature");
+        assert!(sig.is_procedure);
+        assert_eq!(sig.params.len(), 2);
+        assert!(sig.params[0].contains("INPUT pOrder"));
+        assert!(sig.params[1].contains("OUTPUT pTotal"));
+    }
 }
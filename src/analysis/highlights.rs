@@ -0,0 +1,102 @@
+use tower_lsp::lsp_types::{DocumentHighlight, DocumentHighlightKind};
+use tree_sitter::Node;
+
+use crate::analysis::diagnostics::symbols::assignment_target_identifier;
+use crate::analysis::scopes::ByteScope;
+use crate::utils::ts::node_to_range;
+
+/// Collects highlights for every `identifier` named `symbol` inside `scope`.
+pub fn collect_document_highlights(
+    node: Node<'_>,
+    src: &[u8],
+    symbol: &str,
+    scope: ByteScope,
+    out: &mut Vec<DocumentHighlight>,
+) {
+    if node.end_byte() < scope.start || node.start_byte() > scope.end {
+        return;
+    }
+
+    if node.kind() == "identifier"
+        && node.start_byte() >= scope.start
+        && node.end_byte() <= scope.end
+        && let Ok(text) = node.utf8_text(src)
+        && text.trim().eq_ignore_ascii_case(symbol)
+    {
+        let kind = if is_write_site(node) {
+            DocumentHighlightKind::WRITE
+        } else {
+            DocumentHighlightKind::READ
+        };
+        out.push(DocumentHighlight {
+            range: node_to_range(node),
+            kind: Some(kind),
+        });
+        return;
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_document_highlights(ch, src, symbol, scope, out);
+        }
+    }
+}
+
+/// Assignment targets and definition names; every other identifier occurrence is a read.
+pub fn is_write_site(identifier: Node<'_>) -> bool {
+    let Some(parent) = identifier.parent() else {
+        return false;
+    };
+    if assignment_target_identifier(parent).is_some_and(|left| left.id() == identifier.id()) {
+        return true;
+    }
+    parent.kind().ends_with("_definition")
+        && parent
+            .child_by_field_name("name")
+            .is_some_and(|name| name.id() == identifier.id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::collect_document_highlights;
+    use crate::analysis::parse_abl;
+    use crate::analysis::scopes::containing_scope;
+    use tower_lsp::lsp_types::DocumentHighlightKind;
+
+    #[test]
+    fn marks_definition_and_assignment_as_write() {
+        let src = r#"
+PROCEDURE calc:
+  DEFINE VARIABLE lv_total AS INTEGER NO-UNDO.
+  lv_total = 1.
+  MESSAGE lv_total.
+END PROCEDURE.
+DEFINE VARIABLE lv_total AS INTEGER NO-UNDO.
+"#;
+        let tree = parse_abl(src);
+        let offset = src.find("lv_total = 1").expect("assignment");
+        let scope = containing_scope(tree.root_node(), offset).expect("scope");
+
+        let mut highlights = Vec::new();
+        collect_document_highlights(
+            tree.root_node(),
+            src.as_bytes(),
+            "LV_TOTAL",
+            scope,
+            &mut highlights,
+        );
+
+        let kinds = highlights
+            .iter()
+            .map(|h| h.kind.expect("kind"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                DocumentHighlightKind::WRITE,
+                DocumentHighlightKind::WRITE,
+                DocumentHighlightKind::READ
+            ]
+        );
+    }
+}
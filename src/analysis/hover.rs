@@ -3,6 +3,7 @@ use tower_lsp::lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind};
 use tree_sitter::Node;
 
 use crate::analysis::buffers::collect_buffer_mappings;
+use crate::analysis::df::DfIndexFlags;
 use crate::analysis::functions::FunctionSignature;
 use crate::analysis::local_tables::collect_local_table_definitions;
 use crate::backend::DbFieldInfo;
@@ -50,7 +51,11 @@ pub fn markdown_hover(markdown: String) -> Hover {
 }
 
 pub fn function_signature_hover(sig: &FunctionSignature) -> Hover {
-    let header = match sig.return_type {
+    markdown_hover(function_signature_markdown(sig))
+}
+
+pub fn function_signature_markdown(sig: &FunctionSignature) -> String {
+    match sig.return_type {
         Some(ref ret) => format!(
             "`FUNCTION {}({}) RETURNS {}`",
             sig.name,
@@ -58,28 +63,143 @@ pub fn function_signature_hover(sig: &FunctionSignature) -> Hover {
             ret
         ),
         None => format!("`FUNCTION {}({})`", sig.name, sig.params.join(", ")),
+    }
+}
+
+pub fn builtin_function_hover(signature: &str, description: &str) -> Hover {
+    markdown_hover(format!(
+        "**Builtin function** `{signature}`\n\n{description}"
+    ))
+}
+
+pub fn preprocessor_define_hover(
+    name: &str,
+    value: Option<&str>,
+    expanded: Option<&str>,
+    is_global: bool,
+    source_path: Option<&str>,
+) -> Hover {
+    let directive = if is_global {
+        "&GLOBAL-DEFINE"
+    } else {
+        "&SCOPED-DEFINE"
+    };
+    let mut markdown = match value.map(str::trim).filter(|v| !v.is_empty()) {
+        Some(value) => abl_code_block(&format!("{} {} {}", directive, name, value)),
+        None => abl_code_block(&format!("{} {}", directive, name)),
     };
-    markdown_hover(header)
+    if let Some(expanded) = expanded
+        .map(str::trim)
+        .filter(|e| Some(*e) != value.map(str::trim))
+    {
+        markdown.push_str(&format!("\n\nExpands to:\n\n{}", abl_code_block(expanded)));
+    }
+    if let Some(path) = source_path {
+        markdown.push_str(&format!("\n\nDefined in: `{}`", path));
+    }
+    markdown_hover(markdown)
+}
+
+/// Fences `code` as an `abl` block; the fence outgrows any backtick run in the code, so
+/// multi-line values and values with backticks render verbatim.
+fn abl_code_block(code: &str) -> String {
+    let longest_run = code
+        .split(|c: char| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{fence}abl\n{code}\n{fence}")
+}
+
+const DB_TABLE_HOVER_FIELD_LIMIT: usize = 12;
+
+/// Renders a DB table hover with a field preview (name + type) and its index names.
+pub fn db_table_hover(name: &str, fields: &[DbFieldInfo], indexes: &[String]) -> Hover {
+    let mut sections = vec![format!("**DB Table** `{}`", name)];
+
+    if !fields.is_empty() {
+        let mut preview = fields
+            .iter()
+            .take(DB_TABLE_HOVER_FIELD_LIMIT)
+            .map(|f| match &f.field_type {
+                Some(ty) => format!("- `{}` {}", f.name, ty),
+                None => format!("- `{}`", f.name),
+            })
+            .collect::<Vec<_>>();
+        if fields.len() > DB_TABLE_HOVER_FIELD_LIMIT {
+            preview.push(format!(
+                "- … and {} more",
+                fields.len() - DB_TABLE_HOVER_FIELD_LIMIT
+            ));
+        }
+        sections.push(format!("Fields:\n{}", preview.join("\n")));
+    }
+
+    if !indexes.is_empty() {
+        let names = indexes
+            .iter()
+            .map(|idx| format!("`{}`", idx))
+            .collect::<Vec<_>>()
+            .join(", ");
+        sections.push(format!("Indexes: {}", names));
+    }
+
+    markdown_hover(sections.join("\n\n"))
+}
+
+pub fn db_index_hover(index: &str, table: &str, flags: DfIndexFlags, fields: &[String]) -> Hover {
+    let mut sections = vec![format!("**DB Index** `{}`", index)];
+    sections.push(format!("Table: `{}`", table));
+
+    let mut options = Vec::new();
+    if flags.primary {
+        options.push("PRIMARY");
+    }
+    if flags.unique {
+        options.push("UNIQUE");
+    }
+    if !options.is_empty() {
+        sections.push(options.join(" "));
+    }
+
+    if !fields.is_empty() {
+        let list = fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| format!("{}. `{}`", i + 1, f))
+            .collect::<Vec<_>>();
+        sections.push(format!("Fields:\n{}", list.join("\n")));
+    }
+
+    markdown_hover(sections.join("\n\n"))
 }
 
+/// Every table defining `field`, resolved through the `db_tables_by_field` index instead of a
+/// scan over all tables.
 pub fn find_db_field_matches(
     db_fields_by_table: &DashMap<String, Vec<DbFieldInfo>>,
-    field_upper: &str,
+    db_tables_by_field: &DashMap<String, Vec<String>>,
+    field: &str,
 ) -> Vec<DbFieldMatch> {
-    let mut out = Vec::new();
-    for entry in db_fields_by_table.iter() {
-        let table = entry.key();
-        let fields = entry.value();
-        for field in fields {
-            if field.name.eq_ignore_ascii_case(field_upper) {
-                out.push(DbFieldMatch {
-                    table: table.clone(),
-                    field: field.clone(),
-                });
-            }
-        }
-    }
-    out
+    let field_upper = field.to_ascii_uppercase();
+    let Some(tables) = db_tables_by_field.get(&field_upper) else {
+        return Vec::new();
+    };
+    tables
+        .iter()
+        .filter_map(|table| {
+            let fields = db_fields_by_table.get(table)?;
+            let field = fields
+                .iter()
+                .find(|f| f.name.eq_ignore_ascii_case(&field_upper))?
+                .clone();
+            Some(DbFieldMatch {
+                table: table.clone(),
+                field,
+            })
+        })
+        .collect()
 }
 
 pub fn find_local_table_field_hover(root: Node<'_>, text: &str, offset: usize) -> Option<Hover> {
@@ -224,9 +344,11 @@ fn is_ident_char(b: u8) -> bool {
 #[cfg(test)]
 mod tests {
     use super::{
-        extract_qualified_field_at_offset, find_db_field_matches,
-        find_local_table_field_hover_by_symbol, symbol_at_offset,
+        db_index_hover, db_table_hover, extract_qualified_field_at_offset, find_db_field_matches,
+        find_local_table_field_hover_by_symbol, preprocessor_define_hover, symbol_at_offset,
     };
+    use crate::analysis::completion::lookup_case_insensitive_fields;
+    use crate::analysis::df::DfIndexFlags;
     use crate::analysis::parse_abl;
     use crate::backend::DbFieldInfo;
     use dashmap::DashMap;
@@ -284,31 +406,156 @@ DEFINE TEMP-TABLE ZM_CENY NO-UNDO
 
     #[test]
     fn finds_db_field_matches_across_tables() {
+        let field = |name: &str| DbFieldInfo {
+            name: name.to_string(),
+            field_type: Some("CHARACTER".to_string()),
+            format: None,
+            label: None,
+            description: None,
+        };
         let map = DashMap::<String, Vec<DbFieldInfo>>::new();
-        map.insert(
-            "Customer".to_string(),
-            vec![DbFieldInfo {
-                name: "Name".to_string(),
-                field_type: Some("CHARACTER".to_string()),
-                format: None,
-                label: None,
-                description: None,
-            }],
+        map.insert("CUSTOMER".to_string(), vec![field("Name")]);
+        map.insert("ORDER".to_string(), vec![field("name")]);
+        let index = DashMap::<String, Vec<String>>::new();
+        index.insert(
+            "NAME".to_string(),
+            vec!["CUSTOMER".to_string(), "ORDER".to_string()],
         );
-        map.insert(
-            "Order".to_string(),
-            vec![DbFieldInfo {
-                name: "name".to_string(),
-                field_type: Some("CHARACTER".to_string()),
+
+        let matches = find_db_field_matches(&map, &index, "name");
+        assert_eq!(matches.len(), 2);
+        assert!(matches.iter().any(|m| m.table == "CUSTOMER"));
+        assert!(matches.iter().any(|m| m.table == "ORDER"));
+        assert!(find_db_field_matches(&map, &index, "CITY").is_empty());
+    }
+
+    #[test]
+    #[ignore = "timing benchmark; run with `cargo test -- --ignored`"]
+    fn schema_lookups_stay_fast_on_large_schemas() {
+        // 2,000 tables x 50 fields: a scan per lookup would take seconds for these 20,000
+        // lookups, while keyed access finishes in milliseconds even in debug builds.
+        let map = DashMap::<String, Vec<DbFieldInfo>>::new();
+        let index = DashMap::<String, Vec<String>>::new();
+        for t in 0..2_000 {
+            let table = format!("TABLE{t}");
+            let fields = (0..50)
+                .map(|f| DbFieldInfo {
+                    name: format!("Field{t}_{f}"),
+                    field_type: None,
+                    format: None,
+                    label: None,
+                    description: None,
+                })
+                .collect::<Vec<_>>();
+            for f in &fields {
+                index
+                    .entry(f.name.to_ascii_uppercase())
+                    .or_default()
+                    .push(table.clone());
+            }
+            map.insert(table, fields);
+        }
+
+        let started = std::time::Instant::now();
+        for i in 0..20_000 {
+            let (t, f) = (i % 2_000, i % 50);
+            let matches = find_db_field_matches(&map, &index, &format!("field{t}_{f}"));
+            assert_eq!(matches.len(), 1);
+            assert_eq!(matches[0].table, format!("TABLE{t}"));
+            assert!(lookup_case_insensitive_fields(&map, &format!("table{t}")).is_some());
+        }
+        assert!(
+            started.elapsed() < std::time::Duration::from_secs(2),
+            "schema lookups took {:?}",
+            started.elapsed()
+        );
+    }
+
+    #[test]
+    fn renders_preprocessor_define_hover_with_source() {
+        let hover =
+            preprocessor_define_hover("MY_DEFINE", Some("42"), None, true, Some("inc/defs.i"));
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("expected markdown hover");
+        };
+        assert_eq!(
+            markup.value,
+            "```abl\n&GLOBAL-DEFINE MY_DEFINE 42\n```\n\nDefined in: `inc/defs.i`"
+        );
+
+        let hover = preprocessor_define_hover("LOCAL", None, None, false, None);
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("expected markdown hover");
+        };
+        assert_eq!(markup.value, "```abl\n&SCOPED-DEFINE LOCAL\n```");
+
+        let hover = preprocessor_define_hover("A", Some("{&B}"), Some("\"x\""), false, None);
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("expected markdown hover");
+        };
+        assert_eq!(
+            markup.value,
+            "```abl\n&SCOPED-DEFINE A {&B}\n```\n\nExpands to:\n\n```abl\n\"x\"\n```"
+        );
+    }
+
+    #[test]
+    fn fences_multi_line_and_backtick_define_values() {
+        let hover = preprocessor_define_hover(
+            "QUERY",
+            Some("FOR EACH customer ~\n  WHERE name = \"```x\""),
+            None,
+            true,
+            Some("inc/defs.i"),
+        );
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("expected markdown hover");
+        };
+        assert_eq!(
+            markup.value,
+            "````abl\n&GLOBAL-DEFINE QUERY FOR EACH customer ~\n  WHERE name = \"```x\"\n````\n\nDefined in: `inc/defs.i`"
+        );
+    }
+
+    #[test]
+    fn renders_db_table_hover_with_truncated_fields() {
+        let fields = (1..=14)
+            .map(|i| DbFieldInfo {
+                name: format!("f{}", i),
+                field_type: Some("integer".to_string()),
                 format: None,
                 label: None,
                 description: None,
-            }],
-        );
+            })
+            .collect::<Vec<_>>();
+        let hover = db_table_hover("Customer", &fields, &["CustNum".to_string()]);
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("expected markdown hover");
+        };
+        assert!(markup.value.starts_with("**DB Table** `Customer`"));
+        assert!(markup.value.contains("- `f12` integer"));
+        assert!(!markup.value.contains("`f13`"));
+        assert!(markup.value.contains("… and 2 more"));
+        assert!(markup.value.contains("Indexes: `CustNum`"));
+    }
 
-        let matches = find_db_field_matches(&map, "NAME");
-        assert_eq!(matches.len(), 2);
-        assert!(matches.iter().any(|m| m.table == "Customer"));
-        assert!(matches.iter().any(|m| m.table == "Order"));
+    #[test]
+    fn db_index_hover_lists_flags_and_ordered_fields() {
+        let hover = db_index_hover(
+            "cust_idx",
+            "customer",
+            DfIndexFlags {
+                unique: true,
+                primary: true,
+            },
+            &["cust_num".to_string(), "name".to_string()],
+        );
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("expected markdown hover");
+        };
+        assert_eq!(
+            markup.value,
+            "**DB Index** `cust_idx`\n\nTable: `customer`\n\nPRIMARY UNIQUE\n\nFields:\n1. `cust_num`\n2. `name`"
+        );
     }
 }
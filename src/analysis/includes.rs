@@ -1,8 +1,20 @@
-use crate::analysis::definitions::PreprocessorDefineSite;
-use crate::utils::ts::collect_nodes_by_kind;
-use std::path::Path;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use log::info;
+use tower_lsp::lsp_types::Url;
 use tree_sitter::Node;
 
+use crate::analysis::definitions::{
+    PreprocessorDefineSite, collect_global_preprocessor_define_sites,
+    collect_preprocessor_define_sites,
+};
+use crate::analysis::diagnostics::semantic::is_latest_version;
+use crate::backend::Backend;
+use crate::utils::ts::collect_nodes_by_kind;
+
 pub struct IncludeSite {
     pub path: String,
     pub prefix_macro: Option<String>,
@@ -181,13 +193,199 @@ fn join_prefix_with_include_path(prefix: &str, include_path: &str) -> String {
     }
 }
 
+/// Parses the includes reachable from `root`; `None` once `version` of `uri` is superseded.
+pub async fn collect_resolved_include_parses(
+    backend: &Backend,
+    uri: &Url,
+    version: i32,
+    current_path: &Path,
+    text: &str,
+    root: Node<'_>,
+) -> Option<Vec<(PathBuf, Arc<String>, tree_sitter::Tree)>> {
+    collect_resolved_include_parses_until(backend, uri, version, current_path, text, root, None)
+        .await
+        .map(|(parses, _)| parses)
+}
+
+/// [`collect_resolved_include_parses`] that stops at `deadline`, for latency-bound requests such
+/// as completion; the flag is set when the walk was cut short.
+pub async fn collect_resolved_include_parses_until(
+    backend: &Backend,
+    uri: &Url,
+    version: i32,
+    current_path: &Path,
+    text: &str,
+    root: Node<'_>,
+    deadline: Option<Instant>,
+) -> Option<(Vec<(PathBuf, Arc<String>, tree_sitter::Tree)>, bool)> {
+    let mut state = IncludeCollectState {
+        seen: HashSet::new(),
+        out: Vec::new(),
+        pending: Vec::new(),
+        max_depth: backend.config.lock().await.includes.max_depth,
+        depth_limit_reported: false,
+        uri: uri.clone(),
+        version,
+        stale: false,
+        deadline,
+        timed_out: false,
+    };
+
+    collect_resolved_includes_for_file(backend, current_path, text, root, &[], 0, &mut state).await;
+
+    while !state.stale
+        && !state.timed_out
+        && let Some(next) = state.pending.pop()
+    {
+        collect_resolved_includes_for_file(
+            backend,
+            &next.path,
+            next.text.as_str(),
+            next.tree.root_node(),
+            &next.inherited_globals,
+            next.depth,
+            &mut state,
+        )
+        .await;
+    }
+
+    (!state.stale).then_some((state.out, state.timed_out))
+}
+
+async fn collect_resolved_includes_for_file(
+    backend: &Backend,
+    file_path: &Path,
+    file_text: &str,
+    file_root: Node<'_>,
+    inherited_globals: &[PreprocessorDefineSite],
+    depth: usize,
+    state: &mut IncludeCollectState,
+) {
+    let include_sites = collect_include_sites_from_tree(file_root, file_text.as_bytes());
+    if include_sites.is_empty() {
+        return;
+    }
+    if depth >= state.max_depth {
+        if !state.depth_limit_reported {
+            state.depth_limit_reported = true;
+            info!(
+                "include depth limit ({}) reached at {}; skipping nested includes",
+                state.max_depth,
+                file_path.display()
+            );
+        }
+        return;
+    }
+
+    // `&IF` filtering only knows this file's text, so it must run before inherited globals join.
+    let mut available_define_sites = Vec::new();
+    collect_preprocessor_define_sites(file_root, file_text.as_bytes(), &mut available_define_sites);
+    available_define_sites.splice(0..0, inherited_globals.iter().cloned());
+
+    for include in include_sites {
+        // Each include costs disk I/O and a parse; stop as soon as a newer edit arrives.
+        if !is_latest_version(backend, &state.uri, state.version) {
+            state.stale = true;
+            return;
+        }
+        if state
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            state.timed_out = true;
+            return;
+        }
+        let include_path_value = resolve_include_site_path(&include, &available_define_sites);
+        let Some(resolved_path) = backend
+            .resolve_include_path_for(file_path, &include_path_value)
+            .await
+        else {
+            continue;
+        };
+
+        if let Some((include_text, include_tree)) =
+            backend.get_cached_include_parse(&resolved_path).await
+        {
+            let mut include_global_defines = Vec::new();
+            collect_global_preprocessor_define_sites(
+                include_tree.root_node(),
+                include_text.as_bytes(),
+                &mut include_global_defines,
+            );
+            for mut define in include_global_defines {
+                define.start_byte = include.start_offset;
+                available_define_sites.push(define);
+            }
+
+            if state.seen.insert(resolved_path.clone()) {
+                state.out.push((
+                    resolved_path.clone(),
+                    include_text.clone(),
+                    include_tree.clone(),
+                ));
+                state.pending.push(PendingInclude {
+                    path: resolved_path,
+                    text: include_text,
+                    tree: include_tree,
+                    inherited_globals: globals_visible_at_offset(
+                        &available_define_sites,
+                        include.start_offset,
+                    ),
+                    depth: depth + 1,
+                });
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct PendingInclude {
+    path: PathBuf,
+    text: Arc<String>,
+    tree: tree_sitter::Tree,
+    inherited_globals: Vec<PreprocessorDefineSite>,
+    depth: usize,
+}
+
+struct IncludeCollectState {
+    seen: HashSet<PathBuf>,
+    out: Vec<(PathBuf, Arc<String>, tree_sitter::Tree)>,
+    pending: Vec<PendingInclude>,
+    max_depth: usize,
+    depth_limit_reported: bool,
+    uri: Url,
+    version: i32,
+    stale: bool,
+    deadline: Option<Instant>,
+    timed_out: bool,
+}
+
+fn globals_visible_at_offset(
+    available_define_sites: &[PreprocessorDefineSite],
+    offset: usize,
+) -> Vec<PreprocessorDefineSite> {
+    available_define_sites
+        .iter()
+        .filter(|d| d.is_global && d.start_byte <= offset)
+        .cloned()
+        .map(|mut d| {
+            d.start_byte = 0;
+            d
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        collect_include_sites, collect_include_sites_from_tree, resolve_include_site_path,
+        collect_include_sites, collect_include_sites_from_tree, collect_resolved_include_parses,
+        collect_resolved_include_parses_until, globals_visible_at_offset,
+        resolve_include_site_path,
     };
     use crate::analysis::definitions::PreprocessorDefineSite;
     use crate::analysis::parse_abl;
+    use crate::backend::Backend;
+    use std::time::Instant;
     use tower_lsp::lsp_types::{Position, Range};
 
     #[test]
@@ -275,4 +473,121 @@ mod tests {
         let resolved = resolve_include_site_path(&site, &define_sites);
         assert_eq!(resolved, "zm_cim.i");
     }
+
+    fn define(label: &str, start_byte: usize, is_global: bool) -> PreprocessorDefineSite {
+        PreprocessorDefineSite {
+            label: label.to_string(),
+            value: Some("v".to_string()),
+            range: Range::new(Position::new(0, 0), Position::new(0, 1)),
+            start_byte,
+            is_global,
+        }
+    }
+
+    #[test]
+    fn keeps_only_global_defines_visible_at_offset_and_resets_start_byte() {
+        let defs = vec![
+            define("A", 5, true),
+            define("B", 10, false),
+            define("C", 20, true),
+        ];
+
+        let visible = globals_visible_at_offset(&defs, 12);
+        assert_eq!(visible.len(), 1);
+        assert_eq!(visible[0].label, "A");
+        assert_eq!(visible[0].start_byte, 0);
+    }
+
+    #[tokio::test]
+    async fn abandons_include_resolution_for_superseded_versions() {
+        let backend = Backend::for_tests();
+        let uri = tower_lsp::lsp_types::Url::parse("file:///tmp/doc3.p").expect("uri");
+        let text = "{missing.i}\nMESSAGE z.";
+        backend.set_document_text_version(&uri, 2, text.to_string(), true);
+        let tree = parse_abl(text);
+        let path = std::path::Path::new("/tmp/doc3.p");
+
+        let current =
+            collect_resolved_include_parses(&backend, &uri, 2, path, text, tree.root_node()).await;
+        assert!(current.is_some_and(|parses| parses.is_empty()));
+
+        let stale =
+            collect_resolved_include_parses(&backend, &uri, 1, path, text, tree.root_node()).await;
+        assert!(stale.is_none());
+    }
+
+    #[tokio::test]
+    async fn stops_include_resolution_at_the_deadline() {
+        let backend = Backend::for_tests();
+        let uri = tower_lsp::lsp_types::Url::parse("file:///tmp/doc4.p").expect("uri");
+        let text = "{missing.i}\nMESSAGE z.";
+        backend.set_document_text_version(&uri, 1, text.to_string(), true);
+        let tree = parse_abl(text);
+        let path = std::path::Path::new("/tmp/doc4.p");
+
+        let (parses, timed_out) = collect_resolved_include_parses_until(
+            &backend,
+            &uri,
+            1,
+            path,
+            text,
+            tree.root_node(),
+            Some(Instant::now()),
+        )
+        .await
+        .expect("current version");
+        assert!(parses.is_empty());
+        assert!(timed_out);
+    }
+
+    #[tokio::test]
+    async fn stops_descending_past_the_include_depth_limit() {
+        let base = std::env::temp_dir().join(format!(
+            "abl_ls_include_depth_test_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("epoch")
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(&base).expect("create temp dir");
+        for (name, next) in [
+            ("a.i", "{b.i}"),
+            ("b.i", "{c.i}"),
+            ("c.i", "{d.i}"),
+            ("d.i", ""),
+        ] {
+            std::fs::write(base.join(name), format!("{next}\n")).expect("write include");
+        }
+
+        let backend = Backend::for_tests();
+        *backend.workspace_roots.lock().await = vec![base.clone()];
+        let path = base.join("main.p");
+        let uri = tower_lsp::lsp_types::Url::from_file_path(&path).expect("uri");
+        let text = "{a.i}\n";
+        backend.set_document_text_version(&uri, 1, text.to_string(), true);
+        let tree = parse_abl(text);
+        let names = |parses: Vec<(std::path::PathBuf, _, _)>| {
+            parses
+                .into_iter()
+                .filter_map(|(path, _, _)| {
+                    path.file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let all = collect_resolved_include_parses(&backend, &uri, 1, &path, text, tree.root_node())
+            .await
+            .expect("current version");
+        assert_eq!(names(all), vec!["a.i", "b.i", "c.i", "d.i"]);
+
+        backend.config.lock().await.includes.max_depth = 2;
+        let limited =
+            collect_resolved_include_parses(&backend, &uri, 1, &path, text, tree.root_node())
+                .await
+                .expect("current version");
+        assert_eq!(names(limited), vec!["a.i", "b.i"]);
+
+        let _ = std::fs::remove_dir_all(base);
+    }
 }
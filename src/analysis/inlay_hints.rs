@@ -0,0 +1,131 @@
+use tree_sitter::Node;
+
+use crate::analysis::signature::{run_target_name, split_argument_offsets};
+
+pub struct CallArgumentSite {
+    pub name: String,
+    pub is_run: bool,
+    pub argument_offsets: Vec<usize>,
+}
+
+/// Collects `function_call`/`RUN` sites intersecting `[start, end)` with their argument offsets.
+pub fn collect_call_argument_sites(
+    node: Node<'_>,
+    src: &[u8],
+    start: usize,
+    end: usize,
+    out: &mut Vec<CallArgumentSite>,
+) {
+    if node.end_byte() < start || node.start_byte() > end {
+        return;
+    }
+
+    match node.kind() {
+        "function_call" => {
+            if let Some(function) = node.child_by_field_name("function")
+                && let Ok(name) = function.utf8_text(src)
+                && let Some(arguments) = node
+                    .children(&mut node.walk())
+                    .find(|n| n.kind() == "arguments")
+            {
+                push_site(
+                    name.trim(),
+                    false,
+                    src,
+                    arguments.start_byte(),
+                    arguments.end_byte(),
+                    out,
+                );
+            }
+        }
+        "run_statement" => {
+            if let Some(name) = run_target_name(node, src)
+                && let Some(rel_open) = src
+                    .get(node.start_byte()..node.end_byte())
+                    .and_then(|text| text.iter().position(|b| *b == b'('))
+            {
+                push_site(
+                    &name,
+                    true,
+                    src,
+                    node.start_byte() + rel_open,
+                    node.end_byte(),
+                    out,
+                );
+            }
+        }
+        _ => {}
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_call_argument_sites(ch, src, start, end, out);
+        }
+    }
+}
+
+/// Renders `INPUT p_a: INTEGER` as the `INPUT p_a:` hint label.
+pub fn parameter_hint_label(param: &str) -> String {
+    let without_type = param.split(':').next().unwrap_or(param).trim();
+    format!("{without_type}:")
+}
+
+fn push_site(
+    name: &str,
+    is_run: bool,
+    src: &[u8],
+    open: usize,
+    close: usize,
+    out: &mut Vec<CallArgumentSite>,
+) {
+    if name.is_empty() {
+        return;
+    }
+    let argument_offsets = split_argument_offsets(src, open, close);
+    if argument_offsets.is_empty() {
+        return;
+    }
+    out.push(CallArgumentSite {
+        name: name.to_string(),
+        is_run,
+        argument_offsets,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_call_argument_sites, parameter_hint_label};
+    use crate::analysis::parse_abl;
+
+    #[test]
+    fn collects_function_call_argument_offsets() {
+        let src = r#"
+FUNCTION local_mul RETURNS INTEGER (INPUT p_a AS INTEGER, INPUT p_b AS INTEGER):
+  RETURN p_a * p_b.
+END FUNCTION.
+lv_counter = local_mul(lv_counter, 2).
+"#;
+        let tree = parse_abl(src);
+        let mut sites = Vec::new();
+        collect_call_argument_sites(tree.root_node(), src.as_bytes(), 0, src.len(), &mut sites);
+
+        let site = sites
+            .iter()
+            .find(|s| s.name.eq_ignore_ascii_case("local_mul"))
+            .expect("call site");
+        assert!(!site.is_run);
+        assert_eq!(
+            site.argument_offsets,
+            vec![
+                src.find("lv_counter, 2").expect("first arg"),
+                src.find("2).").expect("second arg")
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_parameter_hint_label_without_type() {
+        assert_eq!(parameter_hint_label("INPUT p_a: INTEGER"), "INPUT p_a:");
+        assert_eq!(parameter_hint_label("p_b: ANY"), "p_b:");
+    }
+}
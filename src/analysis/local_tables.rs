@@ -1,11 +1,14 @@
 use tree_sitter::Node;
 
 use crate::backend::DbFieldInfo;
+use crate::utils::ts::first_descendant_by_kind;
 
 pub struct LocalTableDefinition {
     pub name_upper: String,
     pub fields: Vec<DbFieldInfo>,
     pub like_table_upper: Option<String>,
+    /// Names declared by `INDEX` clauses, in source order.
+    pub indexes: Vec<String>,
 }
 
 pub fn collect_local_table_definitions(
@@ -51,13 +54,40 @@ fn parse_local_table_definition(node: Node<'_>, src: &[u8]) -> Option<LocalTable
     });
     fields.dedup_by(|a, b| a.name.eq_ignore_ascii_case(&b.name));
 
+    let mut indexes = Vec::new();
+    collect_local_table_indexes(node, src, &mut indexes);
+
     Some(LocalTableDefinition {
         name_upper: name,
         fields,
         like_table_upper: extract_like_table_upper(node, src),
+        indexes,
     })
 }
 
+/// Unions `LIKE` base-table fields with locally declared ones; local declarations win on name clashes.
+pub fn merge_like_table_fields(
+    local_fields: &[DbFieldInfo],
+    like_fields: &[DbFieldInfo],
+) -> Vec<DbFieldInfo> {
+    let mut fields = local_fields.to_vec();
+    for field in like_fields {
+        if !fields
+            .iter()
+            .any(|f| f.name.eq_ignore_ascii_case(&field.name))
+        {
+            fields.push(field.clone());
+        }
+    }
+    fields.sort_by(|a, b| {
+        a.name
+            .to_ascii_uppercase()
+            .cmp(&b.name.to_ascii_uppercase())
+            .then(a.name.cmp(&b.name))
+    });
+    fields
+}
+
 fn collect_local_table_fields(node: Node<'_>, src: &[u8], out: &mut Vec<DbFieldInfo>) {
     if matches!(node.kind(), "temp_table_field" | "field")
         && let Some(name_node) = node.child_by_field_name("name")
@@ -88,6 +118,26 @@ fn collect_local_table_fields(node: Node<'_>, src: &[u8], out: &mut Vec<DbFieldI
     }
 }
 
+fn collect_local_table_indexes(node: Node<'_>, src: &[u8], out: &mut Vec<String>) {
+    if node.kind() == "temp_table_index" {
+        if let Some(name_node) = node
+            .child_by_field_name("name")
+            .or_else(|| first_descendant_by_kind(node, "identifier"))
+            && let Ok(name) = name_node.utf8_text(src)
+            && !name.trim().is_empty()
+        {
+            out.push(name.trim().to_string());
+        }
+        return;
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_local_table_indexes(ch, src, out);
+        }
+    }
+}
+
 fn extract_like_table_upper(node: Node<'_>, src: &[u8]) -> Option<String> {
     for i in 0..node.child_count() {
         let Some(ch) = node.child(i as u32) else {
@@ -121,8 +171,9 @@ fn extract_like_table_upper(node: Node<'_>, src: &[u8]) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
-    use super::collect_local_table_definitions;
+    use super::{collect_local_table_definitions, merge_like_table_fields};
     use crate::analysis::parse_abl;
+    use crate::backend::DbFieldInfo;
 
     #[test]
     fn collects_temp_table_and_work_table_fields() {
@@ -166,6 +217,27 @@ DEFINE WORK-TABLE wtCust NO-UNDO
         );
     }
 
+    #[test]
+    fn collects_temp_table_indexes() {
+        let src = r#"
+DEFINE TEMP-TABLE ttOrder NO-UNDO
+  FIELD ordNo AS INTEGER
+  FIELD ordName AS CHARACTER
+  INDEX idxNo IS PRIMARY UNIQUE ordNo
+  INDEX idxName ordName.
+"#;
+
+        let tree = parse_abl(src);
+
+        let mut defs = Vec::new();
+        collect_local_table_definitions(tree.root_node(), src.as_bytes(), &mut defs);
+        let tt = defs
+            .iter()
+            .find(|d| d.name_upper == "TTORDER")
+            .expect("temp-table definition");
+        assert_eq!(tt.indexes, vec!["idxNo", "idxName"]);
+    }
+
     #[test]
     fn collects_like_table_reference() {
         let src = r#"
@@ -182,4 +254,22 @@ DEFINE TEMP-TABLE ttCustomer LIKE sports.Customer NO-UNDO.
             .expect("temp-table definition");
         assert_eq!(tt.like_table_upper.as_deref(), Some("CUSTOMER"));
     }
+
+    #[test]
+    fn merges_like_fields_with_local_fields() {
+        let field = |name: &str, ty: &str| DbFieldInfo {
+            name: name.to_string(),
+            field_type: Some(ty.to_string()),
+            format: None,
+            label: None,
+            description: None,
+        };
+        let local = vec![field("Extra", "LOGICAL"), field("custnum", "INT64")];
+        let like = vec![field("CustNum", "INTEGER"), field("Name", "CHARACTER")];
+
+        let merged = merge_like_table_fields(&local, &like);
+        let names = merged.iter().map(|f| f.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["custnum", "Extra", "Name"]);
+        assert_eq!(merged[0].field_type.as_deref(), Some("INT64"));
+    }
 }
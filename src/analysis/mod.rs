@@ -1,21 +1,34 @@
 pub mod buffers;
 pub mod builtins;
+pub mod call_hierarchy;
+pub mod classes;
+pub mod code_actions;
+pub mod code_lens;
 pub mod completion;
 pub mod completion_support;
+pub mod datasets;
 pub mod definition;
 pub mod definitions;
 pub mod df;
 pub mod diagnostics;
+pub mod document_symbols;
+pub mod enums;
 pub mod formatting;
 pub mod functions;
+pub mod highlights;
 pub mod hover;
 pub mod includes;
+pub mod inlay_hints;
 pub mod local_tables;
+pub mod preprocessor;
+pub mod rename;
 pub mod schema;
 pub mod schema_lookup;
 pub mod scopes;
+pub mod selection_range;
 pub mod semantic_tokens;
 pub mod signature;
+pub mod system_handles;
 pub mod types;
 
 #[cfg(test)]
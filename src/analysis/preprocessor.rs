@@ -0,0 +1,416 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use crate::utils::source::mask_comments;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchState {
+    Taken,
+    Skipped,
+    /// The condition could not be evaluated, so the branch may or may not be compiled.
+    Unknown,
+}
+
+/// One `&IF`/`&ELSEIF`/`&ELSE` body; branches of the same `&IF ... &ENDIF` share `chain`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConditionalBranch {
+    pub chain: usize,
+    pub range: Range<usize>,
+    pub state: BranchState,
+}
+
+struct Frame {
+    chain: usize,
+    parent_active: bool,
+    condition_start: Option<usize>,
+    body_start: usize,
+    state: BranchState,
+    decided: bool,
+    undecided: bool,
+}
+
+/// Scans preprocessor conditional chains; `DEFINED(name)` and literal conditions are evaluated
+/// against the `&GLOBAL-DEFINE`/`&SCOPED-DEFINE`/`&UNDEFINE` directives seen before them.
+/// Names this file never defines may come from an including file and stay undecided.
+pub fn collect_conditional_branches(text: &str) -> Vec<ConditionalBranch> {
+    if !text
+        .as_bytes()
+        .windows(3)
+        .any(|w| w.eq_ignore_ascii_case(b"&IF"))
+    {
+        return Vec::new();
+    }
+
+    let masked = mask_comments(text);
+    let directives = scan_directives(&masked);
+    let local_names = directives
+        .iter()
+        .filter(|d| is_define_directive(d.keyword))
+        .filter_map(|d| directive_argument(&masked, d.end))
+        .collect::<HashSet<_>>();
+
+    let mut defined = HashSet::<String>::new();
+    let mut maybe_defined = HashSet::<String>::new();
+    let mut stack = Vec::<Frame>::new();
+    let mut out = Vec::new();
+    let mut next_chain = 0;
+
+    for directive in directives {
+        let active = stack.iter().all(|f| f.state != BranchState::Skipped);
+        let certain = stack.iter().all(|f| f.state == BranchState::Taken);
+        match directive.keyword {
+            "IF" => {
+                stack.push(Frame {
+                    chain: next_chain,
+                    parent_active: active,
+                    condition_start: Some(directive.end),
+                    body_start: directive.end,
+                    state: BranchState::Skipped,
+                    decided: false,
+                    undecided: false,
+                });
+                next_chain += 1;
+            }
+            "ELSEIF" | "ELSE" => {
+                let Some(frame) = stack.last_mut() else {
+                    continue;
+                };
+                close_branch(frame, directive.start, &mut out);
+                if directive.keyword == "ELSEIF" {
+                    frame.condition_start = Some(directive.end);
+                } else {
+                    frame.condition_start = None;
+                    frame.body_start = directive.end;
+                    frame.state = branch_state(frame, Some(true));
+                }
+            }
+            "THEN" => {
+                let Some(frame) = stack.last_mut() else {
+                    continue;
+                };
+                let Some(condition_start) = frame.condition_start.take() else {
+                    continue;
+                };
+                let condition = masked
+                    .get(condition_start..directive.start)
+                    .unwrap_or_default();
+                let value = evaluate_condition(condition, &defined, &maybe_defined, &local_names);
+                frame.body_start = directive.end;
+                frame.state = branch_state(frame, value);
+            }
+            "ENDIF" => {
+                if let Some(mut frame) = stack.pop() {
+                    close_branch(&mut frame, directive.start, &mut out);
+                }
+            }
+            keyword if active => {
+                let Some(name) = directive_argument(&masked, directive.end) else {
+                    continue;
+                };
+                match (is_define_directive(keyword), certain) {
+                    (true, true) => {
+                        defined.insert(name);
+                    }
+                    (true, false) => {
+                        maybe_defined.insert(name);
+                    }
+                    (false, true) if keyword == "UNDEFINE" => {
+                        defined.remove(&name);
+                    }
+                    (false, false) if keyword == "UNDEFINE" && defined.remove(&name) => {
+                        maybe_defined.insert(name);
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    for mut frame in stack.into_iter().rev() {
+        close_branch(&mut frame, text.len(), &mut out);
+    }
+    out.sort_by_key(|branch| branch.range.start);
+    out
+}
+
+/// Drops items declared in skipped branches and, within an undecided chain, names an earlier
+/// branch of that chain already declared, so a conditional declaration is counted once.
+pub fn retain_reachable_definitions<T>(
+    text: &str,
+    items: &mut Vec<T>,
+    key: impl Fn(&T) -> (&str, usize),
+) {
+    let branches = collect_conditional_branches(text);
+    if branches.is_empty() {
+        return;
+    }
+
+    let mut order = (0..items.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&idx| key(&items[idx]).1);
+    let mut first_branch_by_name = HashMap::<(usize, String), usize>::new();
+    let mut keep = vec![true; items.len()];
+    for idx in order {
+        let (label, start_byte) = key(&items[idx]);
+        let containing = branches
+            .iter()
+            .enumerate()
+            .filter(|(_, branch)| branch.range.contains(&start_byte))
+            .collect::<Vec<_>>();
+        if containing
+            .iter()
+            .any(|(_, branch)| branch.state == BranchState::Skipped)
+        {
+            keep[idx] = false;
+            continue;
+        }
+        for (branch_idx, branch) in containing {
+            if branch.state != BranchState::Unknown {
+                continue;
+            }
+            let first = *first_branch_by_name
+                .entry((branch.chain, label.to_ascii_uppercase()))
+                .or_insert(branch_idx);
+            if first != branch_idx {
+                keep[idx] = false;
+            }
+        }
+    }
+
+    let mut keep = keep.into_iter();
+    items.retain(|_| keep.next().unwrap_or(true));
+}
+
+fn branch_state(frame: &mut Frame, value: Option<bool>) -> BranchState {
+    if !frame.parent_active || frame.decided {
+        return BranchState::Skipped;
+    }
+    match value {
+        Some(false) => BranchState::Skipped,
+        Some(true) => {
+            frame.decided = true;
+            if frame.undecided {
+                BranchState::Unknown
+            } else {
+                BranchState::Taken
+            }
+        }
+        None => {
+            frame.undecided = true;
+            BranchState::Unknown
+        }
+    }
+}
+
+fn close_branch(frame: &mut Frame, end: usize, out: &mut Vec<ConditionalBranch>) {
+    if frame.condition_start.is_some() {
+        return;
+    }
+    out.push(ConditionalBranch {
+        chain: frame.chain,
+        range: frame.body_start..end,
+        state: frame.state,
+    });
+}
+
+struct Directive {
+    keyword: &'static str,
+    start: usize,
+    end: usize,
+}
+
+const DIRECTIVES: &[&str] = &[
+    "IF",
+    "THEN",
+    "ELSEIF",
+    "ELSE",
+    "ENDIF",
+    "GLOBAL-DEFINE",
+    "SCOPED-DEFINE",
+    "UNDEFINE",
+];
+
+/// `&keyword` directives outside string literals, in source order.
+fn scan_directives(masked: &str) -> Vec<Directive> {
+    let bytes = masked.as_bytes();
+    let mut out = Vec::new();
+    let mut quote: Option<u8> = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if let Some(q) = quote {
+            if b == b'~' {
+                i += 1;
+            } else if b == q {
+                quote = None;
+            }
+            i += 1;
+            continue;
+        }
+        if matches!(b, b'"' | b'\'') {
+            quote = Some(b);
+        } else if b == b'&' {
+            let word_end = bytes[i + 1..]
+                .iter()
+                .position(|c| !(c.is_ascii_alphabetic() || *c == b'-'))
+                .map_or(bytes.len(), |len| i + 1 + len);
+            let word = &masked[i + 1..word_end];
+            if let Some(keyword) = DIRECTIVES.iter().find(|k| k.eq_ignore_ascii_case(word)) {
+                out.push(Directive {
+                    keyword,
+                    start: i,
+                    end: word_end,
+                });
+            }
+            i = word_end;
+            continue;
+        }
+        i += 1;
+    }
+    out
+}
+
+fn is_define_directive(keyword: &str) -> bool {
+    matches!(keyword, "GLOBAL-DEFINE" | "SCOPED-DEFINE")
+}
+
+/// Upper-cased name following a define/undefine directive.
+fn directive_argument(masked: &str, from: usize) -> Option<String> {
+    let rest = masked.get(from..)?.trim_start_matches([' ', '\t']);
+    let name = rest
+        .split(|c: char| c.is_ascii_whitespace())
+        .next()
+        .filter(|name| !name.is_empty())?;
+    Some(name.to_ascii_uppercase())
+}
+
+/// Evaluates `TRUE`/`FALSE`/`YES`/`NO` and `[NOT] DEFINED(name) [> 0 | <> 0 | = 0]`.
+fn evaluate_condition(
+    condition: &str,
+    defined: &HashSet<String>,
+    maybe_defined: &HashSet<String>,
+    local_names: &HashSet<String>,
+) -> Option<bool> {
+    let compact = condition
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_uppercase();
+    let mut expr = compact.as_str();
+    match expr {
+        "TRUE" | "YES" => return Some(true),
+        "FALSE" | "NO" => return Some(false),
+        _ => {}
+    }
+
+    let negate = match expr.strip_prefix("NOT") {
+        Some(rest) if rest.starts_with('(') || rest.starts_with("DEFINED(") => {
+            expr = rest;
+            true
+        }
+        _ => false,
+    };
+    while let Some(inner) = expr.strip_prefix('(').and_then(|e| e.strip_suffix(')')) {
+        expr = inner;
+    }
+
+    let rest = expr.strip_prefix("DEFINED(")?;
+    let close = rest.find(')')?;
+    let name = &rest[..close];
+    let nonzero = match &rest[close + 1..] {
+        "" | ">0" | "<>0" | "GT0" | "NE0" => true,
+        "=0" | "EQ0" => false,
+        _ => return None,
+    };
+    if !local_names.contains(name) || maybe_defined.contains(name) {
+        return None;
+    }
+    Some((defined.contains(name) == nonzero) != negate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BranchState, collect_conditional_branches, retain_reachable_definitions};
+
+    fn states(text: &str) -> Vec<(usize, BranchState)> {
+        collect_conditional_branches(text)
+            .into_iter()
+            .map(|branch| (branch.chain, branch.state))
+            .collect()
+    }
+
+    #[test]
+    fn evaluates_defined_conditions_against_earlier_defines() {
+        let src = r#"
+&GLOBAL-DEFINE USE-LONG YES
+&IF DEFINED(USE-LONG) &THEN
+DEFINE VARIABLE lvValue AS INT64 NO-UNDO.
+&ELSE
+DEFINE VARIABLE lvValue AS INTEGER NO-UNDO.
+&ENDIF
+&IF DEFINED(USE-LONG) = 0 &THEN
+&ELSEIF TRUE &THEN
+&ENDIF
+"#;
+        assert_eq!(
+            states(src),
+            vec![
+                (0, BranchState::Taken),
+                (0, BranchState::Skipped),
+                (1, BranchState::Skipped),
+                (1, BranchState::Taken),
+            ]
+        );
+    }
+
+    #[test]
+    fn leaves_conditions_on_external_names_undecided() {
+        let src = "&IF DEFINED(FROM-PARENT) &THEN\n/* &ENDIF */ x.\n&ELSE\ny.\n&ENDIF\n";
+        assert_eq!(
+            states(src),
+            vec![(0, BranchState::Unknown), (0, BranchState::Unknown)]
+        );
+        assert!(collect_conditional_branches("DEFINE VARIABLE x AS INTEGER.").is_empty());
+    }
+
+    #[test]
+    fn nested_conditionals_in_skipped_branches_are_skipped() {
+        let src = "&IF FALSE &THEN\n&IF TRUE &THEN\na.\n&ENDIF\n&ENDIF\n";
+        assert_eq!(
+            states(src),
+            vec![(0, BranchState::Skipped), (1, BranchState::Skipped)]
+        );
+    }
+
+    #[test]
+    fn keeps_taken_branch_and_counts_undecided_declarations_once() {
+        let src = r#"
+&SCOPED-DEFINE USE-LONG YES
+&IF DEFINED(USE-LONG) &THEN
+DEFINE VARIABLE lvValue AS INT64 NO-UNDO.
+&ELSE
+DEFINE VARIABLE lvValue AS INTEGER NO-UNDO.
+&ENDIF
+&IF DEFINED(OTHER) &THEN
+DEFINE VARIABLE lvOther AS INT64 NO-UNDO.
+&ELSE
+DEFINE VARIABLE lvOther AS INTEGER NO-UNDO.
+DEFINE VARIABLE lvExtra AS INTEGER NO-UNDO.
+&ENDIF
+"#;
+        let mut items = ["lvValue", "lvValue", "lvOther", "lvOther", "lvExtra"]
+            .iter()
+            .zip(src.match_indices("DEFINE VARIABLE"))
+            .map(|(name, (offset, _))| (name.to_string(), offset))
+            .collect::<Vec<_>>();
+        let starts = items.iter().map(|(_, start)| *start).collect::<Vec<_>>();
+        retain_reachable_definitions(src, &mut items, |(name, start)| (name.as_str(), *start));
+        assert_eq!(
+            items,
+            vec![
+                ("lvValue".to_string(), starts[0]),
+                ("lvOther".to_string(), starts[2]),
+                ("lvExtra".to_string(), starts[4]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use tower_lsp::lsp_types::Range;
+use tree_sitter::Node;
+
+use crate::analysis::definitions::collect_definition_sites;
+use crate::analysis::highlights::collect_document_highlights;
+use crate::analysis::scopes::{ByteScope, containing_scope};
+use crate::utils::ts::is_keyword_leaf;
+
+/// Whether the token spanning `start..end` is a keyword (`DEFINE`, `NO-UNDO`, ...).
+pub fn is_keyword_at(root: Node<'_>, src: &[u8], start: usize, end: usize) -> bool {
+    root.descendant_for_byte_range(start, end)
+        .is_some_and(|node| is_keyword_leaf(node, src))
+}
+
+/// Whether `name` can stand in for an ABL identifier.
+pub fn is_valid_identifier(name: &str) -> bool {
+    let mut bytes = name.bytes();
+    bytes.next().is_some_and(|b| b.is_ascii_alphabetic())
+        && bytes.all(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-'))
+}
+
+/// Byte-column ranges of every occurrence of `symbol` to rename from `offset`: the enclosing
+/// procedure, function or method when it declares `symbol`, otherwise the whole file.
+pub fn collect_rename_ranges(
+    root: Node<'_>,
+    src: &[u8],
+    symbol: &str,
+    offset: usize,
+) -> Vec<Range> {
+    let file = ByteScope {
+        start: root.start_byte(),
+        end: root.end_byte(),
+    };
+    let scope = containing_scope(root, offset)
+        .filter(|scope| {
+            let mut sites = Vec::new();
+            collect_definition_sites(root, src, &mut sites);
+            sites.iter().any(|site| {
+                site.label.eq_ignore_ascii_case(symbol)
+                    && scope.start <= site.start_byte
+                    && site.start_byte <= scope.end
+            })
+        })
+        .unwrap_or(file);
+
+    let mut highlights = Vec::new();
+    collect_document_highlights(root, src, symbol, scope, &mut highlights);
+    highlights.into_iter().map(|h| h.range).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_rename_ranges, is_keyword_at, is_valid_identifier};
+    use crate::analysis::parse_abl;
+
+    #[test]
+    fn renames_within_the_declaring_procedure() {
+        let src = r#"
+DEFINE VARIABLE total AS INTEGER NO-UNDO.
+PROCEDURE calc:
+  DEFINE VARIABLE total AS INTEGER NO-UNDO.
+  total = total + 1.
+END PROCEDURE.
+total = 2.
+"#;
+        let tree = parse_abl(src);
+        let offset = src.find("total = total").expect("assignment");
+
+        let ranges = collect_rename_ranges(tree.root_node(), src.as_bytes(), "total", offset);
+        let lines = ranges.iter().map(|r| r.start.line).collect::<Vec<_>>();
+        assert_eq!(lines, vec![3, 4, 4]);
+
+        let outer = src.rfind("total = 2").expect("outer assignment");
+        let ranges = collect_rename_ranges(tree.root_node(), src.as_bytes(), "total", outer);
+        assert!(ranges.iter().any(|r| r.start.line == 1));
+        assert!(ranges.iter().any(|r| r.start.line == 6));
+    }
+
+    #[test]
+    fn detects_keywords_and_invalid_names() {
+        let src = "DEFINE VARIABLE total AS INTEGER NO-UNDO.\n";
+        let tree = parse_abl(src);
+        assert!(is_keyword_at(tree.root_node(), src.as_bytes(), 0, 6));
+        assert!(!is_keyword_at(tree.root_node(), src.as_bytes(), 16, 21));
+
+        assert!(is_valid_identifier("new-total_2"));
+        assert!(!is_valid_identifier("2total"));
+        assert!(!is_valid_identifier("to tal"));
+    }
+}
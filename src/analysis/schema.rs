@@ -1,12 +1,39 @@
+use dashmap::DashMap;
+
 pub fn normalize_lookup_key(symbol: &str, allow_dash: bool) -> String {
     symbol
         .trim_matches(|c: char| !c.is_ascii_alphanumeric() && c != '_' && (!allow_dash || c != '-'))
         .to_ascii_uppercase()
 }
 
+/// Whether `name` is the logical database name of a `[[database]]` dumpfile.
+pub fn is_logical_db_name(db_name_by_table: &DashMap<String, String>, name: &str) -> bool {
+    db_name_by_table
+        .iter()
+        .any(|entry| entry.value().eq_ignore_ascii_case(name.trim()))
+}
+
+/// Table part of `dbname.table` when `dbname` is the logical database that defines `table`.
+pub fn strip_db_qualifier<'a>(
+    db_name_by_table: &DashMap<String, String>,
+    name: &'a str,
+) -> Option<&'a str> {
+    let (db_name, table) = name.trim().split_once('.')?;
+    let table = table.trim();
+    if table.is_empty() || table.contains('.') {
+        return None;
+    }
+    db_name_by_table
+        .get(&table.to_ascii_uppercase())
+        .is_some_and(|owner| owner.value().eq_ignore_ascii_case(db_name.trim()))
+        .then_some(table)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::normalize_lookup_key;
+    use dashmap::DashMap;
+
+    use super::{is_logical_db_name, normalize_lookup_key, strip_db_qualifier};
 
     #[test]
     fn normalizes_and_trims_symbols() {
@@ -19,4 +46,20 @@ mod tests {
         assert_eq!(normalize_lookup_key("---foo---", false), "FOO");
         assert_eq!(normalize_lookup_key("---foo---", true), "---FOO---");
     }
+
+    #[test]
+    fn resolves_db_qualifier_only_for_the_owning_database() {
+        let db_name_by_table = DashMap::new();
+        db_name_by_table.insert("CUSTOMER".to_string(), "sports".to_string());
+
+        assert!(is_logical_db_name(&db_name_by_table, "SPORTS"));
+        assert!(!is_logical_db_name(&db_name_by_table, "hr"));
+        assert_eq!(
+            strip_db_qualifier(&db_name_by_table, "Sports.customer"),
+            Some("customer")
+        );
+        assert_eq!(strip_db_qualifier(&db_name_by_table, "hr.customer"), None);
+        assert_eq!(strip_db_qualifier(&db_name_by_table, "sports.order"), None);
+        assert_eq!(strip_db_qualifier(&db_name_by_table, "customer"), None);
+    }
 }
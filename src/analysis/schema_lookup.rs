@@ -1,10 +1,8 @@
 use tower_lsp::lsp_types::Location;
 
-pub fn has_schema_key(map: &dashmap::DashMap<String, Vec<Location>>, key_upper: &str) -> bool {
-    map.contains_key(key_upper)
-        || map
-            .iter()
-            .any(|entry| entry.key().eq_ignore_ascii_case(key_upper))
+/// Schema maps are keyed by upper-cased names at load, so a lookup is one hash probe.
+pub fn has_schema_key(map: &dashmap::DashMap<String, Vec<Location>>, key: &str) -> bool {
+    map.contains_key(&key.to_ascii_uppercase())
 }
 
 pub fn pick_single_location(locations: &[Location]) -> Option<Location> {
@@ -19,21 +17,10 @@ pub fn pick_single_location(locations: &[Location]) -> Option<Location> {
 
 pub fn lookup_schema_location(
     defs: &dashmap::DashMap<String, Vec<Location>>,
-    symbol_upper: &str,
+    symbol: &str,
 ) -> Option<Location> {
-    if let Some(locations) = defs.get(symbol_upper)
-        && let Some(location) = pick_single_location(locations.value())
-    {
-        return Some(location);
-    }
-
-    defs.iter().find_map(|entry| {
-        if entry.key().eq_ignore_ascii_case(symbol_upper) {
-            pick_single_location(entry.value())
-        } else {
-            None
-        }
-    })
+    defs.get(&symbol.to_ascii_uppercase())
+        .and_then(|locations| pick_single_location(locations.value()))
 }
 
 #[cfg(test)]
@@ -56,7 +43,7 @@ mod tests {
     fn checks_schema_key_case_insensitively() {
         let map = DashMap::<String, Vec<Location>>::new();
         map.insert(
-            "Customer".to_string(),
+            "CUSTOMER".to_string(),
             vec![loc("file:///tmp/customer.df", 1, 1)],
         );
 
@@ -83,11 +70,11 @@ mod tests {
     fn looks_up_schema_location_case_insensitively() {
         let defs = DashMap::<String, Vec<Location>>::new();
         defs.insert(
-            "customer".to_string(),
+            "CUSTOMER".to_string(),
             vec![loc("file:///tmp/customer.df", 6, 4)],
         );
 
-        let hit = lookup_schema_location(&defs, "CUSTOMER").expect("location");
+        let hit = lookup_schema_location(&defs, "Customer").expect("location");
         assert_eq!(hit.uri.as_str(), "file:///tmp/customer.df");
         assert_eq!(hit.range.start.line, 6);
     }
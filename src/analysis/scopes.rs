@@ -7,27 +7,25 @@ pub struct ByteScope {
 }
 
 pub fn containing_scope(root: Node<'_>, offset: usize) -> Option<ByteScope> {
+    containing_scope_node(root, offset).map(|node| ByteScope {
+        start: node.start_byte(),
+        end: node.end_byte(),
+    })
+}
+
+/// Innermost procedure/function/method node around `offset`, or `root` outside of one.
+pub fn containing_scope_node<'tree>(root: Node<'tree>, offset: usize) -> Option<Node<'tree>> {
     let mut node = root.named_descendant_for_byte_range(offset, offset)?;
-    loop {
-        if is_scope_node(node.kind()) {
-            return Some(ByteScope {
-                start: node.start_byte(),
-                end: node.end_byte(),
-            });
-        }
-        let Some(parent) = node.parent() else {
-            break;
+    while !is_scope_node(node.kind()) {
+        node = match node.parent() {
+            Some(parent) => parent,
+            None => return Some(root),
         };
-        node = parent;
     }
-
-    Some(ByteScope {
-        start: root.start_byte(),
-        end: root.end_byte(),
-    })
+    Some(node)
 }
 
-fn is_scope_node(kind: &str) -> bool {
+pub fn is_scope_node(kind: &str) -> bool {
     matches!(
         kind,
         "function_definition"
@@ -0,0 +1,57 @@
+use tower_lsp::lsp_types::SelectionRange;
+use tree_sitter::Node;
+
+use crate::utils::ts::node_to_range;
+
+/// Builds the nested selection chain from the innermost named node at `offset` up to the root.
+pub fn selection_range_at(root: Node<'_>, offset: usize) -> Option<SelectionRange> {
+    let mut node = root.named_descendant_for_byte_range(offset, offset)?;
+    let mut ancestry = vec![node];
+    while let Some(parent) = node.parent() {
+        node = parent;
+        ancestry.push(node);
+    }
+
+    let mut chain: Option<SelectionRange> = None;
+    for node in ancestry.into_iter().rev() {
+        let range = node_to_range(node);
+        if chain.as_ref().is_some_and(|outer| outer.range == range) {
+            continue;
+        }
+        chain = Some(SelectionRange {
+            range,
+            parent: chain.map(Box::new),
+        });
+    }
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::selection_range_at;
+    use crate::analysis::parse_abl;
+
+    #[test]
+    fn expands_from_identifier_to_root() {
+        let src = "PROCEDURE calc:\n  lv_total = lv_total + 1.\nEND PROCEDURE.\n";
+        let tree = parse_abl(src);
+        let offset = src.find("lv_total +").expect("identifier");
+
+        let selection = selection_range_at(tree.root_node(), offset).expect("selection");
+        assert_eq!(selection.range.start.line, 1);
+        assert_eq!(
+            selection.range.end.character - selection.range.start.character,
+            8
+        );
+
+        let mut outermost = &selection;
+        let mut depth = 1;
+        while let Some(parent) = outermost.parent.as_deref() {
+            assert_ne!(parent.range, outermost.range);
+            outermost = parent;
+            depth += 1;
+        }
+        assert!(depth >= 3);
+        assert_eq!(outermost.range.start.line, 0);
+    }
+}
@@ -1,4 +1,282 @@
-use tower_lsp::lsp_types::Range;
+use std::collections::{HashMap, HashSet};
+
+use tower_lsp::lsp_types::{
+    Range, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokensLegend,
+};
+use tree_sitter::Node;
+
+use crate::analysis::builtins::is_builtin_function_name;
+use crate::utils::ts::is_keyword_leaf;
+
+pub const TYPE_TOKEN: u32 = 0;
+pub const FUNCTION_TOKEN: u32 = 1;
+pub const VARIABLE_TOKEN: u32 = 2;
+pub const PARAMETER_TOKEN: u32 = 3;
+pub const PROPERTY_TOKEN: u32 = 4;
+pub const KEYWORD_TOKEN: u32 = 5;
+pub const STRING_TOKEN: u32 = 6;
+
+/// Modifier bit marking identifiers that resolve to the DB schema (tables and their fields).
+pub const DATABASE_MODIFIER: u32 = 1 << 0;
+/// Modifier bit marking calls to ABL builtin functions.
+pub const DEFAULT_LIBRARY_MODIFIER: u32 = 1 << 1;
+
+/// Legend order must match the `*_TOKEN` indices and modifier bits above.
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: vec![
+            SemanticTokenType::TYPE,
+            SemanticTokenType::FUNCTION,
+            SemanticTokenType::VARIABLE,
+            SemanticTokenType::PARAMETER,
+            SemanticTokenType::PROPERTY,
+            SemanticTokenType::KEYWORD,
+            SemanticTokenType::STRING,
+        ],
+        token_modifiers: vec![
+            SemanticTokenModifier::new("database"),
+            SemanticTokenModifier::DEFAULT_LIBRARY,
+        ],
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RawSemanticToken {
+    pub line: u32,
+    pub start: u32,
+    pub length: u32,
+    pub token_type: u32,
+    pub modifiers: u32,
+}
+
+/// Table names the classifier should treat as types, split by whether they come from the DB schema.
+pub struct SemanticTableNames<'a> {
+    pub is_db_table: &'a dyn Fn(&str) -> bool,
+    pub local_tables: &'a HashSet<String>,
+}
+
+/// Classifies identifiers, keywords and strings under `root` into raw (absolute) tokens.
+pub fn collect_semantic_tokens(
+    root: Node<'_>,
+    text: &str,
+    tables: &SemanticTableNames<'_>,
+    range: Option<&Range>,
+) -> Vec<RawSemanticToken> {
+    let mut definitions = HashMap::new();
+    collect_definition_token_types(root, text.as_bytes(), &mut definitions);
+    let mut walker = TokenWalker {
+        text,
+        line_starts: line_start_offsets(text),
+        tables,
+        definitions,
+        range,
+        out: Vec::new(),
+    };
+    walker.walk(root);
+    walker.out
+}
+
+/// Sorts raw tokens and applies LSP relative (delta) encoding.
+pub fn encode_semantic_tokens(mut raw: Vec<RawSemanticToken>) -> Vec<SemanticToken> {
+    raw.sort();
+    raw.dedup_by(|a, b| a.line == b.line && a.start == b.start);
+
+    let mut out = Vec::with_capacity(raw.len());
+    let mut prev_line = 0u32;
+    let mut prev_start = 0u32;
+    for token in raw {
+        let delta_line = token.line - prev_line;
+        let delta_start = if delta_line == 0 {
+            token.start - prev_start
+        } else {
+            token.start
+        };
+        out.push(SemanticToken {
+            delta_line,
+            delta_start,
+            length: token.length,
+            token_type: token.token_type,
+            token_modifiers_bitset: token.modifiers,
+        });
+        prev_line = token.line;
+        prev_start = token.start;
+    }
+    out
+}
+
+fn collect_definition_token_types(node: Node<'_>, src: &[u8], out: &mut HashMap<String, u32>) {
+    let token_type = match node.kind() {
+        "parameter_definition" | "parameter" => Some(PARAMETER_TOKEN),
+        "property_definition" => Some(PROPERTY_TOKEN),
+        "function_definition"
+        | "function_forward_definition"
+        | "procedure_definition"
+        | "method_definition" => Some(FUNCTION_TOKEN),
+        "variable_definition" | "buffer_definition" | "stream_definition" => Some(VARIABLE_TOKEN),
+        _ => None,
+    };
+    if let Some(token_type) = token_type
+        && let Some(name) = node.child_by_field_name("name")
+        && let Ok(name) = name.utf8_text(src)
+    {
+        out.entry(name.trim().trim_matches('"').to_ascii_uppercase())
+            .or_insert(token_type);
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_definition_token_types(ch, src, out);
+        }
+    }
+}
+
+struct TokenWalker<'a> {
+    text: &'a str,
+    line_starts: Vec<usize>,
+    tables: &'a SemanticTableNames<'a>,
+    definitions: HashMap<String, u32>,
+    range: Option<&'a Range>,
+    out: Vec<RawSemanticToken>,
+}
+
+impl TokenWalker<'_> {
+    fn walk(&mut self, node: Node<'_>) {
+        match node.kind() {
+            "comment" => return,
+            "string_literal" => {
+                self.push_node(node, STRING_TOKEN, 0);
+                return;
+            }
+            "qualified_name" if self.push_qualified_name(node) => return,
+            "function_call" => {
+                if let Some(function) = node.child_by_field_name("function") {
+                    let is_builtin = function.utf8_text(self.text.as_bytes()).is_ok_and(|name| {
+                        is_builtin_function_name(&name.trim().to_ascii_uppercase())
+                    });
+                    let modifiers = if is_builtin {
+                        DEFAULT_LIBRARY_MODIFIER
+                    } else {
+                        0
+                    };
+                    self.push_node(function, FUNCTION_TOKEN, modifiers);
+                }
+                for i in 0..node.child_count() {
+                    if let Some(ch) = node.child(i as u32)
+                        && Some(ch) != node.child_by_field_name("function")
+                    {
+                        self.walk(ch);
+                    }
+                }
+                return;
+            }
+            "identifier" => {
+                self.push_identifier(node);
+                return;
+            }
+            _ => {}
+        }
+
+        if is_keyword_leaf(node, self.text.as_bytes()) {
+            self.push_node(node, KEYWORD_TOKEN, 0);
+            return;
+        }
+
+        for i in 0..node.child_count() {
+            if let Some(ch) = node.child(i as u32) {
+                self.walk(ch);
+            }
+        }
+    }
+
+    fn push_identifier(&mut self, node: Node<'_>) {
+        let Ok(name) = node.utf8_text(self.text.as_bytes()) else {
+            return;
+        };
+        let name_upper = name.trim().to_ascii_uppercase();
+        if let Some(modifiers) = self.table_modifiers(&name_upper) {
+            self.push_node(node, TYPE_TOKEN, modifiers);
+        } else if let Some(token_type) = self.definitions.get(&name_upper).copied() {
+            self.push_node(node, token_type, 0);
+        }
+    }
+
+    /// Emits `table.field` as a type + property pair when the qualifier is a known table.
+    fn push_qualified_name(&mut self, node: Node<'_>) -> bool {
+        let Ok(raw) = node.utf8_text(self.text.as_bytes()) else {
+            return false;
+        };
+        let Some((qualifier, field)) = raw.trim_end().rsplit_once('.') else {
+            return false;
+        };
+        let table = qualifier.rsplit('.').next().unwrap_or(qualifier);
+        let Some(modifiers) = self.table_modifiers(&table.to_ascii_uppercase()) else {
+            return false;
+        };
+        if raw.contains('\n') || field.is_empty() {
+            return false;
+        }
+
+        let start = node.start_byte();
+        let table_start = start + qualifier.len() - table.len();
+        self.push_span(table_start, table.len(), TYPE_TOKEN, modifiers);
+        self.push_span(
+            start + qualifier.len() + 1,
+            field.len(),
+            PROPERTY_TOKEN,
+            modifiers,
+        );
+        true
+    }
+
+    fn table_modifiers(&self, name_upper: &str) -> Option<u32> {
+        if (self.tables.is_db_table)(name_upper) {
+            Some(DATABASE_MODIFIER)
+        } else if self.tables.local_tables.contains(name_upper) {
+            Some(0)
+        } else {
+            None
+        }
+    }
+
+    fn push_node(&mut self, node: Node<'_>, token_type: u32, modifiers: u32) {
+        if node.start_position().row != node.end_position().row {
+            return;
+        }
+        self.push_span(
+            node.start_byte(),
+            node.end_byte() - node.start_byte(),
+            token_type,
+            modifiers,
+        );
+    }
+
+    fn push_span(&mut self, start_byte: usize, len: usize, token_type: u32, modifiers: u32) {
+        let line = match self.line_starts.binary_search(&start_byte) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        };
+        let column_byte = start_byte - self.line_starts[line];
+        let Some(start) =
+            point_column_byte_to_utf16(self.text, &self.line_starts, line as u32, column_byte)
+        else {
+            return;
+        };
+        let Some(slice) = self.text.get(start_byte..start_byte + len) else {
+            return;
+        };
+        let length = slice.encode_utf16().count() as u32;
+        if length == 0 || !is_in_range(line as u32, start, length, self.range) {
+            return;
+        }
+        self.out.push(RawSemanticToken {
+            line: line as u32,
+            start,
+            length,
+            token_type,
+            modifiers,
+        });
+    }
+}
 
 pub fn is_in_range(start_line: u32, start_col: u32, length: u32, range: Option<&Range>) -> bool {
     let Some(range) = range else {
@@ -45,8 +323,162 @@ pub fn point_column_byte_to_utf16(
 
 #[cfg(test)]
 mod tests {
-    use super::{is_in_range, line_start_offsets, point_column_byte_to_utf16};
-    use tower_lsp::lsp_types::{Position, Range};
+    use super::{
+        DATABASE_MODIFIER, DEFAULT_LIBRARY_MODIFIER, FUNCTION_TOKEN, KEYWORD_TOKEN,
+        PARAMETER_TOKEN, PROPERTY_TOKEN, RawSemanticToken, STRING_TOKEN, SemanticTableNames,
+        TYPE_TOKEN, VARIABLE_TOKEN, collect_semantic_tokens, encode_semantic_tokens, is_in_range,
+        line_start_offsets, point_column_byte_to_utf16, semantic_tokens_legend,
+    };
+    use crate::analysis::parse_abl;
+    use std::collections::HashSet;
+    use tower_lsp::lsp_types::{Position, Range, SemanticTokenType};
+
+    fn raw(line: u32, start: u32, length: u32, token_type: u32) -> RawSemanticToken {
+        RawSemanticToken {
+            line,
+            start,
+            length,
+            token_type,
+            modifiers: 0,
+        }
+    }
+
+    #[test]
+    fn delta_encodes_sorted_tokens() {
+        let tokens = encode_semantic_tokens(vec![
+            raw(2, 4, 3, VARIABLE_TOKEN),
+            raw(0, 7, 5, TYPE_TOKEN),
+            raw(0, 2, 4, FUNCTION_TOKEN),
+            raw(2, 10, 1, VARIABLE_TOKEN),
+        ]);
+        let encoded = tokens
+            .iter()
+            .map(|t| (t.delta_line, t.delta_start, t.length, t.token_type))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            encoded,
+            vec![
+                (0, 2, 4, FUNCTION_TOKEN),
+                (0, 5, 5, TYPE_TOKEN),
+                (2, 4, 3, VARIABLE_TOKEN),
+                (0, 6, 1, VARIABLE_TOKEN),
+            ]
+        );
+    }
+
+    #[test]
+    fn legend_order_matches_token_indices() {
+        let legend = semantic_tokens_legend();
+        let index = |ty: SemanticTokenType| {
+            legend
+                .token_types
+                .iter()
+                .position(|t| *t == ty)
+                .expect("legend entry") as u32
+        };
+        assert_eq!(index(SemanticTokenType::TYPE), TYPE_TOKEN);
+        assert_eq!(index(SemanticTokenType::FUNCTION), FUNCTION_TOKEN);
+        assert_eq!(index(SemanticTokenType::VARIABLE), VARIABLE_TOKEN);
+        assert_eq!(index(SemanticTokenType::PARAMETER), PARAMETER_TOKEN);
+        assert_eq!(index(SemanticTokenType::PROPERTY), PROPERTY_TOKEN);
+        assert_eq!(index(SemanticTokenType::KEYWORD), KEYWORD_TOKEN);
+        assert_eq!(index(SemanticTokenType::STRING), STRING_TOKEN);
+    }
+
+    #[test]
+    fn classifies_keywords_strings_and_parameters() {
+        let src = "PROCEDURE greet:\n  DEFINE INPUT PARAMETER pName AS CHARACTER NO-UNDO.\n  MESSAGE \"Hello\" pName.\nEND PROCEDURE.\n";
+        let tree = parse_abl(src);
+        let is_db_table = |_: &str| false;
+        let local_tables = HashSet::new();
+        let tables = SemanticTableNames {
+            is_db_table: &is_db_table,
+            local_tables: &local_tables,
+        };
+        let tokens = collect_semantic_tokens(tree.root_node(), src, &tables, None);
+        let on_line = |line: u32| {
+            tokens
+                .iter()
+                .filter(|t| t.line == line)
+                .map(|t| (t.start, t.length, t.token_type))
+                .collect::<Vec<_>>()
+        };
+
+        let message_line = on_line(2);
+        assert!(message_line.contains(&(2, 7, KEYWORD_TOKEN)));
+        assert!(message_line.contains(&(10, 7, STRING_TOKEN)));
+        assert!(message_line.contains(&(18, 5, PARAMETER_TOKEN)));
+        assert!(on_line(0).contains(&(0, 9, KEYWORD_TOKEN)));
+        // Without tables nothing is a type or a field.
+        assert!(
+            tokens
+                .iter()
+                .all(|t| t.token_type != TYPE_TOKEN && t.token_type != PROPERTY_TOKEN)
+        );
+    }
+
+    #[test]
+    fn classifies_local_table_fields_as_properties() {
+        let src = "DEFINE TEMP-TABLE ttItem NO-UNDO FIELD qty AS INTEGER.\nttItem.qty = 1.\n";
+        let tree = parse_abl(src);
+        let is_db_table = |_: &str| false;
+        let local_tables = HashSet::from(["TTITEM".to_string()]);
+        let tables = SemanticTableNames {
+            is_db_table: &is_db_table,
+            local_tables: &local_tables,
+        };
+        let tokens = collect_semantic_tokens(tree.root_node(), src, &tables, None);
+        let line = tokens
+            .iter()
+            .filter(|t| t.line == 1)
+            .map(|t| (t.start, t.length, t.token_type, t.modifiers))
+            .collect::<Vec<_>>();
+        assert!(line.contains(&(0, 6, TYPE_TOKEN, 0)));
+        assert!(line.contains(&(7, 3, PROPERTY_TOKEN, 0)));
+    }
+
+    #[test]
+    fn tags_builtin_calls_with_default_library_modifier() {
+        let src = "FUNCTION fmt RETURNS CHARACTER (p AS CHARACTER):\n  RETURN p.\nEND FUNCTION.\nMESSAGE SUBSTRING(fmt(\"ab\"), 1, 1).\n";
+        let tree = parse_abl(src);
+        let is_db_table = |_: &str| false;
+        let local_tables = HashSet::new();
+        let tables = SemanticTableNames {
+            is_db_table: &is_db_table,
+            local_tables: &local_tables,
+        };
+        let tokens = collect_semantic_tokens(tree.root_node(), src, &tables, None);
+        let calls = tokens
+            .iter()
+            .filter(|t| t.line == 3 && t.token_type == FUNCTION_TOKEN)
+            .map(|t| (t.length, t.modifiers))
+            .collect::<Vec<_>>();
+        assert_eq!(calls, vec![(9, DEFAULT_LIBRARY_MODIFIER), (3, 0)]);
+    }
+
+    #[test]
+    fn classifies_db_tables_and_variables() {
+        let src = "DEFINE VARIABLE lv_name AS CHARACTER NO-UNDO.\nFIND FIRST Customer NO-LOCK.\nlv_name = Customer.Name.\n";
+        let tree = parse_abl(src);
+        let is_db_table = |name: &str| name == "CUSTOMER";
+        let local_tables = HashSet::new();
+        let tables = SemanticTableNames {
+            is_db_table: &is_db_table,
+            local_tables: &local_tables,
+        };
+        let tokens = collect_semantic_tokens(tree.root_node(), src, &tables, None);
+
+        let customer = tokens
+            .iter()
+            .find(|t| t.line == 1 && t.token_type == TYPE_TOKEN)
+            .expect("table token");
+        assert_eq!(customer.modifiers, DATABASE_MODIFIER);
+        assert!(
+            tokens
+                .iter()
+                .any(|t| t.line == 2 && t.start == 0 && t.token_type == VARIABLE_TOKEN)
+        );
+    }
 
     #[test]
     fn converts_byte_column_to_utf16_with_non_ascii_prefix() {
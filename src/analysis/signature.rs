@@ -6,6 +6,8 @@ use crate::analysis::functions::FunctionSignature;
 pub struct CallContext {
     pub name: String,
     pub active_param: usize,
+    /// `RUN proc (...)` invocation of a procedure rather than a function call.
+    pub is_run: bool,
 }
 
 pub fn call_context_at_offset(root: Node<'_>, src: &[u8], offset: usize) -> Option<CallContext> {
@@ -15,6 +17,7 @@ pub fn call_context_at_offset(root: Node<'_>, src: &[u8], offset: usize) -> Opti
 pub fn to_signature_information(sig: &FunctionSignature) -> SignatureInformation {
     let params_text = sig.params.join(", ");
     let label = match sig.return_type.as_deref() {
+        _ if sig.is_procedure => format!("PROCEDURE {}({})", sig.name, params_text),
         Some(ret) => format!("FUNCTION {}({}) RETURNS {}", sig.name, params_text, ret),
         None => format!("FUNCTION {}({})", sig.name, params_text),
     };
@@ -61,10 +64,19 @@ fn call_context_from_tree(root: Node<'_>, src: &[u8], offset: usize) -> Option<C
                 let end = arguments.end_byte();
                 if offset >= start.saturating_add(1) && offset <= end {
                     let active_param = count_active_argument_index(src, start, end, offset);
-                    return Some(CallContext { name, active_param });
+                    return Some(CallContext {
+                        name,
+                        active_param,
+                        is_run: false,
+                    });
                 }
             }
         }
+        if node.kind() == "run_statement"
+            && let Some(ctx) = run_call_context(node, src, offset)
+        {
+            return Some(ctx);
+        }
         let Some(parent) = node.parent() else {
             break;
         };
@@ -98,10 +110,14 @@ fn call_context_from_text(src: &[u8], offset: usize) -> Option<CallContext> {
                     if b != b'(' {
                         continue;
                     }
-                    let (name, _) = extract_call_name_before_open_paren(src, i)?;
+                    let (name, name_start) = extract_call_name_before_open_paren(src, i)?;
                     let active_param = count_active_argument_index(src, i, offset, offset);
                     if !name.is_empty() {
-                        return Some(CallContext { name, active_param });
+                        return Some(CallContext {
+                            name,
+                            active_param,
+                            is_run: preceded_by_run_keyword(src, name_start),
+                        });
                     }
                     return None;
                 }
@@ -114,6 +130,176 @@ fn call_context_from_text(src: &[u8], offset: usize) -> Option<CallContext> {
     None
 }
 
+fn run_call_context(node: Node<'_>, src: &[u8], offset: usize) -> Option<CallContext> {
+    let name = run_target_name(node, src)?;
+    let name_end = node
+        .child_by_field_name("procedure")
+        .or_else(|| node.child_by_field_name("name"))
+        .map(|n| n.end_byte())
+        .unwrap_or_else(|| node.start_byte());
+
+    let open = node.start_byte().max(name_end)
+        + src
+            .get(node.start_byte().max(name_end)..node.end_byte())?
+            .iter()
+            .position(|b| *b == b'(')?;
+    let close = node.end_byte();
+    if offset <= open || offset > close {
+        return None;
+    }
+    Some(CallContext {
+        name,
+        active_param: count_active_argument_index(src, open, close, offset),
+        is_run: true,
+    })
+}
+
+/// Returns the procedure name targeted by a `run_statement`.
+pub fn run_target_name(node: Node<'_>, src: &[u8]) -> Option<String> {
+    let name = match node
+        .child_by_field_name("procedure")
+        .or_else(|| node.child_by_field_name("name"))
+    {
+        Some(n) => n
+            .utf8_text(src)
+            .ok()?
+            .trim()
+            .trim_matches(['"', '\''])
+            .to_string(),
+        None => {
+            let text = node.utf8_text(src).ok()?.trim_start();
+            text.get(3..)?
+                .trim_start()
+                .split(|c: char| c.is_ascii_whitespace() || c == '(' || c == '.')
+                .next()?
+                .trim_matches(['"', '\''])
+                .to_string()
+        }
+    };
+    if name.is_empty() { None } else { Some(name) }
+}
+
+const RUN_PROGRAM_EXTENSIONS: &[&str] = &[".p", ".w", ".r"];
+
+/// External program run by `RUN "lib/util.p"` / `RUN lib/util.p`, with the byte span of its name.
+pub fn run_program_target(node: Node<'_>, src: &[u8]) -> Option<(String, usize, usize)> {
+    let name_node = node
+        .child_by_field_name("procedure")
+        .or_else(|| node.child_by_field_name("name"))?;
+    let program = name_node
+        .utf8_text(src)
+        .ok()?
+        .trim()
+        .trim_matches(['"', '\''])
+        .to_string();
+    let lower = program.to_ascii_lowercase();
+    RUN_PROGRAM_EXTENSIONS
+        .iter()
+        .any(|ext| lower.len() > ext.len() && lower.ends_with(ext))
+        .then(|| (program, name_node.start_byte(), name_node.end_byte()))
+}
+
+/// `RUN prog.p ON [SERVER] hServer`: the program runs on an AppServer, not from PROPATH.
+pub fn run_statement_is_remote(node: Node<'_>, src: &[u8]) -> bool {
+    node.child_by_field_name("server").is_some()
+        || (0..node.child_count())
+            .filter_map(|i| node.child(i as u32))
+            .any(|child| {
+                child.child_count() == 0
+                    && child
+                        .utf8_text(src)
+                        .is_ok_and(|raw| raw.eq_ignore_ascii_case("ON"))
+            })
+}
+
+/// `run_statement`s that start an external program.
+pub fn collect_run_program_sites<'a>(node: Node<'a>, src: &[u8], out: &mut Vec<Node<'a>>) {
+    if node.kind() == "run_statement" && run_program_target(node, src).is_some() {
+        out.push(node);
+    }
+    for i in 0..node.child_count() {
+        if let Some(ch) = node.child(i as u32) {
+            collect_run_program_sites(ch, src, out);
+        }
+    }
+}
+
+/// Returns the start offset of each top-level argument between `open` and `close` parens.
+pub fn split_argument_offsets(src: &[u8], open: usize, close: usize) -> Vec<usize> {
+    split_argument_spans(src, open, close)
+        .into_iter()
+        .map(|(start, _)| start)
+        .collect()
+}
+
+/// Returns the byte span of each top-level argument between `open` and `close` parens,
+/// without the whitespace before the `,` / `)` that ends it.
+pub fn split_argument_spans(src: &[u8], open: usize, close: usize) -> Vec<(usize, usize)> {
+    let close = close.min(src.len());
+    let trimmed_end = |start: usize, mut end: usize| {
+        while end > start && src[end - 1].is_ascii_whitespace() {
+            end -= 1;
+        }
+        end
+    };
+    let mut out = Vec::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut current = None;
+    let mut end = close;
+    let mut i = open.saturating_add(1);
+
+    while i < close {
+        let b = src[i];
+        if let Some(q) = quote {
+            if b == q {
+                quote = None;
+            }
+            i += 1;
+            continue;
+        }
+        if current.is_none() && !b.is_ascii_whitespace() {
+            if depth == 0 && b == b')' {
+                break;
+            }
+            current = Some(i);
+        }
+        match b {
+            b'"' | b'\'' => quote = Some(b),
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' if depth == 0 => {
+                end = i;
+                break;
+            }
+            b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+            b',' if depth == 0 => {
+                if let Some(start) = current.take() {
+                    out.push((start, trimmed_end(start, i)));
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    if let Some(start) = current {
+        out.push((start, trimmed_end(start, end)));
+    }
+
+    out
+}
+
+fn preceded_by_run_keyword(src: &[u8], name_start: usize) -> bool {
+    let mut end = name_start;
+    while end > 0 && src[end - 1].is_ascii_whitespace() {
+        end -= 1;
+    }
+    let mut start = end;
+    while start > 0 && src[start - 1].is_ascii_alphabetic() {
+        start -= 1;
+    }
+    src[start..end].eq_ignore_ascii_case(b"RUN")
+}
+
 fn extract_call_name_before_open_paren(src: &[u8], open_paren: usize) -> Option<(String, usize)> {
     if open_paren == 0 {
         return None;
@@ -193,14 +379,42 @@ fn count_active_argument_index(
 
 #[cfg(test)]
 mod tests {
-    use super::{call_context_at_offset, count_active_argument_index};
-    use crate::analysis::functions::find_function_signature;
+    use super::{
+        call_context_at_offset, collect_run_program_sites, count_active_argument_index,
+        run_program_target, split_argument_spans, to_signature_information,
+    };
+    use crate::analysis::functions::{find_function_signature, find_procedure_signature};
     use crate::analysis::parse_abl;
 
     fn parse(src: &str) -> tree_sitter::Tree {
         parse_abl(src)
     }
 
+    #[test]
+    fn splits_top_level_argument_spans() {
+        let src = b"RUN calc (1 , f(a, b) , \"x,y\" , 'p)q', 2) NO-ERROR.";
+        let open = src.iter().position(|b| *b == b'(').expect("open");
+        let spans = split_argument_spans(src, open, src.len())
+            .into_iter()
+            .map(|(start, end)| std::str::from_utf8(&src[start..end]).expect("utf8"))
+            .collect::<Vec<_>>();
+        assert_eq!(spans, vec!["1", "f(a, b)", "\"x,y\"", "'p)q'", "2"]);
+    }
+
+    #[test]
+    fn finds_external_run_programs() {
+        let src = "RUN \"lib/util.p\".\nRUN lib/other.w (INPUT 1).\nRUN local_proc.\n";
+        let tree = parse(src);
+        let mut sites = Vec::new();
+        collect_run_program_sites(tree.root_node(), src.as_bytes(), &mut sites);
+        let programs = sites
+            .iter()
+            .filter_map(|node| run_program_target(*node, src.as_bytes()))
+            .map(|(program, _, _)| program)
+            .collect::<Vec<_>>();
+        assert_eq!(programs, vec!["lib/util.p", "lib/other.w"]);
+    }
+
     #[test]
     fn detects_call_context_and_active_param_on_complete_call() {
         let src = r#"
@@ -258,4 +472,33 @@ lv_counter = local_mul(1, 2).
         assert_eq!(sig.params.len(), 2);
         assert_eq!(sig.return_type.as_deref(), Some("INTEGER"));
     }
+
+    #[test]
+    fn detects_run_call_context_for_local_procedure() {
+        let src = r#"
+PROCEDURE calc_total:
+  DEFINE INPUT PARAMETER p_qty AS INTEGER NO-UNDO.
+  DEFINE OUTPUT PARAMETER p_total AS DECIMAL NO-UNDO.
+END PROCEDURE.
+RUN calc_total (INPUT 5, OUTPUT
+"#;
+        let tree = parse(src);
+        let offset = src.len();
+        let call =
+            call_context_at_offset(tree.root_node(), src.as_bytes(), offset).expect("call context");
+        assert_eq!(call.name.to_ascii_lowercase(), "calc_total");
+        assert!(call.is_run);
+        assert_eq!(call.active_param, 1);
+
+        let sig = find_procedure_signature(tree.root_node(), src.as_bytes(), &call.name)
+            .expect("procedure signature");
+        assert_eq!(sig.params.len(), 2);
+        assert!(sig.params[0].contains("p_qty"));
+        assert!(sig.params[1].starts_with("OUTPUT"));
+        assert!(
+            to_signature_information(&sig)
+                .label
+                .starts_with("PROCEDURE calc_total(")
+        );
+    }
 }
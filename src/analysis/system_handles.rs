@@ -0,0 +1,141 @@
+/// Attributes and methods offered after `HANDLE:` for an ABL system handle.
+pub struct SystemHandleMembers {
+    pub attributes: &'static [&'static str],
+    pub methods: &'static [&'static str],
+}
+
+const SESSION: SystemHandleMembers = SystemHandleMembers {
+    attributes: &[
+        "APPL-ALERT-BOXES",
+        "BATCH-MODE",
+        "CLIENT-TYPE",
+        "CURRENT-REQUEST-INFO",
+        "DATE-FORMAT",
+        "DISPLAY-TYPE",
+        "ERROR-STACK-TRACE",
+        "FIRST-PROCEDURE",
+        "FIRST-SERVER",
+        "LAST-PROCEDURE",
+        "NUMERIC-DECIMAL-POINT",
+        "NUMERIC-FORMAT",
+        "NUMERIC-SEPARATOR",
+        "PARAMETER",
+        "REMOTE",
+        "SERVER-CONNECTION-ID",
+        "STARTUP-PARAMETERS",
+        "SUPER-PROCEDURES",
+        "TEMP-DIRECTORY",
+        "TIMEZONE",
+        "WINDOW-SYSTEM",
+        "YEAR-OFFSET",
+    ],
+    methods: &[
+        "ADD-SUPER-PROCEDURE",
+        "EXPORT",
+        "GET-PRINTERS",
+        "REMOVE-SUPER-PROCEDURE",
+        "SET-NUMERIC-FORMAT",
+        "SET-WAIT-STATE",
+    ],
+};
+
+const ERROR_STATUS: SystemHandleMembers = SystemHandleMembers {
+    attributes: &[
+        "ERROR",
+        "ERROR-OBJECT-DETAIL",
+        "INSTANTIATING-PROCEDURE",
+        "NUM-MESSAGES",
+        "TYPE",
+    ],
+    methods: &["GET-MESSAGE", "GET-NUMBER"],
+};
+
+const PROCEDURE: SystemHandleMembers = SystemHandleMembers {
+    attributes: &[
+        "FILE-NAME",
+        "INSTANTIATING-PROCEDURE",
+        "INTERNAL-ENTRIES",
+        "NAME",
+        "NEXT-SIBLING",
+        "PERSISTENT",
+        "PREV-SIBLING",
+        "PRIVATE-DATA",
+        "PROXY",
+        "PUBLISHED-EVENTS",
+        "REMOTE",
+        "SERVER",
+        "SUPER-PROCEDURES",
+        "TYPE",
+        "UNIQUE-ID",
+    ],
+    methods: &[
+        "ADD-SUPER-PROCEDURE",
+        "GET-SIGNATURE",
+        "REMOVE-SUPER-PROCEDURE",
+    ],
+};
+
+const WINDOW: SystemHandleMembers = SystemHandleMembers {
+    attributes: &[
+        "BGCOLOR",
+        "FGCOLOR",
+        "FIRST-CHILD",
+        "HEIGHT-CHARS",
+        "HIDDEN",
+        "MAX-HEIGHT-CHARS",
+        "MAX-WIDTH-CHARS",
+        "MENUBAR",
+        "MESSAGE-AREA",
+        "NAME",
+        "SENSITIVE",
+        "STATUS-AREA",
+        "TITLE",
+        "VISIBLE",
+        "WIDTH-CHARS",
+        "WINDOW-STATE",
+    ],
+    methods: &["LOAD-ICON", "MOVE-TO-BOTTOM", "MOVE-TO-TOP"],
+};
+
+/// Returns the curated members of a system handle (`SESSION`, `ERROR-STATUS`, `THIS-PROCEDURE`, ...).
+pub fn system_handle_members(name: &str) -> Option<&'static SystemHandleMembers> {
+    match name.to_ascii_uppercase().as_str() {
+        "SESSION" => Some(&SESSION),
+        "ERROR-STATUS" => Some(&ERROR_STATUS),
+        "THIS-PROCEDURE" | "SOURCE-PROCEDURE" | "TARGET-PROCEDURE" => Some(&PROCEDURE),
+        "CURRENT-WINDOW" | "DEFAULT-WINDOW" | "ACTIVE-WINDOW" => Some(&WINDOW),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::system_handle_members;
+    use crate::analysis::builtins::is_builtin_variable_name;
+
+    #[test]
+    fn maps_system_handles_to_members() {
+        let error_status = system_handle_members("error-status").expect("ERROR-STATUS");
+        assert!(error_status.attributes.contains(&"NUM-MESSAGES"));
+        assert!(error_status.methods.contains(&"GET-MESSAGE"));
+        assert!(system_handle_members("SOURCE-PROCEDURE").is_some());
+        assert!(system_handle_members("hWidget").is_none());
+    }
+
+    #[test]
+    fn covers_only_builtin_variables() {
+        for name in [
+            "SESSION",
+            "ERROR-STATUS",
+            "THIS-PROCEDURE",
+            "SOURCE-PROCEDURE",
+            "TARGET-PROCEDURE",
+            "CURRENT-WINDOW",
+            "DEFAULT-WINDOW",
+            "ACTIVE-WINDOW",
+        ] {
+            assert!(is_builtin_variable_name(name), "{name}");
+            assert!(system_handle_members(name).is_some(), "{name}");
+        }
+    }
+}
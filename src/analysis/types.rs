@@ -38,9 +38,54 @@ pub fn builtin_type_from_name(raw: &str) -> Option<BasicType> {
     }
 }
 
+/// Upper-cased declared type with the common abbreviations (`INT`, `CHAR`, `DEC`, `LOG`)
+/// spelled out, so `AS INT` and `AS INTEGER` compare equal while `INTEGER` and `DECIMAL` do not.
+pub fn canonical_type_name(raw: &str) -> String {
+    const ABBREVIATED: &[(&str, usize)] = &[
+        ("CHARACTER", 4),
+        ("INTEGER", 3),
+        ("DECIMAL", 3),
+        ("LOGICAL", 3),
+    ];
+    let upper = raw
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_ascii_uppercase();
+    ABBREVIATED
+        .iter()
+        .find(|(full, min_len)| upper.len() >= *min_len && full.starts_with(upper.as_str()))
+        .map(|(full, _)| full.to_string())
+        .unwrap_or(upper)
+}
+
+/// Builtin data types, which have no definition to navigate to.
+pub fn is_primitive_type_name(raw: &str) -> bool {
+    builtin_type_from_name(raw).is_some()
+        || ["RECID", "ROWID", "MEMPTR", "RAW", "BLOB"]
+            .iter()
+            .any(|ty| raw.trim().eq_ignore_ascii_case(ty))
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{BasicType, builtin_type_from_name};
+    use super::{BasicType, builtin_type_from_name, canonical_type_name, is_primitive_type_name};
+
+    #[test]
+    fn recognizes_primitive_type_names() {
+        assert!(is_primitive_type_name("INTEGER"));
+        assert!(is_primitive_type_name("rowid"));
+        assert!(!is_primitive_type_name("app.Customer"));
+    }
+
+    #[test]
+    fn spells_out_abbreviated_type_names() {
+        assert_eq!(canonical_type_name("int"), "INTEGER");
+        assert_eq!(canonical_type_name("Char"), "CHARACTER");
+        assert_eq!(canonical_type_name("DEC"), "DECIMAL");
+        assert_eq!(canonical_type_name("int64"), "INT64");
+        assert_eq!(canonical_type_name("app.Customer"), "APP.CUSTOMER");
+    }
 
     #[test]
     fn maps_builtin_type_aliases() {
@@ -7,14 +7,33 @@ use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::Mutex as StdMutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Instant, SystemTime};
 use tokio::sync::Mutex as AsyncMutex;
+use tokio::task::AbortHandle;
 use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::request::{
+    GotoImplementationParams, GotoImplementationResponse, GotoTypeDefinitionParams,
+    GotoTypeDefinitionResponse,
+};
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer};
 use tree_sitter::{Language, Parser, Tree};
 
-use crate::config::{AblConfig, find_workspace_root, load_from_workspace_root};
-use crate::utils::paths::{resolve_dumpfile_path, resolve_include_path};
+use crate::analysis::classes::{UsingImport, collect_using_imports, imported_class_names};
+use crate::analysis::df::DfIndexFlags;
+use crate::analysis::semantic_tokens::semantic_tokens_legend;
+use crate::config::{
+    AblConfig, apply_client_settings, find_workspace_roots, load_from_workspace_root,
+};
+use crate::handlers::diagnostics::{cancel_workspace_diagnostics, spawn_workspace_diagnostics};
+use crate::handlers::execute_command::supported_commands;
+use crate::utils::paths::{
+    RootPropaths, collect_propath_include_files, list_package_class_names, resolve_config_path,
+    resolve_dumpfile_path, resolve_include_path,
+};
+use crate::utils::position::byte_ranges_to_utf16;
+use crate::utils::source::{parse_source, read_source_file};
 
 #[derive(Clone)]
 pub struct DbFieldInfo {
@@ -39,8 +58,13 @@ pub struct IncludeCompletionCacheEntry {
 pub struct IncludeParseCacheEntry {
     pub text: Arc<String>,
     pub tree: Tree,
+    pub modified: Option<SystemTime>,
+    pub last_used: Instant,
 }
 
+const MAX_INCLUDE_PARSE_CACHE_ENTRIES: usize = 256;
+const INCLUDE_INDEX_PROGRESS_STEP: usize = 10;
+
 pub struct DiagTask {
     pub handle: tokio::task::JoinHandle<()>,
     pub version: i32,
@@ -52,8 +76,12 @@ pub struct DocumentState {
     pub version: i32,
     pub tree_version: i32,
     pub tree: Option<Tree>,
+    /// Previous tree with pending `did_change` edits applied, used as the base for reparsing.
+    pub edited_tree: Option<Tree>,
     pub parser: StdMutex<Parser>,
     pub diag_task: Option<DiagTask>,
+    /// Diagnostics last published for this document (UTF-16 ranges).
+    pub published_diags: Vec<Diagnostic>,
 }
 
 pub struct BackendState {
@@ -61,19 +89,39 @@ pub struct BackendState {
     pub df_parser: AsyncMutex<Parser>,
     pub documents: DashMap<Url, DocumentState>,
     pub workspace_root: AsyncMutex<Option<std::path::PathBuf>>,
+    /// All workspace folders; `workspace_root` is the first and provides the main `abl.toml`.
+    pub workspace_roots: AsyncMutex<Vec<PathBuf>>,
     pub config: AsyncMutex<AblConfig>,
+    /// Config from the `abl.toml` of each workspace folder after the first (whose config is
+    /// `config`), for folders that have one.
+    pub folder_configs: AsyncMutex<HashMap<PathBuf, AblConfig>>,
+    /// Last `workspace/didChangeConfiguration` settings, merged over `abl.toml` on every reload.
+    pub client_settings: AsyncMutex<Value>,
     pub db_tables: DashSet<String>,
     pub db_sequences: DashSet<String>,
     pub db_table_labels: DashMap<String, String>,
     pub db_table_definitions: DashMap<String, Vec<Location>>,
     pub db_sequence_definitions: DashMap<String, Vec<Location>>,
     pub db_field_definitions: DashMap<String, Vec<Location>>,
+    pub db_field_definitions_by_table: DashMap<String, Vec<Location>>,
     pub db_index_definitions: DashMap<String, Vec<Location>>,
     pub db_indexes_by_table: DashMap<String, Vec<String>>,
     pub db_index_fields_by_table_index: DashMap<String, Vec<String>>,
+    pub db_index_flags_by_table_index: DashMap<String, DfIndexFlags>,
     pub db_fields_by_table: DashMap<String, Vec<DbFieldInfo>>,
+    /// Upper-cased field name to the upper-cased tables that define it.
+    pub db_tables_by_field: DashMap<String, Vec<String>>,
+    /// Upper-cased table name to the logical database name of its `[[database]]` dumpfile.
+    pub db_name_by_table: DashMap<String, String>,
     pub include_completion_cache: DashMap<PathBuf, IncludeCompletionCacheEntry>,
     pub include_parse_cache: DashMap<PathBuf, IncludeParseCacheEntry>,
+    /// `USING` directives of each open document, refreshed on every text update.
+    pub using_imports: DashMap<Url, Vec<UsingImport>>,
+    /// Upper-cased package name to the class names found under it on the propath.
+    pub package_class_cache: DashMap<String, Vec<String>>,
+    pub client_snippet_support: AtomicBool,
+    /// The running `diagnostics.scope = "workspace"` pass, aborted on restart and shutdown.
+    pub workspace_diagnostics_task: StdMutex<Option<AbortHandle>>,
 }
 
 #[derive(Clone)]
@@ -82,6 +130,41 @@ pub struct Backend {
     pub state: Arc<BackendState>,
 }
 
+impl BackendState {
+    pub fn new(abl_language: Language, df_parser: Parser) -> Self {
+        Self {
+            abl_language,
+            df_parser: AsyncMutex::new(df_parser),
+            documents: DashMap::new(),
+            workspace_root: AsyncMutex::new(None),
+            workspace_roots: AsyncMutex::new(Vec::new()),
+            config: AsyncMutex::new(AblConfig::default()),
+            folder_configs: AsyncMutex::new(HashMap::new()),
+            client_settings: AsyncMutex::new(Value::Null),
+            db_tables: DashSet::new(),
+            db_sequences: DashSet::new(),
+            db_table_labels: DashMap::new(),
+            db_table_definitions: DashMap::new(),
+            db_sequence_definitions: DashMap::new(),
+            db_field_definitions: DashMap::new(),
+            db_field_definitions_by_table: DashMap::new(),
+            db_index_definitions: DashMap::new(),
+            db_indexes_by_table: DashMap::new(),
+            db_index_fields_by_table_index: DashMap::new(),
+            db_index_flags_by_table_index: DashMap::new(),
+            db_fields_by_table: DashMap::new(),
+            db_tables_by_field: DashMap::new(),
+            db_name_by_table: DashMap::new(),
+            include_completion_cache: DashMap::new(),
+            include_parse_cache: DashMap::new(),
+            using_imports: DashMap::new(),
+            package_class_cache: DashMap::new(),
+            client_snippet_support: AtomicBool::new(false),
+            workspace_diagnostics_task: StdMutex::new(None),
+        }
+    }
+}
+
 impl Deref for Backend {
     type Target = BackendState;
 
@@ -90,28 +173,65 @@ impl Deref for Backend {
     }
 }
 
+#[cfg(test)]
+impl Backend {
+    /// Backend over empty state whose client is already disconnected.
+    pub fn for_tests() -> Self {
+        let mut df_parser = Parser::new();
+        df_parser
+            .set_language(&tree_sitter_df::LANGUAGE.into())
+            .expect("set df language");
+        let (service, _socket) = tower_lsp::LspService::build(|client| Backend {
+            client,
+            state: Arc::new(BackendState::new(
+                tree_sitter_abl::LANGUAGE.into(),
+                df_parser,
+            )),
+        })
+        .finish();
+        let backend = service.inner().clone();
+        drop(service);
+        backend
+    }
+}
+
 #[tower_lsp::async_trait]
 impl LanguageServer for Backend {
     async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
-        let root = find_workspace_root(&params);
-        {
-            let mut workspace_root = self.workspace_root.lock().await;
-            *workspace_root = root;
-        }
+        let roots = find_workspace_roots(&params);
+        let snippet_support = params
+            .capabilities
+            .text_document
+            .as_ref()
+            .and_then(|td| td.completion.as_ref())
+            .and_then(|completion| completion.completion_item.as_ref())
+            .and_then(|item| item.snippet_support)
+            .unwrap_or(false);
+        self.client_snippet_support
+            .store(snippet_support, Ordering::Relaxed);
+        self.set_workspace_roots(roots).await;
         self.reload_workspace_config().await;
         let semantic_tokens_enabled = self.config.lock().await.semantic_tokens.enabled;
+        let inlay_hints_enabled = self.config.lock().await.inlay_hints.enabled;
+        let code_lens_enabled = self.config.lock().await.code_lens.enabled;
 
         Ok(InitializeResult {
             server_info: None,
             offset_encoding: None,
 
             capabilities: ServerCapabilities {
+                // UTF-16 is mandatory for every client, so it is always the negotiated encoding.
+                position_encoding: Some(PositionEncodingKind::UTF16),
                 document_formatting_provider: Some(OneOf::Left(true)),
-                inlay_hint_provider: None,
+                inlay_hint_provider: if inlay_hints_enabled {
+                    Some(OneOf::Left(true))
+                } else {
+                    None
+                },
                 text_document_sync: Some(TextDocumentSyncCapability::Options(
                     TextDocumentSyncOptions {
                         open_close: Some(true),
-                        change: Some(TextDocumentSyncKind::FULL),
+                        change: Some(TextDocumentSyncKind::INCREMENTAL),
                         save: Some(TextDocumentSyncSaveOptions::SaveOptions(SaveOptions {
                             include_text: Some(true),
                         })),
@@ -119,8 +239,14 @@ impl LanguageServer for Backend {
                     },
                 )),
                 completion_provider: Some(CompletionOptions {
-                    resolve_provider: Some(false),
-                    trigger_characters: Some(vec![".".to_string(), " ".to_string()]),
+                    resolve_provider: Some(true),
+                    trigger_characters: Some(vec![
+                        ".".to_string(),
+                        ":".to_string(),
+                        " ".to_string(),
+                        "{".to_string(),
+                        "&".to_string(),
+                    ]),
                     work_done_progress_options: Default::default(),
                     all_commit_characters: None,
                     completion_item: None,
@@ -131,15 +257,23 @@ impl LanguageServer for Backend {
                     work_done_progress_options: WorkDoneProgressOptions::default(),
                 }),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
-                execute_command_provider: None,
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                code_lens_provider: if code_lens_enabled {
+                    Some(CodeLensOptions {
+                        resolve_provider: Some(false),
+                    })
+                } else {
+                    None
+                },
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: supported_commands(),
+                    work_done_progress_options: Default::default(),
+                }),
                 workspace: None,
                 semantic_tokens_provider: if semantic_tokens_enabled {
                     Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
                         SemanticTokensOptions {
-                            legend: SemanticTokensLegend {
-                                token_types: vec![SemanticTokenType::TYPE],
-                                token_modifiers: vec![],
-                            },
+                            legend: semantic_tokens_legend(),
                             range: Some(true),
                             full: Some(SemanticTokensFullOptions::Bool(true)),
                             work_done_progress_options: WorkDoneProgressOptions::default(),
@@ -149,18 +283,33 @@ impl LanguageServer for Backend {
                     None
                 },
                 definition_provider: Some(OneOf::Left(true)),
+                type_definition_provider: Some(TypeDefinitionProviderCapability::Simple(true)),
+                implementation_provider: Some(ImplementationProviderCapability::Simple(true)),
                 references_provider: Some(OneOf::Left(true)),
-                rename_provider: None,
+                document_highlight_provider: Some(OneOf::Left(true)),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+                call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+                rename_provider: Some(OneOf::Right(RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                })),
                 ..ServerCapabilities::default()
             },
         })
     }
 
     async fn initialized(&self, _: InitializedParams) {
+        let backend = self.clone();
+        tokio::spawn(async move {
+            backend.index_includes().await;
+            spawn_workspace_diagnostics(&backend);
+        });
         debug!("initialized!");
     }
 
     async fn shutdown(&self) -> Result<()> {
+        cancel_workspace_diagnostics(self);
         Ok(())
     }
 
@@ -187,10 +336,66 @@ impl LanguageServer for Backend {
         self.handle_goto_definition(params).await
     }
 
+    async fn goto_type_definition(
+        &self,
+        params: GotoTypeDefinitionParams,
+    ) -> Result<Option<GotoTypeDefinitionResponse>> {
+        self.handle_goto_type_definition(params).await
+    }
+
+    async fn goto_implementation(
+        &self,
+        params: GotoImplementationParams,
+    ) -> Result<Option<GotoImplementationResponse>> {
+        self.handle_goto_implementation(params).await
+    }
+
     async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
         self.handle_references(params).await
     }
 
+    async fn document_highlight(
+        &self,
+        params: DocumentHighlightParams,
+    ) -> Result<Option<Vec<DocumentHighlight>>> {
+        self.handle_document_highlight(params).await
+    }
+
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        self.handle_document_symbol(params).await
+    }
+
+    async fn selection_range(
+        &self,
+        params: SelectionRangeParams,
+    ) -> Result<Option<Vec<SelectionRange>>> {
+        self.handle_selection_range(params).await
+    }
+
+    async fn prepare_call_hierarchy(
+        &self,
+        params: CallHierarchyPrepareParams,
+    ) -> Result<Option<Vec<CallHierarchyItem>>> {
+        self.handle_prepare_call_hierarchy(params).await
+    }
+
+    async fn incoming_calls(
+        &self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyIncomingCall>>> {
+        self.handle_incoming_calls(params).await
+    }
+
+    async fn outgoing_calls(
+        &self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+        self.handle_outgoing_calls(params).await
+    }
+
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
         self.handle_hover(params).await
     }
@@ -209,49 +414,83 @@ impl LanguageServer for Backend {
         self.handle_semantic_tokens_range(params).await
     }
 
-    async fn inlay_hint(
-        &self,
-        _params: tower_lsp::lsp_types::InlayHintParams,
-    ) -> Result<Option<Vec<InlayHint>>> {
-        Ok(None)
+    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        self.handle_inlay_hint(params).await
+    }
+
+    async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+        self.handle_code_lens(params).await
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        self.handle_code_action(params).await
     }
 
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
         self.handle_completion(params).await
     }
 
+    async fn completion_resolve(&self, item: CompletionItem) -> Result<CompletionItem> {
+        self.handle_completion_resolve(item).await
+    }
+
     async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
         self.handle_signature_help(params).await
     }
 
-    async fn rename(&self, _params: RenameParams) -> Result<Option<WorkspaceEdit>> {
-        Ok(None)
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        self.handle_prepare_rename(params).await
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        self.handle_rename(params).await
     }
 
     async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
         self.handle_formatting(params).await
     }
 
-    async fn did_change_configuration(&self, _: DidChangeConfigurationParams) {
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        *self.client_settings.lock().await = params.settings;
         self.reload_workspace_config().await;
         debug!("configuration changed!");
     }
 
     async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
-        if let Some(folder) = params.event.added.first() {
+        let mut roots = self.workspace_roots.lock().await.clone();
+        for folder in &params.event.removed {
             if let Ok(path) = folder.uri.to_file_path() {
-                let mut workspace_root = self.workspace_root.lock().await;
-                *workspace_root = Some(path);
+                roots.retain(|root| root != &path);
             }
-        } else {
-            let mut workspace_root = self.workspace_root.lock().await;
-            *workspace_root = None;
         }
+        for folder in &params.event.added {
+            if let Ok(path) = folder.uri.to_file_path()
+                && !roots.contains(&path)
+            {
+                roots.push(path);
+            }
+        }
+        self.set_workspace_roots(roots).await;
         self.reload_workspace_config().await;
         debug!("workspace folders changed!");
     }
 
     async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        for change in &params.changes {
+            if is_include_uri(&change.uri) {
+                self.reindex_include_for_uri(&change.uri, change.typ).await;
+            }
+        }
+        if params
+            .changes
+            .iter()
+            .any(|change| change.typ != FileChangeType::CHANGED && is_class_uri(&change.uri))
+        {
+            self.package_class_cache.clear();
+        }
         for change in params.changes {
             if is_abl_toml_uri(&change.uri) {
                 self.reload_workspace_config().await;
@@ -264,10 +503,9 @@ impl LanguageServer for Backend {
         debug!("watched files have changed!");
     }
 
-    async fn execute_command(&self, _: ExecuteCommandParams) -> Result<Option<Value>> {
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<Value>> {
         debug!("command executed!");
-
-        Ok(None)
+        self.handle_execute_command(params).await
     }
 }
 
@@ -278,8 +516,10 @@ impl Backend {
             version,
             tree_version: -1,
             tree: None,
+            edited_tree: None,
             parser: StdMutex::new(self.new_abl_parser()),
             diag_task: None,
+            published_diags: Vec::new(),
         }
     }
 
@@ -306,6 +546,8 @@ impl Backend {
         text: String,
         clear_tree: bool,
     ) {
+        self.using_imports
+            .insert(uri.clone(), collect_using_imports(&text));
         match self.documents.entry(uri.clone()) {
             Entry::Occupied(mut entry) => {
                 let doc = entry.get_mut();
@@ -313,6 +555,7 @@ impl Backend {
                 doc.text = text;
                 if clear_tree {
                     doc.tree = None;
+                    doc.edited_tree = None;
                     doc.tree_version = -1;
                 }
             }
@@ -327,6 +570,124 @@ impl Backend {
         }
     }
 
+    /// Returns the document's `USING` imports, parsing `text` when the document is not open.
+    pub fn using_imports_for(&self, uri: &Url, text: &str) -> Vec<UsingImport> {
+        self.using_imports
+            .get(uri)
+            .map(|imports| imports.clone())
+            .unwrap_or_else(|| collect_using_imports(text))
+    }
+
+    /// Short class names imported by the document's `USING` directives, with qualified names.
+    pub async fn imported_class_names_for(&self, uri: &Url, text: &str) -> Vec<(String, String)> {
+        let imports = self.using_imports_for(uri, text);
+        if imports.is_empty() {
+            return Vec::new();
+        }
+        let missing = imports
+            .iter()
+            .filter(|import| import.is_package)
+            .map(|import| import.name.clone())
+            .filter(|package| {
+                !self
+                    .package_class_cache
+                    .contains_key(&package.to_ascii_uppercase())
+            })
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            let roots = self.propath_roots().await;
+            let listed = tokio::task::spawn_blocking(move || {
+                missing
+                    .into_iter()
+                    .map(|package| {
+                        let classes = list_package_class_names(&roots, &package);
+                        (package.to_ascii_uppercase(), classes)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await
+            .unwrap_or_default();
+            for (package, classes) in listed {
+                self.package_class_cache.insert(package, classes);
+            }
+        }
+        imported_class_names(&imports, |package| {
+            self.package_class_cache
+                .get(&package.to_ascii_uppercase())
+                .map(|classes| classes.clone())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Propath entries resolved against each workspace root (its own `abl.toml` propath when it
+    /// has one), followed by the roots themselves.
+    pub async fn propath_roots(&self) -> Vec<PathBuf> {
+        let workspace_roots = self.workspace_roots().await;
+        let propath = self.config.lock().await.propath.clone();
+        let root_propaths = self.root_propaths().await;
+        let mut roots = Vec::new();
+        for workspace_root in &workspace_roots {
+            let entries = root_propaths.get(workspace_root).unwrap_or(&propath);
+            for path in entries
+                .iter()
+                .filter_map(|entry| resolve_config_path(Some(workspace_root), entry))
+            {
+                if !roots.contains(&path) {
+                    roots.push(path);
+                }
+            }
+        }
+        roots.extend(workspace_roots);
+        roots
+    }
+
+    /// Propath of every workspace folder with its own `abl.toml`, other than the first.
+    async fn root_propaths(&self) -> RootPropaths {
+        self.folder_configs
+            .lock()
+            .await
+            .iter()
+            .map(|(root, config)| (root.clone(), config.propath.clone()))
+            .collect()
+    }
+
+    /// `.df` sources of the workspace config followed by those of the other folders' configs.
+    async fn dumpfile_sources(&self) -> Vec<(Option<String>, String)> {
+        let mut sources = self.config.lock().await.dumpfile_sources();
+        let workspace_roots = self.workspace_roots().await;
+        let folder_configs = self.folder_configs.lock().await;
+        for config in workspace_roots
+            .iter()
+            .filter_map(|root| folder_configs.get(root))
+        {
+            for source in config.dumpfile_sources() {
+                if !sources.contains(&source) {
+                    sources.push(source);
+                }
+            }
+        }
+        sources
+    }
+
+    /// All workspace folders, falling back to the single `workspace_root`.
+    pub async fn workspace_roots(&self) -> Vec<PathBuf> {
+        let roots = self.workspace_roots.lock().await.clone();
+        if !roots.is_empty() {
+            return roots;
+        }
+        self.workspace_root
+            .lock()
+            .await
+            .clone()
+            .into_iter()
+            .collect()
+    }
+
+    async fn set_workspace_roots(&self, roots: Vec<PathBuf>) {
+        *self.workspace_root.lock().await = roots.first().cloned();
+        *self.workspace_roots.lock().await = roots;
+    }
+
     pub fn get_document_tree_or_parse(&self, uri: &Url) -> Option<Tree> {
         let mut doc = self.documents.get_mut(uri)?;
         if doc.tree_version == doc.version
@@ -337,9 +698,10 @@ impl Backend {
         let text = doc.text.clone();
         let parsed = {
             let mut parser = doc.parser.lock().expect("ABL parser mutex poisoned");
-            parser.parse(text.as_str(), None)?
+            parse_source(&mut parser, &text, doc.edited_tree.as_ref())?
         };
         doc.tree = Some(parsed.clone());
+        doc.edited_tree = None;
         doc.tree_version = doc.version;
         Some(parsed)
     }
@@ -349,10 +711,42 @@ impl Backend {
             && doc.version == version
         {
             doc.tree = Some(tree);
+            doc.edited_tree = None;
             doc.tree_version = version;
         }
     }
 
+    /// Tree that incoming edits can be applied to: pending edits first, else the current tree.
+    pub fn take_document_edit_base_tree(&self, uri: &Url) -> Option<Tree> {
+        let mut doc = self.documents.get_mut(uri)?;
+        doc.edited_tree.take().or_else(|| {
+            if doc.tree_version == doc.version {
+                doc.tree.clone()
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn set_document_edited_tree(&self, uri: &Url, tree: Option<Tree>) {
+        if let Some(mut doc) = self.documents.get_mut(uri) {
+            doc.edited_tree = tree;
+        }
+    }
+
+    pub fn get_document_published_diags(&self, uri: &Url) -> Vec<Diagnostic> {
+        self.documents
+            .get(uri)
+            .map(|doc| doc.published_diags.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn set_document_published_diags(&self, uri: &Url, diags: Vec<Diagnostic>) {
+        if let Some(mut doc) = self.documents.get_mut(uri) {
+            doc.published_diags = diags;
+        }
+    }
+
     pub fn take_document_diag_task(&self, uri: &Url) -> Option<DiagTask> {
         let mut doc = self.documents.get_mut(uri)?;
         doc.diag_task.take()
@@ -402,15 +796,41 @@ impl Backend {
 
     pub async fn reload_workspace_config(&self) {
         let workspace_root = self.workspace_root.lock().await.clone();
-        let loaded = load_from_workspace_root(workspace_root.as_deref()).await;
+        let mut loaded = load_from_workspace_root(workspace_root.as_deref()).await;
+        if apply_client_settings(
+            &mut loaded.config,
+            &*self.client_settings.lock().await,
+            workspace_root.as_deref(),
+        ) {
+            debug!("applied client settings over workspace config");
+        }
 
-        let dumpfiles = loaded.config.dumpfile.clone();
         let mut config = self.config.lock().await;
         *config = loaded.config;
         drop(config);
 
-        self.reload_db_tables(workspace_root.as_deref(), &dumpfiles)
-            .await;
+        // Other folders keep their own propath and dump files; without an `abl.toml` they use
+        // the first folder's.
+        let workspace_roots = self.workspace_roots().await;
+        let mut folder_configs = HashMap::new();
+        for root in workspace_roots.iter().skip(1) {
+            let mut folder = load_from_workspace_root(Some(root)).await;
+            if !folder.path.as_deref().is_some_and(Path::exists) {
+                continue;
+            }
+            apply_client_settings(
+                &mut folder.config,
+                &*self.client_settings.lock().await,
+                Some(root),
+            );
+            folder_configs.insert(root.clone(), folder.config);
+        }
+        *self.folder_configs.lock().await = folder_configs;
+        // Propath or workspace folders may have changed under the cached listings.
+        self.package_class_cache.clear();
+
+        let dumpfiles = self.dumpfile_sources().await;
+        self.reload_db_tables(&workspace_roots, &dumpfiles).await;
 
         if let Some(path) = loaded.path {
             if Path::new(&path).exists() {
@@ -443,20 +863,58 @@ impl Backend {
         current_file: &Path,
         include: &str,
     ) -> Option<std::path::PathBuf> {
-        let workspace_root = self.workspace_root.lock().await.clone();
+        let workspace_roots = self.workspace_roots().await;
         let propath = self.config.lock().await.propath.clone();
-        resolve_include_path(workspace_root.as_deref(), &propath, current_file, include)
+        let root_propaths = self.root_propaths().await;
+        resolve_include_path(
+            &workspace_roots,
+            &root_propaths,
+            &propath,
+            current_file,
+            include,
+        )
+    }
+
+    /// Resolves a `RUN` program like an include; `.r` targets also match their `.p`/`.w` source.
+    pub async fn resolve_run_program_path(
+        &self,
+        current_file: &Path,
+        program: &str,
+    ) -> Option<std::path::PathBuf> {
+        if let Some(path) = self.resolve_include_path_for(current_file, program).await {
+            return Some(path);
+        }
+        let stem = program
+            .strip_suffix(".r")
+            .or_else(|| program.strip_suffix(".R"))?;
+        for ext in ["p", "w"] {
+            if let Some(path) = self
+                .resolve_include_path_for(current_file, &format!("{stem}.{ext}"))
+                .await
+            {
+                return Some(path);
+            }
+        }
+        None
     }
 
     pub async fn get_cached_include_parse(
         &self,
         include_path: &Path,
     ) -> Option<(Arc<String>, Tree)> {
-        if let Some(entry) = self.include_parse_cache.get(include_path) {
+        let modified = tokio::fs::metadata(include_path)
+            .await
+            .ok()
+            .and_then(|meta| meta.modified().ok());
+        if let Some(mut entry) = self.include_parse_cache.get_mut(include_path)
+            && modified.is_some()
+            && entry.modified == modified
+        {
+            entry.last_used = Instant::now();
             return Some((entry.text.clone(), entry.tree.clone()));
         }
 
-        let include_text = tokio::fs::read_to_string(include_path).await.ok()?;
+        let include_text = read_source_file(include_path).await.ok()?;
         let mut parser = self.new_abl_parser();
         let include_tree = parser.parse(include_text.as_str(), None)?;
         let text = Arc::new(include_text);
@@ -465,11 +923,127 @@ impl Backend {
             IncludeParseCacheEntry {
                 text: text.clone(),
                 tree: include_tree.clone(),
+                modified,
+                last_used: Instant::now(),
             },
         );
+        evict_least_recently_used(&self.include_parse_cache, MAX_INCLUDE_PARSE_CACHE_ENTRIES);
         Some((text, include_tree))
     }
 
+    /// Converts a location's tree-sitter byte columns into the UTF-16 columns clients expect,
+    /// reading the target from the open document, the include cache or disk.
+    pub async fn location_to_utf16(&self, mut location: Location) -> Location {
+        if location.range == Range::default() {
+            return location;
+        }
+        let mut text = self.get_document_text(&location.uri);
+        if text.is_none()
+            && let Ok(path) = location.uri.to_file_path()
+        {
+            let cached = self
+                .include_parse_cache
+                .get(&path)
+                .map(|entry| entry.text.to_string());
+            text = match cached {
+                Some(cached) => Some(cached),
+                None => read_source_file(&path).await.ok(),
+            };
+        }
+        if let Some(text) = text {
+            byte_ranges_to_utf16(&text, [&mut location.range]);
+        }
+        location
+    }
+
+    /// Pre-parses propath `.i` files into `include_parse_cache`, reporting work-done progress.
+    pub async fn index_includes(&self) {
+        let workspace_root = self.workspace_root.lock().await.clone();
+        let config = self.config.lock().await.clone();
+        let Ok(files) = tokio::task::spawn_blocking(move || {
+            collect_propath_include_files(
+                workspace_root.as_deref(),
+                &config.propath,
+                &config.workspace.exclude_dirs,
+                MAX_INCLUDE_PARSE_CACHE_ENTRIES,
+            )
+        })
+        .await
+        else {
+            return;
+        };
+        if files.is_empty() {
+            return;
+        }
+
+        let token = NumberOrString::String("abl/indexIncludes".to_string());
+        let progress = self
+            .client
+            .send_request::<request::WorkDoneProgressCreate>(WorkDoneProgressCreateParams {
+                token: token.clone(),
+            })
+            .await
+            .is_ok();
+        let total = files.len();
+        if progress {
+            self.send_index_progress(
+                &token,
+                WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                    title: "Indexing includes".to_string(),
+                    cancellable: Some(false),
+                    message: Some(format!("0/{total}")),
+                    percentage: Some(0),
+                }),
+            )
+            .await;
+        }
+
+        for (i, path) in files.iter().enumerate() {
+            self.get_cached_include_parse(path).await;
+            let done = i + 1;
+            if progress && (done % INCLUDE_INDEX_PROGRESS_STEP == 0 || done == total) {
+                self.send_index_progress(
+                    &token,
+                    WorkDoneProgress::Report(WorkDoneProgressReport {
+                        cancellable: Some(false),
+                        message: Some(format!("{done}/{total}")),
+                        percentage: Some((done * 100 / total) as u32),
+                    }),
+                )
+                .await;
+            }
+        }
+
+        if progress {
+            self.send_index_progress(
+                &token,
+                WorkDoneProgress::End(WorkDoneProgressEnd {
+                    message: Some(format!("Indexed {total} include file(s)")),
+                }),
+            )
+            .await;
+        }
+        debug!("indexed {} include file(s)", total);
+    }
+
+    async fn send_index_progress(&self, token: &NumberOrString, value: WorkDoneProgress) {
+        self.client
+            .send_notification::<notification::Progress>(ProgressParams {
+                token: token.clone(),
+                value: ProgressParamsValue::WorkDone(value),
+            })
+            .await;
+    }
+
+    async fn reindex_include_for_uri(&self, uri: &Url, change: FileChangeType) {
+        self.invalidate_include_caches_for_uri(uri);
+        if change != FileChangeType::DELETED
+            && let Ok(path) = uri.to_file_path()
+        {
+            self.get_cached_include_parse(&path).await;
+        }
+    }
+
     pub fn invalidate_include_caches_for_uri(&self, uri: &Url) {
         let Ok(path) = uri.to_file_path() else {
             return;
@@ -478,22 +1052,29 @@ impl Backend {
         self.include_parse_cache.remove(&path);
     }
 
-    async fn reload_db_tables(&self, workspace_root: Option<&Path>, dumpfiles: &[String]) {
+    async fn reload_db_tables(
+        &self,
+        workspace_roots: &[PathBuf],
+        dumpfiles: &[(Option<String>, String)],
+    ) {
         let mut tables = HashSet::<String>::new();
         let mut sequences = HashSet::<String>::new();
         let mut table_labels = HashMap::<String, String>::new();
         let mut definitions = HashMap::<String, Vec<Location>>::new();
         let mut sequence_definitions = HashMap::<String, Vec<Location>>::new();
         let mut field_definitions = HashMap::<String, Vec<Location>>::new();
+        let mut field_definitions_by_table = HashMap::<String, Vec<Location>>::new();
         let mut index_definitions = HashMap::<String, Vec<Location>>::new();
         let mut indexes_by_table = HashMap::<String, Vec<String>>::new();
         let mut index_fields_by_table_index = HashMap::<String, Vec<String>>::new();
+        let mut index_flags_by_table_index = HashMap::<String, DfIndexFlags>::new();
         let mut fields_by_table = HashMap::<String, Vec<DbFieldInfo>>::new();
-        for dumpfile in dumpfiles {
-            let Some(path) = resolve_dumpfile_path(workspace_root, dumpfile) else {
+        let mut db_name_by_table = HashMap::<String, String>::new();
+        for (db_name, dumpfile) in dumpfiles {
+            let Some(path) = resolve_dumpfile_path(workspace_roots, dumpfile) else {
                 continue;
             };
-            let Ok(contents) = tokio::fs::read_to_string(&path).await else {
+            let Ok(contents) = read_source_file(&path).await else {
                 continue;
             };
 
@@ -521,6 +1102,9 @@ impl Backend {
             );
             for site in sites {
                 let key = site.name.to_ascii_uppercase();
+                if let Some(db_name) = db_name {
+                    db_name_by_table.insert(key.clone(), db_name.clone());
+                }
                 tables.insert(key.clone());
                 table_labels.entry(key.clone()).or_insert(site.name);
                 definitions.entry(key).or_default().push(Location {
@@ -551,13 +1135,20 @@ impl Backend {
                 &mut field_sites,
             );
             for site in field_sites {
+                let location = Location {
+                    uri: uri.clone(),
+                    range: site.range,
+                };
+                if let Some(table) = &site.table {
+                    field_definitions_by_table
+                        .entry(table_field_key(table, &site.name))
+                        .or_default()
+                        .push(location.clone());
+                }
                 field_definitions
                     .entry(site.name.to_ascii_uppercase())
                     .or_default()
-                    .push(Location {
-                        uri: uri.clone(),
-                        range: site.range,
-                    });
+                    .push(location);
             }
 
             let mut table_fields = Vec::new();
@@ -608,8 +1199,9 @@ impl Backend {
                     .entry(table_upper.clone())
                     .or_default()
                     .push(pair.index.clone());
-                index_fields_by_table_index
-                    .insert(format!("{table_upper}\u{1f}{index_upper}"), pair.fields);
+                let key = format!("{table_upper}\u{1f}{index_upper}");
+                index_flags_by_table_index.insert(key.clone(), pair.flags);
+                index_fields_by_table_index.insert(key, pair.fields);
             }
         }
 
@@ -637,6 +1229,10 @@ impl Backend {
         for (k, v) in field_definitions {
             self.db_field_definitions.insert(k, v);
         }
+        self.db_field_definitions_by_table.clear();
+        for (k, v) in field_definitions_by_table {
+            self.db_field_definitions_by_table.insert(k, v);
+        }
         self.db_index_definitions.clear();
         for (k, v) in index_definitions {
             self.db_index_definitions.insert(k, v);
@@ -657,6 +1253,10 @@ impl Backend {
         for (k, v) in index_fields_by_table_index {
             self.db_index_fields_by_table_index.insert(k, v);
         }
+        self.db_index_flags_by_table_index.clear();
+        for (k, v) in index_flags_by_table_index {
+            self.db_index_flags_by_table_index.insert(k, v);
+        }
         for fields in fields_by_table.values_mut() {
             fields.sort_by(|a, b| {
                 a.name
@@ -666,10 +1266,27 @@ impl Backend {
             });
             fields.dedup_by(|a, b| a.name.eq_ignore_ascii_case(&b.name));
         }
+        let mut tables_by_field = HashMap::<String, Vec<String>>::new();
+        for (table, fields) in &fields_by_table {
+            for field in fields {
+                tables_by_field
+                    .entry(field.name.to_ascii_uppercase())
+                    .or_default()
+                    .push(table.clone());
+            }
+        }
         self.db_fields_by_table.clear();
         for (k, v) in fields_by_table {
             self.db_fields_by_table.insert(k, v);
         }
+        self.db_tables_by_field.clear();
+        for (k, v) in tables_by_field {
+            self.db_tables_by_field.insert(k, v);
+        }
+        self.db_name_by_table.clear();
+        for (k, v) in db_name_by_table {
+            self.db_name_by_table.insert(k, v);
+        }
         debug!(
             "loaded schema from dumpfile(s): tables={}, sequences={}, fields={}, indexes={}, table_field_sets={}",
             self.db_tables.len(),
@@ -680,11 +1297,10 @@ impl Backend {
         );
     }
 
-    async fn reload_db_tables_from_current_config(&self) {
-        let workspace_root = self.workspace_root.lock().await.clone();
-        let dumpfiles = self.config.lock().await.dumpfile.clone();
-        self.reload_db_tables(workspace_root.as_deref(), &dumpfiles)
-            .await;
+    pub async fn reload_db_tables_from_current_config(&self) {
+        let workspace_roots = self.workspace_roots().await;
+        let dumpfiles = self.dumpfile_sources().await;
+        self.reload_db_tables(&workspace_roots, &dumpfiles).await;
     }
 
     async fn is_configured_dumpfile_uri(&self, uri: &Url) -> bool {
@@ -692,19 +1308,194 @@ impl Backend {
             return false;
         };
 
-        let workspace_root = self.workspace_root.lock().await.clone();
-        let dumpfiles = self.config.lock().await.dumpfile.clone();
-        dumpfiles.iter().any(|dumpfile| {
-            resolve_dumpfile_path(workspace_root.as_deref(), dumpfile)
+        let workspace_roots = self.workspace_roots().await;
+        let dumpfiles = self.dumpfile_sources().await;
+        dumpfiles.iter().any(|(_, dumpfile)| {
+            resolve_dumpfile_path(&workspace_roots, dumpfile)
                 .map(|p| p == uri_path)
                 .unwrap_or(false)
         })
     }
 }
 
+/// Key for `db_field_definitions_by_table`: upper-cased `TABLE\u{1f}FIELD`.
+pub fn table_field_key(table: &str, field: &str) -> String {
+    format!(
+        "{}\u{1f}{}",
+        table.to_ascii_uppercase(),
+        field.to_ascii_uppercase()
+    )
+}
+
+fn evict_least_recently_used(cache: &DashMap<PathBuf, IncludeParseCacheEntry>, max_entries: usize) {
+    while cache.len() > max_entries {
+        let oldest = cache
+            .iter()
+            .min_by_key(|entry| entry.value().last_used)
+            .map(|entry| entry.key().clone());
+        let Some(oldest) = oldest else {
+            return;
+        };
+        cache.remove(&oldest);
+    }
+}
+
+fn is_include_uri(uri: &Url) -> bool {
+    uri.to_file_path()
+        .ok()
+        .and_then(|path| path.extension().map(|ext| ext.eq_ignore_ascii_case("i")))
+        .unwrap_or(false)
+}
+
+fn is_class_uri(uri: &Url) -> bool {
+    uri.to_file_path()
+        .ok()
+        .and_then(|path| path.extension().map(|ext| ext.eq_ignore_ascii_case("cls")))
+        .unwrap_or(false)
+}
+
 fn is_abl_toml_uri(uri: &Url) -> bool {
     uri.to_file_path()
         .ok()
         .and_then(|path| path.file_name().map(|name| name == "abl.toml"))
         .unwrap_or(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Backend, IncludeParseCacheEntry, evict_least_recently_used};
+    use crate::analysis::parse_abl;
+    use dashmap::DashMap;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use tower_lsp::LanguageServer;
+    use tower_lsp::lsp_types::{DidChangeWatchedFilesParams, FileChangeType, FileEvent, Url};
+
+    #[test]
+    fn evicts_least_recently_used_include_parses() {
+        let cache = DashMap::new();
+        let base = Instant::now();
+        for (idx, name) in ["a.i", "b.i", "c.i"].iter().enumerate() {
+            cache.insert(
+                PathBuf::from(name),
+                IncludeParseCacheEntry {
+                    text: Arc::new(String::new()),
+                    tree: parse_abl(""),
+                    modified: None,
+                    last_used: base + Duration::from_secs(idx as u64),
+                },
+            );
+        }
+        if let Some(mut entry) = cache.get_mut(&PathBuf::from("a.i")) {
+            entry.last_used = base + Duration::from_secs(10);
+        }
+
+        evict_least_recently_used(&cache, 2);
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains_key(&PathBuf::from("a.i")));
+        assert!(!cache.contains_key(&PathBuf::from("b.i")));
+        assert!(cache.contains_key(&PathBuf::from("c.i")));
+    }
+
+    #[tokio::test]
+    async fn resolves_includes_through_each_folders_own_propath() {
+        let base = std::env::temp_dir().join(format!(
+            "abl_ls_folder_propath_test_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("epoch")
+                .as_nanos()
+        ));
+        let app = base.join("app");
+        let lib = base.join("lib");
+        std::fs::create_dir_all(app.join("inc")).expect("create app");
+        std::fs::create_dir_all(lib.join("src")).expect("create lib");
+        std::fs::write(app.join("abl.toml"), "propath = [\"inc\"]\n").expect("write app config");
+        std::fs::write(
+            lib.join("abl.toml"),
+            "propath = [\"src\"]\ndumpfile = [\"lib.df\"]\n",
+        )
+        .expect("write lib config");
+        std::fs::write(app.join("inc/shared.i"), "/* app */").expect("write app include");
+        std::fs::write(lib.join("src/shared.i"), "/* lib */").expect("write lib include");
+        std::fs::write(lib.join("src/only_lib.i"), "").expect("write lib-only include");
+
+        let backend = Backend::for_tests();
+        backend
+            .set_workspace_roots(vec![app.clone(), lib.clone()])
+            .await;
+        backend.reload_workspace_config().await;
+
+        let app_file = app.join("main.p");
+        let lib_file = lib.join("main.p");
+        assert_eq!(
+            backend
+                .resolve_include_path_for(&app_file, "shared.i")
+                .await,
+            Some(app.join("inc").join("shared.i"))
+        );
+        assert_eq!(
+            backend
+                .resolve_include_path_for(&lib_file, "shared.i")
+                .await,
+            Some(lib.join("src").join("shared.i"))
+        );
+        assert_eq!(
+            backend
+                .resolve_include_path_for(&app_file, "only_lib.i")
+                .await,
+            Some(lib.join("src").join("only_lib.i"))
+        );
+        assert!(
+            backend
+                .dumpfile_sources()
+                .await
+                .contains(&(None, lib.join("lib.df").to_string_lossy().to_string()))
+        );
+
+        let _ = std::fs::remove_dir_all(base);
+    }
+
+    #[tokio::test]
+    async fn caches_package_listings_until_a_class_file_is_created() {
+        let base = std::env::temp_dir().join(format!(
+            "abl_ls_package_cache_test_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("epoch")
+                .as_nanos()
+        ));
+        let package_dir = base.join("app").join("util");
+        std::fs::create_dir_all(&package_dir).expect("create package dir");
+        std::fs::write(package_dir.join("Strings.cls"), "").expect("write class");
+
+        let backend = Backend::for_tests();
+        *backend.workspace_roots.lock().await = vec![base.clone()];
+        let uri = Url::from_file_path(base.join("main.p")).expect("uri");
+        let src = "USING app.util.*.\n";
+        backend.set_document_text_version(&uri, 1, src.to_string(), true);
+
+        let names = backend.imported_class_names_for(&uri, src).await;
+        assert_eq!(names.len(), 1);
+
+        let dates_path = package_dir.join("Dates.cls");
+        std::fs::write(&dates_path, "").expect("write class");
+        let names = backend.imported_class_names_for(&uri, src).await;
+        assert_eq!(names.len(), 1, "listing should come from the cache");
+
+        backend
+            .did_change_watched_files(DidChangeWatchedFilesParams {
+                changes: vec![FileEvent {
+                    uri: Url::from_file_path(&dates_path).expect("class uri"),
+                    typ: FileChangeType::CREATED,
+                }],
+            })
+            .await;
+        let names = backend.imported_class_names_for(&uri, src).await;
+        assert!(names.iter().any(|(short, _)| short == "Dates"));
+
+        let _ = std::fs::remove_dir_all(base);
+    }
+}
@@ -1,32 +1,117 @@
+use log::warn;
 use serde::Deserialize;
 use std::collections::{HashMap, HashSet};
 use std::io::ErrorKind;
 use std::path::Component;
 use std::path::{Path, PathBuf};
-use tower_lsp::lsp_types::InitializeParams;
+use tower_lsp::lsp_types::{DiagnosticSeverity, InitializeParams};
 
-#[derive(Debug, Clone, Deserialize, Default)]
+use crate::analysis::diagnostics::deprecated::DEFAULT_DEPRECATED_STATEMENTS;
+
+#[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
 pub struct AblConfig {
     pub completion: CompletionConfig,
     pub diagnostics: DiagnosticsConfig,
     pub formatting: FormattingConfig,
     pub semantic_tokens: SemanticTokensConfig,
+    pub inlay_hints: InlayHintsConfig,
+    pub code_lens: CodeLensConfig,
+    pub includes: IncludesConfig,
+    pub workspace: WorkspaceConfig,
     #[serde(default, deserialize_with = "deserialize_dumpfile")]
     pub dumpfile: Vec<String>,
+    /// `[[database]]` dumpfiles with the logical database name used in `dbname.table` references.
+    pub database: Vec<DatabaseConfig>,
     #[serde(default, deserialize_with = "deserialize_propath")]
     pub propath: Vec<String>,
+    /// Extensions of files parsed as ABL; other documents get no ABL diagnostics.
+    #[serde(deserialize_with = "deserialize_string_or_vec")]
+    pub file_extensions: Vec<String>,
+    /// Documents larger than this (in KiB) only get syntax errors and word completion; 0 disables.
+    pub max_file_size_kb: usize,
+}
+
+impl Default for AblConfig {
+    fn default() -> Self {
+        Self {
+            completion: CompletionConfig::default(),
+            diagnostics: DiagnosticsConfig::default(),
+            formatting: FormattingConfig::default(),
+            semantic_tokens: SemanticTokensConfig::default(),
+            inlay_hints: InlayHintsConfig::default(),
+            code_lens: CodeLensConfig::default(),
+            includes: IncludesConfig::default(),
+            workspace: WorkspaceConfig::default(),
+            dumpfile: Vec::new(),
+            database: Vec::new(),
+            propath: Vec::new(),
+            file_extensions: [".p", ".w", ".i", ".cls"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            max_file_size_kb: 2048,
+        }
+    }
+}
+
+impl AblConfig {
+    /// Whether `path` has one of `file_extensions` (case-insensitive, leading dot optional).
+    pub fn is_abl_path(&self, path: &Path) -> bool {
+        let Some(ext) = path.extension().and_then(|ext| ext.to_str()) else {
+            return false;
+        };
+        self.file_extensions
+            .iter()
+            .any(|candidate| candidate.trim_start_matches('.').eq_ignore_ascii_case(ext))
+    }
+
+    /// Whether a document of `len` bytes is above `max_file_size_kb`.
+    pub fn exceeds_max_file_size(&self, len: usize) -> bool {
+        self.max_file_size_kb > 0 && len > self.max_file_size_kb.saturating_mul(1024)
+    }
+
+    /// Every configured `.df` path with its logical database name (`None` for plain `dumpfile`s).
+    pub fn dumpfile_sources(&self) -> Vec<(Option<String>, String)> {
+        self.dumpfile
+            .iter()
+            .map(|file| (None, file.clone()))
+            .chain(
+                self.database
+                    .iter()
+                    .map(|db| (Some(db.name.clone()), db.file.clone())),
+            )
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct DatabaseConfig {
+    pub name: String,
+    pub file: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
 pub struct CompletionConfig {
     pub enabled: bool,
+    pub keywords: bool,
+    pub snippets: bool,
+    /// Offers widget attributes/methods after `hVar:` for HANDLE variables.
+    pub handle_members: bool,
+    /// Characters to type before completion is offered; 0 completes immediately.
+    pub min_prefix_length: usize,
 }
 
 impl Default for CompletionConfig {
     fn default() -> Self {
-        Self { enabled: true }
+        Self {
+            enabled: true,
+            keywords: false,
+            snippets: true,
+            handle_members: true,
+            min_prefix_length: 0,
+        }
     }
 }
 
@@ -34,20 +119,105 @@ impl Default for CompletionConfig {
 #[serde(default)]
 pub struct DiagnosticsConfig {
     pub enabled: bool,
+    pub scope: DiagnosticsScope,
     pub unknown_variables: DiagnosticFeatureConfig,
-    pub unknown_functions: DiagnosticFeatureConfig,
+    pub unknown_functions: UnknownFunctionsConfig,
+    pub unknown_includes: DiagnosticFeatureConfig,
+    /// `RUN prog.p` targets that cannot be found on PROPATH.
+    pub unknown_programs: DiagnosticFeatureConfig,
+    pub type_checks: TypeChecksConfig,
+    /// Hints `DEFINE VARIABLE`s that are never read.
+    pub unused_variables: bool,
+    /// Hints variable and temp-table definitions without `NO-UNDO`.
+    pub require_no_undo: bool,
+    /// Flags ordering comparisons and arithmetic with the unknown value `?`.
+    pub unknown_value_checks: bool,
+    /// Warns about assignments to `INPUT` parameters, which never reach the caller.
+    pub input_param_writes: bool,
+    /// Flags `table.field = ...` on DB tables with no `FOR`/`FIND`/`CREATE` of the table in scope.
+    pub record_scope_checks: bool,
+    /// Warns about function calls above every in-file definition and `FORWARD` declaration.
+    pub call_before_definition: bool,
+    /// Node kinds or leading statement keywords warned about as deprecated, with their messages.
+    pub deprecated_statements: HashMap<String, String>,
+    /// Quiet period after an edit before the semantic pass runs; syntax errors are published at once.
+    pub debounce_ms: u64,
+    /// Site-specific names treated like builtin functions/variables by unknown-symbol checks.
+    #[serde(default, deserialize_with = "deserialize_string_or_vec")]
+    pub extra_builtin_functions: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_string_or_vec")]
+    pub extra_builtin_variables: Vec<String>,
 }
 
 impl Default for DiagnosticsConfig {
     fn default() -> Self {
         Self {
             enabled: true,
+            scope: DiagnosticsScope::Open,
             unknown_variables: DiagnosticFeatureConfig::default(),
-            unknown_functions: DiagnosticFeatureConfig::default(),
+            unknown_functions: UnknownFunctionsConfig::default(),
+            unknown_includes: DiagnosticFeatureConfig::default(),
+            unknown_programs: DiagnosticFeatureConfig::default(),
+            type_checks: TypeChecksConfig::default(),
+            unused_variables: false,
+            require_no_undo: false,
+            unknown_value_checks: false,
+            input_param_writes: false,
+            record_scope_checks: false,
+            call_before_definition: false,
+            deprecated_statements: DEFAULT_DEPRECATED_STATEMENTS
+                .iter()
+                .map(|(key, message)| (key.to_string(), message.to_string()))
+                .collect(),
+            debounce_ms: 150,
+            extra_builtin_functions: Vec::new(),
+            extra_builtin_variables: Vec::new(),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticsScope {
+    /// Only documents opened in the editor.
+    #[default]
+    Open,
+    /// Also checks unopened `.p`/`.cls` files on the PROPATH in the background.
+    Workspace,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SeverityLevel {
+    #[default]
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl SeverityLevel {
+    pub fn to_lsp(self) -> DiagnosticSeverity {
+        match self {
+            Self::Error => DiagnosticSeverity::ERROR,
+            Self::Warning => DiagnosticSeverity::WARNING,
+            Self::Information => DiagnosticSeverity::INFORMATION,
+            Self::Hint => DiagnosticSeverity::HINT,
+        }
+    }
+}
+
+/// What unknown-function diagnostics do in `.cls` files, where most unresolved plain calls are
+/// method invocations.
+#[derive(Debug, Clone, Copy, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClassFileBehavior {
+    Error,
+    Warning,
+    #[default]
+    Off,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
 pub struct DiagnosticFeatureConfig {
@@ -56,6 +226,7 @@ pub struct DiagnosticFeatureConfig {
     pub exclude: Vec<String>,
     #[serde(default, deserialize_with = "deserialize_string_or_vec")]
     pub ignore: Vec<String>,
+    pub severity: SeverityLevel,
 }
 
 impl Default for DiagnosticFeatureConfig {
@@ -64,6 +235,32 @@ impl Default for DiagnosticFeatureConfig {
             enabled: true,
             exclude: Vec::new(),
             ignore: Vec::new(),
+            severity: SeverityLevel::Error,
+        }
+    }
+}
+
+/// `diagnostics.unknown_functions`: the shared feature keys plus the `.cls` file handling.
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+pub struct UnknownFunctionsConfig {
+    #[serde(flatten)]
+    pub feature: DiagnosticFeatureConfig,
+    pub class_file_behavior: ClassFileBehavior,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TypeChecksConfig {
+    pub enabled: bool,
+    pub severity: SeverityLevel,
+}
+
+impl Default for TypeChecksConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            severity: SeverityLevel::Error,
         }
     }
 }
@@ -75,6 +272,13 @@ pub struct FormattingConfig {
     pub indent_size: usize,
     pub use_tabs: bool,
     pub idempotence: bool,
+    pub keyword_case: KeywordCase,
+    pub trim_trailing_whitespace: bool,
+    pub insert_final_newline: bool,
+    /// Aligns `AS` in runs of adjacent `DEFINE VARIABLE` lines.
+    pub align_define_as: bool,
+    /// Splits multi-target `ASSIGN` statements into one assignment per line.
+    pub expand_assign: bool,
 }
 
 impl Default for FormattingConfig {
@@ -84,10 +288,25 @@ impl Default for FormattingConfig {
             indent_size: 2,
             use_tabs: false,
             idempotence: true,
+            keyword_case: KeywordCase::Preserve,
+            trim_trailing_whitespace: true,
+            insert_final_newline: true,
+            align_define_as: false,
+            expand_assign: false,
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeywordCase {
+    Upper,
+    Lower,
+    /// Leaves keywords as written.
+    #[default]
+    Preserve,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
 pub struct SemanticTokensConfig {
@@ -100,28 +319,73 @@ impl Default for SemanticTokensConfig {
     }
 }
 
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+pub struct InlayHintsConfig {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+pub struct CodeLensConfig {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct IncludesConfig {
+    pub max_depth: usize,
+}
+
+impl Default for IncludesConfig {
+    fn default() -> Self {
+        Self { max_depth: 16 }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceConfig {
+    /// Directory names skipped by workspace scans (matched per path component, case-insensitive).
+    #[serde(deserialize_with = "deserialize_string_or_vec")]
+    pub exclude_dirs: Vec<String>,
+}
+
+impl Default for WorkspaceConfig {
+    fn default() -> Self {
+        Self {
+            exclude_dirs: ["build", "dist", ".git"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct LoadedAblConfig {
     pub config: AblConfig,
     pub path: Option<PathBuf>,
 }
 
-pub fn find_workspace_root(params: &InitializeParams) -> Option<PathBuf> {
-    if let Some(folders) = &params.workspace_folders {
-        for folder in folders {
-            if let Ok(path) = folder.uri.to_file_path() {
-                return Some(path);
-            }
-        }
-    }
-
-    if let Some(root_uri) = &params.root_uri
-        && let Ok(path) = root_uri.to_file_path()
-    {
-        return Some(path);
+/// All workspace folders in client order, or the legacy `root_uri` when no folders are sent.
+pub fn find_workspace_roots(params: &InitializeParams) -> Vec<PathBuf> {
+    let roots = params
+        .workspace_folders
+        .iter()
+        .flatten()
+        .filter_map(|folder| folder.uri.to_file_path().ok())
+        .collect::<Vec<_>>();
+    if !roots.is_empty() {
+        return roots;
     }
 
-    None
+    params
+        .root_uri
+        .as_ref()
+        .and_then(|root_uri| root_uri.to_file_path().ok())
+        .into_iter()
+        .collect()
 }
 
 pub async fn load_from_workspace_root(root: Option<&Path>) -> LoadedAblConfig {
@@ -164,24 +428,52 @@ struct PartialAblConfig {
     diagnostics: Option<PartialDiagnosticsConfig>,
     formatting: Option<PartialFormattingConfig>,
     semantic_tokens: Option<PartialSemanticTokensConfig>,
+    inlay_hints: Option<PartialInlayHintsConfig>,
+    code_lens: Option<PartialCodeLensConfig>,
+    includes: Option<PartialIncludesConfig>,
+    workspace: Option<PartialWorkspaceConfig>,
     #[serde(default, deserialize_with = "deserialize_optional_string_or_vec")]
     dumpfile: Option<Vec<String>>,
-    #[serde(default, deserialize_with = "deserialize_optional_string_or_vec")]
+    database: Option<Vec<DatabaseConfig>>,
+    #[serde(default, deserialize_with = "deserialize_optional_propath")]
     propath: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "deserialize_optional_string_or_vec")]
+    file_extensions: Option<Vec<String>>,
+    max_file_size_kb: Option<usize>,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
 #[serde(default)]
 struct PartialCompletionConfig {
     enabled: Option<bool>,
+    keywords: Option<bool>,
+    snippets: Option<bool>,
+    handle_members: Option<bool>,
+    min_prefix_length: Option<usize>,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
 #[serde(default)]
 struct PartialDiagnosticsConfig {
     enabled: Option<bool>,
+    scope: Option<DiagnosticsScope>,
     unknown_variables: Option<PartialDiagnosticFeatureConfig>,
-    unknown_functions: Option<PartialDiagnosticFeatureConfig>,
+    unknown_functions: Option<PartialUnknownFunctionsConfig>,
+    unknown_includes: Option<PartialDiagnosticFeatureConfig>,
+    unknown_programs: Option<PartialDiagnosticFeatureConfig>,
+    type_checks: Option<PartialTypeChecksConfig>,
+    unused_variables: Option<bool>,
+    require_no_undo: Option<bool>,
+    unknown_value_checks: Option<bool>,
+    input_param_writes: Option<bool>,
+    record_scope_checks: Option<bool>,
+    call_before_definition: Option<bool>,
+    deprecated_statements: Option<HashMap<String, String>>,
+    debounce_ms: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_optional_string_or_vec")]
+    extra_builtin_functions: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "deserialize_optional_string_or_vec")]
+    extra_builtin_variables: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -192,6 +484,22 @@ struct PartialDiagnosticFeatureConfig {
     exclude: Option<Vec<String>>,
     #[serde(default, deserialize_with = "deserialize_optional_string_or_vec")]
     ignore: Option<Vec<String>>,
+    severity: Option<SeverityLevel>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct PartialUnknownFunctionsConfig {
+    #[serde(flatten)]
+    feature: PartialDiagnosticFeatureConfig,
+    class_file_behavior: Option<ClassFileBehavior>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct PartialTypeChecksConfig {
+    enabled: Option<bool>,
+    severity: Option<SeverityLevel>,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -201,6 +509,11 @@ struct PartialFormattingConfig {
     indent_size: Option<usize>,
     use_tabs: Option<bool>,
     idempotence: Option<bool>,
+    keyword_case: Option<KeywordCase>,
+    trim_trailing_whitespace: Option<bool>,
+    insert_final_newline: Option<bool>,
+    align_define_as: Option<bool>,
+    expand_assign: Option<bool>,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -209,6 +522,31 @@ struct PartialSemanticTokensConfig {
     enabled: Option<bool>,
 }
 
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct PartialInlayHintsConfig {
+    enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct PartialCodeLensConfig {
+    enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct PartialIncludesConfig {
+    max_depth: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct PartialWorkspaceConfig {
+    #[serde(default, deserialize_with = "deserialize_optional_string_or_vec")]
+    exclude_dirs: Option<Vec<String>>,
+}
+
 async fn load_with_inheritance(path: &Path, root_partial: PartialAblConfig) -> AblConfig {
     let root_identity = path_identity(path);
     let mut partials = HashMap::<PathBuf, PartialAblConfig>::new();
@@ -299,41 +637,133 @@ fn path_identity(path: &Path) -> PathBuf {
     std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
 }
 
+/// Merges the `abl` section of client settings over `base` (file < client settings).
+/// Relative paths resolve against `root`; returns `false` when there is no usable section, with a
+/// warning logged when the section is present but malformed.
+pub fn apply_client_settings(
+    base: &mut AblConfig,
+    settings: &serde_json::Value,
+    root: Option<&Path>,
+) -> bool {
+    let Some(section) = settings.get("abl").filter(|section| !section.is_null()) else {
+        return false;
+    };
+    let partial = match PartialAblConfig::deserialize(section) {
+        Ok(partial) => partial,
+        Err(err) => {
+            warn!("ignoring client `abl` settings that do not match the config schema: {err}");
+            return false;
+        }
+    };
+    let config_path = root.unwrap_or_else(|| Path::new(".")).join("abl.toml");
+    merge_partial_into(base, &partial, &config_path);
+    true
+}
+
 fn merge_partial_into(base: &mut AblConfig, partial: &PartialAblConfig, config_path: &Path) {
-    if let Some(completion) = &partial.completion
-        && let Some(enabled) = completion.enabled
-    {
-        base.completion.enabled = enabled;
+    if let Some(completion) = &partial.completion {
+        if let Some(enabled) = completion.enabled {
+            base.completion.enabled = enabled;
+        }
+        if let Some(keywords) = completion.keywords {
+            base.completion.keywords = keywords;
+        }
+        if let Some(snippets) = completion.snippets {
+            base.completion.snippets = snippets;
+        }
+        if let Some(handle_members) = completion.handle_members {
+            base.completion.handle_members = handle_members;
+        }
+        if let Some(min_prefix_length) = completion.min_prefix_length {
+            base.completion.min_prefix_length = min_prefix_length;
+        }
     }
 
     if let Some(diagnostics) = &partial.diagnostics {
         if let Some(enabled) = diagnostics.enabled {
             base.diagnostics.enabled = enabled;
         }
+        if let Some(scope) = diagnostics.scope {
+            base.diagnostics.scope = scope;
+        }
         if let Some(unknown_variables) = &diagnostics.unknown_variables {
-            if let Some(enabled) = unknown_variables.enabled {
-                base.diagnostics.unknown_variables.enabled = enabled;
-            }
-            if let Some(exclude) = &unknown_variables.exclude {
-                base.diagnostics.unknown_variables.exclude =
-                    resolve_path_list_relative_to_config(config_path, exclude);
-            }
-            if let Some(ignore) = &unknown_variables.ignore {
-                base.diagnostics.unknown_variables.ignore = ignore.clone();
-            }
+            merge_feature_partial_into(
+                &mut base.diagnostics.unknown_variables,
+                unknown_variables,
+                config_path,
+            );
         }
         if let Some(unknown_functions) = &diagnostics.unknown_functions {
-            if let Some(enabled) = unknown_functions.enabled {
-                base.diagnostics.unknown_functions.enabled = enabled;
+            merge_feature_partial_into(
+                &mut base.diagnostics.unknown_functions.feature,
+                &unknown_functions.feature,
+                config_path,
+            );
+            if let Some(class_file_behavior) = unknown_functions.class_file_behavior {
+                base.diagnostics.unknown_functions.class_file_behavior = class_file_behavior;
             }
-            if let Some(exclude) = &unknown_functions.exclude {
-                base.diagnostics.unknown_functions.exclude =
-                    resolve_path_list_relative_to_config(config_path, exclude);
+        }
+        if let Some(unknown_includes) = &diagnostics.unknown_includes {
+            merge_feature_partial_into(
+                &mut base.diagnostics.unknown_includes,
+                unknown_includes,
+                config_path,
+            );
+        }
+        if let Some(unknown_programs) = &diagnostics.unknown_programs {
+            merge_feature_partial_into(
+                &mut base.diagnostics.unknown_programs,
+                unknown_programs,
+                config_path,
+            );
+        }
+        if let Some(type_checks) = &diagnostics.type_checks {
+            if let Some(enabled) = type_checks.enabled {
+                base.diagnostics.type_checks.enabled = enabled;
             }
-            if let Some(ignore) = &unknown_functions.ignore {
-                base.diagnostics.unknown_functions.ignore = ignore.clone();
+            if let Some(severity) = type_checks.severity {
+                base.diagnostics.type_checks.severity = severity;
+            }
+        }
+        if let Some(unused_variables) = diagnostics.unused_variables {
+            base.diagnostics.unused_variables = unused_variables;
+        }
+        if let Some(require_no_undo) = diagnostics.require_no_undo {
+            base.diagnostics.require_no_undo = require_no_undo;
+        }
+        if let Some(unknown_value_checks) = diagnostics.unknown_value_checks {
+            base.diagnostics.unknown_value_checks = unknown_value_checks;
+        }
+        if let Some(input_param_writes) = diagnostics.input_param_writes {
+            base.diagnostics.input_param_writes = input_param_writes;
+        }
+        if let Some(record_scope_checks) = diagnostics.record_scope_checks {
+            base.diagnostics.record_scope_checks = record_scope_checks;
+        }
+        if let Some(call_before_definition) = diagnostics.call_before_definition {
+            base.diagnostics.call_before_definition = call_before_definition;
+        }
+        if let Some(deprecated_statements) = &diagnostics.deprecated_statements {
+            // Entries merge over the defaults; an empty message removes one.
+            for (key, message) in deprecated_statements {
+                if message.is_empty() {
+                    base.diagnostics.deprecated_statements.remove(key);
+                } else {
+                    base.diagnostics
+                        .deprecated_statements
+                        .insert(key.clone(), message.clone());
+                }
             }
         }
+        if let Some(debounce_ms) = diagnostics.debounce_ms {
+            base.diagnostics.debounce_ms = debounce_ms;
+        }
+        if let Some(extra) = &diagnostics.extra_builtin_functions {
+            base.diagnostics.extra_builtin_functions = extra.clone();
+        }
+        if let Some(extra) = &diagnostics.extra_builtin_variables {
+            base.diagnostics.extra_builtin_variables = extra.clone();
+        }
     }
 
     if let Some(formatting) = &partial.formatting {
@@ -349,6 +779,21 @@ fn merge_partial_into(base: &mut AblConfig, partial: &PartialAblConfig, config_p
         if let Some(idempotence) = formatting.idempotence {
             base.formatting.idempotence = idempotence;
         }
+        if let Some(keyword_case) = formatting.keyword_case {
+            base.formatting.keyword_case = keyword_case;
+        }
+        if let Some(trim_trailing_whitespace) = formatting.trim_trailing_whitespace {
+            base.formatting.trim_trailing_whitespace = trim_trailing_whitespace;
+        }
+        if let Some(insert_final_newline) = formatting.insert_final_newline {
+            base.formatting.insert_final_newline = insert_final_newline;
+        }
+        if let Some(align_define_as) = formatting.align_define_as {
+            base.formatting.align_define_as = align_define_as;
+        }
+        if let Some(expand_assign) = formatting.expand_assign {
+            base.formatting.expand_assign = expand_assign;
+        }
     }
 
     if let Some(semantic_tokens) = &partial.semantic_tokens
@@ -357,15 +802,54 @@ fn merge_partial_into(base: &mut AblConfig, partial: &PartialAblConfig, config_p
         base.semantic_tokens.enabled = enabled;
     }
 
+    if let Some(inlay_hints) = &partial.inlay_hints
+        && let Some(enabled) = inlay_hints.enabled
+    {
+        base.inlay_hints.enabled = enabled;
+    }
+
+    if let Some(code_lens) = &partial.code_lens
+        && let Some(enabled) = code_lens.enabled
+    {
+        base.code_lens.enabled = enabled;
+    }
+
+    if let Some(includes) = &partial.includes
+        && let Some(max_depth) = includes.max_depth
+    {
+        base.includes.max_depth = max_depth;
+    }
+
+    if let Some(workspace) = &partial.workspace
+        && let Some(exclude_dirs) = &workspace.exclude_dirs
+    {
+        base.workspace.exclude_dirs = exclude_dirs.clone();
+    }
+
     if let Some(dumpfile) = &partial.dumpfile {
         base.dumpfile
             .extend(resolve_path_list_relative_to_config(config_path, dumpfile));
     }
+    if let Some(database) = &partial.database {
+        for db in database {
+            let file = resolve_path_list_relative_to_config(config_path, &[db.file.clone()]);
+            base.database.push(DatabaseConfig {
+                name: db.name.clone(),
+                file: file.into_iter().next().unwrap_or_default(),
+            });
+        }
+    }
     if let Some(propath) = &partial.propath {
         for resolved in resolve_path_list_relative_to_config(config_path, propath) {
             push_unique_string_value(&mut base.propath, resolved);
         }
     }
+    if let Some(file_extensions) = &partial.file_extensions {
+        base.file_extensions = file_extensions.clone();
+    }
+    if let Some(max_file_size_kb) = partial.max_file_size_kb {
+        base.max_file_size_kb = max_file_size_kb;
+    }
 
     let config_dir = config_path
         .parent()
@@ -374,6 +858,25 @@ fn merge_partial_into(base: &mut AblConfig, partial: &PartialAblConfig, config_p
     push_unique_string_path(&mut base.propath, &config_dir);
 }
 
+fn merge_feature_partial_into(
+    base: &mut DiagnosticFeatureConfig,
+    partial: &PartialDiagnosticFeatureConfig,
+    config_path: &Path,
+) {
+    if let Some(enabled) = partial.enabled {
+        base.enabled = enabled;
+    }
+    if let Some(exclude) = &partial.exclude {
+        base.exclude = resolve_path_list_relative_to_config(config_path, exclude);
+    }
+    if let Some(ignore) = &partial.ignore {
+        base.ignore = ignore.clone();
+    }
+    if let Some(severity) = partial.severity {
+        base.severity = severity;
+    }
+}
+
 fn resolve_path_list_relative_to_config(config_path: &Path, values: &[String]) -> Vec<String> {
     let base_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
     values
@@ -425,7 +928,37 @@ fn deserialize_propath<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    deserialize_optional_string_or_vec(deserializer).map(|v| v.unwrap_or_default())
+    deserialize_optional_propath(deserializer).map(|v| v.unwrap_or_default())
+}
+
+/// Like `deserialize_optional_string_or_vec`, but a single string is a classic ABL PROPATH
+/// (`".:src:includes"`) and is split on the OS path separator and `,`.
+fn deserialize_optional_propath<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrVec {
+        Single(String),
+        Multiple(Vec<String>),
+    }
+
+    let parsed = Option::<StringOrVec>::deserialize(deserializer)?;
+    Ok(parsed.map(|v| match v {
+        StringOrVec::Single(propath) => split_propath(&propath),
+        StringOrVec::Multiple(paths) => paths,
+    }))
+}
+
+fn split_propath(propath: &str) -> Vec<String> {
+    let separator = if cfg!(windows) { ';' } else { ':' };
+    propath
+        .split([separator, ','])
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect()
 }
 
 fn deserialize_string_or_vec<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
@@ -457,8 +990,39 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::{AblConfig, load_from_workspace_root};
+    use super::{
+        AblConfig, ClassFileBehavior, DiagnosticsScope, KeywordCase, SeverityLevel,
+        apply_client_settings, find_workspace_roots, load_from_workspace_root,
+    };
+    use std::path::{Path, PathBuf};
     use std::time::{SystemTime, UNIX_EPOCH};
+    use tower_lsp::lsp_types::{InitializeParams, Url, WorkspaceFolder};
+
+    #[test]
+    fn finds_all_workspace_folders() {
+        let folder = |path: &str| WorkspaceFolder {
+            uri: Url::from_file_path(path).expect("folder uri"),
+            name: path.to_string(),
+        };
+        let params = InitializeParams {
+            workspace_folders: Some(vec![folder("/work/app"), folder("/work/lib")]),
+            root_uri: Some(Url::from_file_path("/work/legacy").expect("root uri")),
+            ..Default::default()
+        };
+        assert_eq!(
+            find_workspace_roots(&params),
+            vec![PathBuf::from("/work/app"), PathBuf::from("/work/lib")]
+        );
+
+        let params = InitializeParams {
+            root_uri: Some(Url::from_file_path("/work/legacy").expect("root uri")),
+            ..Default::default()
+        };
+        assert_eq!(
+            find_workspace_roots(&params),
+            vec![PathBuf::from("/work/legacy")]
+        );
+    }
 
     #[test]
     fn parses_dumpfile_and_propath_as_single_string() {
@@ -488,6 +1052,40 @@ propath = ["/global/a", "relative/includes"]
         assert_eq!(cfg.propath, vec!["/global/a", "relative/includes"]);
     }
 
+    #[test]
+    fn splits_delimited_propath_string() {
+        let sep = if cfg!(windows) { ';' } else { ':' };
+        let cfg: AblConfig = toml::from_str(&format!("propath = \".{sep}src,includes{sep}\"\n"))
+            .expect("parse config");
+        assert_eq!(cfg.propath, vec![".", "src", "includes"]);
+
+        let cfg: AblConfig = toml::from_str("propath = [\".\", \"src\"]\n").expect("parse config");
+        assert_eq!(cfg.propath, vec![".", "src"]);
+    }
+
+    #[test]
+    fn parses_database_entries_alongside_plain_dumpfiles() {
+        let cfg: AblConfig = toml::from_str(
+            r#"
+dumpfile = "legacy.df"
+
+[[database]]
+name = "sports"
+file = "sports.df"
+"#,
+        )
+        .expect("parse config");
+
+        assert_eq!(cfg.dumpfile, vec!["legacy.df"]);
+        assert_eq!(
+            cfg.dumpfile_sources(),
+            vec![
+                (None, "legacy.df".to_string()),
+                (Some("sports".to_string()), "sports.df".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn parses_formatting_settings() {
         let cfg: AblConfig = toml::from_str(
@@ -497,6 +1095,10 @@ enabled = true
 indent_size = 4
 use_tabs = false
 idempotence = false
+keyword_case = "upper"
+trim_trailing_whitespace = false
+align_define_as = true
+expand_assign = true
 "#,
         )
         .expect("parse config");
@@ -505,6 +1107,47 @@ idempotence = false
         assert_eq!(cfg.formatting.indent_size, 4);
         assert!(!cfg.formatting.use_tabs);
         assert!(!cfg.formatting.idempotence);
+        assert_eq!(cfg.formatting.keyword_case, KeywordCase::Upper);
+        assert!(!cfg.formatting.trim_trailing_whitespace);
+        assert!(cfg.formatting.insert_final_newline);
+        assert!(cfg.formatting.align_define_as);
+        assert!(!AblConfig::default().formatting.align_define_as);
+        assert!(cfg.formatting.expand_assign);
+        assert!(!AblConfig::default().formatting.expand_assign);
+        assert_eq!(
+            AblConfig::default().formatting.keyword_case,
+            KeywordCase::Preserve
+        );
+    }
+
+    #[test]
+    fn inlay_hints_are_disabled_by_default() {
+        let cfg: AblConfig = toml::from_str("").expect("parse config");
+        assert!(!cfg.inlay_hints.enabled);
+
+        let cfg: AblConfig = toml::from_str(
+            r#"
+[inlay_hints]
+enabled = true
+"#,
+        )
+        .expect("parse config");
+        assert!(cfg.inlay_hints.enabled);
+    }
+
+    #[test]
+    fn code_lens_is_disabled_by_default() {
+        let cfg: AblConfig = toml::from_str("").expect("parse config");
+        assert!(!cfg.code_lens.enabled);
+
+        let cfg: AblConfig = toml::from_str(
+            r#"
+[code_lens]
+enabled = true
+"#,
+        )
+        .expect("parse config");
+        assert!(cfg.code_lens.enabled);
     }
 
     #[test]
@@ -519,6 +1162,7 @@ ignore = ["BatchRun", "Today"]
 enabled = false
 exclude = "special.p"
 ignore = "custom_func"
+class_file_behavior = "warning"
 "#,
         )
         .expect("parse config");
@@ -531,12 +1175,292 @@ ignore = "custom_func"
             cfg.diagnostics.unknown_variables.ignore,
             vec!["BatchRun", "Today"]
         );
-        assert!(!cfg.diagnostics.unknown_functions.enabled);
-        assert_eq!(cfg.diagnostics.unknown_functions.exclude, vec!["special.p"]);
+        assert!(!cfg.diagnostics.unknown_functions.feature.enabled);
         assert_eq!(
-            cfg.diagnostics.unknown_functions.ignore,
+            cfg.diagnostics.unknown_functions.feature.exclude,
+            vec!["special.p"]
+        );
+        assert_eq!(
+            cfg.diagnostics.unknown_functions.feature.ignore,
             vec!["custom_func"]
         );
+        assert_eq!(
+            cfg.diagnostics.unknown_functions.class_file_behavior,
+            ClassFileBehavior::Warning
+        );
+    }
+
+    #[test]
+    fn parses_diagnostic_severities() {
+        let cfg: AblConfig = toml::from_str(
+            r#"
+[diagnostics.unknown_variables]
+severity = "warning"
+
+[diagnostics.type_checks]
+severity = "hint"
+"#,
+        )
+        .expect("parse config");
+
+        assert_eq!(
+            cfg.diagnostics.unknown_variables.severity,
+            SeverityLevel::Warning
+        );
+        assert_eq!(
+            cfg.diagnostics.unknown_functions.feature.severity,
+            SeverityLevel::Error
+        );
+        assert!(cfg.diagnostics.type_checks.enabled);
+        assert_eq!(cfg.diagnostics.type_checks.severity, SeverityLevel::Hint);
+    }
+
+    #[test]
+    fn parses_diagnostics_scope() {
+        assert_eq!(
+            AblConfig::default().diagnostics.scope,
+            DiagnosticsScope::Open
+        );
+        let cfg: AblConfig = toml::from_str(
+            r#"
+[diagnostics]
+scope = "workspace"
+"#,
+        )
+        .expect("parse config");
+        assert_eq!(cfg.diagnostics.scope, DiagnosticsScope::Workspace);
+    }
+
+    #[test]
+    fn parses_file_extensions() {
+        let cfg = AblConfig::default();
+        assert!(cfg.is_abl_path(Path::new("/src/a.P")));
+        assert!(cfg.is_abl_path(Path::new("/src/Customer.cls")));
+        assert!(!cfg.is_abl_path(Path::new("/db/sports.df")));
+        assert!(!cfg.is_abl_path(Path::new("/src/README")));
+
+        let cfg: AblConfig =
+            toml::from_str(r#"file_extensions = ["p", ".t"]"#).expect("parse config");
+        assert!(cfg.is_abl_path(Path::new("/src/a.t")));
+        assert!(!cfg.is_abl_path(Path::new("/src/a.cls")));
+    }
+
+    #[test]
+    fn parses_max_file_size() {
+        let cfg = AblConfig::default();
+        assert_eq!(cfg.max_file_size_kb, 2048);
+        assert!(!cfg.exceeds_max_file_size(2048 * 1024));
+        assert!(cfg.exceeds_max_file_size(2048 * 1024 + 1));
+
+        let cfg: AblConfig = toml::from_str("max_file_size_kb = 0").expect("parse config");
+        assert!(!cfg.exceeds_max_file_size(usize::MAX));
+    }
+
+    #[test]
+    fn parses_unused_variables_flag() {
+        assert!(!AblConfig::default().diagnostics.unused_variables);
+        assert!(!AblConfig::default().diagnostics.unknown_value_checks);
+        let cfg: AblConfig = toml::from_str(
+            r#"
+[diagnostics]
+unused_variables = true
+unknown_value_checks = true
+"#,
+        )
+        .expect("parse config");
+        assert!(cfg.diagnostics.unused_variables);
+        assert!(cfg.diagnostics.unknown_value_checks);
+    }
+
+    #[test]
+    fn parses_debounce_ms() {
+        assert_eq!(AblConfig::default().diagnostics.debounce_ms, 150);
+        let mut cfg: AblConfig = toml::from_str(
+            r#"
+[diagnostics]
+debounce_ms = 400
+"#,
+        )
+        .expect("parse config");
+        assert_eq!(cfg.diagnostics.debounce_ms, 400);
+
+        let settings = serde_json::json!({ "abl": { "diagnostics": { "debounce_ms": 0 } } });
+        assert!(apply_client_settings(&mut cfg, &settings, None));
+        assert_eq!(cfg.diagnostics.debounce_ms, 0);
+    }
+
+    #[test]
+    fn parses_require_no_undo_flag() {
+        assert!(!AblConfig::default().diagnostics.require_no_undo);
+        assert!(!AblConfig::default().diagnostics.record_scope_checks);
+        let cfg: AblConfig = toml::from_str(
+            r#"
+[diagnostics]
+require_no_undo = true
+record_scope_checks = true
+"#,
+        )
+        .expect("parse config");
+        assert!(cfg.diagnostics.require_no_undo);
+        assert!(cfg.diagnostics.record_scope_checks);
+    }
+
+    #[test]
+    fn input_param_writes_are_opt_in() {
+        assert!(!AblConfig::default().diagnostics.input_param_writes);
+        let cfg: AblConfig = toml::from_str(
+            r#"
+[diagnostics]
+input_param_writes = true
+"#,
+        )
+        .expect("parse config");
+        assert!(cfg.diagnostics.input_param_writes);
+    }
+
+    #[test]
+    fn call_before_definition_is_opt_in() {
+        assert!(!AblConfig::default().diagnostics.call_before_definition);
+        let cfg: AblConfig = toml::from_str(
+            r#"
+[diagnostics]
+call_before_definition = true
+"#,
+        )
+        .expect("parse config");
+        assert!(cfg.diagnostics.call_before_definition);
+    }
+
+    #[test]
+    fn merges_deprecated_statements_over_defaults() {
+        let mut cfg = AblConfig::default();
+        assert!(
+            cfg.diagnostics
+                .deprecated_statements
+                .contains_key("work_table_definition")
+        );
+
+        let settings = serde_json::json!({
+            "abl": {
+                "diagnostics": {
+                    "deprecated_statements": { "PAUSE": "avoid PAUSE", "CHOOSE": "" }
+                }
+            }
+        });
+        assert!(apply_client_settings(&mut cfg, &settings, None));
+        let deprecated = &cfg.diagnostics.deprecated_statements;
+        assert_eq!(
+            deprecated.get("PAUSE").map(String::as_str),
+            Some("avoid PAUSE")
+        );
+        assert!(!deprecated.contains_key("CHOOSE"));
+        assert!(deprecated.contains_key("work_table_definition"));
+    }
+
+    #[test]
+    fn parses_extra_builtin_names() {
+        let cfg: AblConfig = toml::from_str(
+            r#"
+[diagnostics]
+extra_builtin_functions = ["getSiteParam", "logMsg"]
+extra_builtin_variables = "gvCompany"
+"#,
+        )
+        .expect("parse config");
+        assert_eq!(
+            cfg.diagnostics.extra_builtin_functions,
+            vec!["getSiteParam".to_string(), "logMsg".to_string()]
+        );
+        assert_eq!(
+            cfg.diagnostics.extra_builtin_variables,
+            vec!["gvCompany".to_string()]
+        );
+    }
+
+    #[test]
+    fn parses_includes_max_depth() {
+        assert_eq!(AblConfig::default().includes.max_depth, 16);
+        let cfg: AblConfig = toml::from_str(
+            r#"
+[includes]
+max_depth = 4
+"#,
+        )
+        .expect("parse config");
+        assert_eq!(cfg.includes.max_depth, 4);
+    }
+
+    #[test]
+    fn parses_workspace_exclude_dirs() {
+        assert_eq!(
+            AblConfig::default().workspace.exclude_dirs,
+            vec!["build", "dist", ".git"]
+        );
+        let cfg: AblConfig = toml::from_str(
+            r#"
+[workspace]
+exclude_dirs = "generated"
+"#,
+        )
+        .expect("parse config");
+        assert_eq!(cfg.workspace.exclude_dirs, vec!["generated"]);
+    }
+
+    #[test]
+    fn keyword_completion_is_opt_in() {
+        assert!(!AblConfig::default().completion.keywords);
+        let cfg: AblConfig = toml::from_str(
+            r#"
+[completion]
+keywords = true
+"#,
+        )
+        .expect("parse config");
+        assert!(cfg.completion.keywords);
+    }
+
+    #[test]
+    fn client_settings_override_file_config() {
+        let mut cfg: AblConfig = toml::from_str(
+            r#"
+[diagnostics]
+enabled = true
+unused_variables = true
+
+[completion]
+keywords = true
+"#,
+        )
+        .expect("parse config");
+
+        let settings = serde_json::json!({
+            "abl": {
+                "diagnostics": { "enabled": false },
+                "completion": { "min_prefix_length": 2 },
+                "propath": "client/includes"
+            }
+        });
+        assert!(apply_client_settings(
+            &mut cfg,
+            &settings,
+            Some(Path::new("/work"))
+        ));
+        assert!(!cfg.diagnostics.enabled);
+        assert!(cfg.diagnostics.unused_variables);
+        assert!(cfg.completion.keywords);
+        assert_eq!(cfg.completion.min_prefix_length, 2);
+        assert_eq!(cfg.propath[0], "/work/client/includes");
+
+        assert!(!apply_client_settings(
+            &mut cfg,
+            &serde_json::json!({ "other": {} }),
+            None
+        ));
+        assert!(!apply_client_settings(
+            &mut cfg,
+            &serde_json::json!({ "abl": { "diagnostics": { "enabled": "no" } } }),
+            None
+        ));
     }
 
     #[tokio::test]
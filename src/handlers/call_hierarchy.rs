@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::*;
+
+use crate::analysis::call_hierarchy::{
+    CallHierarchySource, collect_incoming_calls, collect_outgoing_calls, find_callable_item,
+};
+use crate::backend::Backend;
+use crate::utils::position::{
+    ascii_ident_at_or_before, ascii_ident_or_dash_at_or_before, lsp_pos_to_utf8_byte_offset,
+};
+
+impl Backend {
+    pub async fn handle_prepare_call_hierarchy(
+        &self,
+        params: CallHierarchyPrepareParams,
+    ) -> Result<Option<Vec<CallHierarchyItem>>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let pos = params.text_document_position_params.position;
+
+        let text = match self.get_document_text(&uri) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+        let offset = match lsp_pos_to_utf8_byte_offset(&text, pos) {
+            Some(o) => o,
+            None => return Ok(None),
+        };
+        let symbol = match ascii_ident_or_dash_at_or_before(&text, offset)
+            .or_else(|| ascii_ident_at_or_before(&text, offset))
+        {
+            Some(s) => s,
+            None => return Ok(None),
+        };
+
+        let sources = self.call_hierarchy_sources(&uri).await;
+        Ok(find_callable_item(&sources, &symbol).map(|item| vec![item]))
+    }
+
+    pub async fn handle_incoming_calls(
+        &self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyIncomingCall>>> {
+        let sources = self.call_hierarchy_sources(&params.item.uri).await;
+        Ok(Some(collect_incoming_calls(&sources, &params.item.name)))
+    }
+
+    pub async fn handle_outgoing_calls(
+        &self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+        let sources = self.call_hierarchy_sources(&params.item.uri).await;
+        Ok(collect_outgoing_calls(&sources, &params.item))
+    }
+
+    /// Open documents (starting with `first`) followed by cached include parses.
+    async fn call_hierarchy_sources(&self, first: &Url) -> Vec<CallHierarchySource> {
+        let mut uris = self
+            .documents
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect::<Vec<_>>();
+        uris.sort_by_key(|uri| uri != first);
+
+        let mut sources = Vec::new();
+        for uri in uris {
+            if let Some(text) = self.get_document_text(&uri)
+                && let Some(tree) = self.get_document_tree_or_parse(&uri)
+            {
+                sources.push(CallHierarchySource {
+                    uri,
+                    text: Arc::new(text),
+                    tree,
+                });
+            }
+        }
+
+        if !sources.iter().any(|s| &s.uri == first)
+            && let Ok(path) = first.to_file_path()
+            && let Some((text, tree)) = self.get_cached_include_parse(&path).await
+        {
+            sources.insert(
+                0,
+                CallHierarchySource {
+                    uri: first.clone(),
+                    text,
+                    tree,
+                },
+            );
+        }
+
+        for entry in self.include_parse_cache.iter() {
+            let Ok(uri) = Url::from_file_path(entry.key()) else {
+                continue;
+            };
+            if sources.iter().any(|s| s.uri == uri) {
+                continue;
+            }
+            sources.push(CallHierarchySource {
+                uri,
+                text: entry.text.clone(),
+                tree: entry.tree.clone(),
+            });
+        }
+        sources
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::*;
+
+use crate::analysis::code_actions::{
+    create_variable_edit, insert_no_undo_edit, is_missing_no_undo_message, organize_defines_edit,
+    unknown_variable_name_from_message,
+};
+use crate::analysis::formatting::preserves_ast_shape_ignoring_order;
+use crate::backend::Backend;
+use crate::utils::position::{lsp_pos_to_utf8_byte_offset, utf8_byte_offset_to_lsp_pos};
+
+pub const ORGANIZE_DEFINES_KIND: &str = "source.abl.organizeDefines";
+
+impl Backend {
+    pub async fn handle_code_action(
+        &self,
+        params: CodeActionParams,
+    ) -> Result<Option<CodeActionResponse>> {
+        let uri = params.text_document.uri;
+        let text = match self.get_document_text(&uri) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+        let tree = match self.get_document_tree_or_parse(&uri) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+
+        let mut actions = Vec::new();
+        for diagnostic in params.context.diagnostics {
+            if diagnostic.source.as_deref() != Some("abl-semantic") {
+                continue;
+            }
+            let Some(offset) = lsp_pos_to_utf8_byte_offset(&text, diagnostic.range.start) else {
+                continue;
+            };
+            if let Some(name) = unknown_variable_name_from_message(&diagnostic.message) {
+                let edit = create_variable_edit(tree.root_node(), &text, name, offset);
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: format!("Define variable '{name}'"),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diagnostic.clone()]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                        ..Default::default()
+                    }),
+                    is_preferred: Some(true),
+                    ..Default::default()
+                }));
+            } else if is_missing_no_undo_message(&diagnostic.message)
+                && let Some(edit) = insert_no_undo_edit(tree.root_node(), &text, offset)
+            {
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: "Add NO-UNDO".to_string(),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diagnostic.clone()]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                        ..Default::default()
+                    }),
+                    is_preferred: Some(true),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        if wants_kind(params.context.only.as_deref(), ORGANIZE_DEFINES_KIND)
+            && let Some(offset) = lsp_pos_to_utf8_byte_offset(&text, params.range.start)
+            && let Some((start, end, new_text)) =
+                organize_defines_edit(tree.root_node(), &text, offset)
+        {
+            let mut organized = text.clone();
+            organized.replace_range(start..end, &new_text);
+            let mut parser = self.new_abl_parser();
+            if preserves_ast_shape_ignoring_order(&text, &organized, &mut parser) {
+                let edit = TextEdit {
+                    range: Range::new(
+                        utf8_byte_offset_to_lsp_pos(&text, start),
+                        utf8_byte_offset_to_lsp_pos(&text, end),
+                    ),
+                    new_text,
+                };
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: "Organize DEFINE statements".to_string(),
+                    kind: Some(CodeActionKind::from(ORGANIZE_DEFINES_KIND.to_string())),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        Ok(Some(actions))
+    }
+}
+
+/// Whether a client `only` filter (e.g. `source`) admits the given action kind.
+fn wants_kind(only: Option<&[CodeActionKind]>, kind: &str) -> bool {
+    only.is_none_or(|only| {
+        only.iter().any(|k| {
+            kind == k.as_str()
+                || kind
+                    .strip_prefix(k.as_str())
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+    })
+}
@@ -0,0 +1,62 @@
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::*;
+
+use crate::analysis::code_lens::{document_reference_lens_sites, reference_lens_title};
+use crate::backend::Backend;
+use crate::utils::position::byte_ranges_to_utf16;
+
+/// Client-side command (VS Code and compatible editors) that opens a references peek view.
+const SHOW_REFERENCES_COMMAND: &str = "editor.action.showReferences";
+
+impl Backend {
+    pub async fn handle_code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+        if !self.config.lock().await.code_lens.enabled {
+            return Ok(None);
+        }
+        let uri = params.text_document.uri;
+        let text = match self.get_document_text(&uri) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+        let tree = match self.get_document_tree_or_parse(&uri) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+
+        let mut sites = document_reference_lens_sites(tree.root_node(), text.as_bytes());
+        for site in &mut sites {
+            byte_ranges_to_utf16(
+                &text,
+                std::iter::once(&mut site.name_range).chain(site.references.iter_mut()),
+            );
+        }
+
+        let lenses = sites
+            .into_iter()
+            .map(|site| {
+                let locations = site
+                    .references
+                    .iter()
+                    .map(|range| Location {
+                        uri: uri.clone(),
+                        range: *range,
+                    })
+                    .collect::<Vec<_>>();
+                CodeLens {
+                    range: site.name_range,
+                    command: Some(Command {
+                        title: reference_lens_title(locations.len()),
+                        command: SHOW_REFERENCES_COMMAND.to_string(),
+                        arguments: Some(vec![
+                            serde_json::json!(uri),
+                            serde_json::json!(site.name_range.start),
+                            serde_json::json!(locations),
+                        ]),
+                    }),
+                    data: None,
+                }
+            })
+            .collect();
+        Ok(Some(lenses))
+    }
+}
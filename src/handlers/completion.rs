@@ -1,41 +1,73 @@
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use std::sync::atomic::Ordering;
 use std::time::{Duration, Instant};
 
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tree_sitter::Node;
 
-use crate::analysis::buffers::collect_buffer_mappings;
+use crate::analysis::buffers::{collect_buffer_mappings, resolve_buffer_table};
+use crate::analysis::builtins::{HANDLE_ATTRIBUTES, HANDLE_METHODS};
+use crate::analysis::classes::{
+    MAX_INHERITS_DEPTH, class_inherits_name, class_name_to_relative_path, collect_class_members,
+    collect_inherited_class_members, enclosing_class_definition,
+};
 use crate::analysis::completion::{
-    lookup_case_insensitive_fields_by_table_symbol,
-    lookup_case_insensitive_indexes_by_table_symbol, qualifier_before_dot,
-    text_has_dot_before_cursor, use_index_table_symbol_at_offset,
+    document_words_with_prefix, field_detail, field_documentation, include_path_prefix,
+    is_handle_type_detail, lookup_case_insensitive_fields_by_table_symbol,
+    lookup_case_insensitive_indexes_by_table_symbol, preprocessor_reference_prefix,
+    qualifier_before_colon, qualifier_before_dot, text_has_dot_before_cursor,
+    type_qualifier_before_colon, use_index_table_symbol_at_offset,
     use_index_table_symbol_in_statement_prefix,
 };
 use crate::analysis::completion_support::{
-    build_field_completion_items, completion_response, is_parameter_symbol_at_byte,
-    symbol_is_in_current_scope,
+    CompletionRank, build_field_completion_items, build_keyword_completion_items,
+    build_member_completion_items, build_parameter_name_completion_items,
+    build_snippet_completion_items, completion_response, is_below_min_prefix_length,
+    is_parameter_symbol_at_byte, ranked_sort_text, symbol_is_in_current_scope,
 };
+use crate::analysis::datasets::collect_dataset_definitions;
 use crate::analysis::definitions::{
-    collect_definition_symbols, collect_global_preprocessor_define_sites,
+    SHARED_DETAIL_PREFIX, collect_definition_symbols, collect_global_preprocessor_define_sites,
     collect_global_preprocessor_define_symbols, collect_preprocessor_define_sites,
     collect_preprocessor_define_symbols,
 };
-use crate::analysis::includes::{collect_include_sites_from_tree, resolve_include_site_path};
-use crate::analysis::local_tables::collect_local_table_definitions;
+use crate::analysis::diagnostics::symbols::collect_active_buffer_like_names;
+use crate::analysis::diagnostics::types::binding_type_at;
+use crate::analysis::enums::resolve_enum_definition;
+use crate::analysis::functions::find_function_signature_from_includes;
+use crate::analysis::hover::function_signature_markdown;
+use crate::analysis::includes::{
+    collect_include_sites_from_tree, collect_resolved_include_parses_until,
+    resolve_include_site_path,
+};
+use crate::analysis::local_tables::{
+    LocalTableDefinition, collect_local_table_definitions, merge_like_table_fields,
+};
 use crate::analysis::scopes::containing_scope;
+use crate::analysis::signature::call_context_at_offset;
+use crate::analysis::system_handles::system_handle_members;
+use crate::analysis::types::BasicType;
 use crate::backend::Backend;
 use crate::backend::CachedCompletionSymbol;
-use crate::utils::position::{ascii_ident_prefix, lsp_pos_to_utf8_byte_offset};
+use crate::utils::paths::{list_include_path_candidates, resolve_config_path};
+use crate::utils::position::{
+    ascii_ident_prefix, lsp_pos_to_utf8_byte_offset, utf8_byte_offset_to_lsp_pos,
+};
 
 struct CompletionCandidate {
     label: String,
     kind: CompletionItemKind,
     detail: String,
+    rank: CompletionRank,
+    /// Table of a DB field, used to document it on `completionItem/resolve`.
+    table: Option<String>,
 }
 
 const COMPLETION_INCLUDE_BUDGET_MS: u64 = 120;
+const MAX_INCLUDE_PATH_CANDIDATES: usize = 200;
+const MAX_DOCUMENT_WORD_ITEMS: usize = 200;
 
 impl Backend {
     pub(crate) async fn resolve_use_index_table_key(
@@ -123,6 +155,66 @@ impl Backend {
         Some(table_key)
     }
 
+    /// `{path` completion: `.i` files and folders from the PROPATH, current dir and workspace.
+    async fn include_path_completion_items(
+        &self,
+        uri: &Url,
+        text: &str,
+        offset: usize,
+        include_prefix: &str,
+    ) -> Vec<CompletionItem> {
+        let workspace_root = self.workspace_root.lock().await.clone();
+        let propath = self.config.lock().await.propath.clone();
+        let mut roots = propath
+            .iter()
+            .filter_map(|entry| resolve_config_path(workspace_root.as_deref(), entry))
+            .collect::<Vec<_>>();
+        if let Ok(path) = uri.to_file_path()
+            && let Some(dir) = path.parent()
+        {
+            roots.push(dir.to_path_buf());
+        }
+        roots.extend(workspace_root);
+
+        let range = Range::new(
+            utf8_byte_offset_to_lsp_pos(text, offset - include_prefix.len()),
+            utf8_byte_offset_to_lsp_pos(text, offset),
+        );
+        let snippets = self.client_snippet_support.load(Ordering::Relaxed);
+        let include_prefix = include_prefix.to_string();
+        let candidates = tokio::task::spawn_blocking(move || {
+            list_include_path_candidates(&roots, &include_prefix, MAX_INCLUDE_PATH_CANDIDATES)
+        })
+        .await
+        .unwrap_or_default();
+
+        candidates
+            .into_iter()
+            .map(|(label, is_dir)| {
+                let (new_text, format) = if is_dir {
+                    (label.clone(), InsertTextFormat::PLAIN_TEXT)
+                } else if snippets {
+                    (format!("{label}}}$0"), InsertTextFormat::SNIPPET)
+                } else {
+                    (format!("{label}}}"), InsertTextFormat::PLAIN_TEXT)
+                };
+                CompletionItem {
+                    label: label.clone(),
+                    kind: Some(if is_dir {
+                        CompletionItemKind::FOLDER
+                    } else {
+                        CompletionItemKind::FILE
+                    }),
+                    detail: Some(if is_dir { "Directory" } else { "Include file" }.to_string()),
+                    filter_text: Some(label),
+                    text_edit: Some(CompletionTextEdit::Edit(TextEdit { range, new_text })),
+                    insert_text_format: Some(format),
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
     pub async fn handle_completion(
         &self,
         params: CompletionParams,
@@ -133,10 +225,16 @@ impl Backend {
             .and_then(|ctx| ctx.trigger_character.as_deref())
             .map(|ch| ch == ".")
             .unwrap_or(false);
+        // An explicit request (Ctrl+Space) always completes, whatever `min_prefix_length` says.
+        let explicitly_invoked = params
+            .context
+            .as_ref()
+            .is_some_and(|ctx| ctx.trigger_kind == CompletionTriggerKind::INVOKED);
 
         let uri = params.text_document_position.text_document.uri;
         let pos = params.text_document_position.position;
-        if !self.config.lock().await.completion.enabled {
+        let completion_config = self.config.lock().await.completion.clone();
+        if !completion_config.enabled {
             return Ok(Some(CompletionResponse::Array(vec![])));
         }
 
@@ -144,6 +242,37 @@ impl Backend {
             Some(t) => t,
             None => return Ok(Some(CompletionResponse::Array(vec![]))),
         };
+        // Oversized documents are never walked; complete from the words of the text instead.
+        if self.config.lock().await.exceeds_max_file_size(text.len()) {
+            let Some(offset) = lsp_pos_to_utf8_byte_offset(&text, pos) else {
+                return Ok(Some(CompletionResponse::Array(vec![])));
+            };
+            let prefix = ascii_ident_prefix(&text, offset);
+            if !explicitly_invoked
+                && is_below_min_prefix_length(
+                    &text,
+                    offset,
+                    &prefix,
+                    completion_config.min_prefix_length,
+                )
+            {
+                return Ok(Some(completion_response(vec![], true)));
+            }
+            let mut items = document_words_with_prefix(&text, &prefix, MAX_DOCUMENT_WORD_ITEMS)
+                .into_iter()
+                .map(|word| CompletionItem {
+                    label: word.clone(),
+                    kind: Some(CompletionItemKind::TEXT),
+                    insert_text: Some(word),
+                    insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>();
+            if completion_config.keywords {
+                items.extend(build_keyword_completion_items(&prefix));
+            }
+            return Ok(Some(completion_response(items, true)));
+        }
         // Completion must use a tree parsed from the current text. A stale tree can panic
         // when tree-sitter slices node byte ranges against newer document contents.
         let tree = match self.get_document_tree_or_parse(&uri) {
@@ -159,32 +288,179 @@ impl Backend {
         };
 
         let prefix = ascii_ident_prefix(&text, offset);
+        // Incomplete so the client asks again once the prefix is long enough.
+        if !explicitly_invoked
+            && is_below_min_prefix_length(
+                &text,
+                offset,
+                &prefix,
+                completion_config.min_prefix_length,
+            )
+        {
+            return Ok(Some(completion_response(vec![], true)));
+        }
         let root = tree.root_node();
 
+        if let Some(include_prefix) = include_path_prefix(&text, offset) {
+            let items = self
+                .include_path_completion_items(&uri, &text, offset, &include_prefix)
+                .await;
+            return Ok(Some(completion_response(items, is_incomplete)));
+        }
+
+        if let Some(name_prefix) = preprocessor_reference_prefix(&text, offset) {
+            let (items, timed_out) = self
+                .preprocessor_reference_completion_items(
+                    &uri,
+                    &text,
+                    root,
+                    offset,
+                    &name_prefix,
+                    include_deadline,
+                )
+                .await;
+            return Ok(Some(completion_response(items, timed_out)));
+        }
+
         if let Some(table_key) = self
             .resolve_use_index_table_key(&uri, &text, root, offset)
             .await
         {
             let pref_up = prefix.to_ascii_uppercase();
-            let items = lookup_case_insensitive_indexes_by_table_symbol(
+            let (indexes, detail) = match lookup_case_insensitive_indexes_by_table_symbol(
                 &self.db_indexes_by_table,
                 &table_key,
+            ) {
+                Some(indexes) => (indexes, format!("DB index ({table_key})")),
+                None => (
+                    self.local_table_indexes(root, text.as_bytes(), &table_key),
+                    format!("Temp-table index ({table_key})"),
+                ),
+            };
+            let items = indexes
+                .into_iter()
+                .filter(|index| index.to_ascii_uppercase().starts_with(&pref_up))
+                .map(|index| CompletionItem {
+                    label: index.clone(),
+                    kind: Some(CompletionItemKind::REFERENCE),
+                    detail: Some(detail.clone()),
+                    insert_text: Some(index),
+                    insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>();
+            return Ok(Some(completion_response(items, is_incomplete)));
+        }
+
+        // Colon completion on the current object: THIS-OBJECT:<prefix>, SUPER:<prefix>
+        if let Some(qualifier) = qualifier_before_colon(&text, offset, &prefix)
+            && matches!(
+                qualifier.to_ascii_uppercase().as_str(),
+                "THIS-OBJECT" | "SELF" | "SUPER"
             )
-            .unwrap_or_default()
-            .into_iter()
-            .filter(|index| index.to_ascii_uppercase().starts_with(&pref_up))
-            .map(|index| CompletionItem {
-                label: index.clone(),
-                kind: Some(CompletionItemKind::REFERENCE),
-                detail: Some(format!("DB index ({table_key})")),
-                insert_text: Some(index),
-                insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
-                ..Default::default()
-            })
-            .collect::<Vec<_>>();
+        {
+            let inherited_only = qualifier.eq_ignore_ascii_case("SUPER");
+            let members = self
+                .collect_class_members_for_completion(&uri, &text, root, offset, inherited_only)
+                .await;
+            let pref_up = prefix.to_ascii_uppercase();
+            let mut items = members
+                .into_iter()
+                .filter(|m| m.label.to_ascii_uppercase().starts_with(&pref_up))
+                .map(|m| CompletionItem {
+                    label: m.label.clone(),
+                    kind: Some(m.kind),
+                    detail: Some(m.detail),
+                    insert_text: Some(m.label),
+                    insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>();
+            items.sort_by_key(|item| item.label.to_ascii_uppercase());
+            items.dedup_by(|a, b| a.label.eq_ignore_ascii_case(&b.label));
             return Ok(Some(completion_response(items, is_incomplete)));
         }
 
+        // Colon completion on a system handle: ERROR-STATUS:<prefix>, SESSION:<prefix>, ...
+        if completion_config.handle_members
+            && let Some(qualifier) = qualifier_before_colon(&text, offset, &prefix)
+            && let Some(members) = system_handle_members(&qualifier)
+        {
+            let owner = qualifier.to_ascii_uppercase();
+            let items =
+                build_member_completion_items(members.attributes, members.methods, &owner, &prefix);
+            return Ok(Some(completion_response(items, is_incomplete)));
+        }
+
+        // Colon completion on a HANDLE variable: hWidget:<prefix> lists common widget members.
+        if completion_config.handle_members
+            && let Some(qualifier) = qualifier_before_colon(&text, offset, &prefix)
+            && binding_type_at(
+                root,
+                text.as_bytes(),
+                &qualifier.to_ascii_uppercase(),
+                offset,
+            ) == Some(BasicType::Handle)
+        {
+            let items =
+                build_member_completion_items(HANDLE_ATTRIBUTES, HANDLE_METHODS, "HANDLE", &prefix);
+            return Ok(Some(completion_response(items, is_incomplete)));
+        }
+
+        // Colon completion on an ENUM type: Color:<prefix> / Acme.Color:<prefix> lists its members.
+        if let Some(qualifier) = type_qualifier_before_colon(&text, offset, &prefix)
+            && let Some(definition) =
+                resolve_enum_definition(self, &uri, &text, root, &qualifier).await
+        {
+            let pref_up = prefix.to_ascii_uppercase();
+            let detail = format!("{} member", definition.name);
+            let items = definition
+                .members
+                .into_iter()
+                .filter(|m| m.name.to_ascii_uppercase().starts_with(&pref_up))
+                .map(|m| CompletionItem {
+                    label: m.name.clone(),
+                    kind: Some(CompletionItemKind::ENUM_MEMBER),
+                    detail: Some(detail.clone()),
+                    insert_text: Some(m.name),
+                    insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>();
+            return Ok(Some(completion_response(items, is_incomplete)));
+        }
+
+        // Colon completion on a ProDataSet/data-source: dsOrders:<prefix> lists its member buffers.
+        if let Some(qualifier) = qualifier_before_colon(&text, offset, &prefix) {
+            let mut datasets = Vec::new();
+            collect_dataset_definitions(root, text.as_bytes(), &mut datasets);
+            if let Some(dataset) = datasets
+                .into_iter()
+                .find(|d| d.name.eq_ignore_ascii_case(&qualifier))
+            {
+                let pref_up = prefix.to_ascii_uppercase();
+                let detail = if dataset.is_data_source {
+                    format!("Data-source buffer ({})", dataset.name)
+                } else {
+                    format!("Dataset buffer ({})", dataset.name)
+                };
+                let items = dataset
+                    .members
+                    .into_iter()
+                    .filter(|member| member.to_ascii_uppercase().starts_with(&pref_up))
+                    .map(|member| CompletionItem {
+                        label: member.clone(),
+                        kind: Some(CompletionItemKind::STRUCT),
+                        detail: Some(detail.clone()),
+                        insert_text: Some(member),
+                        insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+                        ..Default::default()
+                    })
+                    .collect::<Vec<_>>();
+                return Ok(Some(completion_response(items, is_incomplete)));
+            }
+        }
+
         // Dot completion: table_or_buffer.<prefix>
         let dot_qualifier = qualifier_before_dot(&text, offset, &prefix).or_else(|| {
             if trigger_is_dot && !prefix.is_empty() {
@@ -194,6 +470,16 @@ impl Backend {
                 None
             }
         });
+        // Colon field access on a buffer (`bCust:NAME`); HANDLE variables keep attribute syntax.
+        let dot_qualifier = dot_qualifier.or_else(|| {
+            let qualifier = qualifier_before_colon(&text, offset, &prefix)?;
+            let mut symbols = Vec::new();
+            collect_definition_symbols(root, text.as_bytes(), &mut symbols);
+            let is_handle = symbols.iter().any(|s| {
+                s.label.eq_ignore_ascii_case(&qualifier) && is_handle_type_detail(&s.detail)
+            });
+            (!is_handle).then_some(qualifier)
+        });
         let field_prefix = if trigger_is_dot
             && dot_qualifier.is_some()
             && !text_has_dot_before_cursor(&text, offset)
@@ -213,6 +499,22 @@ impl Backend {
                 text.as_bytes(),
                 &mut local_table_defs,
             );
+            if !self.db_fields_by_table.contains_key(&qualifier_upper)
+                && !local_table_defs
+                    .iter()
+                    .any(|d| d.name_upper == qualifier_upper)
+            {
+                let (include_defs, timed_out) = self
+                    .collect_include_table_definitions(
+                        &uri,
+                        &text,
+                        tree.root_node(),
+                        include_deadline,
+                    )
+                    .await;
+                is_incomplete |= timed_out;
+                local_table_defs.extend(include_defs);
+            }
             let local_fields_by_table = local_table_defs
                 .iter()
                 .map(|d| (d.name_upper.clone(), d.fields.clone()))
@@ -239,17 +541,18 @@ impl Backend {
             }
 
             if let Some(table_key) = table_upper {
-                if let Some(fields) = local_fields_by_table.get(&table_key) {
-                    let items = build_field_completion_items(fields, &table_key, &field_prefix);
-                    return Ok(Some(completion_response(items, is_incomplete)));
-                }
-
-                if let Some(like_key) = local_like_by_table.get(&table_key)
-                    && let Some(fields) = lookup_case_insensitive_fields_by_table_symbol(
+                let local_fields = local_fields_by_table.get(&table_key);
+                let like_fields = local_like_by_table.get(&table_key).and_then(|like_key| {
+                    lookup_case_insensitive_fields_by_table_symbol(
                         &self.db_fields_by_table,
                         like_key,
                     )
-                {
+                });
+                if local_fields.is_some() || like_fields.is_some() {
+                    let fields = merge_like_table_fields(
+                        local_fields.map(Vec::as_slice).unwrap_or_default(),
+                        like_fields.as_deref().unwrap_or_default(),
+                    );
                     let items = build_field_completion_items(&fields, &table_key, &field_prefix);
                     return Ok(Some(completion_response(items, is_incomplete)));
                 }
@@ -288,6 +591,8 @@ impl Backend {
                     label: s.label,
                     kind: s.kind,
                     detail: s.detail,
+                    rank: CompletionRank::Local,
+                    table: None,
                 }),
         );
         let (include_candidates, include_timed_out) = self
@@ -302,6 +607,42 @@ impl Backend {
         is_incomplete |= include_timed_out;
         candidates.extend(include_candidates);
 
+        candidates.extend(
+            self.imported_class_names_for(&uri, &text)
+                .await
+                .into_iter()
+                .map(|(short, qualified)| CompletionCandidate {
+                    label: short,
+                    kind: CompletionItemKind::CLASS,
+                    detail: qualified,
+                    rank: CompletionRank::Include,
+                    table: None,
+                }),
+        );
+
+        // Bare fields of the buffers in use here (`FOR EACH order OF customer` adds both tables).
+        let mut mappings = Vec::new();
+        collect_buffer_mappings(root, text.as_bytes(), &mut mappings);
+        for buffer in collect_active_buffer_like_names(root, text.as_bytes()) {
+            if offset < buffer.start_byte || offset > buffer.end_byte {
+                continue;
+            }
+            let table_key = resolve_buffer_table(&mappings, &buffer.name_upper, offset)
+                .map(str::to_ascii_uppercase)
+                .unwrap_or(buffer.name_upper);
+            if let Some(fields) =
+                lookup_case_insensitive_fields_by_table_symbol(&self.db_fields_by_table, &table_key)
+            {
+                candidates.extend(fields.iter().map(|f| CompletionCandidate {
+                    label: f.name.clone(),
+                    kind: CompletionItemKind::FIELD,
+                    detail: field_detail(f, &table_key),
+                    rank: CompletionRank::Local,
+                    table: Some(table_key.clone()),
+                }));
+            }
+        }
+
         let table_labels = &self.db_table_labels;
         candidates.extend(
             table_labels
@@ -311,6 +652,8 @@ impl Backend {
                     label,
                     kind: CompletionItemKind::STRUCT,
                     detail: "DB table".to_string(),
+                    rank: CompletionRank::DbTable,
+                    table: None,
                 }),
         );
 
@@ -319,27 +662,262 @@ impl Backend {
                 .to_ascii_uppercase()
                 .cmp(&b.label.to_ascii_uppercase())
                 .then(a.label.cmp(&b.label))
+                .then(a.rank.cmp(&b.rank))
                 .then(a.detail.cmp(&b.detail))
         });
         candidates.dedup_by(|a, b| a.label.eq_ignore_ascii_case(&b.label) && a.kind == b.kind);
 
         let pref_up = prefix.to_ascii_uppercase();
-        let items = candidates
+        // Include functions are documented on `completionItem/resolve`, from the same position.
+        let include_resolve_data = serde_json::json!({ "uri": uri.as_str(), "offset": offset });
+        let mut items = candidates
             .into_iter()
             .filter(|s| completion_label_matches_prefix(&s.label, &pref_up))
             .map(|s| CompletionItem {
+                data: match &s.table {
+                    Some(table) => Some(serde_json::json!({ "table": table })),
+                    None => (s.rank == CompletionRank::Include
+                        && s.kind == CompletionItemKind::FUNCTION)
+                        .then(|| include_resolve_data.clone()),
+                },
                 label: s.label.clone(),
                 kind: Some(s.kind),
+                label_details: s.detail.starts_with(SHARED_DETAIL_PREFIX).then(|| {
+                    CompletionItemLabelDetails {
+                        detail: Some(" (shared)".to_string()),
+                        description: None,
+                    }
+                }),
                 detail: Some(s.detail),
+                sort_text: Some(ranked_sort_text(s.rank, &s.label)),
                 insert_text: Some(s.label),
                 insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
                 ..Default::default()
             })
             .collect::<Vec<_>>();
+        // Inside `RUN proc (...)`: the procedure's parameter names, listed ahead of other symbols.
+        if let Some(call) = call_context_at_offset(root, text.as_bytes(), offset)
+            && call.is_run
+            && let Some(sig) = self
+                .resolve_call_signature(&uri, &text, root, offset, &call)
+                .await
+        {
+            let mut param_items =
+                build_parameter_name_completion_items(&sig.params, call.active_param, &prefix);
+            param_items.append(&mut items);
+            items = param_items;
+        }
+        if completion_config.keywords {
+            items.extend(build_keyword_completion_items(&prefix));
+        }
+        if completion_config.snippets && self.client_snippet_support.load(Ordering::Relaxed) {
+            items.extend(build_snippet_completion_items(&prefix));
+        }
 
         Ok(Some(completion_response(items, is_incomplete)))
     }
 
+    /// `{&NAME}` items for defines visible at `offset`: this file's scoped/global defines and the
+    /// globals of its includes. The edit replaces the typed `{&prefix` (and a closing `}`).
+    async fn preprocessor_reference_completion_items(
+        &self,
+        uri: &Url,
+        text: &str,
+        root: Node<'_>,
+        offset: usize,
+        name_prefix: &str,
+        deadline: Instant,
+    ) -> (Vec<CompletionItem>, bool) {
+        let mut symbols = Vec::new();
+        collect_preprocessor_define_symbols(root, text.as_bytes(), &mut symbols);
+        let mut candidates = symbols
+            .into_iter()
+            .filter(|s| s.start_byte < offset)
+            .map(|s| CompletionCandidate {
+                label: s.label,
+                kind: s.kind,
+                detail: s.detail,
+                rank: CompletionRank::Local,
+                table: None,
+            })
+            .collect::<Vec<_>>();
+        let (include_candidates, timed_out) = self
+            .collect_symbols_from_includes_for_completion(uri, text, root, offset, deadline)
+            .await;
+        candidates.extend(
+            include_candidates
+                .into_iter()
+                .filter(|c| c.label.starts_with("{&")),
+        );
+        candidates.sort_by(|a, b| {
+            a.label
+                .to_ascii_uppercase()
+                .cmp(&b.label.to_ascii_uppercase())
+                .then(a.rank.cmp(&b.rank))
+        });
+        candidates.dedup_by(|a, b| a.label.eq_ignore_ascii_case(&b.label));
+
+        let start = offset - name_prefix.len() - 2;
+        let end = if text.as_bytes().get(offset) == Some(&b'}') {
+            offset + 1
+        } else {
+            offset
+        };
+        let range = Range::new(
+            utf8_byte_offset_to_lsp_pos(text, start),
+            utf8_byte_offset_to_lsp_pos(text, end),
+        );
+        let pref_up = name_prefix.to_ascii_uppercase();
+        let items = candidates
+            .into_iter()
+            .filter(|c| completion_label_matches_prefix(&c.label, &pref_up))
+            .map(|c| CompletionItem {
+                label: c.label.clone(),
+                kind: Some(CompletionItemKind::CONSTANT),
+                detail: Some(c.detail),
+                sort_text: Some(ranked_sort_text(c.rank, &c.label)),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(range, c.label))),
+                insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+                ..Default::default()
+            })
+            .collect();
+        (items, timed_out)
+    }
+
+    /// `INDEX` names of a local temp-table; without any, a `LIKE` table's DB indexes are inherited.
+    fn local_table_indexes(&self, root: Node<'_>, src: &[u8], table_key: &str) -> Vec<String> {
+        let mut tables = Vec::new();
+        collect_local_table_definitions(root, src, &mut tables);
+        let Some(table) = tables
+            .into_iter()
+            .find(|table| table.name_upper.eq_ignore_ascii_case(table_key))
+        else {
+            return Vec::new();
+        };
+        if !table.indexes.is_empty() {
+            return table.indexes;
+        }
+        table
+            .like_table_upper
+            .and_then(|like| {
+                lookup_case_insensitive_indexes_by_table_symbol(&self.db_indexes_by_table, &like)
+            })
+            .unwrap_or_default()
+    }
+
+    async fn collect_class_members_for_completion(
+        &self,
+        uri: &Url,
+        text: &str,
+        root: Node<'_>,
+        offset: usize,
+        inherited_only: bool,
+    ) -> Vec<CompletionCandidate> {
+        let Some(class_node) = enclosing_class_definition(root, offset) else {
+            return Vec::new();
+        };
+        let mut symbols = Vec::new();
+        if !inherited_only {
+            collect_class_members(class_node, text.as_bytes(), &mut symbols);
+        }
+
+        // Follow INHERITS through propath, bounded against cyclic hierarchies.
+        let mut parent = class_inherits_name(class_node, text.as_bytes());
+        let mut seen = HashSet::new();
+        let current_path = uri.to_file_path().ok();
+        while let Some(parent_name) = parent.take()
+            && let Some(current_path) = current_path.as_deref()
+            && seen.len() < MAX_INHERITS_DEPTH
+            && seen.insert(parent_name.to_ascii_uppercase())
+        {
+            let Some(parent_path) = self
+                .resolve_include_path_for(current_path, &class_name_to_relative_path(&parent_name))
+                .await
+            else {
+                break;
+            };
+            let Some((parent_text, parent_tree)) =
+                self.get_cached_include_parse(&parent_path).await
+            else {
+                break;
+            };
+            let Some(parent_class) =
+                enclosing_class_definition(parent_tree.root_node(), usize::MAX)
+            else {
+                break;
+            };
+            collect_inherited_class_members(parent_class, parent_text.as_bytes(), &mut symbols);
+            parent = class_inherits_name(parent_class, parent_text.as_bytes());
+        }
+
+        symbols
+            .into_iter()
+            .map(|s| CompletionCandidate {
+                label: s.label,
+                kind: s.kind,
+                detail: s.detail,
+                rank: CompletionRank::Local,
+                table: None,
+            })
+            .collect()
+    }
+
+    /// Attaches documentation to the selected item: the signature of an include function, or
+    /// the label/format of a DB field.
+    pub async fn handle_completion_resolve(
+        &self,
+        mut item: CompletionItem,
+    ) -> Result<CompletionItem> {
+        if item.documentation.is_some() {
+            return Ok(item);
+        }
+
+        if item.kind == Some(CompletionItemKind::FIELD)
+            && let Some(table_key) = item
+                .data
+                .as_ref()
+                .and_then(|data| data.get("table"))
+                .and_then(|v| v.as_str())
+            && let Some(fields) =
+                lookup_case_insensitive_fields_by_table_symbol(&self.db_fields_by_table, table_key)
+        {
+            item.documentation = fields
+                .iter()
+                .find(|f| f.name.eq_ignore_ascii_case(&item.label))
+                .and_then(field_documentation);
+            return Ok(item);
+        }
+
+        if item.kind == Some(CompletionItemKind::FUNCTION)
+            && let Some(data) = item.data.as_ref()
+            && let Some(uri) = data
+                .get("uri")
+                .and_then(|v| v.as_str())
+                .and_then(|v| Url::parse(v).ok())
+            && let Some(offset) = data
+                .get("offset")
+                .and_then(|v| v.as_u64())
+                .map(|v| v as usize)
+            && let Some(text) = self.get_document_text(&uri)
+            && let Some(tree) = self.get_document_tree_or_parse(&uri)
+            && let Some(sig) = find_function_signature_from_includes(
+                self,
+                &uri,
+                &text,
+                tree.root_node(),
+                offset.min(text.len()),
+                &item.label,
+            )
+            .await
+        {
+            item.documentation = Some(Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: function_signature_markdown(&sig),
+            }));
+        }
+        Ok(item)
+    }
+
     async fn collect_symbols_from_includes_for_completion(
         &self,
         uri: &Url,
@@ -406,6 +984,48 @@ impl Backend {
         (out, timed_out)
     }
 
+    /// Temp-table definitions from the includes reachable from the current document, walked
+    /// until `deadline`; the flag is set when some includes were left out.
+    async fn collect_include_table_definitions(
+        &self,
+        uri: &Url,
+        text: &str,
+        root: Node<'_>,
+        deadline: Instant,
+    ) -> (Vec<LocalTableDefinition>, bool) {
+        if !text.as_bytes().contains(&b'{') {
+            return (Vec::new(), false);
+        }
+        let (Ok(current_path), Some(version)) =
+            (uri.to_file_path(), self.get_document_version(uri))
+        else {
+            return (Vec::new(), false);
+        };
+        let Some((include_parses, timed_out)) = collect_resolved_include_parses_until(
+            self,
+            uri,
+            version,
+            &current_path,
+            text,
+            root,
+            Some(deadline),
+        )
+        .await
+        else {
+            return (Vec::new(), false);
+        };
+
+        let mut defs = Vec::new();
+        for (_, include_text, include_tree) in include_parses {
+            collect_local_table_definitions(
+                include_tree.root_node(),
+                include_text.as_bytes(),
+                &mut defs,
+            );
+        }
+        (defs, timed_out)
+    }
+
     async fn get_cached_include_completion_candidates(
         &self,
         include_path: &Path,
@@ -418,6 +1038,8 @@ impl Backend {
                     label: s.label.clone(),
                     kind: s.kind,
                     detail: s.detail.clone(),
+                    rank: CompletionRank::Include,
+                    table: None,
                 })
                 .collect();
         }
@@ -456,6 +1078,8 @@ impl Backend {
                 label: s.label,
                 kind: s.kind,
                 detail: s.detail,
+                rank: CompletionRank::Include,
+                table: None,
             })
             .collect()
     }
@@ -508,3 +1132,102 @@ fn completion_label_matches_prefix(label: &str, prefix_upper: &str) -> bool {
         .map(|macro_name| macro_name.to_ascii_uppercase().starts_with(prefix_upper))
         .unwrap_or(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::backend::{Backend, DbFieldInfo};
+    use tower_lsp::lsp_types::{
+        CompletionContext, CompletionItem, CompletionItemKind, CompletionParams,
+        CompletionResponse, CompletionTriggerKind, Documentation, Position, TextDocumentIdentifier,
+        TextDocumentPositionParams, Url,
+    };
+
+    async fn complete_labels(
+        backend: &Backend,
+        uri: &Url,
+        position: Position,
+        trigger_kind: CompletionTriggerKind,
+    ) -> Vec<String> {
+        let response = backend
+            .handle_completion(CompletionParams {
+                text_document_position: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                    position,
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                context: Some(CompletionContext {
+                    trigger_kind,
+                    trigger_character: None,
+                }),
+            })
+            .await
+            .expect("completion");
+        match response {
+            Some(CompletionResponse::Array(items)) => items,
+            Some(CompletionResponse::List(list)) => list.items,
+            None => Vec::new(),
+        }
+        .into_iter()
+        .map(|item| item.label)
+        .collect()
+    }
+
+    #[tokio::test]
+    async fn explicit_invocation_ignores_min_prefix_length() {
+        let backend = Backend::for_tests();
+        backend.config.lock().await.completion.min_prefix_length = 3;
+        let uri = Url::parse("file:///tmp/min_prefix.p").expect("uri");
+        let src = "DEFINE VARIABLE xyz AS INTEGER NO-UNDO.\nx".to_string();
+        backend.set_document_text_version(&uri, 1, src, true);
+        let position = Position::new(1, 1);
+
+        let typed = complete_labels(
+            &backend,
+            &uri,
+            position,
+            CompletionTriggerKind::TRIGGER_FOR_INCOMPLETE_COMPLETIONS,
+        )
+        .await;
+        assert!(typed.is_empty());
+
+        let invoked =
+            complete_labels(&backend, &uri, position, CompletionTriggerKind::INVOKED).await;
+        assert!(invoked.iter().any(|label| label == "xyz"));
+    }
+
+    #[tokio::test]
+    async fn resolves_field_documentation_from_the_table_in_data() {
+        let backend = Backend::for_tests();
+        backend.db_fields_by_table.insert(
+            "CUSTOMER".to_string(),
+            vec![DbFieldInfo {
+                name: "Name".to_string(),
+                field_type: Some("CHARACTER".to_string()),
+                format: Some("x(30)".to_string()),
+                label: Some("Customer name".to_string()),
+                description: None,
+            }],
+        );
+        let item = CompletionItem {
+            label: "Name".to_string(),
+            kind: Some(CompletionItemKind::FIELD),
+            // A detail that does not name the table: resolve must go by `data` alone.
+            detail: Some("CHARACTER".to_string()),
+            data: Some(serde_json::json!({ "table": "customer" })),
+            ..Default::default()
+        };
+
+        let resolved = backend
+            .handle_completion_resolve(item)
+            .await
+            .expect("resolve");
+
+        assert_eq!(
+            resolved.documentation,
+            Some(Documentation::String(
+                "Label: Customer name\nFormat: x(30)".to_string()
+            ))
+        );
+    }
+}
@@ -1,14 +1,22 @@
 use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::request::{GotoTypeDefinitionParams, GotoTypeDefinitionResponse};
 use tower_lsp::lsp_types::*;
 
+use crate::analysis::classes::{
+    class_name_candidates, class_name_to_relative_path, class_type_reference_at_offset,
+};
 use crate::analysis::completion::lookup_case_insensitive_indexes_by_table;
 use crate::analysis::definition::{
-    resolve_buffer_alias_table_location, resolve_include_definition_location,
-    resolve_include_directive_location, resolve_local_definition_location,
-    resolve_preprocessor_define_match,
+    declared_type_name_at, qualified_field_at_offset, resolve_buffer_alias_table_location,
+    resolve_class_method_location, resolve_include_definition_location,
+    resolve_include_directive_location, resolve_include_table_field_location,
+    resolve_local_definition_location, resolve_local_table_field_location,
+    resolve_preprocessor_define_match, resolve_qualified_field_location,
+    resolve_run_program_location,
 };
-use crate::analysis::schema::normalize_lookup_key;
+use crate::analysis::schema::{normalize_lookup_key, strip_db_qualifier};
 use crate::analysis::schema_lookup::lookup_schema_location;
+use crate::analysis::types::is_primitive_type_name;
 use crate::backend::Backend;
 use crate::utils::position::{
     ascii_ident_at_or_before, ascii_ident_or_dash_at_or_before, lsp_pos_to_utf8_byte_offset,
@@ -22,26 +30,24 @@ impl Backend {
     ) -> Result<Option<GotoDefinitionResponse>> {
         let uri = params.text_document_position_params.text_document.uri;
         let pos = params.text_document_position_params.position;
-
-        let text = match self.get_document_text(&uri) {
-            Some(t) => t,
-            None => return Ok(None),
+        let Some(location) = self.resolve_definition_location(&uri, pos).await else {
+            return Ok(None);
         };
+        Ok(Some(GotoDefinitionResponse::Scalar(
+            self.location_to_utf16(location).await,
+        )))
+    }
 
-        let offset = match lsp_pos_to_utf8_byte_offset(&text, pos) {
-            Some(o) => o,
-            None => return Ok(None),
-        };
-
-        let tree = match self.get_document_tree_or_parse(&uri) {
-            Some(t) => t,
-            None => return Ok(None),
-        };
+    /// Definition target under `pos`, with ranges still in tree-sitter byte columns.
+    async fn resolve_definition_location(&self, uri: &Url, pos: Position) -> Option<Location> {
+        let text = self.get_document_text(uri)?;
+        let offset = lsp_pos_to_utf8_byte_offset(&text, pos)?;
+        let tree = self.get_document_tree_or_parse(uri)?;
 
         if let Some(macro_name) = preprocessor_name_at_or_before(&text, offset)
             && let Some(matched) = resolve_preprocessor_define_match(
                 self,
-                &uri,
+                uri,
                 &text,
                 tree.root_node(),
                 &macro_name,
@@ -49,24 +55,32 @@ impl Backend {
             )
             .await
         {
-            return Ok(Some(GotoDefinitionResponse::Scalar(matched.location)));
+            return Some(matched.location);
         }
 
         if let Some(location) =
-            resolve_include_directive_location(self, &uri, &text, tree.root_node(), offset).await
+            resolve_include_directive_location(self, uri, &text, tree.root_node(), offset).await
         {
-            return Ok(Some(GotoDefinitionResponse::Scalar(location)));
+            return Some(location);
         }
 
-        let symbol = match ascii_ident_or_dash_at_or_before(&text, offset)
-            .or_else(|| ascii_ident_at_or_before(&text, offset))
+        if let Some(location) =
+            resolve_run_program_location(self, uri, &text, tree.root_node(), offset).await
         {
-            Some(s) => s,
-            None => return Ok(None),
-        };
+            return Some(location);
+        }
+
+        if let Some(class_name) = class_type_reference_at_offset(&text, offset)
+            && let Some(location) = self.resolve_class_location(uri, &text, &class_name).await
+        {
+            return Some(location);
+        }
+
+        let symbol = ascii_ident_or_dash_at_or_before(&text, offset)
+            .or_else(|| ascii_ident_at_or_before(&text, offset))?;
         let symbol_upper = normalize_lookup_key(&symbol, false);
         let use_index_table_key = self
-            .resolve_use_index_table_key(&uri, &text, tree.root_node(), offset)
+            .resolve_use_index_table_key(uri, &text, tree.root_node(), offset)
             .await;
 
         if let Some(table_key) = &use_index_table_key
@@ -75,60 +89,252 @@ impl Backend {
             && let Some(location) =
                 lookup_schema_location(&self.db_index_definitions, &symbol_upper)
         {
-            return Ok(Some(GotoDefinitionResponse::Scalar(location)));
+            return Some(location);
+        }
+
+        if let Some((qualifier, field)) =
+            qualified_field_at_offset(tree.root_node(), text.as_bytes(), offset)
+        {
+            // `dbname.table`: the part after the dot is a table of that logical database.
+            if strip_db_qualifier(&self.db_name_by_table, &format!("{qualifier}.{field}")).is_some()
+                && let Some(location) =
+                    lookup_schema_location(&self.db_table_definitions, &field.to_ascii_uppercase())
+            {
+                return Some(location);
+            }
+
+            let qualifier = strip_db_qualifier(&self.db_name_by_table, &qualifier)
+                .map(str::to_string)
+                .unwrap_or(qualifier);
+            if let Some(location) = resolve_local_table_field_location(
+                self,
+                uri,
+                tree.root_node(),
+                text.as_bytes(),
+                &qualifier,
+                &field,
+                offset,
+            )
+            .or_else(|| {
+                resolve_qualified_field_location(
+                    self,
+                    tree.root_node(),
+                    text.as_bytes(),
+                    &qualifier,
+                    &field,
+                    offset,
+                )
+            }) {
+                return Some(location);
+            }
+
+            if let Some(location) = resolve_include_table_field_location(
+                self,
+                uri,
+                &text,
+                tree.root_node(),
+                &qualifier,
+                &field,
+                offset,
+            )
+            .await
+            {
+                return Some(location);
+            }
         }
 
         if let Some(location) = resolve_buffer_alias_table_location(
             self,
-            &uri,
+            uri,
             tree.root_node(),
             text.as_bytes(),
             &symbol_upper,
             offset,
         ) {
-            return Ok(Some(GotoDefinitionResponse::Scalar(location)));
+            return Some(location);
+        }
+
+        if let Some(location) =
+            resolve_class_method_location(self, uri, &text, tree.root_node(), offset).await
+        {
+            return Some(location);
         }
 
         if let Some(location) = resolve_local_definition_location(
-            &uri,
+            uri,
             tree.root_node(),
             text.as_bytes(),
             &symbol,
             offset,
         ) {
-            return Ok(Some(GotoDefinitionResponse::Scalar(location)));
+            return Some(location);
         }
 
-        if let Some(location) = resolve_include_definition_location(
-            self,
-            &uri,
-            &text,
-            tree.root_node(),
-            &symbol,
-            offset,
-        )
-        .await
+        if let Some(location) =
+            resolve_include_definition_location(self, uri, &text, tree.root_node(), &symbol, offset)
+                .await
         {
-            return Ok(Some(GotoDefinitionResponse::Scalar(location)));
+            return Some(location);
         }
 
         if let Some(location) = lookup_schema_location(&self.db_table_definitions, &symbol_upper) {
-            return Ok(Some(GotoDefinitionResponse::Scalar(location)));
+            return Some(location);
         }
 
         if let Some(location) = lookup_schema_location(&self.db_sequence_definitions, &symbol_upper)
         {
-            return Ok(Some(GotoDefinitionResponse::Scalar(location)));
+            return Some(location);
         }
 
         if let Some(location) = lookup_schema_location(&self.db_field_definitions, &symbol_upper) {
-            return Ok(Some(GotoDefinitionResponse::Scalar(location)));
+            return Some(location);
         }
 
         if let Some(location) = lookup_schema_location(&self.db_index_definitions, &symbol_upper) {
-            return Ok(Some(GotoDefinitionResponse::Scalar(location)));
+            return Some(location);
+        }
+
+        None
+    }
+
+    /// Jumps from a variable/parameter/property to the `.cls` of its declared `AS` type.
+    pub async fn handle_goto_type_definition(
+        &self,
+        params: GotoTypeDefinitionParams,
+    ) -> Result<Option<GotoTypeDefinitionResponse>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let pos = params.text_document_position_params.position;
+        let Some(text) = self.get_document_text(&uri) else {
+            return Ok(None);
+        };
+        let Some(offset) = lsp_pos_to_utf8_byte_offset(&text, pos) else {
+            return Ok(None);
+        };
+        let Some(tree) = self.get_document_tree_or_parse(&uri) else {
+            return Ok(None);
+        };
+        let Some(symbol) = ascii_ident_or_dash_at_or_before(&text, offset) else {
+            return Ok(None);
+        };
+
+        let Some(type_name) =
+            declared_type_name_at(tree.root_node(), text.as_bytes(), &symbol, offset)
+        else {
+            return Ok(None);
+        };
+        if is_primitive_type_name(&type_name) {
+            return Ok(None);
         }
+        let Some(location) = self.resolve_class_location(&uri, &text, &type_name).await else {
+            return Ok(None);
+        };
+        Ok(Some(GotoTypeDefinitionResponse::Scalar(
+            self.location_to_utf16(location).await,
+        )))
+    }
+
+    /// Resolves a class reference (expanding `USING` imports) to the top of its `.cls` file.
+    async fn resolve_class_location(
+        &self,
+        uri: &Url,
+        text: &str,
+        class_name: &str,
+    ) -> Option<Location> {
+        let current_path = uri.to_file_path().ok()?;
+        let imports = self.using_imports_for(uri, text);
+        for candidate in class_name_candidates(class_name, &imports) {
+            if let Some(path) = self
+                .resolve_include_path_for(&current_path, &class_name_to_relative_path(&candidate))
+                .await
+                && let Ok(target) = Url::from_file_path(path)
+            {
+                return Some(Location {
+                    uri: target,
+                    range: Range::default(),
+                });
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backend::Backend;
+    use tower_lsp::lsp_types::{
+        GotoDefinitionParams, GotoDefinitionResponse, Position, TextDocumentIdentifier,
+        TextDocumentPositionParams, Url,
+    };
+
+    #[tokio::test]
+    async fn reports_definition_columns_in_utf16() {
+        let backend = Backend::for_tests();
+        let uri = Url::parse("file:///tmp/multibyte.p").expect("uri");
+        let prefix = "/* zażółć */ DEFINE VARIABLE ";
+        let src = format!("{prefix}cName AS CHARACTER NO-UNDO.\ncName = \"x\".\n");
+        backend.set_document_text_version(&uri, 1, src, true);
+
+        let response = backend
+            .handle_goto_definition(GotoDefinitionParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                    position: Position::new(1, 2),
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await
+            .expect("goto definition");
+
+        let Some(GotoDefinitionResponse::Scalar(location)) = response else {
+            panic!("expected a single location, got {response:?}");
+        };
+        let column = prefix.encode_utf16().count() as u32;
+        assert_eq!(location.uri, uri);
+        assert_eq!(location.range.start, Position::new(0, column));
+        assert_eq!(location.range.end, Position::new(0, column + 5));
+    }
+
+    #[tokio::test]
+    async fn resolves_new_with_a_using_short_name_to_the_class_file() {
+        let base = std::env::temp_dir().join(format!(
+            "abl_ls_class_definition_test_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("epoch")
+                .as_nanos()
+        ));
+        let package_dir = base.join("app").join("util");
+        std::fs::create_dir_all(&package_dir).expect("create package dir");
+        let class_path = package_dir.join("Strings.cls");
+        std::fs::write(&class_path, "CLASS app.util.Strings:\nEND CLASS.\n").expect("write class");
+
+        let backend = Backend::for_tests();
+        *backend.workspace_roots.lock().await = vec![base.clone()];
+        let uri = Url::from_file_path(base.join("main.p")).expect("uri");
+        let src = "USING app.util.*.\nDEFINE VARIABLE s AS Strings NO-UNDO.\ns = NEW Strings().\n";
+        backend.set_document_text_version(&uri, 1, src.to_string(), true);
+
+        let response = backend
+            .handle_goto_definition(GotoDefinitionParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                    position: Position::new(2, 10),
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await
+            .expect("goto definition");
+
+        let Some(GotoDefinitionResponse::Scalar(location)) = response else {
+            panic!("expected the class file, got {response:?}");
+        };
+        assert_eq!(
+            location.uri,
+            Url::from_file_path(&class_path).expect("class uri")
+        );
 
-        Ok(None)
+        let _ = std::fs::remove_dir_all(base);
     }
 }
@@ -1,19 +1,33 @@
 use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{debug, info};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 
 use tower_lsp::lsp_types::*;
 
-use crate::analysis::diagnostics::config::diagnostics_feature_enabled_for_uri;
+use crate::analysis::diagnostics::config::{
+    diagnostics_feature_enabled_for_uri, unknown_function_severity_for_uri,
+};
+use crate::analysis::diagnostics::includes::collect_unresolved_path_diags;
+use crate::analysis::diagnostics::local::collect_file_local_diags;
 use crate::analysis::diagnostics::semantic::{
     UnknownSymbolDiagParams, collect_function_call_arity_diags, collect_unknown_symbol_diags,
     is_latest_version, should_accept_version,
 };
-use crate::analysis::diagnostics::syntax::collect_ts_error_diags;
-use crate::analysis::diagnostics::types::{
-    collect_assignment_type_diags, collect_function_call_arg_type_diags,
-};
+use crate::analysis::diagnostics::syntax::{collect_syntax_diags, with_previous_semantic_diags};
 use crate::backend::Backend;
+use crate::config::DiagnosticsScope;
+use crate::utils::paths::collect_workspace_source_files;
+use crate::utils::position::byte_ranges_to_utf16;
+use crate::utils::source::{parse_source, read_source_file};
 
 const MAX_SYNTAX_DIAGNOSTICS_PER_CHANGE: usize = 64;
+const MAX_WORKSPACE_DIAGNOSTIC_FILES: usize = 2000;
+const WORKSPACE_DIAGNOSTICS_CONCURRENCY: usize = 4;
 
 pub async fn on_change(
     backend: &Backend,
@@ -21,6 +35,7 @@ pub async fn on_change(
     version: i32,
     text: String,
     include_semantic_diags: bool,
+    debounce_ms: u64,
 ) {
     if !should_accept_version(backend, &uri, version) {
         return;
@@ -32,7 +47,23 @@ pub async fn on_change(
         return;
     }
 
+    // Dump files and other non-ABL documents are never parsed with the ABL grammar.
+    if let Ok(path) = uri.to_file_path()
+        && !backend.config.lock().await.is_abl_path(&path)
+    {
+        backend
+            .client
+            .publish_diagnostics(uri.clone(), vec![], Some(version))
+            .await;
+        return;
+    }
+
     let diagnostics_enabled = backend.config.lock().await.diagnostics.enabled;
+    let oversized = backend
+        .config
+        .lock()
+        .await
+        .exceeds_max_file_size(text.len());
     let diagnostics_cfg = backend.config.lock().await.diagnostics.clone();
     let workspace_root = backend.workspace_root.lock().await.clone();
     let unknown_variables_enabled = diagnostics_feature_enabled_for_uri(
@@ -40,29 +71,37 @@ pub async fn on_change(
         workspace_root.as_deref(),
         &diagnostics_cfg.unknown_variables,
     );
-    let unknown_functions_enabled = diagnostics_feature_enabled_for_uri(
-        &uri,
-        workspace_root.as_deref(),
-        &diagnostics_cfg.unknown_functions,
-    );
+    let unknown_functions_severity =
+        unknown_function_severity_for_uri(&uri, &diagnostics_cfg.unknown_functions);
+    let unknown_functions_enabled = unknown_functions_severity.is_some()
+        && diagnostics_feature_enabled_for_uri(
+            &uri,
+            workspace_root.as_deref(),
+            &diagnostics_cfg.unknown_functions.feature,
+        );
     let unknown_variables_ignored: HashSet<String> = diagnostics_cfg
         .unknown_variables
         .ignore
         .iter()
+        .chain(&diagnostics_cfg.extra_builtin_variables)
         .map(|name| name.to_ascii_uppercase())
         .collect();
     let unknown_functions_ignored: HashSet<String> = diagnostics_cfg
         .unknown_functions
+        .feature
         .ignore
         .iter()
+        .chain(&diagnostics_cfg.extra_builtin_functions)
         .map(|name| name.to_ascii_uppercase())
         .collect();
     let parsed_tree = {
         let Some(doc) = backend.documents.get_mut(&uri) else {
             return;
         };
+        // Pending edits only describe this text when no newer change has arrived.
+        let old_tree = doc.edited_tree.as_ref().filter(|_| doc.version == version);
         let mut parser = doc.parser.lock().expect("ABL parser mutex poisoned");
-        parser.parse(text.clone(), None)
+        parse_source(&mut parser, &text, old_tree)
     };
     let tree = match parsed_tree {
         Some(t) => t,
@@ -95,11 +134,55 @@ pub async fn on_change(
     }
 
     let mut diags: Vec<Diagnostic> = Vec::new();
-    collect_ts_error_diags(
+    collect_syntax_diags(
         tree.root_node(),
+        text.as_bytes(),
         &mut diags,
         MAX_SYNTAX_DIAGNOSTICS_PER_CHANGE,
     );
+    if oversized {
+        // Large (usually generated) files keep syntax errors from this parse but skip the
+        // semantic tree walks; `include_semantic_diags` is only set on open/save, so log there.
+        if include_semantic_diags {
+            info!(
+                "{} is {} KiB, above max_file_size_kb; semantic diagnostics are skipped",
+                uri,
+                text.len() / 1024
+            );
+        }
+        byte_ranges_to_utf16(&text, diags.iter_mut().map(|diag| &mut diag.range));
+        if !is_latest_version(backend, &uri, version) {
+            return;
+        }
+        backend.set_document_published_diags(&uri, diags.clone());
+        backend
+            .client
+            .publish_diagnostics(uri.clone(), diags, Some(version))
+            .await;
+        backend.set_document_tree_if_version(&uri, version, tree);
+        return;
+    }
+    // Syntax errors go out right away next to the previous semantic results; the full set is
+    // re-published for this version once the semantic passes finish, superseding this one.
+    let mut syntax_diags = diags.clone();
+    byte_ranges_to_utf16(&text, syntax_diags.iter_mut().map(|diag| &mut diag.range));
+    let immediate =
+        with_previous_semantic_diags(syntax_diags, backend.get_document_published_diags(&uri));
+    if !is_latest_version(backend, &uri, version) {
+        return;
+    }
+    backend
+        .client
+        .publish_diagnostics(uri.clone(), immediate, Some(version))
+        .await;
+    backend.set_document_tree_if_version(&uri, version, tree.clone());
+    if debounce_ms > 0 {
+        // The semantic pass waits until typing pauses (a newer change aborts this task).
+        tokio::time::sleep(Duration::from_millis(debounce_ms)).await;
+    }
+    if !is_latest_version(backend, &uri, version) {
+        return;
+    }
     if !collect_function_call_arity_diags(
         backend,
         &uri,
@@ -123,6 +206,9 @@ pub async fn on_change(
             include_semantic_diags,
             unknown_variables_enabled,
             unknown_functions_enabled,
+            unknown_variables_severity: diagnostics_cfg.unknown_variables.severity.to_lsp(),
+            unknown_functions_severity: unknown_functions_severity
+                .unwrap_or(DiagnosticSeverity::ERROR),
             unknown_variables_ignored: &unknown_variables_ignored,
             unknown_functions_ignored: &unknown_functions_ignored,
         },
@@ -132,12 +218,34 @@ pub async fn on_change(
     {
         return;
     }
-    // Keep lightweight assignment type checks active for on-change diagnostics.
-    collect_assignment_type_diags(tree.root_node(), text.as_bytes(), &mut diags);
-    collect_function_call_arg_type_diags(tree.root_node(), text.as_bytes(), &mut diags);
+    collect_file_local_diags(
+        tree.root_node(),
+        text.as_bytes(),
+        &uri,
+        &diagnostics_cfg,
+        &backend.db_tables,
+        include_semantic_diags,
+        &mut diags,
+    );
+    // Tree-sitter ranges carry byte columns; clients expect UTF-16.
+    byte_ranges_to_utf16(&text, diags.iter_mut().map(|diag| &mut diag.range));
+    // Include resolution hits the disk, so it runs on open/save rather than every keystroke.
+    if include_semantic_diags {
+        collect_unresolved_path_diags(
+            backend,
+            &uri,
+            &text,
+            tree.root_node(),
+            &diagnostics_cfg,
+            workspace_root.as_deref(),
+            &mut diags,
+        )
+        .await;
+    }
     if !is_latest_version(backend, &uri, version) {
         return;
     }
+    backend.set_document_published_diags(&uri, diags.clone());
     backend
         .client
         .publish_diagnostics(uri.clone(), diags, Some(version))
@@ -148,3 +256,128 @@ pub async fn on_change(
     }
     backend.set_document_tree_if_version(&uri, version, tree);
 }
+
+/// Starts the background pass for `diagnostics.scope = "workspace"`, cancelling a running one.
+pub fn spawn_workspace_diagnostics(backend: &Backend) {
+    let task = {
+        let backend = backend.clone();
+        tokio::spawn(async move { run_workspace_diagnostics(&backend).await })
+    };
+    let previous = backend
+        .workspace_diagnostics_task
+        .lock()
+        .expect("workspace diagnostics mutex poisoned")
+        .replace(task.abort_handle());
+    if let Some(previous) = previous {
+        previous.abort();
+    }
+}
+
+/// Stops the workspace pass, if one is running (the spawned per-file tasks go with its `JoinSet`).
+pub fn cancel_workspace_diagnostics(backend: &Backend) {
+    if let Some(task) = backend
+        .workspace_diagnostics_task
+        .lock()
+        .expect("workspace diagnostics mutex poisoned")
+        .take()
+    {
+        task.abort();
+    }
+}
+
+/// Checks the unopened files with one of `file_extensions` in every workspace folder. Include
+/// files are skipped: they only make sense in the context of the file that includes them.
+async fn run_workspace_diagnostics(backend: &Backend) {
+    let config = backend.config.lock().await.clone();
+    if !config.diagnostics.enabled || config.diagnostics.scope != DiagnosticsScope::Workspace {
+        return;
+    }
+    let roots = backend.workspace_roots.lock().await.clone();
+    let Ok(files) = tokio::task::spawn_blocking(move || {
+        let extensions = config
+            .file_extensions
+            .iter()
+            .map(|ext| ext.trim_start_matches('.'))
+            .filter(|ext| !ext.eq_ignore_ascii_case("i"))
+            .collect::<Vec<_>>();
+        collect_workspace_source_files(
+            &roots,
+            &config.propath,
+            &extensions,
+            &config.workspace.exclude_dirs,
+            MAX_WORKSPACE_DIAGNOSTIC_FILES,
+        )
+    })
+    .await
+    else {
+        return;
+    };
+
+    let semaphore = Arc::new(Semaphore::new(WORKSPACE_DIAGNOSTICS_CONCURRENCY));
+    let mut tasks = JoinSet::new();
+    for path in files {
+        let Ok(uri) = Url::from_file_path(&path) else {
+            continue;
+        };
+        let Ok(permit) = semaphore.clone().acquire_owned().await else {
+            break;
+        };
+        let backend = backend.clone();
+        tasks.spawn(async move {
+            let _permit = permit;
+            publish_unopened_file_diagnostics(&backend, uri, &path).await;
+        });
+    }
+    while tasks.join_next().await.is_some() {}
+    debug!("workspace diagnostics pass finished");
+}
+
+/// Publishes the checks that need no open-document state: syntax, the file-local passes and
+/// unresolved includes / `RUN` targets.
+async fn publish_unopened_file_diagnostics(backend: &Backend, uri: Url, path: &Path) {
+    // Opened documents are owned by `on_change`; never overwrite their fresher results.
+    if backend.documents.contains_key(&uri) {
+        return;
+    }
+    let Ok(text) = read_source_file(path).await else {
+        return;
+    };
+    let Some(tree) = backend.new_abl_parser().parse(&text, None) else {
+        return;
+    };
+    let diagnostics_cfg = backend.config.lock().await.diagnostics.clone();
+    let workspace_root = backend.workspace_root.lock().await.clone();
+
+    let mut diags = Vec::new();
+    collect_syntax_diags(
+        tree.root_node(),
+        text.as_bytes(),
+        &mut diags,
+        MAX_SYNTAX_DIAGNOSTICS_PER_CHANGE,
+    );
+    collect_file_local_diags(
+        tree.root_node(),
+        text.as_bytes(),
+        &uri,
+        &diagnostics_cfg,
+        &backend.db_tables,
+        true,
+        &mut diags,
+    );
+    byte_ranges_to_utf16(&text, diags.iter_mut().map(|diag| &mut diag.range));
+    collect_unresolved_path_diags(
+        backend,
+        &uri,
+        &text,
+        tree.root_node(),
+        &diagnostics_cfg,
+        workspace_root.as_deref(),
+        &mut diags,
+    )
+    .await;
+
+    if backend.documents.contains_key(&uri) {
+        return;
+    }
+    backend.client.publish_diagnostics(uri, diags, None).await;
+}
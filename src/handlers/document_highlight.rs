@@ -0,0 +1,50 @@
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::*;
+
+use crate::analysis::highlights::collect_document_highlights;
+use crate::analysis::scopes::containing_scope;
+use crate::backend::Backend;
+use crate::utils::position::{
+    ascii_ident_at_or_before, byte_ranges_to_utf16, lsp_pos_to_utf8_byte_offset,
+};
+
+impl Backend {
+    pub async fn handle_document_highlight(
+        &self,
+        params: DocumentHighlightParams,
+    ) -> Result<Option<Vec<DocumentHighlight>>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let pos = params.text_document_position_params.position;
+
+        let text = match self.get_document_text(&uri) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+        let offset = match lsp_pos_to_utf8_byte_offset(&text, pos) {
+            Some(o) => o,
+            None => return Ok(None),
+        };
+        let symbol = match ascii_ident_at_or_before(&text, offset) {
+            Some(s) => s,
+            None => return Ok(None),
+        };
+        let tree = match self.get_document_tree_or_parse(&uri) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+
+        let root = tree.root_node();
+        let Some(scope) = containing_scope(root, offset) else {
+            return Ok(None);
+        };
+        let mut highlights = Vec::new();
+        collect_document_highlights(root, text.as_bytes(), &symbol, scope, &mut highlights);
+        byte_ranges_to_utf16(&text, highlights.iter_mut().map(|h| &mut h.range));
+
+        if highlights.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(highlights))
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::*;
+
+use crate::analysis::document_symbols::{
+    FlatSymbol, collect_document_symbols, collect_flat_symbols,
+};
+use crate::backend::Backend;
+use crate::utils::position::byte_ranges_to_utf16;
+
+impl Backend {
+    pub async fn handle_document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        let uri = params.text_document.uri;
+        let text = match self.get_document_text(&uri) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+        let tree = match self.get_document_tree_or_parse(&uri) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+
+        let mut symbols = Vec::new();
+        collect_document_symbols(tree.root_node(), text.as_bytes(), &mut symbols);
+
+        let mut ranges = Vec::new();
+        let mut pending = symbols.iter_mut().collect::<Vec<_>>();
+        while let Some(symbol) = pending.pop() {
+            ranges.push(&mut symbol.range);
+            ranges.push(&mut symbol.selection_range);
+            if let Some(children) = symbol.children.as_mut() {
+                pending.extend(children.iter_mut());
+            }
+        }
+        byte_ranges_to_utf16(&text, ranges);
+        Ok(Some(DocumentSymbolResponse::Nested(symbols)))
+    }
+
+    /// Custom `abl/documentSymbols`: a flat `{name, kind, line, type}` list for scripts and tooling.
+    pub async fn handle_abl_document_symbols(
+        &self,
+        params: TextDocumentIdentifier,
+    ) -> Result<Vec<FlatSymbol>> {
+        let Some(text) = self.get_document_text(&params.uri) else {
+            return Ok(vec![]);
+        };
+        let Some(tree) = self.get_document_tree_or_parse(&params.uri) else {
+            return Ok(vec![]);
+        };
+        Ok(collect_flat_symbols(tree.root_node(), text.as_bytes()))
+    }
+}
@@ -0,0 +1,184 @@
+use serde_json::{Value, json};
+use tower_lsp::jsonrpc::{Error, Result};
+use tower_lsp::lsp_types::*;
+
+use crate::backend::Backend;
+use crate::utils::position::lsp_pos_to_utf8_byte_offset;
+use crate::utils::source::read_source_file;
+
+pub const RELOAD_SCHEMA_COMMAND: &str = "abl.reloadSchema";
+pub const RELOAD_CONFIG_COMMAND: &str = "abl.reloadConfig";
+pub const DEBUG_SYNTAX_TREE_COMMAND: &str = "abl.debugSyntaxTree";
+
+/// Commands advertised through `execute_command_provider`.
+pub fn supported_commands() -> Vec<String> {
+    vec![
+        RELOAD_SCHEMA_COMMAND.to_string(),
+        RELOAD_CONFIG_COMMAND.to_string(),
+        DEBUG_SYNTAX_TREE_COMMAND.to_string(),
+    ]
+}
+
+impl Backend {
+    pub async fn handle_execute_command(
+        &self,
+        params: ExecuteCommandParams,
+    ) -> Result<Option<Value>> {
+        match params.command.as_str() {
+            RELOAD_SCHEMA_COMMAND => {
+                self.reload_db_tables_from_current_config().await;
+                Ok(Some(self.schema_summary()))
+            }
+            RELOAD_CONFIG_COMMAND => {
+                self.reload_workspace_config().await;
+                Ok(Some(self.schema_summary()))
+            }
+            DEBUG_SYNTAX_TREE_COMMAND => self.debug_syntax_tree(&params.arguments).await,
+            other => Err(Error::invalid_params(format!("unknown command: {other}"))),
+        }
+    }
+
+    /// `[uri, position?]` -> s-expression of the document, or of the smallest named node at `position`.
+    async fn debug_syntax_tree(&self, arguments: &[Value]) -> Result<Option<Value>> {
+        let uri = arguments
+            .first()
+            .and_then(Value::as_str)
+            .and_then(|raw| Url::parse(raw).ok())
+            .ok_or_else(|| {
+                Error::invalid_params("expected a document URI as the first argument")
+            })?;
+        let position = match arguments.get(1) {
+            Some(raw) => Some(
+                serde_json::from_value::<Position>(raw.clone())
+                    .map_err(|err| Error::invalid_params(format!("invalid position: {err}")))?,
+            ),
+            None => None,
+        };
+
+        let (text, tree) = match (
+            self.get_document_text(&uri),
+            self.get_document_tree_or_parse(&uri),
+        ) {
+            (Some(text), Some(tree)) => (text, tree),
+            _ => {
+                let path = uri
+                    .to_file_path()
+                    .map_err(|_| Error::invalid_params("document is not open and not a file"))?;
+                let text = read_source_file(&path)
+                    .await
+                    .map_err(|err| Error::invalid_params(format!("cannot read document: {err}")))?;
+                let tree = self
+                    .new_abl_parser()
+                    .parse(&text, None)
+                    .ok_or_else(Error::internal_error)?;
+                (text, tree)
+            }
+        };
+
+        let root = tree.root_node();
+        let node = match position {
+            Some(position) => {
+                let offset = lsp_pos_to_utf8_byte_offset(&text, position)
+                    .ok_or_else(|| Error::invalid_params("position is outside the document"))?;
+                root.named_descendant_for_byte_range(offset, offset)
+                    .unwrap_or(root)
+            }
+            None => root,
+        };
+        Ok(Some(Value::String(node.to_sexp())))
+    }
+
+    fn schema_summary(&self) -> Value {
+        let fields = self
+            .db_fields_by_table
+            .iter()
+            .map(|entry| entry.value().len())
+            .sum::<usize>();
+        json!({
+            "tables": self.db_tables.len(),
+            "fields": fields,
+            "indexes": self.db_index_definitions.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RELOAD_SCHEMA_COMMAND, supported_commands};
+    use crate::backend::Backend;
+    use serde_json::json;
+    use tower_lsp::lsp_types::ExecuteCommandParams;
+
+    fn command(name: &str) -> ExecuteCommandParams {
+        ExecuteCommandParams {
+            command: name.to_string(),
+            arguments: Vec::new(),
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn reload_schema_rereads_the_dumpfile_and_reports_counts() {
+        let base = std::env::temp_dir().join(format!(
+            "abl_ls_reload_schema_test_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("epoch")
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(&base).expect("create temp dir");
+        let df_path = base.join("schema.df");
+        std::fs::write(
+            &df_path,
+            "ADD TABLE \"Customer\"\n  AREA \"Schema Area\"\n.\n",
+        )
+        .expect("write df");
+
+        let backend = Backend::for_tests();
+        *backend.workspace_roots.lock().await = vec![base.clone()];
+        backend.config.lock().await.dumpfile = vec!["schema.df".to_string()];
+
+        let summary = backend
+            .handle_execute_command(command(RELOAD_SCHEMA_COMMAND))
+            .await
+            .expect("reload schema");
+        assert_eq!(
+            summary,
+            Some(json!({"tables": 1, "fields": 0, "indexes": 0}))
+        );
+
+        // A rebuilt dumpfile is picked up without a watched-files notification.
+        std::fs::write(
+            &df_path,
+            concat!(
+                "ADD TABLE \"Customer\"\n  AREA \"Schema Area\"\n.\n",
+                "ADD TABLE \"Order\"\n  AREA \"Schema Area\"\n.\n",
+                "ADD FIELD \"Name\" OF \"Customer\" AS character\n  FORMAT \"x(30)\"\n.\n",
+            ),
+        )
+        .expect("rewrite df");
+        let summary = backend
+            .handle_execute_command(command(RELOAD_SCHEMA_COMMAND))
+            .await
+            .expect("reload schema");
+        assert_eq!(
+            summary,
+            Some(json!({"tables": 2, "fields": 1, "indexes": 0}))
+        );
+        assert!(backend.db_tables.contains("ORDER"));
+
+        let _ = std::fs::remove_dir_all(base);
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_commands() {
+        let backend = Backend::for_tests();
+        assert!(supported_commands().contains(&RELOAD_SCHEMA_COMMAND.to_string()));
+        assert!(
+            backend
+                .handle_execute_command(command("abl.unknown"))
+                .await
+                .is_err()
+        );
+    }
+}
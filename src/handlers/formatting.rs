@@ -1,8 +1,12 @@
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::{DocumentFormattingParams, Position, Range, TextEdit};
 
-use crate::analysis::formatting::{IndentOptions, autoindent_text, preserves_ast_shape};
+use crate::analysis::formatting::{
+    ALIGN_DEFINE_AS_MAX_COLUMN, IndentOptions, align_define_as, apply_keyword_case,
+    autoindent_text, expand_assign, normalize_line_endings_whitespace, preserves_ast_shape,
+};
 use crate::backend::Backend;
+use crate::config::KeywordCase;
 
 impl Backend {
     pub async fn handle_formatting(
@@ -29,7 +33,26 @@ impl Backend {
             use_tabs: !params.options.insert_spaces || config.formatting.use_tabs,
         };
 
-        let formatted = autoindent_text(&text, options);
+        let mut formatted = if config.formatting.expand_assign {
+            autoindent_text(&expand_assign(&text), options)
+        } else {
+            autoindent_text(&text, options)
+        };
+        if config.formatting.align_define_as {
+            formatted = align_define_as(&formatted, ALIGN_DEFINE_AS_MAX_COLUMN);
+        }
+        match config.formatting.keyword_case {
+            KeywordCase::Upper => formatted = apply_keyword_case(&formatted, true),
+            KeywordCase::Lower => formatted = apply_keyword_case(&formatted, false),
+            KeywordCase::Preserve => {}
+        }
+        if config.formatting.trim_trailing_whitespace || config.formatting.insert_final_newline {
+            formatted = normalize_line_endings_whitespace(
+                &formatted,
+                config.formatting.trim_trailing_whitespace,
+                config.formatting.insert_final_newline,
+            );
+        }
         if formatted == text {
             return Ok(Some(vec![]));
         }
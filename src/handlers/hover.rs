@@ -2,21 +2,27 @@ use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 
 use crate::analysis::buffers::collect_buffer_mappings;
-use crate::analysis::completion::lookup_case_insensitive_indexes_by_table;
+use crate::analysis::builtins::{builtin_function_doc, is_builtin_function_name};
+use crate::analysis::completion::{
+    lookup_case_insensitive_fields_by_table_symbol, lookup_case_insensitive_indexes_by_table,
+    lookup_case_insensitive_indexes_by_table_symbol,
+};
 use crate::analysis::definition::{
+    expand_preprocessor_define_value, qualified_field_at_offset,
     resolve_include_definition_location, resolve_include_directive_location,
     resolve_preprocessor_define_match,
 };
-use crate::analysis::definitions::collect_definition_symbols;
+use crate::analysis::definitions::{collect_definition_symbols, find_variable_declaration};
 use crate::analysis::functions::{find_function_signature, find_function_signature_from_includes};
 use crate::analysis::hover::{
-    find_db_field_matches, find_local_table_field_hover, find_local_table_field_hover_by_symbol,
-    function_signature_hover, markdown_hover, symbol_at_offset,
+    builtin_function_hover, db_index_hover, db_table_hover, find_db_field_matches,
+    find_local_table_field_hover, find_local_table_field_hover_by_symbol, function_signature_hover,
+    markdown_hover, preprocessor_define_hover, symbol_at_offset,
 };
 use crate::analysis::includes::{
     collect_include_sites_from_tree, include_site_matches_file_offset,
 };
-use crate::analysis::schema::normalize_lookup_key;
+use crate::analysis::schema::{is_logical_db_name, normalize_lookup_key, strip_db_qualifier};
 use crate::analysis::schema_lookup::has_schema_key;
 use crate::backend::Backend;
 use crate::utils::position::{
@@ -90,13 +96,15 @@ impl Backend {
             return Ok(Some(markdown_hover(lines.join("\n\n"))));
         }
 
-        let symbol = match symbol_at_offset(tree.root_node(), &text, offset).or_else(|| {
-            ascii_ident_or_dash_at_or_before(&text, offset)
-                .or_else(|| ascii_ident_at_or_before(&text, offset))
-        }) {
-            Some(s) => s,
-            None => return Ok(None),
-        };
+        if let Some(site) = collect_include_sites_from_tree(tree.root_node(), text.as_bytes())
+            .into_iter()
+            .find(|site| include_site_matches_file_offset(site, offset))
+        {
+            return Ok(Some(markdown_hover(format!(
+                "**Include File**\n\n`{}` not found on PROPATH",
+                site.path
+            ))));
+        }
 
         if let Some(macro_name) = preprocessor_name_at_or_before(&text, offset)
             && let Some(matched) = resolve_preprocessor_define_match(
@@ -109,17 +117,46 @@ impl Backend {
             )
             .await
         {
-            let kind = if matched.is_global {
-                "&GLOBAL-DEFINE"
-            } else {
-                "&SCOPED-DEFINE"
+            let source_path = (matched.location.uri != uri).then(|| {
+                matched
+                    .location
+                    .uri
+                    .to_file_path()
+                    .ok()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|| matched.location.uri.to_string())
+            });
+            let expanded = match matched.value.as_deref() {
+                Some(value) if value.contains("{&") => Some(
+                    expand_preprocessor_define_value(
+                        self,
+                        &uri,
+                        &text,
+                        tree.root_node(),
+                        value,
+                        offset,
+                    )
+                    .await,
+                ),
+                _ => None,
             };
-            let mut markdown = format!("**{}** `{}`", kind, matched.name);
-            if let Some(value) = matched.value {
-                markdown.push_str(&format!("\n\nValue: `{}`", value));
-            }
-            return Ok(Some(markdown_hover(markdown)));
+            return Ok(Some(preprocessor_define_hover(
+                &matched.name,
+                matched.value.as_deref(),
+                expanded.as_deref(),
+                matched.is_global,
+                source_path.as_deref(),
+            )));
         }
+
+        let symbol = match symbol_at_offset(tree.root_node(), &text, offset).or_else(|| {
+            ascii_ident_or_dash_at_or_before(&text, offset)
+                .or_else(|| ascii_ident_at_or_before(&text, offset))
+        }) {
+            Some(s) => s,
+            None => return Ok(None),
+        };
+
         let symbol_upper = normalize_lookup_key(&symbol, true);
         let use_index_table_key = self
             .resolve_use_index_table_key(&uri, &text, tree.root_node(), offset)
@@ -138,17 +175,17 @@ impl Backend {
                 table_key.to_ascii_uppercase(),
                 index_name.to_ascii_uppercase()
             );
-            let mut lines = vec![format!("**DB Index** `{}`", index_name)];
-            lines.push(format!("Table: `{}`", table_key));
-            if let Some(fields) = self.db_index_fields_by_table_index.get(&key)
-                && !fields.is_empty()
-            {
-                lines.push("Fields (order):".to_string());
-                for field in fields.iter() {
-                    lines.push(format!("- `{field}`"));
-                }
-            }
-            return Ok(Some(markdown_hover(lines.join("\n\n"))));
+            let flags = self
+                .db_index_flags_by_table_index
+                .get(&key)
+                .map(|flags| *flags)
+                .unwrap_or_default();
+            let fields = self
+                .db_index_fields_by_table_index
+                .get(&key)
+                .map(|fields| fields.clone())
+                .unwrap_or_default();
+            return Ok(Some(db_index_hover(&index_name, table_key, flags, &fields)));
         }
 
         if let Some(sig) = find_function_signature(tree.root_node(), text.as_bytes(), &symbol) {
@@ -167,6 +204,12 @@ impl Backend {
             return Ok(Some(function_signature_hover(&sig)));
         }
 
+        if let Some(declaration) =
+            find_variable_declaration(tree.root_node(), text.as_bytes(), &symbol, offset)
+        {
+            return Ok(Some(markdown_hover(format!("`{declaration}`"))));
+        }
+
         let mut defs = Vec::new();
         collect_definition_symbols(tree.root_node(), text.as_bytes(), &mut defs);
         if let Some(def) = defs
@@ -230,8 +273,23 @@ impl Backend {
             ))));
         }
 
+        if is_logical_db_name(&self.db_name_by_table, &symbol) {
+            return Ok(Some(markdown_hover(format!("**Database** `{}`", symbol))));
+        }
+
         if has_schema_key(&self.db_table_definitions, &symbol_upper) {
-            return Ok(Some(markdown_hover(format!("**DB Table** `{}`", symbol))));
+            let name = self
+                .db_table_labels
+                .get(&symbol_upper)
+                .map(|label| label.value().clone())
+                .unwrap_or_else(|| symbol.clone());
+            let fields =
+                lookup_case_insensitive_fields_by_table_symbol(&self.db_fields_by_table, &symbol)
+                    .unwrap_or_default();
+            let indexes =
+                lookup_case_insensitive_indexes_by_table_symbol(&self.db_indexes_by_table, &symbol)
+                    .unwrap_or_default();
+            return Ok(Some(db_table_hover(&name, &fields, &indexes)));
         }
 
         if has_schema_key(&self.db_sequence_definitions, &symbol_upper) {
@@ -252,7 +310,23 @@ impl Backend {
             return Ok(Some(local_field_hover));
         }
 
-        let field_matches = find_db_field_matches(&self.db_fields_by_table, &symbol_upper);
+        let mut field_matches = find_db_field_matches(
+            &self.db_fields_by_table,
+            &self.db_tables_by_field,
+            &symbol_upper,
+        );
+        if let Some((qualifier, _)) =
+            qualified_field_at_offset(tree.root_node(), text.as_bytes(), offset)
+        {
+            let table = strip_db_qualifier(&self.db_name_by_table, &qualifier)
+                .unwrap_or(qualifier.as_str());
+            if field_matches
+                .iter()
+                .any(|m| m.table.eq_ignore_ascii_case(table))
+            {
+                field_matches.retain(|m| m.table.eq_ignore_ascii_case(table));
+            }
+        }
         if !field_matches.is_empty() {
             if field_matches.len() == 1 {
                 let m = &field_matches[0];
@@ -294,6 +368,14 @@ impl Backend {
             return Ok(Some(markdown_hover(format!("**DB Index** `{}`", symbol))));
         }
 
+        // Checked last, so user definitions and schema names (a `NAME` field, say) shadow it.
+        let builtin_upper = symbol.trim().to_ascii_uppercase();
+        if is_builtin_function_name(&builtin_upper)
+            && let Some((signature, description)) = builtin_function_doc(&builtin_upper)
+        {
+            return Ok(Some(builtin_function_hover(signature, description)));
+        }
+
         Ok(None)
     }
 }
@@ -325,3 +407,63 @@ fn extract_preprocessor_names_from_include_text(include_text: &str) -> Vec<Strin
 
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::backend::Backend;
+    use tower_lsp::lsp_types::{
+        HoverContents, HoverParams, Position, TextDocumentIdentifier, TextDocumentPositionParams,
+        Url,
+    };
+
+    async fn hover_text(backend: &Backend, uri: &Url, position: Position) -> String {
+        let hover = backend
+            .handle_hover(HoverParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                    position,
+                },
+                work_done_progress_params: Default::default(),
+            })
+            .await
+            .expect("hover")
+            .expect("hover result");
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("expected markdown hover");
+        };
+        markup.value
+    }
+
+    #[tokio::test]
+    async fn shows_resolved_path_or_not_found_for_include_directives() {
+        let base = std::env::temp_dir().join(format!(
+            "abl_ls_include_hover_test_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("epoch")
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(base.join("inc")).expect("create include dir");
+        let include_path = base.join("inc").join("defs.i");
+        std::fs::write(&include_path, "DEFINE VARIABLE x AS INTEGER NO-UNDO.\n")
+            .expect("write include");
+
+        let backend = Backend::for_tests();
+        *backend.workspace_roots.lock().await = vec![base.clone()];
+        let uri = Url::from_file_path(base.join("main.p")).expect("uri");
+        let src = "{inc/defs.i}\n{inc/missing.i}\n";
+        backend.set_document_text_version(&uri, 1, src.to_string(), true);
+
+        let found = hover_text(&backend, &uri, Position::new(0, 3)).await;
+        assert!(found.starts_with("**Include File**"));
+        assert!(found.contains(&include_path.display().to_string()));
+
+        let missing = hover_text(&backend, &uri, Position::new(1, 3)).await;
+        assert_eq!(
+            missing,
+            "**Include File**\n\n`inc/missing.i` not found on PROPATH"
+        );
+
+        let _ = std::fs::remove_dir_all(base);
+    }
+}
@@ -0,0 +1,113 @@
+use std::time::{Duration, Instant};
+
+use log::debug;
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::request::{GotoImplementationParams, GotoImplementationResponse};
+use tower_lsp::lsp_types::*;
+
+use crate::analysis::classes::{implementing_method_ranges, interface_declaring_method};
+use crate::backend::Backend;
+use crate::utils::paths::collect_propath_files;
+use crate::utils::position::{ascii_ident_or_dash_at_or_before, lsp_pos_to_utf8_byte_offset};
+use crate::utils::source::{parse_source, read_source_file};
+
+const MAX_IMPLEMENTATION_SCAN_FILES: usize = 2000;
+const IMPLEMENTATION_SCAN_BUDGET_MS: u64 = 2000;
+
+impl Backend {
+    pub async fn handle_goto_implementation(
+        &self,
+        params: GotoImplementationParams,
+    ) -> Result<Option<GotoImplementationResponse>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let pos = params.text_document_position_params.position;
+
+        let text = match self.get_document_text(&uri) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+        let offset = match lsp_pos_to_utf8_byte_offset(&text, pos) {
+            Some(o) => o,
+            None => return Ok(None),
+        };
+        let tree = match self.get_document_tree_or_parse(&uri) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+
+        let Some(method) = ascii_ident_or_dash_at_or_before(&text, offset) else {
+            return Ok(None);
+        };
+        let Some(interface) =
+            interface_declaring_method(tree.root_node(), text.as_bytes(), offset, &method)
+        else {
+            return Ok(None);
+        };
+        let interface_short_upper = interface
+            .rsplit('.')
+            .next()
+            .unwrap_or(&interface)
+            .to_ascii_uppercase();
+
+        let workspace_root = self.workspace_root.lock().await.clone();
+        let config = self.config.lock().await.clone();
+        let files = tokio::task::spawn_blocking(move || {
+            collect_propath_files(
+                workspace_root.as_deref(),
+                &config.propath,
+                &["cls"],
+                &config.workspace.exclude_dirs,
+                MAX_IMPLEMENTATION_SCAN_FILES,
+            )
+        })
+        .await
+        .unwrap_or_default();
+        let current_path = uri.to_file_path().ok();
+        let deadline = Instant::now() + Duration::from_millis(IMPLEMENTATION_SCAN_BUDGET_MS);
+        // Candidates are parsed once here, not kept in the include parse cache.
+        let mut parser = self.new_abl_parser();
+
+        let mut locations = Vec::new();
+        for path in files {
+            if Instant::now() >= deadline {
+                debug!("go to implementation: scan budget spent, results may be partial");
+                break;
+            }
+            if current_path.as_deref() == Some(path.as_path()) {
+                continue;
+            }
+            // Cheap text filter before parsing: the interface must be named in the file.
+            let Ok(class_text) = read_source_file(&path).await else {
+                continue;
+            };
+            let raw_upper = class_text.to_ascii_uppercase();
+            if !raw_upper.contains("IMPLEMENTS") || !raw_upper.contains(&interface_short_upper) {
+                continue;
+            }
+            let (Some(class_tree), Ok(target)) = (
+                parse_source(&mut parser, &class_text, None),
+                Url::from_file_path(&path),
+            ) else {
+                continue;
+            };
+            locations.extend(
+                implementing_method_ranges(
+                    class_tree.root_node(),
+                    &class_text,
+                    &interface,
+                    &method,
+                )
+                .into_iter()
+                .map(|range| Location {
+                    uri: target.clone(),
+                    range,
+                }),
+            );
+        }
+
+        if locations.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(GotoImplementationResponse::Array(locations)))
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::*;
+
+use crate::analysis::functions::{
+    FunctionSignature, find_function_signature, find_function_signature_from_includes,
+    find_procedure_signature, find_procedure_signature_from_includes,
+};
+use crate::analysis::inlay_hints::{collect_call_argument_sites, parameter_hint_label};
+use crate::backend::Backend;
+use crate::utils::position::{lsp_pos_to_utf8_byte_offset, utf8_byte_offset_to_lsp_pos};
+
+impl Backend {
+    pub async fn handle_inlay_hint(
+        &self,
+        params: InlayHintParams,
+    ) -> Result<Option<Vec<InlayHint>>> {
+        if !self.config.lock().await.inlay_hints.enabled {
+            return Ok(None);
+        }
+        let uri = params.text_document.uri;
+        let text = match self.get_document_text(&uri) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+        let tree = match self.get_document_tree_or_parse(&uri) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+        let start = lsp_pos_to_utf8_byte_offset(&text, params.range.start).unwrap_or(0);
+        let end = lsp_pos_to_utf8_byte_offset(&text, params.range.end).unwrap_or(text.len());
+
+        let root = tree.root_node();
+        let mut sites = Vec::new();
+        collect_call_argument_sites(root, text.as_bytes(), start, end, &mut sites);
+
+        let mut signatures = HashMap::<(bool, String), Option<FunctionSignature>>::new();
+        let mut hints = Vec::new();
+        for site in sites {
+            let key = (site.is_run, site.name.to_ascii_uppercase());
+            if !signatures.contains_key(&key) {
+                let first_offset = site.argument_offsets.first().copied().unwrap_or(start);
+                let sig = if site.is_run {
+                    match find_procedure_signature(root, text.as_bytes(), &site.name) {
+                        Some(sig) => Some(sig),
+                        None => {
+                            find_procedure_signature_from_includes(
+                                self,
+                                &uri,
+                                &text,
+                                root,
+                                first_offset,
+                                &site.name,
+                            )
+                            .await
+                        }
+                    }
+                } else {
+                    match find_function_signature(root, text.as_bytes(), &site.name) {
+                        Some(sig) => Some(sig),
+                        None => {
+                            find_function_signature_from_includes(
+                                self,
+                                &uri,
+                                &text,
+                                root,
+                                first_offset,
+                                &site.name,
+                            )
+                            .await
+                        }
+                    }
+                };
+                signatures.insert(key.clone(), sig);
+            }
+            let Some(Some(sig)) = signatures.get(&key) else {
+                continue;
+            };
+
+            for (param, arg_offset) in sig.params.iter().zip(site.argument_offsets.iter()) {
+                if *arg_offset < start || *arg_offset > end {
+                    continue;
+                }
+                hints.push(InlayHint {
+                    position: utf8_byte_offset_to_lsp_pos(&text, *arg_offset),
+                    label: InlayHintLabel::String(parameter_hint_label(param)),
+                    kind: Some(InlayHintKind::PARAMETER),
+                    text_edits: None,
+                    tooltip: None,
+                    padding_left: None,
+                    padding_right: Some(true),
+                    data: None,
+                });
+            }
+        }
+
+        Ok(Some(hints))
+    }
+}
@@ -1,9 +1,19 @@
+pub mod call_hierarchy;
+pub mod code_action;
+pub mod code_lens;
 pub mod completion;
 pub mod definition;
 pub mod diagnostics;
+pub mod document_highlight;
+pub mod document_symbol;
+pub mod execute_command;
 pub mod formatting;
 pub mod hover;
+pub mod implementation;
+pub mod inlay_hints;
 pub mod references;
+pub mod rename;
+pub mod selection_range;
 pub mod semantic_tokens;
 pub mod signature;
 pub mod sync;
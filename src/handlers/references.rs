@@ -1,8 +1,13 @@
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 
+use crate::analysis::call_hierarchy::callable_names_match;
+use crate::analysis::code_lens::document_reference_lens_sites;
 use crate::backend::Backend;
-use crate::utils::position::{ascii_ident_at_or_before, lsp_pos_to_utf8_byte_offset};
+use crate::utils::position::{
+    ascii_ident_at_or_before, ascii_ident_or_dash_at_or_before, byte_ranges_to_utf16,
+    lsp_pos_to_utf8_byte_offset,
+};
 
 impl Backend {
     pub async fn handle_references(
@@ -26,6 +31,7 @@ impl Backend {
             Some(s) => s.to_ascii_uppercase(),
             None => return Ok(None),
         };
+        let include_declaration = params.context.include_declaration;
 
         let locations = self
             .db_table_definitions
@@ -37,10 +43,40 @@ impl Backend {
                     .map(|entry| entry.value().clone())
             })
             .unwrap_or_default();
-        if locations.is_empty() {
-            Ok(None)
-        } else {
-            Ok(Some(locations))
+        if !locations.is_empty() {
+            return Ok(Some(locations));
         }
+
+        let callable = ascii_ident_or_dash_at_or_before(&text, offset).unwrap_or(symbol);
+        Ok(self.callable_references(&uri, &text, &callable, include_declaration))
+    }
+
+    /// Calls of a function/procedure defined in the document (the targets of its reference lens).
+    fn callable_references(
+        &self,
+        uri: &Url,
+        text: &str,
+        name: &str,
+        include_declaration: bool,
+    ) -> Option<Vec<Location>> {
+        let tree = self.get_document_tree_or_parse(uri)?;
+        let site = document_reference_lens_sites(tree.root_node(), text.as_bytes())
+            .into_iter()
+            .find(|site| callable_names_match(&site.name, name))?;
+        let mut ranges = Vec::new();
+        if include_declaration {
+            ranges.push(site.name_range);
+        }
+        ranges.extend(site.references);
+        byte_ranges_to_utf16(text, ranges.iter_mut());
+        Some(
+            ranges
+                .into_iter()
+                .map(|range| Location {
+                    uri: uri.clone(),
+                    range,
+                })
+                .collect(),
+        )
     }
 }
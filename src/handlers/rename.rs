@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use tower_lsp::jsonrpc::{Error, Result};
+use tower_lsp::lsp_types::*;
+
+use crate::analysis::definitions::{collect_definition_symbols, find_variable_declaration};
+use crate::analysis::hover::find_db_field_matches;
+use crate::analysis::rename::{collect_rename_ranges, is_keyword_at, is_valid_identifier};
+use crate::analysis::schema::normalize_lookup_key;
+use crate::analysis::schema_lookup::has_schema_key;
+use crate::backend::Backend;
+use crate::utils::position::{
+    ascii_ident_or_dash_at_or_before, ascii_ident_or_dash_span_at_or_before, byte_ranges_to_utf16,
+    lsp_pos_to_utf8_byte_offset, utf8_byte_offset_to_lsp_pos,
+};
+
+/// A renameable identifier: its byte span and spelling.
+struct RenameSymbol {
+    start: usize,
+    end: usize,
+    name: String,
+}
+
+impl Backend {
+    /// Pre-flight for rename: refuses what `rename` refuses, otherwise returns the identifier range.
+    pub async fn handle_prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        let uri = params.text_document.uri;
+        let Some(text) = self.get_document_text(&uri) else {
+            return Ok(None);
+        };
+        let Some(symbol) = self.rename_symbol_at(&uri, &text, params.position)? else {
+            return Ok(None);
+        };
+
+        Ok(Some(PrepareRenameResponse::Range(Range::new(
+            utf8_byte_offset_to_lsp_pos(&text, symbol.start),
+            utf8_byte_offset_to_lsp_pos(&text, symbol.end),
+        ))))
+    }
+
+    /// Renames a variable, parameter or other local definition within the current document.
+    pub async fn handle_rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let uri = params.text_document_position.text_document.uri;
+        if !is_valid_identifier(&params.new_name) {
+            return Err(Error::invalid_params(format!(
+                "'{}' is not a valid ABL identifier",
+                params.new_name
+            )));
+        }
+        let Some(text) = self.get_document_text(&uri) else {
+            return Ok(None);
+        };
+        let Some(symbol) =
+            self.rename_symbol_at(&uri, &text, params.text_document_position.position)?
+        else {
+            return Ok(None);
+        };
+        let Some(tree) = self.get_document_tree_or_parse(&uri) else {
+            return Ok(None);
+        };
+
+        let mut ranges = collect_rename_ranges(
+            tree.root_node(),
+            text.as_bytes(),
+            &symbol.name,
+            symbol.start,
+        );
+        if ranges.is_empty() {
+            return Ok(None);
+        }
+        byte_ranges_to_utf16(&text, ranges.iter_mut());
+        let edits = ranges
+            .into_iter()
+            .map(|range| TextEdit {
+                range,
+                new_text: params.new_name.clone(),
+            })
+            .collect();
+        Ok(Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(uri, edits)])),
+            ..Default::default()
+        }))
+    }
+
+    /// The identifier at `position`, refusing keywords and schema-bound symbols.
+    fn rename_symbol_at(
+        &self,
+        uri: &Url,
+        text: &str,
+        position: Position,
+    ) -> Result<Option<RenameSymbol>> {
+        let Some(offset) = lsp_pos_to_utf8_byte_offset(text, position) else {
+            return Ok(None);
+        };
+        let Some((start, end)) = ascii_ident_or_dash_span_at_or_before(text, offset) else {
+            return Ok(None);
+        };
+        let Some(name) = ascii_ident_or_dash_at_or_before(text, offset) else {
+            return Ok(None);
+        };
+        let Some(tree) = self.get_document_tree_or_parse(uri) else {
+            return Ok(None);
+        };
+        let root = tree.root_node();
+        if is_keyword_at(root, text.as_bytes(), start, end) {
+            return Err(Error::invalid_params("Cannot rename a keyword"));
+        }
+
+        // Local declarations shadow schema names, so only unshadowed DB symbols are refused.
+        let locally_defined =
+            find_variable_declaration(root, text.as_bytes(), &name, start).is_some() || {
+                let mut defs = Vec::new();
+                collect_definition_symbols(root, text.as_bytes(), &mut defs);
+                defs.iter().any(|d| d.label.eq_ignore_ascii_case(&name))
+            };
+        let symbol_upper = normalize_lookup_key(&name, true);
+        if !locally_defined
+            && (self.db_tables.contains(&symbol_upper)
+                || has_schema_key(&self.db_table_definitions, &symbol_upper)
+                || !find_db_field_matches(
+                    &self.db_fields_by_table,
+                    &self.db_tables_by_field,
+                    &symbol_upper,
+                )
+                .is_empty())
+        {
+            return Err(Error::invalid_params("Cannot rename database field/table"));
+        }
+
+        Ok(Some(RenameSymbol { start, end, name }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backend::Backend;
+    use tower_lsp::lsp_types::{
+        Position, RenameParams, TextDocumentIdentifier, TextDocumentPositionParams, Url,
+    };
+
+    fn position_params(uri: &Url, position: Position) -> TextDocumentPositionParams {
+        TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            position,
+        }
+    }
+
+    fn rename_params(uri: &Url, position: Position) -> RenameParams {
+        RenameParams {
+            text_document_position: position_params(uri, position),
+            new_name: "newName".to_string(),
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn prepare_rename_and_rename_refuse_the_same_symbols() {
+        let backend = Backend::for_tests();
+        backend.db_tables.insert("CUSTOMER".to_string());
+        let uri = Url::parse("file:///tmp/rename.p").expect("uri");
+        let src = "DEFINE VARIABLE total AS INTEGER NO-UNDO.\nFIND FIRST customer.\ntotal = 1.\n";
+        backend.set_document_text_version(&uri, 1, src.to_string(), true);
+
+        for position in [Position::new(0, 2), Position::new(1, 13)] {
+            assert!(
+                backend
+                    .handle_prepare_rename(position_params(&uri, position))
+                    .await
+                    .is_err()
+            );
+            assert!(
+                backend
+                    .handle_rename(rename_params(&uri, position))
+                    .await
+                    .is_err()
+            );
+        }
+
+        let edit = backend
+            .handle_rename(rename_params(&uri, Position::new(2, 1)))
+            .await
+            .expect("rename")
+            .expect("edit");
+        let edits = edit.changes.expect("changes").remove(&uri).expect("edits");
+        assert_eq!(edits.len(), 2);
+        assert!(edits.iter().all(|e| e.new_text == "newName"));
+    }
+}
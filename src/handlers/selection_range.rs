@@ -0,0 +1,48 @@
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::*;
+
+use crate::analysis::selection_range::selection_range_at;
+use crate::backend::Backend;
+use crate::utils::position::{byte_ranges_to_utf16, lsp_pos_to_utf8_byte_offset};
+
+impl Backend {
+    pub async fn handle_selection_range(
+        &self,
+        params: SelectionRangeParams,
+    ) -> Result<Option<Vec<SelectionRange>>> {
+        let uri = params.text_document.uri;
+        let text = match self.get_document_text(&uri) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+        let tree = match self.get_document_tree_or_parse(&uri) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+
+        let root = tree.root_node();
+        let ranges = params
+            .positions
+            .into_iter()
+            .map(|pos| {
+                lsp_pos_to_utf8_byte_offset(&text, pos)
+                    .and_then(|offset| selection_range_at(root, offset))
+                    .map(|mut selection| {
+                        let mut ranges = Vec::new();
+                        let mut current = Some(&mut selection);
+                        while let Some(node) = current {
+                            ranges.push(&mut node.range);
+                            current = node.parent.as_deref_mut();
+                        }
+                        byte_ranges_to_utf16(&text, ranges);
+                        selection
+                    })
+                    .unwrap_or(SelectionRange {
+                        range: Range::new(pos, pos),
+                        parent: None,
+                    })
+            })
+            .collect();
+        Ok(Some(ranges))
+    }
+}
@@ -1,16 +1,13 @@
+use std::collections::HashSet;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
-use tree_sitter::Node;
 
 use crate::analysis::buffers::collect_buffer_mappings;
 use crate::analysis::local_tables::collect_local_table_definitions;
 use crate::analysis::semantic_tokens::{
-    is_in_range, line_start_offsets, point_column_byte_to_utf16,
+    SemanticTableNames, collect_semantic_tokens, encode_semantic_tokens,
 };
 use crate::backend::Backend;
-use crate::utils::ts::collect_nodes_by_kind;
-
-const TABLE_TOKEN_TYPE_INDEX: u32 = 0;
 
 impl Backend {
     pub async fn handle_semantic_tokens_full(
@@ -21,7 +18,7 @@ impl Backend {
             return Ok(None);
         }
         let uri = params.text_document.uri;
-        let tokens = self.collect_table_semantic_tokens(&uri, None).await;
+        let tokens = self.collect_semantic_tokens(&uri, None).await;
         Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
             result_id: None,
             data: tokens,
@@ -36,20 +33,14 @@ impl Backend {
             return Ok(None);
         }
         let uri = params.text_document.uri;
-        let tokens = self
-            .collect_table_semantic_tokens(&uri, Some(params.range))
-            .await;
+        let tokens = self.collect_semantic_tokens(&uri, Some(params.range)).await;
         Ok(Some(SemanticTokensRangeResult::Tokens(SemanticTokens {
             result_id: None,
             data: tokens,
         })))
     }
 
-    async fn collect_table_semantic_tokens(
-        &self,
-        uri: &Url,
-        range: Option<Range>,
-    ) -> Vec<SemanticToken> {
+    async fn collect_semantic_tokens(&self, uri: &Url, range: Option<Range>) -> Vec<SemanticToken> {
         let Some(text) = self.get_document_text(uri) else {
             return vec![];
         };
@@ -60,77 +51,71 @@ impl Backend {
             }
         };
 
-        let mut nodes = Vec::<Node>::new();
-        collect_nodes_by_kind(tree.root_node(), "identifier", &mut nodes);
-
         let mut buffer_mappings = Vec::new();
         collect_buffer_mappings(tree.root_node(), text.as_bytes(), &mut buffer_mappings);
-        let buffer_aliases = buffer_mappings
-            .into_iter()
-            .map(|m| m.alias.to_ascii_uppercase())
-            .collect::<std::collections::HashSet<_>>();
         let mut local_table_defs = Vec::new();
         collect_local_table_definitions(tree.root_node(), text.as_bytes(), &mut local_table_defs);
-        let local_table_names = local_table_defs
+        let local_tables = buffer_mappings
             .into_iter()
-            .map(|d| d.name_upper)
-            .collect::<std::collections::HashSet<_>>();
-        if self.db_tables.is_empty() && buffer_aliases.is_empty() && local_table_names.is_empty() {
-            return vec![];
-        }
+            .map(|m| m.alias.to_ascii_uppercase())
+            .chain(local_table_defs.into_iter().map(|d| d.name_upper))
+            .collect::<HashSet<_>>();
+        let is_db_table = |name: &str| self.db_tables.contains(name);
+        let tables = SemanticTableNames {
+            is_db_table: &is_db_table,
+            local_tables: &local_tables,
+        };
 
-        let line_starts = line_start_offsets(text.as_str());
-        let mut raw = Vec::<(u32, u32, u32)>::new();
-        for node in nodes {
-            let sp = node.start_position();
-            let start_line = sp.row as u32;
-            let Ok(name) = node.utf8_text(text.as_bytes()) else {
-                continue;
-            };
-            let name_upper = name.to_ascii_uppercase();
-            if self.db_tables.contains(&name_upper)
-                || buffer_aliases.contains(&name_upper)
-                || local_table_names.contains(&name_upper)
-            {
-                let Some(start_col) =
-                    point_column_byte_to_utf16(text.as_str(), &line_starts, start_line, sp.column)
-                else {
-                    continue;
-                };
-                let len = name.encode_utf16().count() as u32;
-                if len == 0 {
-                    continue;
-                }
-                if !is_in_range(start_line, start_col, len, range.as_ref()) {
-                    continue;
-                }
-                raw.push((start_line, start_col, len));
-            }
-        }
-        raw.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
-        raw.dedup();
+        let raw = collect_semantic_tokens(tree.root_node(), &text, &tables, range.as_ref());
+        encode_semantic_tokens(raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::semantic_tokens::{KEYWORD_TOKEN, TYPE_TOKEN};
+    use crate::backend::Backend;
+    use tower_lsp::lsp_types::{
+        SemanticTokensParams, SemanticTokensResult, TextDocumentIdentifier, Url,
+    };
 
-        let mut out = Vec::<SemanticToken>::new();
-        let mut prev_line = 0u32;
-        let mut prev_start = 0u32;
-        for (line, start, length) in raw {
-            let delta_line = line.saturating_sub(prev_line);
-            let delta_start = if delta_line == 0 {
-                start.saturating_sub(prev_start)
-            } else {
-                start
-            };
-            out.push(SemanticToken {
-                delta_line,
-                delta_start,
-                length,
-                token_type: TABLE_TOKEN_TYPE_INDEX,
-                token_modifiers_bitset: 0,
-            });
-            prev_line = line;
-            prev_start = start;
+    fn params(uri: &Url) -> SemanticTokensParams {
+        SemanticTokensParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
         }
+    }
+
+    #[tokio::test]
+    async fn emits_schema_aware_tokens_for_open_documents() {
+        let backend = Backend::for_tests();
+        backend.db_tables.insert("CUSTOMER".to_string());
+        let uri = Url::parse("file:///tmp/tokens.p").expect("uri");
+        backend.set_document_text_version(
+            &uri,
+            1,
+            "FIND FIRST Customer NO-LOCK.\n".to_string(),
+            true,
+        );
+
+        let Some(SemanticTokensResult::Tokens(tokens)) = backend
+            .handle_semantic_tokens_full(params(&uri))
+            .await
+            .expect("semantic tokens")
+        else {
+            panic!("expected tokens");
+        };
+        assert_eq!(tokens.data[0].token_type, KEYWORD_TOKEN);
+        assert!(tokens.data.iter().any(|t| t.token_type == TYPE_TOKEN));
 
-        out
+        backend.config.lock().await.semantic_tokens.enabled = false;
+        assert!(
+            backend
+                .handle_semantic_tokens_full(params(&uri))
+                .await
+                .expect("semantic tokens")
+                .is_none()
+        );
     }
 }
@@ -1,8 +1,12 @@
 use tower_lsp::jsonrpc::Result;
-use tower_lsp::lsp_types::{SignatureHelp, SignatureHelpParams};
+use tower_lsp::lsp_types::{SignatureHelp, SignatureHelpParams, Url};
+use tree_sitter::Node;
 
-use crate::analysis::functions::{find_function_signature, find_function_signature_from_includes};
-use crate::analysis::signature::{call_context_at_offset, to_signature_information};
+use crate::analysis::functions::{
+    FunctionSignature, find_function_signature, find_function_signature_from_includes,
+    find_procedure_signature, find_procedure_signature_from_includes,
+};
+use crate::analysis::signature::{CallContext, call_context_at_offset, to_signature_information};
 use crate::backend::Backend;
 use crate::utils::position::lsp_pos_to_utf8_byte_offset;
 
@@ -31,22 +35,11 @@ impl Backend {
             return Ok(None);
         };
 
-        let local_sig = find_function_signature(tree.root_node(), text.as_bytes(), &call.name);
-        let sig = match local_sig {
-            Some(sig) => sig,
-            None => match find_function_signature_from_includes(
-                self,
-                &uri,
-                &text,
-                tree.root_node(),
-                offset,
-                &call.name,
-            )
+        let Some(sig) = self
+            .resolve_call_signature(&uri, &text, tree.root_node(), offset, &call)
             .await
-            {
-                Some(sig) => sig,
-                None => return Ok(None),
-            },
+        else {
+            return Ok(None);
         };
 
         let sig_info = to_signature_information(&sig);
@@ -62,4 +55,28 @@ impl Backend {
             active_parameter: active_param,
         }))
     }
+
+    /// Signature of the function/procedure called at `offset`: local definitions, then includes.
+    pub(crate) async fn resolve_call_signature(
+        &self,
+        uri: &Url,
+        text: &str,
+        root: Node<'_>,
+        offset: usize,
+        call: &CallContext,
+    ) -> Option<FunctionSignature> {
+        let local_sig = if call.is_run {
+            find_procedure_signature(root, text.as_bytes(), &call.name)
+        } else {
+            find_function_signature(root, text.as_bytes(), &call.name)
+        };
+        if local_sig.is_some() {
+            return local_sig;
+        }
+        if call.is_run {
+            find_procedure_signature_from_includes(self, uri, text, root, offset, &call.name).await
+        } else {
+            find_function_signature_from_includes(self, uri, text, root, offset, &call.name).await
+        }
+    }
 }
@@ -3,22 +3,21 @@ use tower_lsp::lsp_types::*;
 
 use crate::backend::Backend;
 use crate::handlers::diagnostics::on_change;
-use crate::utils::text_sync::apply_content_changes;
-
-const DID_CHANGE_DIAG_DEBOUNCE_MS: u64 = 200;
+use crate::utils::text_sync::apply_content_changes_with_tree;
 
 impl Backend {
     pub async fn handle_did_open(&self, params: DidOpenTextDocumentParams) {
+        let text = params.text_document.text;
         self.set_document_text_version(
             &params.text_document.uri,
             params.text_document.version,
-            params.text_document.text.clone(),
+            text.clone(),
             true,
         );
         self.schedule_on_change(
             params.text_document.uri,
             params.text_document.version,
-            params.text_document.text,
+            text,
             true,
             0,
         )
@@ -29,17 +28,22 @@ impl Backend {
     pub async fn handle_did_change(&self, params: DidChangeTextDocumentParams) {
         let uri = params.text_document.uri;
         let current = self.get_document_text(&uri).unwrap_or_default();
-        let Some(new_text) = apply_content_changes(current, &params.content_changes) else {
+        let base_tree = self.take_document_edit_base_tree(&uri);
+        let Some((new_text, edited_tree)) =
+            apply_content_changes_with_tree(current, &params.content_changes, base_tree)
+        else {
             return;
         };
         self.set_document_text_version(&uri, params.text_document.version, new_text.clone(), false);
+        self.set_document_edited_tree(&uri, edited_tree);
 
+        let debounce_ms = self.config.lock().await.diagnostics.debounce_ms;
         self.schedule_on_change(
             uri,
             params.text_document.version,
             new_text,
             false,
-            DID_CHANGE_DIAG_DEBOUNCE_MS,
+            debounce_ms,
         )
         .await;
         debug!("changed!");
@@ -67,6 +71,7 @@ impl Backend {
             task.handle.abort();
         }
         self.documents.remove(&params.text_document.uri);
+        self.using_imports.remove(&params.text_document.uri);
         debug!("file closed!");
     }
 
@@ -81,11 +86,94 @@ impl Backend {
         let backend = self.clone();
         let task_uri = uri.clone();
         let handle = tokio::spawn(async move {
-            if debounce_ms > 0 {
-                tokio::time::sleep(std::time::Duration::from_millis(debounce_ms)).await;
-            }
-            on_change(&backend, task_uri, version, text, include_semantic_diags).await;
+            on_change(
+                &backend,
+                task_uri,
+                version,
+                text,
+                include_semantic_diags,
+                debounce_ms,
+            )
+            .await;
         });
         self.try_set_document_diag_task(&uri, include_semantic_diags, version, handle);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::backend::Backend;
+    use tower_lsp::lsp_types::{
+        DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+        Position, Range, TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+        Url, VersionedTextDocumentIdentifier,
+    };
+
+    #[tokio::test]
+    async fn close_drops_document_parser_tree_and_imports() {
+        let backend = Backend::for_tests();
+        let kept = Url::parse("file:///tmp/kept.p").expect("uri");
+        let closed = Url::parse("file:///tmp/closed.cls").expect("uri");
+        backend.set_document_text_version(&kept, 1, "MESSAGE 1.".to_string(), true);
+        backend.set_document_text_version(
+            &closed,
+            4,
+            "USING Progress.Lang.*.\nMESSAGE 2.".to_string(),
+            true,
+        );
+        assert!(backend.get_document_tree_or_parse(&closed).is_some());
+        assert_eq!(backend.documents.len(), 2);
+        assert_eq!(backend.using_imports.len(), 2);
+
+        backend
+            .handle_did_close(DidCloseTextDocumentParams {
+                text_document: TextDocumentIdentifier {
+                    uri: closed.clone(),
+                },
+            })
+            .await;
+
+        assert_eq!(backend.documents.len(), 1);
+        assert_eq!(backend.using_imports.len(), 1);
+        assert!(backend.get_document_text(&closed).is_none());
+        assert!(backend.get_document_version(&closed).is_none());
+        assert!(backend.get_document_text(&kept).is_some());
+    }
+
+    #[tokio::test]
+    async fn keeps_bom_so_line_zero_edits_land_where_the_client_put_them() {
+        let backend = Backend::for_tests();
+        let uri = Url::parse("file:///tmp/bom.p").expect("uri");
+        backend
+            .handle_did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "abl".to_string(),
+                    version: 1,
+                    text: "\u{FEFF}a = 1.\n".to_string(),
+                },
+            })
+            .await;
+        // The client counts the BOM as one UTF-16 unit, so `1` sits at column 5.
+        backend
+            .handle_did_change(DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier {
+                    uri: uri.clone(),
+                    version: 2,
+                },
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: Some(Range::new(Position::new(0, 5), Position::new(0, 6))),
+                    range_length: None,
+                    text: "42".to_string(),
+                }],
+            })
+            .await;
+
+        assert_eq!(
+            backend.get_document_text(&uri).as_deref(),
+            Some("\u{FEFF}a = 42.\n")
+        );
+        let tree = backend.get_document_tree_or_parse(&uri).expect("tree");
+        assert!(!tree.root_node().has_error());
+    }
+}
@@ -1,6 +1,4 @@
-use dashmap::{DashMap, DashSet};
 use std::sync::Arc;
-use tokio::sync::Mutex;
 use tower_lsp::{LspService, Server};
 
 mod analysis;
@@ -11,7 +9,6 @@ mod utils;
 
 use backend::Backend;
 use backend::BackendState;
-use config::AblConfig;
 
 #[tokio::main]
 async fn main() {
@@ -30,26 +27,9 @@ async fn main() {
 
     let (service, socket) = LspService::build(|client| Backend {
         client,
-        state: Arc::new(BackendState {
-            abl_language,
-            df_parser: Mutex::new(df_parser),
-            documents: DashMap::new(),
-            workspace_root: Mutex::new(None),
-            config: Mutex::new(AblConfig::default()),
-            db_tables: DashSet::new(),
-            db_sequences: DashSet::new(),
-            db_table_labels: DashMap::new(),
-            db_table_definitions: DashMap::new(),
-            db_sequence_definitions: DashMap::new(),
-            db_field_definitions: DashMap::new(),
-            db_index_definitions: DashMap::new(),
-            db_indexes_by_table: DashMap::new(),
-            db_index_fields_by_table_index: DashMap::new(),
-            db_fields_by_table: DashMap::new(),
-            include_completion_cache: DashMap::new(),
-            include_parse_cache: DashMap::new(),
-        }),
+        state: Arc::new(BackendState::new(abl_language, df_parser)),
     })
+    .custom_method("abl/documentSymbols", Backend::handle_abl_document_symbols)
     .finish();
 
     Server::new(stdin, stdout, socket).serve(service).await;
@@ -1,4 +1,5 @@
 pub mod paths;
 pub mod position;
+pub mod source;
 pub mod text_sync;
 pub mod ts;
@@ -1,13 +1,44 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use tower_lsp::lsp_types::Url;
 
-pub fn resolve_dumpfile_path(workspace_root: Option<&Path>, dumpfile: &str) -> Option<PathBuf> {
-    resolve_config_path(workspace_root, dumpfile)
+/// Resolves a dump file against the first workspace root where it exists (falling back to the
+/// first root).
+pub fn resolve_dumpfile_path(workspace_roots: &[PathBuf], dumpfile: &str) -> Option<PathBuf> {
+    workspace_roots
+        .iter()
+        .filter_map(|root| resolve_config_path(Some(root), dumpfile))
+        .find(|path| path.exists())
+        .or_else(|| resolve_config_path(workspace_roots.first().map(PathBuf::as_path), dumpfile))
 }
 
+/// Orders workspace roots for `file`: roots containing it first (innermost first), then the rest.
+pub fn workspace_roots_for_file<'a>(workspace_roots: &'a [PathBuf], file: &Path) -> Vec<&'a Path> {
+    let mut ancestors = workspace_roots
+        .iter()
+        .filter(|root| file.starts_with(root))
+        .map(PathBuf::as_path)
+        .collect::<Vec<_>>();
+    ancestors.sort_by_key(|root| std::cmp::Reverse(root.components().count()));
+    ancestors.extend(
+        workspace_roots
+            .iter()
+            .filter(|root| !file.starts_with(root))
+            .map(PathBuf::as_path),
+    );
+    ancestors
+}
+
+/// Propath of each workspace root that has its own `abl.toml`, keyed by root.
+pub type RootPropaths = HashMap<PathBuf, Vec<String>>;
+
+/// Resolves an include through the propath of each workspace root (the root containing
+/// `current_file` first), then the current directory, then the roots themselves. Roots missing
+/// from `root_propaths` use `propath`.
 pub fn resolve_include_path(
-    workspace_root: Option<&Path>,
+    workspace_roots: &[PathBuf],
+    root_propaths: &RootPropaths,
     propath: &[String],
     current_file: &Path,
     include: &str,
@@ -17,13 +48,24 @@ pub fn resolve_include_path(
         return Some(candidate);
     }
 
-    for entry in propath {
-        let Some(base) = resolve_config_path(workspace_root, entry) else {
-            continue;
-        };
-        let from_propath = base.join(include);
-        if from_propath.exists() {
-            return Some(from_propath);
+    let roots = workspace_roots_for_file(workspace_roots, current_file);
+    let root_options = if roots.is_empty() {
+        vec![None]
+    } else {
+        roots.iter().copied().map(Some).collect()
+    };
+    for root in &root_options {
+        let entries = root
+            .and_then(|root| root_propaths.get(root))
+            .map_or(propath, Vec::as_slice);
+        for entry in entries {
+            let Some(base) = resolve_config_path(*root, entry) else {
+                continue;
+            };
+            let from_propath = base.join(include);
+            if from_propath.exists() {
+                return Some(from_propath);
+            }
         }
     }
 
@@ -34,7 +76,7 @@ pub fn resolve_include_path(
         }
     }
 
-    if let Some(root) = workspace_root {
+    for root in roots {
         let from_root = root.join(include);
         if from_root.exists() {
             return Some(from_root);
@@ -44,6 +86,186 @@ pub fn resolve_include_path(
     None
 }
 
+/// Lists `.i` files under the propath roots (or the workspace root), up to `limit` files.
+pub fn collect_propath_include_files(
+    workspace_root: Option<&Path>,
+    propath: &[String],
+    exclude_dirs: &[String],
+    limit: usize,
+) -> Vec<PathBuf> {
+    collect_propath_files(workspace_root, propath, &["i"], exclude_dirs, limit)
+}
+
+/// Lists files with one of `extensions` under the propath roots (or the workspace root),
+/// skipping sub-directories named in `exclude_dirs`.
+pub fn collect_propath_files(
+    workspace_root: Option<&Path>,
+    propath: &[String],
+    extensions: &[&str],
+    exclude_dirs: &[String],
+    limit: usize,
+) -> Vec<PathBuf> {
+    let mut roots = propath
+        .iter()
+        .filter_map(|entry| resolve_config_path(workspace_root, entry))
+        .collect::<Vec<_>>();
+    if roots.is_empty()
+        && let Some(root) = workspace_root
+    {
+        roots.push(root.to_path_buf());
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    let mut out = Vec::new();
+    let mut pending = roots;
+    while let Some(dir) = pending.pop() {
+        if out.len() >= limit {
+            continue;
+        }
+        // Symlinked directories are followed; their real path guards against cycles.
+        let Ok(real_dir) = dir.canonicalize() else {
+            continue;
+        };
+        if !seen.insert(real_dir) {
+            continue;
+        }
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut entries = entries.flatten().map(|e| e.path()).collect::<Vec<_>>();
+        entries.sort();
+        for path in entries {
+            if path.is_dir() {
+                if !is_excluded_dir(&path, exclude_dirs) {
+                    pending.push(path);
+                }
+            } else if path.extension().is_some_and(|ext| {
+                extensions
+                    .iter()
+                    .any(|wanted| ext.eq_ignore_ascii_case(wanted))
+            }) && out.len() < limit
+            {
+                out.push(path);
+            }
+        }
+    }
+    out
+}
+
+/// Lists files with one of `extensions` in every workspace folder: the propath of the first
+/// folder (which provides `abl.toml`), then each other folder as a whole, up to `limit` files.
+pub fn collect_workspace_source_files(
+    roots: &[PathBuf],
+    propath: &[String],
+    extensions: &[&str],
+    exclude_dirs: &[String],
+    limit: usize,
+) -> Vec<PathBuf> {
+    let mut out = collect_propath_files(
+        roots.first().map(PathBuf::as_path),
+        propath,
+        extensions,
+        exclude_dirs,
+        limit,
+    );
+    for root in roots.iter().skip(1) {
+        if out.len() >= limit {
+            break;
+        }
+        for path in
+            collect_propath_files(Some(root), &[], extensions, exclude_dirs, limit - out.len())
+        {
+            if !out.contains(&path) {
+                out.push(path);
+            }
+        }
+    }
+    out
+}
+
+fn is_excluded_dir(path: &Path, exclude_dirs: &[String]) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| {
+            exclude_dirs
+                .iter()
+                .any(|excluded| excluded.eq_ignore_ascii_case(name))
+        })
+}
+
+/// Lists `.i` files and sub-directories matching a partially typed include path, relative to
+/// each of `roots`. Returns `(relative_path, is_dir)` pairs, deduplicated and sorted.
+pub fn list_include_path_candidates(
+    roots: &[PathBuf],
+    partial: &str,
+    limit: usize,
+) -> Vec<(String, bool)> {
+    let partial = partial.replace('\\', "/");
+    let (dir_part, name_prefix) = match partial.rfind('/') {
+        Some(idx) => (&partial[..=idx], &partial[idx + 1..]),
+        None => ("", partial.as_str()),
+    };
+    let name_prefix = name_prefix.to_ascii_lowercase();
+
+    let mut out = Vec::<(String, bool)>::new();
+    for root in roots {
+        let Ok(entries) = std::fs::read_dir(root.join(dir_part)) else {
+            continue;
+        };
+        for path in entries.flatten().map(|e| e.path()) {
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if !name.to_ascii_lowercase().starts_with(&name_prefix) {
+                continue;
+            }
+            let is_dir = path.is_dir();
+            let is_include = path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("i"));
+            if !is_dir && !is_include {
+                continue;
+            }
+            let label = if is_dir {
+                format!("{dir_part}{name}/")
+            } else {
+                format!("{dir_part}{name}")
+            };
+            if !out.iter().any(|(existing, _)| existing == &label) {
+                out.push((label, is_dir));
+            }
+        }
+    }
+    out.sort();
+    out.truncate(limit);
+    out
+}
+
+/// Lists class names (`.cls` file stems) directly inside a dotted package under any of `roots`.
+pub fn list_package_class_names(roots: &[PathBuf], package: &str) -> Vec<String> {
+    let package_dir = package.replace('.', "/");
+    let mut out = Vec::new();
+    for root in roots {
+        let Ok(entries) = std::fs::read_dir(root.join(&package_dir)) else {
+            continue;
+        };
+        for path in entries.flatten().map(|e| e.path()) {
+            if path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("cls"))
+                && let Some(stem) = path.file_stem().and_then(|s| s.to_str())
+                && !out
+                    .iter()
+                    .any(|name: &String| name.eq_ignore_ascii_case(stem))
+            {
+                out.push(stem.to_string());
+            }
+        }
+    }
+    out.sort();
+    out
+}
+
 pub fn resolve_config_path(workspace_root: Option<&Path>, value: &str) -> Option<PathBuf> {
     let candidate = PathBuf::from(value);
     if candidate.is_absolute() {
@@ -92,6 +314,13 @@ pub fn normalize_path_for_match(raw: &str) -> String {
 }
 
 pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    // `**/` also matches zero directories (`tmp/**/*.p` matches `tmp/a.p`).
+    if let Some(idx) = pattern.find("**/") {
+        let collapsed = format!("{}{}", &pattern[..idx], &pattern[idx + 3..]);
+        if wildcard_match(&collapsed, text) {
+            return true;
+        }
+    }
     if pattern.is_empty() {
         return text.is_empty();
     }
@@ -130,7 +359,11 @@ pub fn wildcard_match(pattern: &str, text: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::{path_matches_any_pattern, resolve_include_path, wildcard_match};
+    use super::{
+        RootPropaths, collect_propath_include_files, collect_workspace_source_files,
+        list_include_path_candidates, list_package_class_names, path_matches_any_pattern,
+        resolve_dumpfile_path, resolve_include_path, wildcard_match,
+    };
     use std::fs;
 
     #[test]
@@ -164,13 +397,172 @@ mod tests {
         fs::write(&root_include, "/* root */").expect("write root include");
 
         let propath = vec![propath_a.to_string_lossy().to_string(), ".".to_string()];
-        let resolved = resolve_include_path(Some(&workspace), &propath, &current_file, include)
-            .expect("resolved include");
+        let resolved = resolve_include_path(
+            std::slice::from_ref(&workspace),
+            &RootPropaths::new(),
+            &propath,
+            &current_file,
+            include,
+        )
+        .expect("resolved include");
         assert_eq!(resolved, a_file);
 
         let _ = fs::remove_dir_all(&base);
     }
 
+    #[test]
+    fn include_resolution_prefers_propath_of_root_containing_current_file() {
+        let base = std::env::temp_dir().join(format!(
+            "abl_ls_multi_root_test_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("epoch")
+                .as_nanos()
+        ));
+        let root_a = base.join("app");
+        let root_b = base.join("lib");
+        fs::create_dir_all(root_a.join("inc")).expect("create root a");
+        fs::create_dir_all(root_b.join("src")).expect("create root b");
+        fs::write(root_a.join("inc/shared.i"), "/* a */").expect("write a include");
+        fs::write(root_b.join("src/shared.i"), "/* b */").expect("write b include");
+        fs::write(root_a.join("inc/only_a.i"), "/* only a */").expect("write a-only include");
+
+        let roots = vec![root_a.clone(), root_b.clone()];
+        let propath = vec![root_a.join("inc").to_string_lossy().to_string()];
+        let root_propaths = RootPropaths::from([(
+            root_b.clone(),
+            vec![root_b.join("src").to_string_lossy().to_string()],
+        )]);
+        let current_file = root_b.join("main.p");
+
+        let resolve = |include: &str| {
+            resolve_include_path(&roots, &root_propaths, &propath, &current_file, include)
+        };
+        assert_eq!(resolve("shared.i"), Some(root_b.join("src/shared.i")));
+        assert_eq!(resolve("only_a.i"), Some(root_a.join("inc/only_a.i")));
+
+        fs::write(root_b.join("schema.df"), "").expect("write dump file");
+        assert_eq!(
+            resolve_dumpfile_path(&roots, "schema.df"),
+            Some(root_b.join("schema.df"))
+        );
+        assert_eq!(
+            resolve_dumpfile_path(&roots, "missing.df"),
+            Some(root_a.join("missing.df"))
+        );
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn collects_include_files_below_propath_roots() {
+        let base = std::env::temp_dir().join(format!(
+            "abl_ls_index_test_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("epoch")
+                .as_nanos()
+        ));
+        let nested = base.join("inc").join("nested");
+        fs::create_dir_all(&nested).expect("create nested");
+        fs::write(base.join("inc").join("a.i"), "").expect("write a");
+        fs::write(nested.join("b.I"), "").expect("write b");
+        fs::write(nested.join("main.p"), "").expect("write p");
+        let build = base.join("inc").join("Build");
+        fs::create_dir_all(&build).expect("create build");
+        fs::write(build.join("gen.i"), "").expect("write generated");
+
+        let propath = vec!["inc".to_string()];
+        let exclude_dirs = vec!["build".to_string()];
+        let mut files = collect_propath_include_files(Some(&base), &propath, &exclude_dirs, 10);
+        files.sort();
+        assert_eq!(
+            files,
+            vec![base.join("inc").join("a.i"), nested.join("b.I")]
+        );
+        assert_eq!(
+            collect_propath_include_files(Some(&base), &propath, &exclude_dirs, 1).len(),
+            1
+        );
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn include_walk_survives_symlink_cycles() {
+        let base = std::env::temp_dir().join(format!(
+            "abl_ls_index_cycle_test_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("epoch")
+                .as_nanos()
+        ));
+        let inc = base.join("inc");
+        fs::create_dir_all(&inc).expect("create inc");
+        fs::write(inc.join("a.i"), "").expect("write a");
+        std::os::unix::fs::symlink(&inc, inc.join("loop")).expect("symlink");
+
+        let files = collect_propath_include_files(Some(&base), &["inc".to_string()], &[], 100);
+        assert_eq!(files, vec![inc.join("a.i")]);
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn lists_include_candidates_for_partial_path() {
+        let base = std::env::temp_dir().join(format!(
+            "abl_ls_include_completion_test_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("epoch")
+                .as_nanos()
+        ));
+        let inc = base.join("inc");
+        fs::create_dir_all(inc.join("common")).expect("create nested");
+        fs::write(inc.join("common.i"), "").expect("write include");
+        fs::write(inc.join("common.p"), "").expect("write procedure");
+        fs::write(inc.join("common").join("defs.i"), "").expect("write nested include");
+
+        let roots = vec![inc.clone(), base.join("missing")];
+        assert_eq!(
+            list_include_path_candidates(&roots, "com", 10),
+            vec![
+                ("common.i".to_string(), false),
+                ("common/".to_string(), true)
+            ]
+        );
+        assert_eq!(
+            list_include_path_candidates(&roots, "common/", 10),
+            vec![("common/defs.i".to_string(), false)]
+        );
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn lists_class_names_in_package_directory() {
+        let base = std::env::temp_dir().join(format!(
+            "abl_ls_package_classes_test_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("epoch")
+                .as_nanos()
+        ));
+        let pkg = base.join("app").join("util");
+        fs::create_dir_all(&pkg).expect("create package");
+        fs::write(pkg.join("Strings.cls"), "").expect("write class");
+        fs::write(pkg.join("Dates.CLS"), "").expect("write class");
+        fs::write(pkg.join("helper.i"), "").expect("write include");
+
+        assert_eq!(
+            list_package_class_names(std::slice::from_ref(&base), "app.util"),
+            vec!["Dates".to_string(), "Strings".to_string()]
+        );
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
     #[test]
     fn include_resolution_falls_back_to_current_then_workspace() {
         let base = std::env::temp_dir().join(format!(
@@ -193,13 +585,25 @@ mod tests {
         fs::write(&current_include, "/* current */").expect("write current include");
         fs::write(&root_include, "/* root */").expect("write root include");
 
-        let resolved =
-            resolve_include_path(Some(&workspace), &[], &current_file, include).expect("resolved");
+        let resolved = resolve_include_path(
+            std::slice::from_ref(&workspace),
+            &RootPropaths::new(),
+            &[],
+            &current_file,
+            include,
+        )
+        .expect("resolved");
         assert_eq!(resolved, current_include);
 
         fs::remove_file(&current_include).expect("remove current include");
-        let resolved =
-            resolve_include_path(Some(&workspace), &[], &current_file, include).expect("resolved");
+        let resolved = resolve_include_path(
+            std::slice::from_ref(&workspace),
+            &RootPropaths::new(),
+            &[],
+            &current_file,
+            include,
+        )
+        .expect("resolved");
         assert_eq!(resolved, root_include);
 
         let _ = fs::remove_dir_all(&base);
@@ -212,6 +616,16 @@ mod tests {
         assert!(!wildcard_match("legacy/*.p", "other/a.p"));
     }
 
+    #[test]
+    fn wildcard_match_supports_recursive_double_star() {
+        assert!(wildcard_match("tmp/**/*.p", "tmp/a.p"));
+        assert!(wildcard_match("tmp/**/*.p", "tmp/x/y/a.p"));
+        assert!(wildcard_match("**/generated/*", "src/generated/a.p"));
+        assert!(wildcard_match("**/generated/*", "generated/a.p"));
+        assert!(!wildcard_match("tmp/**/*.p", "other/a.p"));
+        assert!(!wildcard_match("tmp/**/*.p", "tmp/a.i"));
+    }
+
     #[test]
     fn path_matching_checks_abs_rel_and_basename() {
         let base = std::env::temp_dir().join(format!(
@@ -240,4 +654,40 @@ mod tests {
 
         let _ = fs::remove_dir_all(&base);
     }
+
+    #[test]
+    fn workspace_source_files_cover_every_root() {
+        let base = std::env::temp_dir().join(format!(
+            "abl_ls_workspace_files_test_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("epoch")
+                .as_nanos()
+        ));
+        let first = base.join("first");
+        let second = base.join("second");
+        fs::create_dir_all(first.join("src")).expect("create first root");
+        fs::create_dir_all(&second).expect("create second root");
+        fs::write(first.join("src").join("main.p"), "").expect("write main.p");
+        fs::write(first.join("src").join("defs.i"), "").expect("write defs.i");
+        fs::write(second.join("Customer.cls"), "").expect("write Customer.cls");
+
+        let files = collect_workspace_source_files(
+            &[first.clone(), second.clone()],
+            &[],
+            &["p", "cls"],
+            &[],
+            10,
+        );
+
+        assert_eq!(
+            files,
+            vec![
+                first.join("src").join("main.p"),
+                second.join("Customer.cls")
+            ]
+        );
+
+        let _ = fs::remove_dir_all(base);
+    }
 }
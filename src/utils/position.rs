@@ -1,10 +1,9 @@
-use tower_lsp::lsp_types::Position;
+use tower_lsp::lsp_types::{Position, Range};
 
 /// Converts an LSP Position to a UTF-8 byte offset in the text.
-/// Assumes Position.character is a UTF-8 byte column within that line.
+/// `Position.character` is counted in UTF-16 code units (the negotiated position encoding).
 pub fn lsp_pos_to_utf8_byte_offset(text: &str, pos: Position) -> Option<usize> {
     let line = pos.line as usize;
-    let col = pos.character as usize;
 
     let mut cur_line = 0usize;
     let mut line_start = 0usize;
@@ -38,79 +37,139 @@ pub fn lsp_pos_to_utf8_byte_offset(text: &str, pos: Position) -> Option<usize> {
         .map(|d| line_start + d)
         .unwrap_or(text.len());
 
-    let target = line_start.saturating_add(col);
-    if target > line_end {
-        Some(line_end)
-    } else {
-        Some(target)
-    }
+    Some(line_start + utf16_col_to_byte_col(&text[line_start..line_end], pos.character))
 }
 
-/// Walks backward from offset and captures [A-Za-z0-9_]* as prefix.
-pub fn ascii_ident_prefix(text: &str, mut offset: usize) -> String {
-    let bytes = text.as_bytes();
-    if offset > bytes.len() {
-        offset = bytes.len();
+/// Converts a UTF-8 byte offset to an LSP Position with a UTF-16 column.
+pub fn utf8_byte_offset_to_lsp_pos(text: &str, offset: usize) -> Position {
+    let (line, line_start, offset) = line_of_byte_offset(text, offset);
+    let character = text[line_start..offset].encode_utf16().count();
+    Position::new(line as u32, character as u32)
+}
+
+/// Converts a UTF-8 byte offset to a tree-sitter style Position (UTF-8 byte column), for
+/// ranges that go through `byte_ranges_to_utf16` with the node ranges around them.
+pub fn utf8_byte_offset_to_byte_pos(text: &str, offset: usize) -> Position {
+    let (line, line_start, offset) = line_of_byte_offset(text, offset);
+    Position::new(line as u32, (offset - line_start) as u32)
+}
+
+/// Line number and line start of `offset`, clamped to the text and to a char boundary.
+fn line_of_byte_offset(text: &str, offset: usize) -> (usize, usize, usize) {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
     }
-    let mut start = offset;
-    while start > 0 {
-        let c = bytes[start - 1];
-        let is_ident = c.is_ascii_alphanumeric() || c == b'_';
-        if !is_ident {
-            break;
+    let before = &text.as_bytes()[..offset];
+    let line = before.iter().filter(|b| **b == b'\n').count();
+    let line_start = before
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    (line, line_start, offset)
+}
+
+/// Rewrites tree-sitter ranges (UTF-8 byte columns) in `text` to UTF-16 columns in place.
+pub fn byte_ranges_to_utf16<'a>(text: &str, ranges: impl IntoIterator<Item = &'a mut Range>) {
+    let mut line_starts = vec![0usize];
+    line_starts.extend(
+        text.bytes()
+            .enumerate()
+            .filter(|(_, b)| *b == b'\n')
+            .map(|(i, _)| i + 1),
+    );
+    let convert = |pos: &mut Position| {
+        let Some(line_start) = line_starts.get(pos.line as usize).copied() else {
+            return;
+        };
+        let line_end = line_starts
+            .get(pos.line as usize + 1)
+            .map(|next| next - 1)
+            .unwrap_or(text.len());
+        let mut column_end = (line_start + pos.character as usize).min(line_end);
+        while !text.is_char_boundary(column_end) {
+            column_end -= 1;
         }
-        start -= 1;
+        pos.character = text[line_start..column_end].encode_utf16().count() as u32;
+    };
+    for range in ranges {
+        convert(&mut range.start);
+        convert(&mut range.end);
     }
-    text[start..offset].to_string()
 }
 
-/// Returns the full ASCII identifier at the given offset or immediately before it.
-pub fn ascii_ident_at_or_before(text: &str, mut offset: usize) -> Option<String> {
-    let bytes = text.as_bytes();
-    if bytes.is_empty() {
-        return None;
+/// Maps a UTF-16 column within a single line to a byte column, clamped to the line end.
+fn utf16_col_to_byte_col(line: &str, col: u32) -> usize {
+    let mut units = 0u32;
+    for (i, ch) in line.char_indices() {
+        if units >= col {
+            return i;
+        }
+        units += ch.len_utf16() as u32;
     }
+    line.len()
+}
 
+/// Walks backward from offset and captures [A-Za-z0-9_]* as prefix.
+/// `~` line continuations inside the identifier are skipped.
+pub fn ascii_ident_prefix(text: &str, mut offset: usize) -> String {
+    let bytes = text.as_bytes();
     if offset > bytes.len() {
         offset = bytes.len();
     }
+    let start = ident_start(bytes, offset, |c| c.is_ascii_alphanumeric() || c == b'_');
+    strip_line_continuations(&text[start..offset])
+}
 
-    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+/// Returns the full ASCII identifier at the given offset or immediately before it.
+pub fn ascii_ident_at_or_before(text: &str, offset: usize) -> Option<String> {
+    let (start, end) = ident_bounds(text.as_bytes(), offset, |b| {
+        b.is_ascii_alphanumeric() || b == b'_'
+    })?;
+    Some(strip_line_continuations(&text[start..end]))
+}
 
-    let cursor = if offset < bytes.len() && is_ident(bytes[offset]) {
-        offset
-    } else if offset > 0 && is_ident(bytes[offset - 1]) {
-        offset - 1
-    } else {
-        return None;
-    };
+/// Returns a symbol at offset allowing dashes inside the token.
+pub fn ascii_ident_or_dash_at_or_before(text: &str, offset: usize) -> Option<String> {
+    let (start, end) = ident_bounds(text.as_bytes(), offset, |b| {
+        b.is_ascii_alphanumeric() || b == b'_' || b == b'-'
+    })?;
+    Some(strip_line_continuations(&text[start..end]))
+}
 
-    let mut start = cursor;
-    while start > 0 && is_ident(bytes[start - 1]) {
-        start -= 1;
-    }
+/// Byte span of the dash-allowing identifier at offset or immediately before it.
+pub fn ascii_ident_or_dash_span_at_or_before(text: &str, offset: usize) -> Option<(usize, usize)> {
+    ident_bounds(text.as_bytes(), offset, |b| {
+        b.is_ascii_alphanumeric() || b == b'_' || b == b'-'
+    })
+}
 
-    let mut end = cursor + 1;
-    while end < bytes.len() && is_ident(bytes[end]) {
-        end += 1;
+/// Returns preprocessor name when cursor is on `{&NAME}`.
+pub fn preprocessor_name_at_or_before(text: &str, offset: usize) -> Option<String> {
+    let bytes = text.as_bytes();
+    let (start, end) = ident_bounds(bytes, offset, |b| {
+        b.is_ascii_alphanumeric() || b == b'_' || b == b'-'
+    })?;
+    if start < 2 || bytes[start - 2] != b'{' || bytes[start - 1] != b'&' {
+        return None;
     }
-
-    Some(text[start..end].to_string())
+    Some(strip_line_continuations(&text[start..end]))
 }
 
-/// Returns a symbol at offset allowing dashes inside the token.
-pub fn ascii_ident_or_dash_at_or_before(text: &str, mut offset: usize) -> Option<String> {
-    let bytes = text.as_bytes();
+/// Byte span of the identifier at `offset` or immediately before it.
+fn ident_bounds(
+    bytes: &[u8],
+    mut offset: usize,
+    is_ident: impl Fn(u8) -> bool,
+) -> Option<(usize, usize)> {
     if bytes.is_empty() {
         return None;
     }
-
     if offset > bytes.len() {
         offset = bytes.len();
     }
 
-    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_' || b == b'-';
-
     let cursor = if offset < bytes.len() && is_ident(bytes[offset]) {
         offset
     } else if offset > 0 && is_ident(bytes[offset - 1]) {
@@ -119,61 +178,77 @@ pub fn ascii_ident_or_dash_at_or_before(text: &str, mut offset: usize) -> Option
         return None;
     };
 
-    let mut start = cursor;
-    while start > 0 && is_ident(bytes[start - 1]) {
-        start -= 1;
-    }
-
+    let start = ident_start(bytes, cursor, &is_ident);
     let mut end = cursor + 1;
-    while end < bytes.len() && is_ident(bytes[end]) {
-        end += 1;
+    loop {
+        if end < bytes.len() && is_ident(bytes[end]) {
+            end += 1;
+        } else if let Some(after) = continuation_after(bytes, end)
+            && after < bytes.len()
+            && is_ident(bytes[after])
+        {
+            end = after;
+        } else {
+            break;
+        }
     }
-
-    Some(text[start..end].to_string())
+    Some((start, end))
 }
 
-/// Returns preprocessor name when cursor is on `{&NAME}`.
-pub fn preprocessor_name_at_or_before(text: &str, mut offset: usize) -> Option<String> {
-    let bytes = text.as_bytes();
-    if bytes.is_empty() {
-        return None;
-    }
-    if offset > bytes.len() {
-        offset = bytes.len();
+fn ident_start(bytes: &[u8], offset: usize, is_ident: impl Fn(u8) -> bool) -> usize {
+    let mut start = offset;
+    loop {
+        if start > 0 && is_ident(bytes[start - 1]) {
+            start -= 1;
+        } else if let Some(before) = continuation_before(bytes, start)
+            && before > 0
+            && is_ident(bytes[before - 1])
+        {
+            start = before;
+        } else {
+            break;
+        }
     }
+    start
+}
 
-    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_' || b == b'-';
-    let cursor = if offset < bytes.len() && is_ident(bytes[offset]) {
-        offset
-    } else if offset > 0 && is_ident(bytes[offset - 1]) {
-        offset - 1
+/// Start of a `~\n` / `~\r\n` continuation ending at `at`.
+fn continuation_before(bytes: &[u8], at: usize) -> Option<usize> {
+    let head = &bytes[..at];
+    if head.ends_with(b"~\r\n") {
+        Some(at - 3)
+    } else if head.ends_with(b"~\n") {
+        Some(at - 2)
     } else {
-        return None;
-    };
-
-    let mut start = cursor;
-    while start > 0 && is_ident(bytes[start - 1]) {
-        start -= 1;
-    }
-    if start < 2 || bytes[start - 2] != b'{' || bytes[start - 1] != b'&' {
-        return None;
+        None
     }
+}
 
-    let mut end = cursor + 1;
-    while end < bytes.len() && is_ident(bytes[end]) {
-        end += 1;
+/// End of a `~\n` / `~\r\n` continuation starting at `at`.
+fn continuation_after(bytes: &[u8], at: usize) -> Option<usize> {
+    let tail = bytes.get(at..)?;
+    if tail.starts_with(b"~\r\n") {
+        Some(at + 3)
+    } else if tail.starts_with(b"~\n") {
+        Some(at + 2)
+    } else {
+        None
     }
+}
 
-    Some(text[start..end].to_string())
+fn strip_line_continuations(text: &str) -> String {
+    text.replace("~\r\n", "").replace("~\n", "")
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
-        ascii_ident_at_or_before, ascii_ident_or_dash_at_or_before, ascii_ident_prefix,
-        lsp_pos_to_utf8_byte_offset, preprocessor_name_at_or_before,
+        ascii_ident_at_or_before, ascii_ident_or_dash_at_or_before,
+        ascii_ident_or_dash_span_at_or_before, ascii_ident_prefix, byte_ranges_to_utf16,
+        lsp_pos_to_utf8_byte_offset, preprocessor_name_at_or_before, utf8_byte_offset_to_byte_pos,
+        utf8_byte_offset_to_lsp_pos,
     };
-    use tower_lsp::lsp_types::Position;
+    use tower_lsp::lsp_types::{Position, Range};
 
     #[test]
     fn extracts_prefix_and_identifiers() {
@@ -184,6 +259,35 @@ mod tests {
             ascii_ident_or_dash_at_or_before(text, 13).as_deref(),
             Some("f-lpd_det")
         );
+        assert_eq!(
+            ascii_ident_or_dash_span_at_or_before(text, 13),
+            Some((4, 13))
+        );
+    }
+
+    #[test]
+    fn identifier_walks_follow_tilde_continuations() {
+        let text = "DISPLAY cust~\nomer-na~\r\nme WITH FRAME f.";
+        let end = text.find(" WITH").expect("end");
+        assert_eq!(ascii_ident_prefix(text, end), "name");
+        assert_eq!(
+            ascii_ident_or_dash_at_or_before(text, end).as_deref(),
+            Some("customer-name")
+        );
+        let after_break = text.find("omer").expect("second line");
+        assert_eq!(
+            ascii_ident_at_or_before(text, after_break).as_deref(),
+            Some("customer")
+        );
+        assert_eq!(ascii_ident_prefix(text, after_break + 2), "custom");
+    }
+
+    #[test]
+    fn continuation_after_separator_does_not_join_identifiers() {
+        let text = "x = a ~\nb.";
+        let b = text.find('b').expect("b");
+        assert_eq!(ascii_ident_prefix(text, b + 1), "b");
+        assert_eq!(ascii_ident_at_or_before(text, b).as_deref(), Some("b"));
     }
 
     #[test]
@@ -207,6 +311,47 @@ mod tests {
         assert_eq!(off, text.len());
     }
 
+    #[test]
+    fn offset_to_position_round_trips() {
+        let text = "abc\n  xy\n";
+        let pos = utf8_byte_offset_to_lsp_pos(text, 6);
+        assert_eq!(pos, Position::new(1, 2));
+        assert_eq!(lsp_pos_to_utf8_byte_offset(text, pos), Some(6));
+    }
+
+    #[test]
+    fn position_to_offset_counts_utf16_units_after_multibyte_chars() {
+        let text = "x = \"Ilość 😀\" + lv_total.";
+        let offset = text.find("lv_total").expect("identifier");
+        let utf16_col = text[..offset].encode_utf16().count() as u32;
+        assert_ne!(utf16_col as usize, offset);
+
+        let pos = Position::new(0, utf16_col);
+        assert_eq!(lsp_pos_to_utf8_byte_offset(text, pos), Some(offset));
+        assert_eq!(utf8_byte_offset_to_lsp_pos(text, offset), pos);
+    }
+
+    #[test]
+    fn converts_byte_ranges_to_utf16_columns() {
+        let text = "abc\nżółw = 1.\n";
+        let byte_col = "żółw".len() as u32;
+        let mut ranges = [Range::new(Position::new(1, 0), Position::new(1, byte_col))];
+        byte_ranges_to_utf16(text, ranges.iter_mut());
+        assert_eq!(ranges[0].end, Position::new(1, 4));
+    }
+
+    #[test]
+    fn offset_to_byte_position_keeps_byte_columns() {
+        let text = "abc\nżółw = 1.\n";
+        let offset = text.find('=').expect("equals");
+        let pos = utf8_byte_offset_to_byte_pos(text, offset);
+        assert_eq!(pos, Position::new(1, "żółw ".len() as u32));
+
+        let mut range = Range::new(pos, pos);
+        byte_ranges_to_utf16(text, [&mut range]);
+        assert_eq!(range.start, utf8_byte_offset_to_lsp_pos(text, offset));
+    }
+
     #[test]
     fn extracts_preprocessor_name_from_reference() {
         let text = r#"MESSAGE {&Test-Mode}."#;
@@ -0,0 +1,150 @@
+use std::path::Path;
+use tree_sitter::{Parser, Point, Tree};
+
+const UTF8_BOM: char = '\u{FEFF}';
+
+/// Drops a leading UTF-8 byte order mark so byte offsets start at the first real character.
+pub fn strip_bom(mut text: String) -> String {
+    if text.starts_with(UTF8_BOM) {
+        text.drain(..UTF8_BOM.len_utf8());
+    }
+    text
+}
+
+/// Parses `text` with a leading BOM read as blanks, so the tree's byte offsets still index
+/// `text` and the BOM never shows up as an error node. Open documents keep their BOM so the
+/// client's line-0 columns stay valid for incremental edits.
+pub fn parse_source(parser: &mut Parser, text: &str, old_tree: Option<&Tree>) -> Option<Tree> {
+    if !text.starts_with(UTF8_BOM) {
+        return parser.parse(text, old_tree);
+    }
+    const BLANKS: &[u8] = b"   ";
+    let bytes = text.as_bytes();
+    let bom_len = UTF8_BOM.len_utf8();
+    parser.parse_with_options(
+        &mut |offset: usize, _: Point| -> &[u8] {
+            if offset < bom_len {
+                &BLANKS[offset..bom_len]
+            } else if offset < bytes.len() {
+                &bytes[offset..]
+            } else {
+                &[]
+            }
+        },
+        old_tree,
+        None,
+    )
+}
+
+/// `tokio::fs::read_to_string` for ABL sources, includes and dumpfiles, without a leading BOM.
+pub async fn read_source_file(path: impl AsRef<Path>) -> std::io::Result<String> {
+    tokio::fs::read_to_string(path).await.map(strip_bom)
+}
+
+/// Replaces (nested) comments with spaces so offsets stay valid.
+pub fn mask_comments(text: &str) -> String {
+    let mut bytes = text.as_bytes().to_vec();
+    let mut depth = 0usize;
+    let mut quote: Option<u8> = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        let next = bytes.get(i + 1).copied();
+        if depth == 0
+            && let Some(q) = quote
+        {
+            if bytes[i] == b'~' {
+                i += 2;
+                continue;
+            }
+            if bytes[i] == q {
+                quote = None;
+            }
+            i += 1;
+            continue;
+        }
+        if bytes[i] == b'/' && next == Some(b'*') {
+            depth += 1;
+            bytes[i] = b' ';
+            bytes[i + 1] = b' ';
+            i += 2;
+            continue;
+        }
+        if depth > 0 {
+            if bytes[i] == b'*' && next == Some(b'/') {
+                depth -= 1;
+                bytes[i + 1] = b' ';
+            }
+            if bytes[i] != b'\n' {
+                bytes[i] = b' ';
+            }
+            i += 1;
+            continue;
+        }
+        if matches!(bytes[i], b'"' | b'\'') {
+            quote = Some(bytes[i]);
+        }
+        i += 1;
+    }
+    String::from_utf8(bytes).unwrap_or_else(|_| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mask_comments, parse_source, read_source_file, strip_bom};
+    use crate::analysis::parse_abl;
+    use std::fs;
+
+    #[test]
+    fn strips_only_a_leading_bom() {
+        assert_eq!(strip_bom("\u{FEFF}DEFINE".to_string()), "DEFINE");
+        assert_eq!(strip_bom("a\u{FEFF}b".to_string()), "a\u{FEFF}b");
+        assert_eq!(strip_bom(String::new()), "");
+    }
+
+    #[test]
+    fn masks_nested_comments_but_not_quoted_slashes() {
+        let src = "a /* x /* y */ z */ b \"/* s */\"\n/* c\nd */ e";
+        let masked = mask_comments(src);
+        assert_eq!(masked.len(), src.len());
+        let expected = format!("a{}b \"/* s */\"\n    \n     e", " ".repeat(19));
+        assert_eq!(masked, expected);
+    }
+
+    #[tokio::test]
+    async fn reads_bom_prefixed_file_with_offsets_from_the_first_token() {
+        let base = std::env::temp_dir().join(format!(
+            "abl_ls_bom_test_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("epoch")
+                .as_nanos()
+        ));
+        fs::create_dir_all(&base).expect("create dir");
+        let path = base.join("bom.i");
+        fs::write(&path, "\u{FEFF}DEFINE VARIABLE x AS INTEGER NO-UNDO.\n").expect("write file");
+
+        let text = read_source_file(&path).await.expect("read file");
+        assert!(text.starts_with("DEFINE"));
+        let tree = parse_abl(&text);
+        let first = tree.root_node().child(0).expect("first statement");
+        assert_eq!(first.start_byte(), 0);
+        assert_eq!(first.start_position().column, 0);
+        assert!(!tree.root_node().has_error());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn parses_bom_prefixed_text_with_offsets_into_the_original_text() {
+        let src = "\u{FEFF}DEFINE VARIABLE x AS INTEGER NO-UNDO.\n";
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_abl::LANGUAGE.into())
+            .expect("set abl language");
+        let tree = parse_source(&mut parser, src, None).expect("parse");
+        assert!(!tree.root_node().has_error());
+        let first = tree.root_node().child(0).expect("first statement");
+        assert_eq!(first.start_byte(), 3);
+        assert!(src[first.start_byte()..].starts_with("DEFINE"));
+    }
+}
@@ -1,19 +1,28 @@
 use tower_lsp::lsp_types::TextDocumentContentChangeEvent;
+use tree_sitter::{InputEdit, Point, Tree};
 
 use crate::utils::position::lsp_pos_to_utf8_byte_offset;
 
+#[cfg(test)]
 pub fn apply_content_changes(
-    mut text: String,
+    text: String,
     changes: &[TextDocumentContentChangeEvent],
 ) -> Option<String> {
-    if changes.is_empty() {
-        return Some(text);
-    }
+    apply_content_changes_with_tree(text, changes, None).map(|(text, _)| text)
+}
 
+/// Applies changes to `text` and mirrors ranged edits onto `tree` via `Tree::edit`, so the
+/// next parse can reuse unchanged subtrees. A change without a range drops the tree.
+pub fn apply_content_changes_with_tree(
+    mut text: String,
+    changes: &[TextDocumentContentChangeEvent],
+    mut tree: Option<Tree>,
+) -> Option<(String, Option<Tree>)> {
     for change in changes {
         match change.range {
             None => {
                 text = change.text.clone();
+                tree = None;
             }
             Some(range) => {
                 let start = lsp_pos_to_utf8_byte_offset(&text, range.start)?;
@@ -21,17 +30,50 @@ pub fn apply_content_changes(
                 if start > end || end > text.len() {
                     return None;
                 }
+                if let Some(tree) = tree.as_mut() {
+                    let start_position = byte_offset_to_point(&text, start);
+                    tree.edit(&InputEdit {
+                        start_byte: start,
+                        old_end_byte: end,
+                        new_end_byte: start + change.text.len(),
+                        start_position,
+                        old_end_position: byte_offset_to_point(&text, end),
+                        new_end_position: advance_point(start_position, &change.text),
+                    });
+                }
                 text.replace_range(start..end, &change.text);
             }
         }
     }
 
-    Some(text)
+    Some((text, tree))
+}
+
+fn byte_offset_to_point(text: &str, offset: usize) -> Point {
+    let before = &text.as_bytes()[..offset];
+    let row = before.iter().filter(|b| **b == b'\n').count();
+    let line_start = before
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    Point::new(row, offset - line_start)
+}
+
+fn advance_point(start: Point, inserted: &str) -> Point {
+    match inserted.rfind('\n') {
+        Some(last_newline) => Point::new(
+            start.row + inserted.matches('\n').count(),
+            inserted.len() - last_newline - 1,
+        ),
+        None => Point::new(start.row, start.column + inserted.len()),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::apply_content_changes;
+    use super::{apply_content_changes, apply_content_changes_with_tree};
+    use crate::analysis::parse_abl;
     use tower_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent};
 
     #[test]
@@ -61,4 +103,49 @@ mod tests {
         .expect("updated text");
         assert_eq!(out, "test_b");
     }
+
+    #[test]
+    fn edits_tree_for_incremental_reparse() {
+        let src = "DEFINE VARIABLE a AS INTEGER NO-UNDO.\na = 1.\n";
+        let tree = parse_abl(src);
+        let (out, edited) = apply_content_changes_with_tree(
+            src.to_string(),
+            &[TextDocumentContentChangeEvent {
+                range: Some(Range::new(Position::new(1, 4), Position::new(1, 5))),
+                range_length: None,
+                text: "42".to_string(),
+            }],
+            Some(tree),
+        )
+        .expect("updated text");
+        assert_eq!(out, "DEFINE VARIABLE a AS INTEGER NO-UNDO.\na = 42.\n");
+
+        let edited = edited.expect("edited tree");
+        assert_eq!(edited.root_node().end_byte(), out.len());
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_abl::LANGUAGE.into())
+            .expect("set abl language");
+        let reparsed = parser.parse(&out, Some(&edited)).expect("reparse");
+        assert_eq!(
+            reparsed.root_node().to_sexp(),
+            parse_abl(&out).root_node().to_sexp()
+        );
+    }
+
+    #[test]
+    fn drops_tree_on_full_text_change() {
+        let tree = parse_abl("a = 1.");
+        let (_, edited) = apply_content_changes_with_tree(
+            "a = 1.".to_string(),
+            &[TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: "b = 2.".to_string(),
+            }],
+            Some(tree),
+        )
+        .expect("updated text");
+        assert!(edited.is_none());
+    }
 }
@@ -57,6 +57,18 @@ pub fn count_nodes_by_kind(node: Node<'_>, kind: &str) -> usize {
     count
 }
 
+/// Anonymous leaf tokens spelled like their own kind (`FOR`, `NO-UNDO`, ...) are keywords.
+pub fn is_keyword_leaf(node: Node<'_>, src: &[u8]) -> bool {
+    let kind = node.kind();
+    !node.is_named()
+        && node.child_count() == 0
+        && kind.len() > 1
+        && kind.bytes().all(|b| b.is_ascii_alphabetic() || b == b'-')
+        && node
+            .utf8_text(src)
+            .is_ok_and(|raw| raw.eq_ignore_ascii_case(kind))
+}
+
 pub fn point_to_position(point: tree_sitter::Point) -> Position {
     Position::new(point.row as u32, point.column as u32)
 }